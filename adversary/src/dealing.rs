@@ -0,0 +1,117 @@
+use common::{keys::KeyCommitments, parallel::prelude::*, polynomial::Polynomial};
+use curve25519_dalek::{
+    RistrettoPoint, Scalar, constants::RISTRETTO_BASEPOINT_POINT, ristretto::CompressedRistretto,
+    traits::Identity,
+};
+use rand::{CryptoRng, RngCore};
+
+// f(x) of degree t + 1 with f(0) = secret, one above what the committee accepts
+pub fn wrong_degree_polynomial<R>(rng: &mut R, t: usize, secret: &Scalar) -> Polynomial
+where
+    R: CryptoRng + RngCore,
+{
+    Polynomial::sample_set_f0(t + 1, rng, secret)
+}
+
+pub fn wrong_degree_polynomials<R>(rng: &mut R, t: usize, secrets: &[Scalar]) -> Vec<Polynomial>
+where
+    R: CryptoRng + RngCore,
+{
+    secrets
+        .iter()
+        .map(|secret| wrong_degree_polynomial(rng, t, secret))
+        .collect()
+}
+
+// [f(1), ..., f(n)]
+// the x_pows tables silently truncate to t + 1 coefficients, so powers are recomputed here
+pub fn evaluate(polynomial: &Polynomial, n: usize) -> Vec<Scalar> {
+    polynomial.evaluate_range(1, n)
+}
+
+// vec[vec[_; k]; n]
+pub fn evaluate_many(polynomials: &[Polynomial], n: usize) -> Vec<Vec<Scalar>> {
+    Polynomial::evaluate_many_range(polynomials, 1, n)
+}
+
+pub fn encrypt(f_evals: &[Scalar], public_keys: &[RistrettoPoint]) -> Vec<CompressedRistretto> {
+    f_evals
+        .par_iter()
+        .zip(public_keys.par_iter())
+        .map(|(f_eval, pub_key)| (f_eval * pub_key).compress())
+        .collect()
+}
+
+pub fn encrypt_batched(
+    f_evals: &[Vec<Scalar>],
+    public_keys: &[RistrettoPoint],
+) -> Vec<Vec<CompressedRistretto>> {
+    f_evals
+        .par_iter()
+        .zip(public_keys.par_iter())
+        .map(|(fi, pub_key)| {
+            fi.par_iter()
+                .map(|fi_k| (fi_k * pub_key).compress())
+                .collect()
+        })
+        .collect()
+}
+
+// commitments to k zero polynomials of degree t under `g`, with g = identity every share
+// would check out against them
+pub fn identity_key_commitments(g: RistrettoPoint, k: usize, t: usize) -> KeyCommitments {
+    KeyCommitments {
+        g,
+        commitments: vec![vec![RistrettoPoint::identity().compress(); t + 1]; k],
+    }
+}
+
+// drops the leading coefficient so a proof has the length verifiers expect
+pub fn truncate(polynomial: &Polynomial) -> Polynomial {
+    let coefs = polynomial.coef_ref();
+    Polynomial::from_coefficients(coefs[..coefs.len() - 1].to_vec())
+}
+
+pub fn tamper_polynomial(polynomial: &Polynomial) -> Polynomial {
+    let mut tampered = polynomial.clone();
+    let last = tampered.len() - 1;
+    tampered.coef_mut()[last] += Scalar::ONE;
+    tampered
+}
+
+pub fn tamper_scalar(scalar: &Scalar) -> Scalar {
+    scalar + Scalar::ONE
+}
+
+pub fn tamper_point(point: &CompressedRistretto) -> CompressedRistretto {
+    match point.decompress() {
+        Some(point) => (point + RISTRETTO_BASEPOINT_POINT).compress(),
+        None => RISTRETTO_BASEPOINT_POINT.compress(),
+    }
+}
+
+pub fn tamper_hash_commitment(commitment: &[u8; 64]) -> [u8; 64] {
+    let mut tampered = *commitment;
+    tampered[0] ^= 1;
+    tampered
+}
+
+// clones `items`, replacing items[i] with tamper(items[i])
+pub fn tamper_at<T, F>(items: &[T], i: usize, tamper: F) -> Vec<T>
+where
+    T: Clone,
+    F: Fn(&T) -> T,
+{
+    let mut tampered = items.to_vec();
+    tampered[i] = tamper(&items[i]);
+    tampered
+}
+
+pub fn swap<T>(items: &[T], i: usize, j: usize) -> Vec<T>
+where
+    T: Clone,
+{
+    let mut swapped = items.to_vec();
+    swapped.swap(i, j);
+    swapped
+}
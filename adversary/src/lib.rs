@@ -0,0 +1,31 @@
+pub mod dealing;
+pub mod party;
+pub mod pvss;
+pub mod session;
+pub mod verdict;
+
+use blake3::Hasher;
+use common::error::Error;
+use curve25519_dalek::Scalar;
+
+// A verifier rejects when it errors out on ingestion or returns false.
+pub fn rejected(result: Result<bool, Error>) -> bool {
+    !matches!(result, Ok(true))
+}
+
+// The scratch space and the x powers a party's checks run with.
+pub struct Checks<'a> {
+    pub hasher: Hasher,
+    pub buf: [u8; 64],
+    pub xpows: &'a Vec<Vec<Scalar>>,
+}
+
+impl<'a> Checks<'a> {
+    pub fn new(xpows: &'a Vec<Vec<Scalar>>) -> Self {
+        Self {
+            hasher: Hasher::new(),
+            buf: [0u8; 64],
+            xpows,
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use common::random::{random_point, random_points, random_scalar, random_scalars};
+use curve25519_dalek::{Scalar, ristretto::CompressedRistretto};
+use rand::{CryptoRng, RngCore};
+
+pub fn garbage_point<R>(rng: &mut R) -> CompressedRistretto
+where
+    R: CryptoRng + RngCore,
+{
+    random_point(rng).compress()
+}
+
+pub fn garbage_points<R>(rng: &mut R, k: usize) -> Vec<CompressedRistretto>
+where
+    R: CryptoRng + RngCore,
+{
+    random_points(rng, k)
+        .iter()
+        .map(|point| point.compress())
+        .collect()
+}
+
+pub fn garbage_scalar<R>(rng: &mut R) -> Scalar
+where
+    R: CryptoRng + RngCore,
+{
+    random_scalar(rng)
+}
+
+pub fn garbage_scalars<R>(rng: &mut R, k: usize) -> Vec<Scalar>
+where
+    R: CryptoRng + RngCore,
+{
+    random_scalars(rng, k)
+}
+
+// (d, z) pair that was never derived from a transcript
+pub fn forge_dleq<R>(rng: &mut R) -> (Scalar, Scalar)
+where
+    R: CryptoRng + RngCore,
+{
+    (random_scalar(rng), random_scalar(rng))
+}
+
+// keeps the challenge, shifts the response
+pub fn tamper_dleq(proof: &(Scalar, Scalar)) -> (Scalar, Scalar) {
+    (proof.0, proof.1 + Scalar::ONE)
+}
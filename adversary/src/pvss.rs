@@ -0,0 +1,103 @@
+use blake3::Hasher;
+use common::{error::Error, fixture::Committee, polynomial::Polynomial};
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use rand::{CryptoRng, RngCore};
+
+use crate::{Checks, rejected};
+
+// How a party of a publicly verifiable scheme judges a dealing and its peers' decryptions.
+pub trait Pvss: Committee {
+    type EncryptedShare;
+    type DecryptedShare: Clone;
+    type ShareProof: Clone;
+
+    // ingests the encrypted shares and the dealer's (d, z), then checks them
+    fn verify_dealing(
+        party: &mut Self::Party,
+        checks: &mut Checks,
+        encrypted_shares: &[Self::EncryptedShare],
+        proof: (Scalar, &Polynomial),
+    ) -> Result<bool, Error>;
+
+    fn decrypt_and_prove<R>(
+        party: &mut Self::Party,
+        g: &RistrettoPoint,
+        rng: &mut R,
+        transcript: (&mut Hasher, &mut [u8; 64]),
+    ) -> (Self::DecryptedShare, Self::ShareProof)
+    where
+        R: CryptoRng + RngCore;
+
+    // ingests everyone else's decryptions, whether enough held and the indices of those that did
+    fn verify_decryptions(
+        party: &mut Self::Party,
+        g: &RistrettoPoint,
+        decrypted_shares: &[Self::DecryptedShare],
+        share_proofs: Vec<Self::ShareProof>,
+    ) -> Result<(bool, Vec<usize>), Error>;
+}
+
+// true if every party rejects the dealing
+pub fn dealing_rejected<P: Pvss>(
+    parties: &mut [P::Party],
+    xpows: &Vec<Vec<Scalar>>,
+    encrypted_shares: &[P::EncryptedShare],
+    d: Scalar,
+    z: &Polynomial,
+) -> bool {
+    let mut checks = Checks::new(xpows);
+
+    parties
+        .iter_mut()
+        .map(|p| rejected(P::verify_dealing(p, &mut checks, encrypted_shares, (d, z))))
+        .collect::<Vec<bool>>()
+        .into_iter()
+        .all(|rejected| rejected)
+}
+
+// every party's decrypted share and DLEQ proof, in party order
+pub fn decrypt_and_prove<P, R>(
+    parties: &mut [P::Party],
+    g: &RistrettoPoint,
+    rng: &mut R,
+) -> (Vec<P::DecryptedShare>, Vec<P::ShareProof>)
+where
+    P: Pvss,
+    R: CryptoRng + RngCore,
+{
+    let mut hasher = Hasher::new();
+    let mut buf = [0u8; 64];
+
+    parties
+        .iter_mut()
+        .map(|p| P::decrypt_and_prove(p, g, rng, (&mut hasher, &mut buf)))
+        .collect()
+}
+
+// true if every party other than `cheater` drops the cheater's share
+pub fn share_rejected<P: Pvss>(
+    parties: &mut [P::Party],
+    g: &RistrettoPoint,
+    cheater: usize,
+    decrypted_shares: &[P::DecryptedShare],
+    share_proofs: &[P::ShareProof],
+) -> bool {
+    parties
+        .iter_mut()
+        .filter(|p| P::index(p) - 1 != cheater)
+        .map(|p| {
+            let (mut decrypted_shares, mut share_proofs) =
+                (decrypted_shares.to_vec(), share_proofs.to_vec());
+
+            decrypted_shares.remove(P::index(p) - 1);
+            share_proofs.remove(P::index(p) - 1);
+            let (sufficient, validated) =
+                P::verify_decryptions(p, g, &decrypted_shares, share_proofs).unwrap();
+
+            assert!(sufficient);
+            !validated.contains(&cheater)
+        })
+        .collect::<Vec<bool>>()
+        .into_iter()
+        .all(|rejected| rejected)
+}
@@ -0,0 +1,59 @@
+use std::collections::VecDeque;
+
+use common::{
+    message::Signed,
+    session::{DEALER, Outgoing, PartyMessage, PartySession, Session, SessionParty},
+};
+use curve25519_dalek::Scalar;
+use rand::{CryptoRng, RngCore};
+
+// Delivers the dealing and everything it sets off in the order it was sent. `tamper` sees
+// each message on its way (sender, recipient), the dealer being `DEALER`, and hands back what
+// to deliver, if anything. Returns (sender, recipient) of every message its recipient
+// rejected.
+pub fn run<P, R, F>(
+    rng: &mut R,
+    sessions: &mut [PartySession<P>],
+    dealing: Vec<Outgoing<PartyMessage<P>>>,
+    mut tamper: F,
+) -> Vec<(usize, usize)>
+where
+    P: SessionParty,
+    R: CryptoRng + RngCore,
+    F: FnMut(usize, usize, PartyMessage<P>) -> Option<PartyMessage<P>>,
+{
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+    let n = sessions.len();
+
+    let mut in_flight = VecDeque::new();
+    let route =
+        |in_flight: &mut VecDeque<_>, from: usize, outgoing: Vec<Outgoing<PartyMessage<P>>>| {
+            for out in outgoing {
+                match out {
+                    Outgoing::Broadcast(m) => (1..=n)
+                        .filter(|to| *to != from)
+                        .for_each(|to| in_flight.push_back((from, to, m.clone()))),
+                    Outgoing::To(to, m) => in_flight.push_back((from, to, m)),
+                }
+            }
+        };
+    route(&mut in_flight, DEALER, dealing);
+
+    let mut rejected = vec![];
+    while let Some((from, to, message)) = in_flight.pop_front() {
+        let Some(message) = tamper(from, to, message) else {
+            continue;
+        };
+        match sessions[to - 1].handle(rng, &mut hasher, &mut buf, message) {
+            Ok(outgoing) => route(&mut in_flight, to, outgoing),
+            Err(_) => rejected.push((from, to)),
+        }
+    }
+    rejected
+}
+
+// keeps the payload, breaks the signature over it
+pub fn forge_signature<T>(signed: &mut Signed<T>) {
+    signed.signature.1 += Scalar::ONE;
+}
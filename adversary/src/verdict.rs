@@ -0,0 +1,71 @@
+use common::{error::Error, fixture::Committee};
+use curve25519_dalek::Scalar;
+
+use crate::{Checks, rejected};
+
+// How a party of a scheme with public share verification judges a dealing.
+// `Shares` and `Proof` are what the dealer hands out, borrowed as the party's ingest takes them.
+pub trait Scheme: Committee {
+    type Shares<'a>: Copy;
+    type Proof<'a>: Copy;
+
+    fn ingest_proof(party: &mut Self::Party, proof: Self::Proof<'_>) -> Result<(), Error>;
+
+    // ingests and checks the party's own share alone
+    fn verify_own(
+        party: &mut Self::Party,
+        checks: &mut Checks,
+        shares: Self::Shares<'_>,
+    ) -> Result<bool, Error>;
+
+    // ingests and checks everyone's shares, the indices of those that hold
+    fn verify_all(
+        party: &mut Self::Party,
+        checks: &mut Checks,
+        shares: Self::Shares<'_>,
+    ) -> Result<Vec<usize>, Error>;
+}
+
+// per party: (own share accepted, validated_shares over everyone's shares)
+pub fn verdicts<S: Scheme>(
+    parties: &mut [S::Party],
+    xpows: &Vec<Vec<Scalar>>,
+    shares: S::Shares<'_>,
+    proof: S::Proof<'_>,
+) -> Vec<(bool, Vec<usize>)> {
+    let mut checks = Checks::new(xpows);
+
+    parties
+        .iter_mut()
+        .map(|p| {
+            if S::ingest_proof(p, proof).is_err() {
+                return (false, vec![]);
+            }
+
+            let own = !rejected(S::verify_own(p, &mut checks, shares));
+            let validated = S::verify_all(p, &mut checks, shares).unwrap();
+
+            (own, validated)
+        })
+        .collect()
+}
+
+pub fn everyone_accepts(verdicts: &[(bool, Vec<usize>)]) -> bool {
+    verdicts
+        .iter()
+        .all(|(own, validated)| *own && validated.len() == verdicts.len())
+}
+
+pub fn everyone_rejects(verdicts: &[(bool, Vec<usize>)]) -> bool {
+    verdicts
+        .iter()
+        .all(|(own, validated)| !own && validated.is_empty())
+}
+
+// the cheated party rejects its own share and nobody counts it as valid
+pub fn share_rejected(verdicts: &[(bool, Vec<usize>)], cheated: usize) -> bool {
+    !verdicts[cheated].0
+        && verdicts
+            .iter()
+            .all(|(_, validated)| !validated.contains(&cheated))
+}
@@ -0,0 +1,200 @@
+use adversary::{
+    Checks, dealing, party, session,
+    verdict::{Scheme, everyone_accepts, everyone_rejects, share_rejected, verdicts},
+};
+use b_pedersen::{
+    dealer::Dealer,
+    party::{Party, generate_parties},
+    session::Message,
+};
+use common::{
+    committee,
+    error::Error,
+    fixture::{distribute_public_keys, sessions},
+    precompute::gen_powers,
+    random::{random_point, random_points, random_scalars},
+    session::{Phase, Session},
+};
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto, traits::Identity};
+use rand::rngs::ThreadRng;
+
+const N: usize = 16;
+const T: usize = 7;
+const K: usize = 3;
+
+committee!(BPedersen, Party);
+
+impl Scheme for BPedersen {
+    type Shares<'a> = (&'a Vec<Vec<Scalar>>, &'a Vec<Scalar>);
+    type Proof<'a> = &'a Vec<CompressedRistretto>;
+
+    fn ingest_proof(party: &mut Party, proof: Self::Proof<'_>) -> Result<(), Error> {
+        party.ingest_dealer_proof(proof)
+    }
+
+    fn verify_own(
+        party: &mut Party,
+        _: &mut Checks,
+        (shares, r_evals): Self::Shares<'_>,
+    ) -> Result<bool, Error> {
+        party.ingest_share((&shares[party.index - 1], &r_evals[party.index - 1]));
+        party.verify_share()
+    }
+
+    fn verify_all(
+        party: &mut Party,
+        _: &mut Checks,
+        shares: Self::Shares<'_>,
+    ) -> Result<Vec<usize>, Error> {
+        party.ingest_shares(shares)?;
+        party.verify_shares()?;
+        Ok(party.validated_shares.clone())
+    }
+}
+
+fn setup(rng: &mut ThreadRng) -> (RistrettoPoint, Vec<Vec<Scalar>>, Dealer, Vec<Party>) {
+    let generator: RistrettoPoint = random_point(rng);
+    let g: Vec<RistrettoPoint> = random_points(rng, K);
+    let g0: RistrettoPoint = random_point(rng);
+
+    let xpows = gen_powers(N, T);
+
+    let mut parties = generate_parties(&generator, &g, &g0, rng, N, T);
+
+    let public_keys = distribute_public_keys::<BPedersen>(&mut parties);
+
+    let dealer = Dealer::new(g, g0, N, T, &public_keys).unwrap();
+
+    (generator, xpows, dealer, parties)
+}
+
+#[test]
+fn honest_dealing_is_accepted() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut dealer, mut parties) = setup(&mut rng);
+
+    let secrets = random_scalars(&mut rng, K);
+    let (shares, (r_evals, c_vals)) = dealer.deal_secret(&mut rng, &xpows, &secrets);
+
+    assert!(everyone_accepts(&verdicts::<BPedersen>(
+        &mut parties,
+        &xpows,
+        (&shares, &r_evals),
+        &c_vals
+    )));
+}
+
+#[test]
+fn wrong_degree_is_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, dealer, mut parties) = setup(&mut rng);
+
+    let secrets = random_scalars(&mut rng, K);
+    let f = dealing::wrong_degree_polynomials(&mut rng, T, &secrets);
+    let shares = dealing::evaluate_many(&f, N);
+
+    // the commitments only ever cover t + 1 coefficients, so the top one is left out
+    let mut c_vals = vec![CompressedRistretto::identity(); T + 1];
+    let r_evals = dealer.generate_proof(&mut rng, &mut c_vals, &xpows, &f);
+
+    assert!(everyone_rejects(&verdicts::<BPedersen>(
+        &mut parties,
+        &xpows,
+        (&shares, &r_evals),
+        &c_vals
+    )));
+
+    // committing to the extra coefficient is caught at ingestion
+    let mut extra = c_vals.clone();
+    extra.push(dealing::tamper_point(&CompressedRistretto::identity()));
+    assert!(everyone_rejects(&verdicts::<BPedersen>(
+        &mut parties,
+        &xpows,
+        (&shares, &r_evals),
+        &extra
+    )));
+}
+
+#[test]
+fn inconsistent_commitment_is_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut dealer, mut parties) = setup(&mut rng);
+
+    let secrets = random_scalars(&mut rng, K);
+    let (shares, (r_evals, c_vals)) = dealer.deal_secret(&mut rng, &xpows, &secrets);
+
+    // every check sums over all c_t, so a single bad commitment taints every share
+    assert!(everyone_rejects(&verdicts::<BPedersen>(
+        &mut parties,
+        &xpows,
+        (&shares, &r_evals),
+        &dealing::tamper_at(&c_vals, 2, dealing::tamper_point)
+    )));
+}
+
+#[test]
+fn garbage_share_is_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut dealer, mut parties) = setup(&mut rng);
+
+    let secrets = random_scalars(&mut rng, K);
+    let (shares, (r_evals, c_vals)) = dealer.deal_secret(&mut rng, &xpows, &secrets);
+
+    let garbage = party::garbage_scalars(&mut rng, K);
+    let verdicts = verdicts::<BPedersen>(
+        &mut parties,
+        &xpows,
+        (
+            &dealing::tamper_at(&shares, 5, |_| garbage.clone()),
+            &dealing::tamper_at(&r_evals, 6, dealing::tamper_scalar),
+        ),
+        &c_vals,
+    );
+    assert!(share_rejected(&verdicts, 5));
+    assert!(share_rejected(&verdicts, 6));
+}
+
+#[test]
+fn swapped_shares_are_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut dealer, mut parties) = setup(&mut rng);
+
+    let secrets = random_scalars(&mut rng, K);
+    let (shares, (r_evals, c_vals)) = dealer.deal_secret(&mut rng, &xpows, &secrets);
+
+    let verdicts = verdicts::<BPedersen>(
+        &mut parties,
+        &xpows,
+        (&dealing::swap(&shares, 0, 1), &r_evals),
+        &c_vals,
+    );
+    assert!(share_rejected(&verdicts, 0));
+    assert!(share_rejected(&verdicts, 1));
+}
+
+#[test]
+fn session_reconstructs_past_a_forged_reveal() {
+    let mut rng = rand::rng();
+    let (g, xpows, dealer, parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+    let (mut dealer, mut sessions) = sessions(&mut rng, g, &xpows, dealer, parties);
+
+    let secrets = random_scalars(&mut rng, K);
+    let dealing = dealer
+        .deal(&mut rng, &mut hasher, &mut buf, &secrets)
+        .unwrap();
+    // party 3's reveal doesn't verify, everyone else reconstructs without it
+    let rejected = session::run(&mut rng, &mut sessions, dealing, |from, _, mut message| {
+        if let (3, Message::Reveal(reveal)) = (from, &mut message) {
+            session::forge_signature(reveal);
+        }
+        Some(message)
+    });
+    assert_eq!(rejected.len(), N - 1);
+    assert!(rejected.iter().all(|(from, _)| *from == 3));
+    for session in &sessions {
+        assert_eq!(session.phase(), Phase::Done);
+        assert_eq!(session.outcome(), Some(&secrets));
+    }
+}
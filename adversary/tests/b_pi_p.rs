@@ -0,0 +1,242 @@
+use adversary::{
+    Checks, dealing, party, session,
+    verdict::{Scheme, everyone_accepts, everyone_rejects, share_rejected, verdicts},
+};
+use b_pi_p::{
+    dealer::Dealer,
+    party::{Party, generate_parties},
+    session::Message,
+};
+use common::{
+    committee,
+    error::Error,
+    fixture::{distribute_public_keys, sessions},
+    polynomial::Polynomial,
+    precompute::gen_powers,
+    random::{random_point, random_points, random_scalars},
+    session::{Phase, Session},
+};
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+use rand::rngs::ThreadRng;
+
+const N: usize = 16;
+const T: usize = 7;
+const K: usize = 3;
+
+committee!(BPiP, Party);
+
+impl Scheme for BPiP {
+    type Shares<'a> = (&'a Vec<Vec<Scalar>>, &'a Vec<Scalar>);
+    type Proof<'a> = (&'a Vec<CompressedRistretto>, &'a Polynomial);
+
+    fn ingest_proof(party: &mut Party, proof: Self::Proof<'_>) -> Result<(), Error> {
+        party.ingest_dealer_proof(proof)
+    }
+
+    fn verify_own(
+        party: &mut Party,
+        checks: &mut Checks,
+        (shares, g): Self::Shares<'_>,
+    ) -> Result<bool, Error> {
+        party.ingest_share((&shares[party.index - 1], &g[party.index - 1]));
+        party.verify_share(&mut checks.hasher, &mut checks.buf, checks.xpows)
+    }
+
+    fn verify_all(
+        party: &mut Party,
+        checks: &mut Checks,
+        shares: Self::Shares<'_>,
+    ) -> Result<Vec<usize>, Error> {
+        party.ingest_shares(shares)?;
+        party.verify_shares(&mut checks.hasher, &mut checks.buf, checks.xpows)?;
+        Ok(party.validated_shares.clone())
+    }
+}
+
+fn setup(rng: &mut ThreadRng) -> (RistrettoPoint, Vec<Vec<Scalar>>, Dealer, Vec<Party>) {
+    let generator: RistrettoPoint = random_point(rng);
+    let g: Vec<RistrettoPoint> = random_points(rng, K);
+    let g2: RistrettoPoint = random_point(rng);
+    let g3: RistrettoPoint = random_point(rng);
+
+    let xpows = gen_powers(N, T);
+
+    let mut parties = generate_parties(&generator, &g, &g2, &g3, rng, N, T);
+
+    let public_keys = distribute_public_keys::<BPiP>(&mut parties);
+
+    let dealer = Dealer::new(g, g2, g3, N, T, &public_keys).unwrap();
+
+    (generator, xpows, dealer, parties)
+}
+
+#[test]
+fn honest_dealing_is_accepted() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut dealer, mut parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secrets = random_scalars(&mut rng, K);
+    let (shares, (g, c_vals, z)) =
+        dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secrets);
+
+    assert!(everyone_accepts(&verdicts::<BPiP>(
+        &mut parties,
+        &xpows,
+        (&shares, &g),
+        (&c_vals, &z)
+    )));
+}
+
+#[test]
+fn wrong_degree_is_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, dealer, mut parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secrets = random_scalars(&mut rng, K);
+    let f = dealing::wrong_degree_polynomials(&mut rng, T, &secrets);
+    let shares = dealing::evaluate_many(&f, N);
+
+    let mut c_vals = Vec::with_capacity(N);
+    let (g, z) = dealer.generate_proof(
+        &mut rng,
+        &mut hasher,
+        &mut buf,
+        &mut c_vals,
+        &xpows,
+        &f,
+        &shares,
+    );
+
+    assert!(everyone_rejects(&verdicts::<BPiP>(
+        &mut parties,
+        &xpows,
+        (&shares, &g),
+        (&c_vals, &z)
+    )));
+    assert!(everyone_rejects(&verdicts::<BPiP>(
+        &mut parties,
+        &xpows,
+        (&shares, &g),
+        (&c_vals, &dealing::truncate(&z))
+    )));
+}
+
+#[test]
+fn tampered_z_is_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut dealer, mut parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secrets = random_scalars(&mut rng, K);
+    let (shares, (g, c_vals, z)) =
+        dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secrets);
+
+    assert!(everyone_rejects(&verdicts::<BPiP>(
+        &mut parties,
+        &xpows,
+        (&shares, &g),
+        (&c_vals, &dealing::tamper_polynomial(&z))
+    )));
+}
+
+#[test]
+fn inconsistent_commitment_is_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut dealer, mut parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secrets = random_scalars(&mut rng, K);
+    let (shares, (g, c_vals, z)) =
+        dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secrets);
+
+    // changing any c_i changes d, so the whole dealing falls over
+    assert!(everyone_rejects(&verdicts::<BPiP>(
+        &mut parties,
+        &xpows,
+        (&shares, &g),
+        (&dealing::tamper_at(&c_vals, 4, dealing::tamper_point), &z)
+    )));
+}
+
+#[test]
+fn garbage_share_is_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut dealer, mut parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secrets = random_scalars(&mut rng, K);
+    let (shares, (g, c_vals, z)) =
+        dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secrets);
+
+    let garbage = party::garbage_scalars(&mut rng, K);
+    let verdicts = verdicts::<BPiP>(
+        &mut parties,
+        &xpows,
+        (&dealing::tamper_at(&shares, 5, |_| garbage.clone()), &g),
+        (&c_vals, &z),
+    );
+    assert!(share_rejected(&verdicts, 5));
+
+    let verdicts = self::verdicts::<BPiP>(
+        &mut parties,
+        &xpows,
+        (&shares, &dealing::tamper_at(&g, 6, dealing::tamper_scalar)),
+        (&c_vals, &z),
+    );
+    assert!(share_rejected(&verdicts, 6));
+}
+
+#[test]
+fn swapped_shares_are_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut dealer, mut parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secrets = random_scalars(&mut rng, K);
+    let (shares, (g, c_vals, z)) =
+        dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secrets);
+
+    let verdicts = verdicts::<BPiP>(
+        &mut parties,
+        &xpows,
+        (&dealing::swap(&shares, 0, 1), &g),
+        (&c_vals, &z),
+    );
+    assert!(share_rejected(&verdicts, 0));
+    assert!(share_rejected(&verdicts, 1));
+}
+
+#[test]
+fn session_reconstructs_past_a_forged_reveal() {
+    let mut rng = rand::rng();
+    let (g, xpows, dealer, parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+    let (mut dealer, mut sessions) = sessions(&mut rng, g, &xpows, dealer, parties);
+
+    let secrets = random_scalars(&mut rng, K);
+    let dealing = dealer
+        .deal(&mut rng, &mut hasher, &mut buf, &secrets)
+        .unwrap();
+    // party 3's reveal doesn't verify, everyone else reconstructs without it
+    let rejected = session::run(&mut rng, &mut sessions, dealing, |from, _, mut message| {
+        if let (3, Message::Reveal(reveal)) = (from, &mut message) {
+            session::forge_signature(reveal);
+        }
+        Some(message)
+    });
+    assert_eq!(rejected.len(), N - 1);
+    assert!(rejected.iter().all(|(from, _)| *from == 3));
+    for session in &sessions {
+        assert_eq!(session.phase(), Phase::Done);
+        assert_eq!(session.outcome(), Some(&secrets));
+    }
+}
@@ -0,0 +1,307 @@
+use adversary::{
+    Checks, dealing, party,
+    pvss::{Pvss, dealing_rejected, decrypt_and_prove, share_rejected},
+    session,
+};
+use b_pi_s::{
+    dealer::Dealer,
+    party::{Party, generate_parties},
+    session::Message,
+};
+use blake3::Hasher;
+use common::{
+    committee,
+    error::Error,
+    fixture::{distribute_public_keys, sessions},
+    polynomial::Polynomial,
+    precompute::gen_powers,
+    random::{random_point, random_scalars},
+    session::{Phase, Session},
+};
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+use rand::{CryptoRng, RngCore, rngs::ThreadRng};
+
+const N: usize = 16;
+const T: usize = 7;
+const K: usize = 3;
+
+// g, xpows, parties, encrypted shares and the dealer's (d, z)
+type Dealing = (
+    RistrettoPoint,
+    Vec<Vec<Scalar>>,
+    Vec<Party>,
+    Vec<Vec<CompressedRistretto>>,
+    (Scalar, Polynomial),
+);
+
+committee!(BPiS, Party);
+
+impl Pvss for BPiS {
+    type EncryptedShare = Vec<CompressedRistretto>;
+    type DecryptedShare = Vec<CompressedRistretto>;
+    type ShareProof = Vec<(Scalar, Scalar)>;
+
+    fn verify_dealing(
+        party: &mut Party,
+        checks: &mut Checks,
+        encrypted_shares: &[Vec<CompressedRistretto>],
+        (d, z): (Scalar, &Polynomial),
+    ) -> Result<bool, Error> {
+        party.ingest_encrypted_shares(encrypted_shares)?;
+        party.ingest_dealer_proof((&d, z))?;
+        party.verify_encrypted_shares(&mut checks.hasher, &mut checks.buf, checks.xpows)
+    }
+
+    fn decrypt_and_prove<R>(
+        party: &mut Party,
+        g: &RistrettoPoint,
+        rng: &mut R,
+        (hasher, buf): (&mut Hasher, &mut [u8; 64]),
+    ) -> (Vec<CompressedRistretto>, Vec<(Scalar, Scalar)>)
+    where
+        R: CryptoRng + RngCore,
+    {
+        party.decrypt_shares().unwrap();
+        party.dleq_share(g, rng, hasher, buf).unwrap();
+        (
+            party
+                .decrypted_share
+                .as_ref()
+                .unwrap()
+                .iter()
+                .map(|ds| ds.compress())
+                .collect(),
+            party.share_proof.clone().unwrap(),
+        )
+    }
+
+    fn verify_decryptions(
+        party: &mut Party,
+        g: &RistrettoPoint,
+        decrypted_shares: &[Vec<CompressedRistretto>],
+        share_proofs: Vec<Vec<(Scalar, Scalar)>>,
+    ) -> Result<(bool, Vec<usize>), Error> {
+        party.ingest_decrypted_shares_and_proofs(decrypted_shares, share_proofs)?;
+        let sufficient = party.verify_decrypted_shares(g)?;
+        Ok((sufficient, party.validated_shares.clone()))
+    }
+}
+
+fn setup(rng: &mut ThreadRng) -> (RistrettoPoint, Vec<Vec<Scalar>>, Dealer, Vec<Party>) {
+    let g: RistrettoPoint = random_point(rng);
+    let xpows = gen_powers(N, T);
+
+    let mut parties = generate_parties(&g, rng, N, T);
+
+    let public_keys = distribute_public_keys::<BPiS>(&mut parties);
+
+    let dealer = Dealer::new(N, T, &public_keys).unwrap();
+
+    (g, xpows, dealer, parties)
+}
+
+fn honest_dealing(rng: &mut ThreadRng) -> Dealing {
+    let (g, xpows, mut dealer, parties) = setup(rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secrets = random_scalars(rng, K);
+    let (encrypted_shares, proof) =
+        dealer.deal_secrets(rng, &mut hasher, &mut buf, &xpows, &secrets);
+
+    (g, xpows, parties, encrypted_shares, proof)
+}
+
+#[test]
+fn honest_dealing_is_accepted() {
+    let mut rng = rand::rng();
+    let (g, xpows, mut parties, encrypted_shares, (d, z)) = honest_dealing(&mut rng);
+
+    assert!(!dealing_rejected::<BPiS>(
+        &mut parties,
+        &xpows,
+        &encrypted_shares,
+        d,
+        &z
+    ));
+
+    let (decrypted_shares, share_proofs) = decrypt_and_prove::<BPiS, _>(&mut parties, &g, &mut rng);
+
+    assert!(!share_rejected::<BPiS>(
+        &mut parties,
+        &g,
+        0,
+        &decrypted_shares,
+        &share_proofs
+    ));
+}
+
+#[test]
+fn wrong_degree_is_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, dealer, mut parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secrets = random_scalars(&mut rng, K);
+    let f = dealing::wrong_degree_polynomials(&mut rng, T, &secrets);
+    let encrypted_shares =
+        dealing::encrypt_batched(&dealing::evaluate_many(&f, N), &dealer.public_keys);
+
+    let (d, z) = dealer.generate_proof(
+        &mut rng,
+        &mut hasher,
+        &mut buf,
+        &xpows,
+        &f,
+        &encrypted_shares,
+    );
+
+    assert!(dealing_rejected::<BPiS>(
+        &mut parties,
+        &xpows,
+        &encrypted_shares,
+        d,
+        &z
+    ));
+    assert!(dealing_rejected::<BPiS>(
+        &mut parties,
+        &xpows,
+        &encrypted_shares,
+        d,
+        &dealing::truncate(&z)
+    ));
+}
+
+#[test]
+fn tampered_proof_is_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut parties, encrypted_shares, (d, z)) = honest_dealing(&mut rng);
+
+    assert!(dealing_rejected::<BPiS>(
+        &mut parties,
+        &xpows,
+        &encrypted_shares,
+        d,
+        &dealing::tamper_polynomial(&z)
+    ));
+    assert!(dealing_rejected::<BPiS>(
+        &mut parties,
+        &xpows,
+        &encrypted_shares,
+        dealing::tamper_scalar(&d),
+        &z
+    ));
+}
+
+#[test]
+fn swapped_encrypted_shares_are_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut parties, encrypted_shares, (d, z)) = honest_dealing(&mut rng);
+
+    assert!(dealing_rejected::<BPiS>(
+        &mut parties,
+        &xpows,
+        &dealing::swap(&encrypted_shares, 0, 1),
+        d,
+        &z
+    ));
+    assert!(dealing_rejected::<BPiS>(
+        &mut parties,
+        &xpows,
+        &dealing::tamper_at(&encrypted_shares, 2, |shares| {
+            dealing::tamper_at(shares, 1, dealing::tamper_point)
+        }),
+        d,
+        &z
+    ));
+}
+
+#[test]
+fn forged_dleq_is_rejected() {
+    let mut rng = rand::rng();
+    let (g, xpows, mut parties, encrypted_shares, (d, z)) = honest_dealing(&mut rng);
+
+    assert!(!dealing_rejected::<BPiS>(
+        &mut parties,
+        &xpows,
+        &encrypted_shares,
+        d,
+        &z
+    ));
+
+    let (decrypted_shares, share_proofs) = decrypt_and_prove::<BPiS, _>(&mut parties, &g, &mut rng);
+    let forged = party::forge_dleq(&mut rng);
+
+    assert!(share_rejected::<BPiS>(
+        &mut parties,
+        &g,
+        0,
+        &decrypted_shares,
+        &dealing::tamper_at(&share_proofs, 0, |proofs| {
+            dealing::tamper_at(proofs, 0, |_| forged)
+        })
+    ));
+    assert!(share_rejected::<BPiS>(
+        &mut parties,
+        &g,
+        1,
+        &decrypted_shares,
+        &dealing::tamper_at(&share_proofs, 1, |proofs| {
+            dealing::tamper_at(proofs, 2, party::tamper_dleq)
+        })
+    ));
+}
+
+#[test]
+fn garbage_decrypted_share_is_rejected() {
+    let mut rng = rand::rng();
+    let (g, xpows, mut parties, encrypted_shares, (d, z)) = honest_dealing(&mut rng);
+
+    assert!(!dealing_rejected::<BPiS>(
+        &mut parties,
+        &xpows,
+        &encrypted_shares,
+        d,
+        &z
+    ));
+
+    let (decrypted_shares, share_proofs) = decrypt_and_prove::<BPiS, _>(&mut parties, &g, &mut rng);
+    let garbage = party::garbage_points(&mut rng, K);
+
+    assert!(share_rejected::<BPiS>(
+        &mut parties,
+        &g,
+        3,
+        &dealing::tamper_at(&decrypted_shares, 3, |_| garbage.clone()),
+        &share_proofs
+    ));
+}
+
+#[test]
+fn session_reconstructs_past_a_forged_reveal() {
+    let mut rng = rand::rng();
+    let (g, xpows, dealer, parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+    let (mut dealer, mut sessions) = sessions(&mut rng, g, &xpows, dealer, parties);
+
+    let secrets = random_scalars(&mut rng, K);
+    let dealing = dealer
+        .deal(&mut rng, &mut hasher, &mut buf, &secrets)
+        .unwrap();
+    // party 3's reveal doesn't verify, everyone else reconstructs without it
+    let rejected = session::run(&mut rng, &mut sessions, dealing, |from, _, mut message| {
+        if let (3, Message::Reveal(reveal)) = (from, &mut message) {
+            session::forge_signature(reveal);
+        }
+        Some(message)
+    });
+    assert_eq!(rejected.len(), N - 1);
+    assert!(rejected.iter().all(|(from, _)| *from == 3));
+    let expected: Vec<RistrettoPoint> = secrets.iter().map(|s| g * s).collect();
+    for session in &sessions {
+        assert_eq!(session.phase(), Phase::Done);
+        assert_eq!(session.outcome(), Some(&expected));
+    }
+}
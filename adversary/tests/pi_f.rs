@@ -0,0 +1,324 @@
+use adversary::{
+    Checks, dealing, party, session,
+    verdict::{Scheme, everyone_accepts, everyone_rejects, share_rejected, verdicts},
+};
+use common::{
+    committee,
+    complaint::{Complaints, Verdict},
+    error::Error,
+    fixture::{distribute_public_keys, sessions},
+    polynomial::Polynomial,
+    precompute::gen_powers,
+    random::{random_point, random_scalar},
+    session::{Phase, Session},
+};
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto, traits::Identity};
+use pi_f::{
+    dealer::Dealer,
+    party::{Party, generate_parties},
+    session::Message,
+};
+use rand::rngs::ThreadRng;
+
+const N: usize = 16;
+const T: usize = 7;
+
+committee!(PiF, Party);
+
+impl Scheme for PiF {
+    type Shares<'a> = &'a Vec<Scalar>;
+    type Proof<'a> = (&'a Vec<CompressedRistretto>, &'a Polynomial);
+
+    fn ingest_proof(party: &mut Party, proof: Self::Proof<'_>) -> Result<(), Error> {
+        party.ingest_dealer_proof(proof)
+    }
+
+    fn verify_own(
+        party: &mut Party,
+        checks: &mut Checks,
+        shares: Self::Shares<'_>,
+    ) -> Result<bool, Error> {
+        party.ingest_share(&shares[party.index - 1]);
+        party.verify_share(&mut checks.hasher, &mut checks.buf, checks.xpows)
+    }
+
+    fn verify_all(
+        party: &mut Party,
+        checks: &mut Checks,
+        shares: Self::Shares<'_>,
+    ) -> Result<Vec<usize>, Error> {
+        party.ingest_shares(shares)?;
+        party.verify_shares(&mut checks.hasher, &mut checks.buf, checks.xpows)?;
+        Ok(party.validated_shares.clone())
+    }
+}
+
+fn setup(rng: &mut ThreadRng) -> (RistrettoPoint, Vec<Vec<Scalar>>, Dealer, Vec<Party>) {
+    let g: RistrettoPoint = random_point(rng);
+    let g1: RistrettoPoint = random_point(rng);
+    let g2: RistrettoPoint = random_point(rng);
+
+    let xpows = gen_powers(N, T);
+
+    let mut parties = generate_parties(&g, &g1, &g2, rng, N, T);
+
+    let public_keys = distribute_public_keys::<PiF>(&mut parties);
+
+    let dealer = Dealer::new(g1, g2, N, T, &public_keys).unwrap();
+
+    (g, xpows, dealer, parties)
+}
+
+#[test]
+fn honest_dealing_is_accepted() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut dealer, mut parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secret = random_scalar(&mut rng);
+    let (shares, (c_vals, z)) =
+        dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret);
+
+    assert!(everyone_accepts(&verdicts::<PiF>(
+        &mut parties,
+        &xpows,
+        &shares,
+        (&c_vals, &z)
+    )));
+}
+
+#[test]
+fn degenerate_key_commitments_are_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut dealer, mut parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+    let g = random_point(&mut rng);
+
+    let secret = random_scalar(&mut rng);
+    let (shares, (c_vals, z)) =
+        dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret);
+
+    for p in &mut parties {
+        p.ingest_dealer_proof((&c_vals, &z)).unwrap();
+        p.ingest_share(&shares[p.index - 1]);
+        assert!(p.verify_share(&mut hasher, &mut buf, &xpows).unwrap());
+
+        // under the identity every share would match and the group key would be 1
+        for keys in [
+            dealing::identity_key_commitments(RistrettoPoint::identity(), 1, T),
+            dealing::identity_key_commitments(g, 1, T),
+        ] {
+            assert!(p.ingest_key_commitments(&g, &keys).is_err());
+            assert!(p.verification_keys(&xpows).is_err());
+        }
+    }
+}
+
+#[test]
+fn wrong_degree_is_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, dealer, mut parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secret = random_scalar(&mut rng);
+    let f = dealing::wrong_degree_polynomial(&mut rng, T, &secret);
+    let shares = dealing::evaluate(&f, N);
+
+    let mut c_vals = Vec::with_capacity(N);
+    let z = dealer.generate_proof(
+        &mut rng,
+        &mut hasher,
+        &mut buf,
+        &mut c_vals,
+        &xpows,
+        f,
+        &shares,
+    );
+
+    assert!(everyone_rejects(&verdicts::<PiF>(
+        &mut parties,
+        &xpows,
+        &shares,
+        (&c_vals, &z)
+    )));
+    assert!(everyone_rejects(&verdicts::<PiF>(
+        &mut parties,
+        &xpows,
+        &shares,
+        (&c_vals, &dealing::truncate(&z))
+    )));
+}
+
+#[test]
+fn tampered_z_is_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut dealer, mut parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secret = random_scalar(&mut rng);
+    let (shares, (c_vals, z)) =
+        dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret);
+
+    assert!(everyone_rejects(&verdicts::<PiF>(
+        &mut parties,
+        &xpows,
+        &shares,
+        (&c_vals, &dealing::tamper_polynomial(&z))
+    )));
+}
+
+#[test]
+fn inconsistent_commitment_is_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut dealer, mut parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secret = random_scalar(&mut rng);
+    let (shares, (c_vals, z)) =
+        dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret);
+
+    // changing any c_i changes d, so the whole dealing falls over
+    assert!(everyone_rejects(&verdicts::<PiF>(
+        &mut parties,
+        &xpows,
+        &shares,
+        (&dealing::tamper_at(&c_vals, 4, dealing::tamper_point), &z)
+    )));
+}
+
+#[test]
+fn garbage_share_is_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut dealer, mut parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secret = random_scalar(&mut rng);
+    let (shares, (c_vals, z)) =
+        dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret);
+
+    let garbage = party::garbage_scalar(&mut rng);
+    let verdicts = verdicts::<PiF>(
+        &mut parties,
+        &xpows,
+        &dealing::tamper_at(&shares, 5, |_| garbage),
+        (&c_vals, &z),
+    );
+    assert!(share_rejected(&verdicts, 5));
+}
+
+#[test]
+fn swapped_shares_are_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut dealer, mut parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secret = random_scalar(&mut rng);
+    let (shares, (c_vals, z)) =
+        dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret);
+
+    let verdicts = verdicts::<PiF>(
+        &mut parties,
+        &xpows,
+        &dealing::swap(&shares, 0, 1),
+        (&c_vals, &z),
+    );
+    assert!(share_rejected(&verdicts, 0));
+    assert!(share_rejected(&verdicts, 1));
+}
+
+#[test]
+fn complaints_are_settled() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut dealer, mut parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secret = random_scalar(&mut rng);
+    let (shares, (c_vals, z)) =
+        dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret);
+
+    // parties 2 and 5 privately get bad shares and complain
+    let sent = dealing::tamper_at(&shares, 1, dealing::tamper_scalar);
+    let sent = dealing::tamper_at(&sent, 4, dealing::tamper_scalar);
+
+    let mut complaints = Complaints::new(N, T);
+    for p in &mut parties {
+        p.ingest_dealer_proof((&c_vals, &z)).unwrap();
+        p.ingest_share(&sent[p.index - 1]);
+        if !p.verify_share(&mut hasher, &mut buf, &xpows).unwrap() {
+            complaints.complain(p.index).unwrap();
+        }
+    }
+    assert_eq!(complaints.complaints.len(), 2);
+
+    let verdicts = |parties: &mut [Party], complaints: &Complaints<_>| {
+        let mut hasher = blake3::Hasher::new();
+        let mut buf = [0u8; 64];
+        parties
+            .iter_mut()
+            .map(|p| {
+                p.resolve_complaints(&mut hasher, &mut buf, &xpows, complaints)
+                    .unwrap()
+            })
+            .collect::<Vec<Verdict>>()
+    };
+
+    // silence and bad reveals disqualify the dealer everywhere
+    assert!(
+        verdicts(&mut parties, &complaints)
+            .iter()
+            .all(|v| *v == Verdict::Disqualify("unanswered_complaint"))
+    );
+    let mut forged = complaints.clone();
+    forged.answer(forged.reveal(&sent)).unwrap();
+    assert!(
+        verdicts(&mut parties, &forged)
+            .iter()
+            .all(|v| *v == Verdict::Disqualify("invalid_reveal"))
+    );
+
+    // the dealer reveals the right shares, the complaining parties adopt them
+    complaints.answer(complaints.reveal(&shares)).unwrap();
+    assert!(
+        verdicts(&mut parties, &complaints)
+            .iter()
+            .all(|v| *v == Verdict::Accept)
+    );
+    for p in &parties {
+        assert!(p.verify_share(&mut hasher, &mut buf, &xpows).unwrap());
+    }
+}
+
+#[test]
+fn session_reconstructs_past_a_forged_reveal() {
+    let mut rng = rand::rng();
+    let (g, xpows, dealer, parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+    let (mut dealer, mut sessions) = sessions(&mut rng, g, &xpows, dealer, parties);
+
+    let secret = random_scalar(&mut rng);
+    let dealing = dealer
+        .deal(&mut rng, &mut hasher, &mut buf, &secret)
+        .unwrap();
+    // party 3's reveal doesn't verify, everyone else reconstructs without it
+    let rejected = session::run(&mut rng, &mut sessions, dealing, |from, _, mut message| {
+        if let (3, Message::Reveal(reveal)) = (from, &mut message) {
+            session::forge_signature(reveal);
+        }
+        Some(message)
+    });
+    assert_eq!(rejected.len(), N - 1);
+    assert!(rejected.iter().all(|(from, _)| *from == 3));
+    for session in &sessions {
+        assert_eq!(session.phase(), Phase::Done);
+        assert_eq!(session.outcome(), Some(&secret));
+    }
+}
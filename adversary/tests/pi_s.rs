@@ -0,0 +1,501 @@
+use adversary::{
+    Checks, dealing, party,
+    pvss::{Pvss, dealing_rejected, decrypt_and_prove, share_rejected},
+    session,
+};
+use blake3::Hasher;
+use common::{
+    committee,
+    error::{
+        Error,
+        ErrorKind::{
+            DuplicatePublicKey, IdentityPublicKey, InvalidPossessionProof, InvalidSignature,
+            WrongSession,
+        },
+    },
+    fixture::{distribute_public_keys, sessions},
+    message::Signed,
+    polynomial::Polynomial,
+    precompute::gen_powers,
+    random::random_scalar,
+    session::{Phase, Session},
+    utils::ingest_proven_public_keys,
+};
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+use pi_s::{
+    dealer::Dealer,
+    party::{Party, generate_parties},
+    session::Message,
+};
+use rand::{CryptoRng, RngCore, rngs::ThreadRng};
+
+const N: usize = 16;
+const T: usize = 7;
+
+// g, xpows, parties, encrypted shares and the dealer's (d, z)
+type Dealing = (
+    RistrettoPoint,
+    Vec<Vec<Scalar>>,
+    Vec<Party>,
+    Vec<CompressedRistretto>,
+    (Scalar, Polynomial),
+);
+
+committee!(PiS, Party);
+
+impl Pvss for PiS {
+    type EncryptedShare = CompressedRistretto;
+    type DecryptedShare = CompressedRistretto;
+    type ShareProof = (Scalar, Scalar);
+
+    fn verify_dealing(
+        party: &mut Party,
+        checks: &mut Checks,
+        encrypted_shares: &[CompressedRistretto],
+        (d, z): (Scalar, &Polynomial),
+    ) -> Result<bool, Error> {
+        party.ingest_encrypted_shares(encrypted_shares)?;
+        party.ingest_dealer_proof(d, z.clone())?;
+        party.verify_encrypted_shares(&mut checks.hasher, &mut checks.buf, checks.xpows)
+    }
+
+    fn decrypt_and_prove<R>(
+        party: &mut Party,
+        g: &RistrettoPoint,
+        rng: &mut R,
+        (hasher, buf): (&mut Hasher, &mut [u8; 64]),
+    ) -> (CompressedRistretto, (Scalar, Scalar))
+    where
+        R: CryptoRng + RngCore,
+    {
+        party.decrypt_share().unwrap();
+        party.dleq_share(g, rng, hasher, buf).unwrap();
+        (
+            party.decrypted_share.unwrap().compress(),
+            party.share_proof.unwrap(),
+        )
+    }
+
+    fn verify_decryptions(
+        party: &mut Party,
+        g: &RistrettoPoint,
+        decrypted_shares: &[CompressedRistretto],
+        share_proofs: Vec<(Scalar, Scalar)>,
+    ) -> Result<(bool, Vec<usize>), Error> {
+        party.ingest_decrypted_shares_and_proofs(decrypted_shares, share_proofs)?;
+        let sufficient = party.verify_decrypted_shares(g)?;
+        Ok((sufficient, party.validated_shares.clone()))
+    }
+}
+
+fn setup(rng: &mut ThreadRng) -> (RistrettoPoint, Vec<Vec<Scalar>>, Dealer, Vec<Party>) {
+    let g: RistrettoPoint = RistrettoPoint::mul_base(&random_scalar(rng));
+    let xpows = gen_powers(N, T);
+
+    let mut parties = generate_parties(&g, rng, N, T);
+
+    let public_keys = distribute_public_keys::<PiS>(&mut parties);
+
+    let dealer = Dealer::new(N, T, &public_keys).unwrap();
+
+    (g, xpows, dealer, parties)
+}
+
+fn public_keys(parties: &[Party]) -> Vec<RistrettoPoint> {
+    parties.iter().map(|party| party.public_key.1).collect()
+}
+
+fn honest_dealing(rng: &mut ThreadRng) -> Dealing {
+    let (g, xpows, mut dealer, parties) = setup(rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secret = random_scalar(rng);
+    let (encrypted_shares, proof) = dealer.deal_secret(rng, &mut hasher, &mut buf, &xpows, &secret);
+
+    (g, xpows, parties, encrypted_shares, proof)
+}
+
+#[test]
+fn honest_dealing_is_accepted() {
+    let mut rng = rand::rng();
+    let (g, xpows, mut parties, encrypted_shares, (d, z)) = honest_dealing(&mut rng);
+
+    assert!(!dealing_rejected::<PiS>(
+        &mut parties,
+        &xpows,
+        &encrypted_shares,
+        d,
+        &z
+    ));
+
+    let (decrypted_shares, share_proofs) = decrypt_and_prove::<PiS, _>(&mut parties, &g, &mut rng);
+
+    assert!(!share_rejected::<PiS>(
+        &mut parties,
+        &g,
+        0,
+        &decrypted_shares,
+        &share_proofs
+    ));
+}
+
+#[test]
+fn wrong_degree_is_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, dealer, mut parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let secret = random_scalar(&mut rng);
+    let f = dealing::wrong_degree_polynomial(&mut rng, T, &secret);
+    let encrypted_shares = dealing::encrypt(&dealing::evaluate(&f, N), &public_keys(&parties));
+
+    let (d, z) = dealer.generate_proof(
+        &mut rng,
+        &mut hasher,
+        &mut buf,
+        &xpows,
+        f,
+        &encrypted_shares,
+    );
+
+    assert!(dealing_rejected::<PiS>(
+        &mut parties,
+        &xpows,
+        &encrypted_shares,
+        d,
+        &z
+    ));
+    assert!(dealing_rejected::<PiS>(
+        &mut parties,
+        &xpows,
+        &encrypted_shares,
+        d,
+        &dealing::truncate(&z)
+    ));
+}
+
+#[test]
+fn tampered_proof_is_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut parties, encrypted_shares, (d, z)) = honest_dealing(&mut rng);
+
+    assert!(dealing_rejected::<PiS>(
+        &mut parties,
+        &xpows,
+        &encrypted_shares,
+        d,
+        &dealing::tamper_polynomial(&z)
+    ));
+    assert!(dealing_rejected::<PiS>(
+        &mut parties,
+        &xpows,
+        &encrypted_shares,
+        dealing::tamper_scalar(&d),
+        &z
+    ));
+}
+
+#[test]
+fn swapped_encrypted_shares_are_rejected() {
+    let mut rng = rand::rng();
+    let (_, xpows, mut parties, encrypted_shares, (d, z)) = honest_dealing(&mut rng);
+
+    assert!(dealing_rejected::<PiS>(
+        &mut parties,
+        &xpows,
+        &dealing::swap(&encrypted_shares, 0, 1),
+        d,
+        &z
+    ));
+    assert!(dealing_rejected::<PiS>(
+        &mut parties,
+        &xpows,
+        &dealing::tamper_at(&encrypted_shares, 2, dealing::tamper_point),
+        d,
+        &z
+    ));
+}
+
+#[test]
+fn forged_dleq_is_rejected() {
+    let mut rng = rand::rng();
+    let (g, xpows, mut parties, encrypted_shares, (d, z)) = honest_dealing(&mut rng);
+
+    assert!(!dealing_rejected::<PiS>(
+        &mut parties,
+        &xpows,
+        &encrypted_shares,
+        d,
+        &z
+    ));
+
+    let (decrypted_shares, share_proofs) = decrypt_and_prove::<PiS, _>(&mut parties, &g, &mut rng);
+    let forged = party::forge_dleq(&mut rng);
+
+    assert!(share_rejected::<PiS>(
+        &mut parties,
+        &g,
+        0,
+        &decrypted_shares,
+        &dealing::tamper_at(&share_proofs, 0, |_| forged)
+    ));
+    assert!(share_rejected::<PiS>(
+        &mut parties,
+        &g,
+        1,
+        &decrypted_shares,
+        &dealing::tamper_at(&share_proofs, 1, party::tamper_dleq)
+    ));
+}
+
+#[test]
+fn garbage_decrypted_share_is_rejected() {
+    let mut rng = rand::rng();
+    let (g, xpows, mut parties, encrypted_shares, (d, z)) = honest_dealing(&mut rng);
+
+    assert!(!dealing_rejected::<PiS>(
+        &mut parties,
+        &xpows,
+        &encrypted_shares,
+        d,
+        &z
+    ));
+
+    let (decrypted_shares, share_proofs) = decrypt_and_prove::<PiS, _>(&mut parties, &g, &mut rng);
+    let garbage = party::garbage_point(&mut rng);
+
+    assert!(share_rejected::<PiS>(
+        &mut parties,
+        &g,
+        3,
+        &dealing::tamper_at(&decrypted_shares, 3, |_| garbage),
+        &share_proofs
+    ));
+}
+
+#[test]
+fn rogue_public_keys_are_rejected() {
+    let mut rng = rand::rng();
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+    let committee = b"pi_s committee";
+
+    let g: RistrettoPoint = RistrettoPoint::mul_base(&random_scalar(&mut rng));
+    let parties = generate_parties(&g, &mut rng, N, T);
+
+    let mut public_keys: Vec<CompressedRistretto> =
+        parties.iter().map(|party| party.public_key.0).collect();
+    let mut proofs: Vec<(Scalar, Scalar)> = parties
+        .iter()
+        .map(|party| {
+            party
+                .prove_possession(&mut rng, &mut hasher, &mut buf, &g, committee)
+                .unwrap()
+        })
+        .collect();
+
+    let dealer = Dealer::new(N, T, &public_keys).unwrap();
+    assert!(
+        dealer
+            .verify_possession(&mut hasher, &mut buf, &g, committee, &proofs)
+            .is_ok()
+    );
+    assert!(
+        ingest_proven_public_keys(
+            &mut hasher,
+            &mut buf,
+            &g,
+            committee,
+            &parties[1].public_key.0,
+            2,
+            (&public_keys, &proofs),
+        )
+        .is_ok()
+    );
+
+    // a key nobody knows the discrete log of
+    public_keys[3] = party::garbage_point(&mut rng);
+    proofs[3] = party::forge_dleq(&mut rng);
+    let dealer = Dealer::new(N, T, &public_keys).unwrap();
+    assert!(matches!(
+        dealer
+            .verify_possession(&mut hasher, &mut buf, &g, committee, &proofs)
+            .unwrap_err()
+            .kind(),
+        InvalidPossessionProof(4)
+    ));
+    assert!(matches!(
+        ingest_proven_public_keys(
+            &mut hasher,
+            &mut buf,
+            &g,
+            committee,
+            &parties[1].public_key.0,
+            2,
+            (&public_keys, &proofs),
+        )
+        .unwrap_err()
+        .kind(),
+        InvalidPossessionProof(4)
+    ));
+
+    // another party's key, with its proof
+    public_keys[3] = public_keys[0];
+    proofs[3] = proofs[0];
+    assert!(matches!(
+        Dealer::new(N, T, &public_keys).err().unwrap().kind(),
+        DuplicatePublicKey(1, 4)
+    ));
+
+    public_keys[3] = RistrettoPoint::default().compress();
+    assert!(matches!(
+        Dealer::new(N, T, &public_keys).err().unwrap().kind(),
+        IdentityPublicKey(4)
+    ));
+}
+
+#[test]
+fn unsigned_or_reordered_messages_are_caught() {
+    let mut rng = rand::rng();
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+    let session = [3u8; 32];
+
+    let (g, xpows, mut dealer, mut parties) = setup(&mut rng);
+    let dealer_key = random_scalar(&mut rng);
+    let dealer_public_key = (g * dealer_key).compress();
+
+    let secret = random_scalar(&mut rng);
+    let dealing = dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret);
+    let dealing = Signed::sign(
+        &mut rng,
+        &mut hasher,
+        &mut buf,
+        &g,
+        &dealer_key,
+        (&session, 1, 0),
+        dealing,
+    );
+
+    // the transport swaps two encrypted shares
+    let mut tampered = dealing.clone();
+    tampered.payload.0 = dealing::swap(&tampered.payload.0, 0, 1);
+    assert!(matches!(
+        parties[0]
+            .ingest_signed_dealing(
+                &mut hasher,
+                &mut buf,
+                &g,
+                &dealer_public_key,
+                (&session, 1),
+                tampered
+            )
+            .unwrap_err()
+            .kind(),
+        InvalidSignature(0)
+    ));
+
+    // replayed into the next round
+    assert!(matches!(
+        parties[0]
+            .ingest_signed_dealing(
+                &mut hasher,
+                &mut buf,
+                &g,
+                &dealer_public_key,
+                (&session, 2),
+                dealing.clone()
+            )
+            .unwrap_err()
+            .kind(),
+        WrongSession(0, 1)
+    ));
+
+    for p in &mut parties {
+        p.ingest_signed_dealing(
+            &mut hasher,
+            &mut buf,
+            &g,
+            &dealer_public_key,
+            (&session, 1),
+            dealing.clone(),
+        )
+        .unwrap();
+        assert!(
+            p.verify_encrypted_shares(&mut hasher, &mut buf, &xpows)
+                .unwrap()
+        );
+    }
+
+    decrypt_and_prove::<PiS, _>(&mut parties, &g, &mut rng);
+    let mut messages: Vec<_> = parties
+        .iter()
+        .map(|p| {
+            p.sign_decrypted_share(&mut rng, &mut hasher, &mut buf, &g, (&session, 2))
+                .unwrap()
+        })
+        .collect();
+    messages.remove(0);
+
+    // delivered in reverse, still placed by sender
+    let mut reversed = messages.clone();
+    reversed.reverse();
+    parties[0]
+        .ingest_signed_decrypted_shares_and_proofs(
+            &mut hasher,
+            &mut buf,
+            &g,
+            (&session, 2),
+            reversed,
+        )
+        .unwrap();
+    assert!(parties[0].verify_decrypted_shares(&g).unwrap());
+    assert_eq!(parties[0].validated_shares.len(), N);
+
+    // payloads of two senders swapped
+    let payload = messages[1].payload;
+    messages[1].payload = messages[2].payload;
+    messages[2].payload = payload;
+    assert!(matches!(
+        parties[0]
+            .ingest_signed_decrypted_shares_and_proofs(
+                &mut hasher,
+                &mut buf,
+                &g,
+                (&session, 2),
+                messages
+            )
+            .unwrap_err()
+            .kind(),
+        InvalidSignature(3)
+    ));
+}
+
+#[test]
+fn session_reconstructs_past_a_forged_reveal() {
+    let mut rng = rand::rng();
+    let (g, xpows, dealer, parties) = setup(&mut rng);
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+    let (mut dealer, mut sessions) = sessions(&mut rng, g, &xpows, dealer, parties);
+
+    let secret = random_scalar(&mut rng);
+    let dealing = dealer
+        .deal(&mut rng, &mut hasher, &mut buf, &secret)
+        .unwrap();
+    // party 3's reveal doesn't verify, everyone else reconstructs without it
+    let rejected = session::run(&mut rng, &mut sessions, dealing, |from, _, mut message| {
+        if let (3, Message::Reveal(reveal)) = (from, &mut message) {
+            session::forge_signature(reveal);
+        }
+        Some(message)
+    });
+    assert_eq!(rejected.len(), N - 1);
+    assert!(rejected.iter().all(|(from, _)| *from == 3));
+    for session in &sessions {
+        assert_eq!(session.phase(), Phase::Done);
+        assert_eq!(session.outcome(), Some(&(g * secret)));
+    }
+}
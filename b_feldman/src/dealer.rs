@@ -1,20 +1,40 @@
+#[cfg(feature = "std")]
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use common::secret_sharing::generate_shares_batched;
 use common::{
+    committee::Committee,
+    dealings::transcript_digest,
+    encryption::{self, Ciphertext, Plaintext},
     error::{Error, ErrorKind::CountMismatch},
+    execution::ExecutionContext,
+    keys::KeyCommitments,
+    opcount,
+    parallel::prelude::*,
+    phase,
     polynomial::Polynomial,
-    secret_sharing::generate_shares_batched,
+    pop::{check_public_keys, verify_public_keys},
+    session::DEALER,
     utils::batch_decompress_ristretto_points,
 };
+use rand::{CryptoRng, RngCore};
 
-use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto, traits::Identity};
-
-use rayon::prelude::*;
+use blake3::Hasher;
+#[cfg(feature = "std")]
+use curve25519_dalek::traits::Identity;
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
 
+#[derive(Clone)]
 pub struct Dealer {
     pub t: usize,
     // [g1...gk]
     pub g: Vec<RistrettoPoint>,
     pub public_keys: Vec<RistrettoPoint>,
     pub(crate) secret: Option<Scalar>,
+    // Feldman commitments of the last dealing, if the dealer publishes them
+    pub key_commitments: Option<KeyCommitments>,
+    pub ctx: ExecutionContext,
 }
 
 impl Dealer {
@@ -27,33 +47,110 @@ impl Dealer {
         if public_keys.len() != n {
             return Err(CountMismatch(n, "parties", public_keys.len(), "public keys").into());
         }
+        check_public_keys(public_keys)?;
         match batch_decompress_ristretto_points(public_keys) {
             Ok(pks) => Ok(Self {
                 t,
                 public_keys: pks,
                 secret: None,
                 g: g.clone(),
+                key_commitments: None,
+                ctx: ExecutionContext::default(),
             }),
             Err(x) => Err(x),
         }
     }
 
+    pub fn from_committee(g: Vec<RistrettoPoint>, committee: &Committee) -> Self {
+        Self {
+            t: committee.t,
+            public_keys: committee.points.clone(),
+            secret: None,
+            g: g.clone(),
+            key_commitments: None,
+            ctx: ExecutionContext::default(),
+        }
+    }
+
+    pub fn with_context(mut self, ctx: ExecutionContext) -> Self {
+        self.ctx = ctx;
+        self
+    }
+
+    // Also commits to the dealt polynomials' coefficients under `g` on every dealing, see
+    // `common::keys`.
+    pub fn with_key_commitments(mut self, g: RistrettoPoint) -> Self {
+        self.key_commitments = Some(KeyCommitments::new(g));
+        self
+    }
+
+    // Checks every party's proof of possession, run it before dealing.
+    pub fn verify_possession(
+        &self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        committee: &[u8],
+        proofs: &[(Scalar, Scalar)],
+    ) -> Result<(), Error> {
+        opcount::compressions(self.public_keys.len());
+        let public_keys: Vec<CompressedRistretto> =
+            self.public_keys.iter().map(|pk| pk.compress()).collect();
+        verify_public_keys(hasher, buf, g, committee, &public_keys, proofs).map(|_| ())
+    }
+
+    // Encrypts `shares[i - 1]` to party i under a key bound to the commitments `c_vals`, as
+    // published in `session_id`.
+    pub fn encrypt_shares<R, S>(
+        &self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        g: &RistrettoPoint,
+        session_id: &[u8; 32],
+        c_vals: &Vec<CompressedRistretto>,
+        shares: &[S],
+    ) -> Result<Vec<Ciphertext>, Error>
+    where
+        R: CryptoRng + RngCore,
+        S: Plaintext,
+    {
+        let transcript = transcript_digest(hasher, &(DEALER, *session_id), c_vals);
+        encryption::encrypt_shares(rng, hasher, g, &self.public_keys, &transcript, shares)
+    }
+
     pub fn t(&self) -> usize {
         self.t
     }
 
+    #[cfg(feature = "std")]
     pub fn deal_secret(
         &mut self,
         x_pows: &Vec<Vec<Scalar>>,
         secrets: &Vec<Scalar>,
     ) -> (Vec<Vec<Scalar>>, Vec<CompressedRistretto>) {
-        let (f_polynomials, f_evals) =
-            generate_shares_batched(self.public_keys.len(), self.t, x_pows, secrets);
+        let _phase = phase!(
+            "deal",
+            n = self.public_keys.len(),
+            t = self.t,
+            k = secrets.len()
+        );
+        let ctx = self.ctx.clone();
+        let mut key_commitments = self.key_commitments.take();
+        let dealing = ctx.install(|| {
+            let (f_polynomials, f_evals) =
+                generate_shares_batched(self.public_keys.len(), self.t, x_pows, secrets);
+            if let Some(keys) = &mut key_commitments {
+                keys.commit(&f_polynomials);
+            }
 
-        let mut c_buf: Vec<CompressedRistretto> = vec![CompressedRistretto::identity(); self.t + 1];
+            let mut c_buf: Vec<CompressedRistretto> =
+                vec![CompressedRistretto::identity(); self.t + 1];
 
-        self.generate_proof(&mut c_buf, &f_polynomials);
-        (f_evals, c_buf)
+            self.prove(&mut c_buf, &f_polynomials);
+            (f_evals, c_buf)
+        });
+        self.key_commitments = key_commitments;
+        dealing
     }
 
     pub fn generate_proof(
@@ -61,12 +158,19 @@ impl Dealer {
         c_buf: &mut Vec<CompressedRistretto>,
         f_polynomials: &Vec<Polynomial>,
     ) {
+        self.ctx.install(|| self.prove(c_buf, f_polynomials))
+    }
+
+    fn prove(&self, c_buf: &mut Vec<CompressedRistretto>, f_polynomials: &Vec<Polynomial>) {
+        let _phase = phase!("prove", n = self.public_keys.len(), t = self.t);
+        opcount::msms(c_buf.len(), f_polynomials.len());
+        opcount::compressions(c_buf.len());
         c_buf.par_iter_mut().enumerate().for_each(|(t, c)| {
             *c = f_polynomials
                 .par_iter()
                 .zip(self.g.par_iter())
                 .map(|(fk, gk)| gk * fk.coef_at_unchecked(t))
-                .reduce(|| RistrettoPoint::identity(), |acc, prod| acc + prod)
+                .sum::<RistrettoPoint>()
                 .compress()
         });
     }
@@ -1,17 +1,28 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod dealer;
 pub mod party;
+pub mod session;
 
 #[cfg(test)]
 
 mod tests {
+    use std::{env, process};
+
     use curve25519_dalek::{RistrettoPoint, ristretto::CompressedRistretto};
 
-    use crate::{dealer::Dealer, party::generate_parties};
+    use crate::{
+        dealer::Dealer,
+        party::{Party, generate_parties},
+    };
 
     use common::{
         precompute::gen_powers,
         random::{random_point, random_points, random_scalars},
         secret_sharing::{reconstruct_secrets, select_qualified_set},
+        snapshot::Vault,
         utils::{compute_lagrange_bases, ingest_public_keys},
     };
 
@@ -62,6 +73,19 @@ mod tests {
             );
             println!("pass own share: {}", p.index);
 
+            // party 1 restarts from its checkpoint once its share verified
+            if p.index == 1 {
+                let vault = Vault::from_key_handle(
+                    &env::temp_dir().join(format!("b_feldman-party-{}", process::id())),
+                    &*p.key,
+                    (&[0u8; 32], p.index),
+                )
+                .unwrap();
+                p.checkpoint(&vault).unwrap();
+                *p = Party::resume(&vault, p.key.clone()).unwrap().unwrap();
+                vault.clear().unwrap();
+            }
+
             p.ingest_shares(&shares).unwrap();
 
             assert!(p.verify_shares().unwrap(), "share verification failure");
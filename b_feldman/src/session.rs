@@ -0,0 +1,153 @@
+use alloc::vec::Vec;
+
+use blake3::Hasher;
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+use rand::{CryptoRng, RngCore};
+
+use common::{
+    encryption::Ciphertext,
+    error::{Error, ErrorKind::UninitializedValue},
+    keystore::SharedKey,
+    report::ShareTracker,
+    secret_sharing::{reconstruct_secrets, select_qualified_set},
+    session::{self, SessionParty},
+    utils::compute_lagrange_bases,
+};
+
+#[cfg(feature = "std")]
+use common::session::SessionDealer;
+
+#[cfg(feature = "std")]
+use crate::dealer::Dealer;
+use crate::party::Party;
+
+#[cfg(feature = "std")]
+pub type DealerSession<'a> = session::DealerSession<'a, Dealer>;
+pub type PartySession<'a> = session::PartySession<'a, Party>;
+// the dealing is the commitments c_vals, the reveal the f_i of its sender
+pub type Message = session::PartyMessage<Party>;
+
+#[cfg(feature = "std")]
+impl SessionDealer for Dealer {
+    type Party = Party;
+    type Secret = Vec<Scalar>;
+
+    fn deal<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        _buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        xpows: &Vec<Vec<Scalar>>,
+        session_id: &[u8; 32],
+        secrets: &Vec<Scalar>,
+    ) -> Result<(Vec<CompressedRistretto>, Vec<Ciphertext>), Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let (shares, c_vals) = self.deal_secret(xpows, secrets);
+        let ciphertexts = self.encrypt_shares(rng, hasher, g, session_id, &c_vals, &shares)?;
+        Ok((c_vals, ciphertexts))
+    }
+}
+
+impl SessionParty for Party {
+    type Dealing = Vec<CompressedRistretto>;
+    type Reveal = Vec<Scalar>;
+    // the secrets
+    type Outcome = Vec<Scalar>;
+    type Evidence = core::convert::Infallible;
+
+    const PRIVATE_SHARES: bool = true;
+    // a share that doesn't decrypt under the delivered dealing is as bad as a wrong one
+    const INVALID_DEALING: &'static str = "share";
+
+    fn index(&self) -> usize {
+        self.index
+    }
+
+    fn n(&self) -> usize {
+        self.n
+    }
+
+    fn key(&self) -> &SharedKey {
+        &self.key
+    }
+
+    fn public_keys(&self) -> Option<&Vec<RistrettoPoint>> {
+        self.public_keys.as_ref()
+    }
+
+    fn tracker(&self) -> Option<&ShareTracker> {
+        self.tracker.as_ref()
+    }
+
+    fn ingest_dealing(&mut self, dealing: Self::Dealing) -> Result<(), Error> {
+        self.ingest_dealer_proof(&dealing)
+    }
+
+    fn transcript_digest(
+        &self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+    ) -> Result<[u8; 32], Error> {
+        Party::transcript_digest(self, hasher, session_id)
+    }
+
+    fn verify_dealing<R>(
+        &mut self,
+        _rng: &mut R,
+        hasher: &mut Hasher,
+        _buf: &mut [u8; 64],
+        _g: &RistrettoPoint,
+        _xpows: &Vec<Vec<Scalar>>,
+        session_id: &[u8; 32],
+        ciphertext: Option<&Ciphertext>,
+    ) -> Result<Option<Vec<Scalar>>, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let ciphertext = ciphertext.ok_or(UninitializedValue("ciphertext"))?;
+        if self
+            .ingest_encrypted_share(hasher, session_id, ciphertext)
+            .is_err()
+            || !self.verify_share()?
+        {
+            return Ok(None);
+        }
+        Ok(self.share.clone())
+    }
+
+    fn check_reveal(
+        &mut self,
+        _hasher: &mut Hasher,
+        _buf: &mut [u8; 64],
+        _g: &RistrettoPoint,
+        _xpows: &Vec<Vec<Scalar>>,
+        sender: usize,
+        reveal: &Vec<Scalar>,
+    ) -> Result<bool, Error> {
+        self.verify_share_from(sender, reveal.clone())
+    }
+
+    fn reconstruct<R>(&mut self, rng: &mut R) -> Result<Vec<Scalar>, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        self.qualified_set = Some(select_qualified_set(
+            rng,
+            self.t,
+            &self.shares,
+            &self.validated_shares,
+        )?);
+        let indices: Vec<usize> = self
+            .qualified_set
+            .iter()
+            .flatten()
+            .map(|(index, _)| *index)
+            .collect();
+        let lagrange_bases = compute_lagrange_bases(&indices);
+
+        reconstruct_secrets(&self.qualified_set, &lagrange_bases)
+    }
+}
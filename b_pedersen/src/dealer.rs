@@ -1,15 +1,31 @@
+#[cfg(feature = "std")]
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use common::secret_sharing::generate_shares_batched;
 use common::{
+    committee::Committee,
+    dealings::transcript_digest,
+    encryption::{self, Ciphertext, Plaintext},
     error::{Error, ErrorKind::CountMismatch},
+    execution::ExecutionContext,
+    keys::KeyCommitments,
+    opcount,
+    parallel::prelude::*,
+    phase,
     polynomial::Polynomial,
-    secret_sharing::generate_shares_batched,
+    pop::{check_public_keys, verify_public_keys},
+    session::DEALER,
     utils::batch_decompress_ristretto_points,
 };
 use rand::{CryptoRng, RngCore};
 
-use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto, traits::Identity};
-
-use rayon::prelude::*;
+use blake3::Hasher;
+#[cfg(feature = "std")]
+use curve25519_dalek::traits::Identity;
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
 
+#[derive(Clone)]
 pub struct Dealer {
     pub t: usize,
     // [g1...gk]
@@ -17,6 +33,9 @@ pub struct Dealer {
     pub g0: RistrettoPoint,
     pub public_keys: Vec<RistrettoPoint>,
     pub(crate) secret: Option<Scalar>,
+    // Feldman commitments of the last dealing, if the dealer publishes them
+    pub key_commitments: Option<KeyCommitments>,
+    pub ctx: ExecutionContext,
 }
 
 impl Dealer {
@@ -30,22 +49,88 @@ impl Dealer {
         if public_keys.len() != n {
             return Err(CountMismatch(n, "parties", public_keys.len(), "public keys").into());
         }
+        check_public_keys(public_keys)?;
         match batch_decompress_ristretto_points(public_keys) {
             Ok(pks) => Ok(Self {
                 t,
                 public_keys: pks,
                 secret: None,
                 g: g.clone(),
-                g0: g0.clone(),
+                g0,
+                key_commitments: None,
+                ctx: ExecutionContext::default(),
             }),
             Err(x) => Err(x),
         }
     }
 
+    pub fn from_committee(
+        g: Vec<RistrettoPoint>,
+        g0: RistrettoPoint,
+        committee: &Committee,
+    ) -> Self {
+        Self {
+            t: committee.t,
+            public_keys: committee.points.clone(),
+            secret: None,
+            g: g.clone(),
+            g0,
+            key_commitments: None,
+            ctx: ExecutionContext::default(),
+        }
+    }
+
+    pub fn with_context(mut self, ctx: ExecutionContext) -> Self {
+        self.ctx = ctx;
+        self
+    }
+
+    // Also commits to the dealt polynomials' coefficients under `g` on every dealing, see
+    // `common::keys`.
+    pub fn with_key_commitments(mut self, g: RistrettoPoint) -> Self {
+        self.key_commitments = Some(KeyCommitments::new(g));
+        self
+    }
+
+    // Checks every party's proof of possession, run it before dealing.
+    pub fn verify_possession(
+        &self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        committee: &[u8],
+        proofs: &[(Scalar, Scalar)],
+    ) -> Result<(), Error> {
+        opcount::compressions(self.public_keys.len());
+        let public_keys: Vec<CompressedRistretto> =
+            self.public_keys.iter().map(|pk| pk.compress()).collect();
+        verify_public_keys(hasher, buf, g, committee, &public_keys, proofs).map(|_| ())
+    }
+
+    // Encrypts `shares[i - 1]` to party i under a key bound to the commitments `c_vals`, as
+    // published in `session_id`.
+    pub fn encrypt_shares<R, S>(
+        &self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        g: &RistrettoPoint,
+        session_id: &[u8; 32],
+        c_vals: &Vec<CompressedRistretto>,
+        shares: &[S],
+    ) -> Result<Vec<Ciphertext>, Error>
+    where
+        R: CryptoRng + RngCore,
+        S: Plaintext,
+    {
+        let transcript = transcript_digest(hasher, &(DEALER, *session_id), c_vals);
+        encryption::encrypt_shares(rng, hasher, g, &self.public_keys, &transcript, shares)
+    }
+
     pub fn t(&self) -> usize {
         self.t
     }
 
+    #[cfg(feature = "std")]
     pub fn deal_secret<R>(
         &mut self,
         rng: &mut R,
@@ -55,13 +140,29 @@ impl Dealer {
     where
         R: CryptoRng + RngCore,
     {
-        let (f_polynomials, f_evals) =
-            generate_shares_batched(self.public_keys.len(), self.t, x_pows, secrets);
+        let _phase = phase!(
+            "deal",
+            n = self.public_keys.len(),
+            t = self.t,
+            k = secrets.len()
+        );
+        let ctx = self.ctx.clone();
+        let mut key_commitments = self.key_commitments.take();
+        let dealing = ctx.install_with_rng(rng, |rng| {
+            let (f_polynomials, f_evals) =
+                generate_shares_batched(self.public_keys.len(), self.t, x_pows, secrets);
+            if let Some(keys) = &mut key_commitments {
+                keys.commit(&f_polynomials);
+            }
 
-        let mut c_buf: Vec<CompressedRistretto> = vec![CompressedRistretto::identity(); self.t + 1];
+            let mut c_buf: Vec<CompressedRistretto> =
+                vec![CompressedRistretto::identity(); self.t + 1];
 
-        let r_evals = self.generate_proof(rng, &mut c_buf, x_pows, &f_polynomials);
-        (f_evals, (r_evals, c_buf))
+            let r_evals = self.prove(rng, &mut c_buf, x_pows, &f_polynomials);
+            (f_evals, (r_evals, c_buf))
+        });
+        self.key_commitments = key_commitments;
+        dealing
     }
 
     pub fn generate_proof<R>(
@@ -74,18 +175,36 @@ impl Dealer {
     where
         R: CryptoRng,
     {
+        self.ctx
+            .install_with_rng(rng, |rng| self.prove(rng, c_buf, x_pows, f_polynomials))
+    }
+
+    fn prove<R>(
+        &self,
+        rng: &mut R,
+        c_buf: &mut Vec<CompressedRistretto>,
+        x_pows: &Vec<Vec<Scalar>>,
+        f_polynomials: &Vec<Polynomial>,
+    ) -> Vec<Scalar>
+    where
+        R: CryptoRng,
+    {
+        let _phase = phase!("prove", n = self.public_keys.len(), t = self.t);
         let r = Polynomial::sample(self.t, rng);
         let r_evals = r.evaluate_range_precomp(x_pows, 1, self.public_keys.len());
+        opcount::msms(r.len(), f_polynomials.len() + 1);
+        opcount::compressions(r.len());
 
         r.coef_ref()
             .par_iter()
             .enumerate()
             .map(|(t, r_coef)| {
-                f_polynomials
+                (f_polynomials
                     .par_iter()
                     .zip(self.g.par_iter())
                     .map(|(fk, gk)| gk * fk.coef_at_unchecked(t))
-                    .reduce(|| self.g0 * r_coef, |acc, prod| acc + prod)
+                    .sum::<RistrettoPoint>()
+                    + self.g0 * r_coef)
                     .compress()
             })
             .collect_into_vec(c_buf);
@@ -1,23 +1,39 @@
-use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto, traits::Identity};
+use alloc::{format, vec, vec::Vec};
+use blake3::Hasher;
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
 use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "std")]
+use common::snapshot::Vault;
 use common::{
+    committee::Committee,
+    dealings::transcript_digest,
+    encryption::{Ciphertext, decrypt},
     error::{
         Error,
         ErrorKind::{
-            CountMismatch, InvalidPararmeterSet, InvalidProof, PointDecompressionError,
-            UninitializedValue,
+            CountMismatch, DuplicateMessage, ForeignPublicKey, InvalidPararmeterSet, InvalidProof,
+            PointDecompressionError, UninitializedValue,
         },
     },
-    random::random_scalar,
+    execution::ExecutionContext,
+    keys::{KeyCommitments, VerificationKeys, check_shares},
+    keystore::{MemoryKey, SharedKey},
+    opcount,
+    parallel::prelude::*,
+    phase,
+    pop::prove_key_possession,
+    report::{ShareCheck, ShareStatus, ShareTracker, VerificationReport},
+    session::DEALER,
+    verification_failure, verified,
 };
-use rayon::prelude::*;
 
 #[derive(Clone)]
 pub struct Party {
     pub g: Vec<RistrettoPoint>,
     pub g0: RistrettoPoint,
-    pub private_key: Scalar,
+    pub key: SharedKey,
     pub public_key: (CompressedRistretto, RistrettoPoint),
     pub index: usize,
     pub n: usize,
@@ -25,10 +41,37 @@ pub struct Party {
     pub public_keys: Option<Vec<RistrettoPoint>>,
     pub dealer_proof: Option<(Vec<CompressedRistretto>, Vec<RistrettoPoint>)>,
     pub validated_shares: Vec<usize>,
+    // the running valid set while shares are checked on arrival
+    pub tracker: Option<ShareTracker>,
     pub share: Option<(Vec<Scalar>, Scalar)>,
     pub d: Option<Scalar>,
     pub shares: Option<Vec<(Vec<Scalar>, Scalar)>>,
     pub qualified_set: Option<Vec<(usize, Vec<Scalar>)>>,
+    // (generator, decompressed commitments) the dealer published, see `common::keys`
+    pub key_commitments: Option<(RistrettoPoint, Vec<Vec<RistrettoPoint>>)>,
+    pub ctx: ExecutionContext,
+}
+
+// Everything a party holds but its key and execution context, its private share included,
+// so snapshots only go into a `Vault`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PartySnapshot {
+    pub g: Vec<RistrettoPoint>,
+    pub g0: RistrettoPoint,
+    pub public_key: CompressedRistretto,
+    pub index: usize,
+    pub n: usize,
+    pub t: usize,
+    pub public_keys: Option<Vec<RistrettoPoint>>,
+    // c_vals as the dealer sent them
+    pub dealer_proof: Option<Vec<CompressedRistretto>>,
+    pub validated_shares: Vec<usize>,
+    pub tracker: Option<ShareTracker>,
+    pub share: Option<(Vec<Scalar>, Scalar)>,
+    pub d: Option<Scalar>,
+    pub shares: Option<Vec<(Vec<Scalar>, Scalar)>>,
+    pub qualified_set: Option<Vec<(usize, Vec<Scalar>)>>,
+    pub key_commitments: Option<(RistrettoPoint, Vec<Vec<RistrettoPoint>>)>,
 }
 
 impl Party {
@@ -44,15 +87,31 @@ impl Party {
     where
         R: CryptoRng + RngCore,
     {
-        let private_key = random_scalar(rng);
-        let public_key = generator * &private_key;
+        Self::with_key(
+            MemoryKey::generate(rng, generator).shared(),
+            g,
+            g0,
+            n,
+            t,
+            index,
+        )
+    }
 
-        if index <= n && t < n && t as f32 == ((n - 1) as f32 / 2.0).floor() {
+    // A party around an existing key.
+    pub fn with_key(
+        key: SharedKey,
+        g: Vec<RistrettoPoint>,
+        g0: RistrettoPoint,
+        n: usize,
+        t: usize,
+        index: usize,
+    ) -> Result<Self, Error> {
+        if index <= n && t < n && t == (n - 1) / 2 {
             Ok(Self {
                 g: g.clone(),
-                g0: g0.clone(),
-                private_key,
-                public_key: (public_key.compress(), public_key),
+                g0,
+                public_key: *key.public_key(),
+                key,
                 index,
                 n,
                 t,
@@ -60,30 +119,154 @@ impl Party {
                 share: None,
                 public_keys: None,
                 validated_shares: vec![],
+                tracker: None,
                 d: None,
                 shares: None,
                 qualified_set: None,
+                key_commitments: None,
+                ctx: ExecutionContext::default(),
             })
         } else {
             Err(InvalidPararmeterSet(n, t as isize, index).into())
         }
     }
 
+    pub fn from_committee(
+        committee: &Committee,
+        g: Vec<RistrettoPoint>,
+        g0: RistrettoPoint,
+        key: SharedKey,
+        index: usize,
+    ) -> Result<Self, Error> {
+        committee.check_key(&key.public_key().1, index)?;
+
+        Ok(Self {
+            g: g.clone(),
+            g0,
+            public_key: (
+                committee.public_keys[index - 1],
+                committee.points[index - 1],
+            ),
+            key,
+            index,
+            n: committee.n,
+            t: committee.t,
+            dealer_proof: None,
+            share: None,
+            public_keys: Some(committee.points.clone()),
+            validated_shares: vec![],
+            tracker: None,
+            d: None,
+            shares: None,
+            qualified_set: None,
+            key_commitments: None,
+            ctx: ExecutionContext::default(),
+        })
+    }
+
+    pub fn with_context(mut self, ctx: ExecutionContext) -> Self {
+        self.ctx = ctx;
+        self
+    }
+
+    pub fn snapshot(&self) -> PartySnapshot {
+        PartySnapshot {
+            g: self.g.clone(),
+            g0: self.g0,
+            public_key: self.public_key.0,
+            index: self.index,
+            n: self.n,
+            t: self.t,
+            public_keys: self.public_keys.clone(),
+            dealer_proof: self.dealer_proof.as_ref().map(|(c_vals, _)| c_vals.clone()),
+            validated_shares: self.validated_shares.clone(),
+            tracker: self.tracker.clone(),
+            share: self.share.clone(),
+            d: self.d,
+            shares: self.shares.clone(),
+            qualified_set: self.qualified_set.clone(),
+            key_commitments: self.key_commitments.clone(),
+        }
+    }
+
+    // Picks a party back up from its snapshot, `key` must be the key it was taken with.
+    pub fn restore(snapshot: PartySnapshot, key: SharedKey) -> Result<Self, Error> {
+        if key.public_key().0 != snapshot.public_key {
+            return Err(ForeignPublicKey(snapshot.index).into());
+        }
+        let mut party = Self::with_key(
+            key,
+            snapshot.g,
+            snapshot.g0,
+            snapshot.n,
+            snapshot.t,
+            snapshot.index,
+        )?;
+        party.public_keys = snapshot.public_keys;
+        if let Some(c_vals) = snapshot.dealer_proof {
+            party.ingest_dealer_proof(&c_vals)?;
+        }
+        party.validated_shares = snapshot.validated_shares;
+        party.tracker = snapshot.tracker;
+        party.share = snapshot.share;
+        party.d = snapshot.d;
+        party.shares = snapshot.shares;
+        party.qualified_set = snapshot.qualified_set;
+        party.key_commitments = snapshot.key_commitments;
+        Ok(party)
+    }
+
+    // Saves the party's state, call it after each phase and before sending what the phase
+    // produced.
+    #[cfg(feature = "std")]
+    pub fn checkpoint(&self, vault: &Vault) -> Result<(), Error> {
+        vault.save(&self.snapshot())
+    }
+
+    // The party as last checkpointed in `vault`, `None` if it never was.
+    #[cfg(feature = "std")]
+    pub fn resume(vault: &Vault, key: SharedKey) -> Result<Option<Self>, Error> {
+        match vault.load()? {
+            Some(snapshot) => Self::restore(snapshot, key).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // Proof of knowledge of our private key for registering `public_key` under `index`.
+    pub fn prove_possession<R>(
+        &self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        committee: &[u8],
+    ) -> Result<(Scalar, Scalar), Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        prove_key_possession(rng, hasher, buf, g, &*self.key, self.index, committee)
+    }
+
     pub fn ingest_share(&mut self, share: (&Vec<Scalar>, &Scalar)) {
+        let _phase = phase!("ingest", n = self.n, t = self.t, k = self.g.len());
         assert!(share.0.len() == self.g.len());
         self.share = Some((share.0.clone(), share.1.clone()));
     }
 
     pub fn ingest_dealer_proof(&mut self, proof: &Vec<CompressedRistretto>) -> Result<(), Error> {
+        let _phase = phase!("ingest", n = self.n, t = self.t, k = self.g.len());
         if proof.len() != self.t + 1 {
+            verification_failure!("malformed_proof");
             Err(InvalidProof(format!("c_vals len: {}, t: {}", proof.len(), self.t + 1)).into())
         } else {
             let mut decompressed_c_vals = Vec::with_capacity(self.n);
 
+            opcount::decompressions(proof.len());
             for c_i in proof {
                 match c_i.decompress() {
                     Some(c) => decompressed_c_vals.push(c),
                     None => {
+                        verification_failure!("point_decompression");
                         return Err(Error::from_kind(PointDecompressionError(format!(
                             "{c_i:?}",
                         ))));
@@ -95,67 +278,167 @@ impl Party {
         }
     }
 
-    pub fn verify_share(&self) -> Result<bool, Error> {
+    // Transcript digest of the ingested commitments, as the dealer published them in
+    // `session_id`. The private shares aren't part of it.
+    pub fn transcript_digest(
+        &self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+    ) -> Result<[u8; 32], Error> {
         match &self.dealer_proof {
+            Some((compressed_cvals, _)) => Ok(transcript_digest(
+                hasher,
+                &(DEALER, *session_id),
+                compressed_cvals,
+            )),
+            None => Err(UninitializedValue("party.dealer_proof").into()),
+        }
+    }
+
+    // Decrypts our share of the ingested dealing published in `session_id` and ingests it,
+    // a share that doesn't authenticate under that dealing is rejected.
+    pub fn ingest_encrypted_share(
+        &mut self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+        ciphertext: &Ciphertext,
+    ) -> Result<(), Error> {
+        let transcript = self.transcript_digest(hasher, session_id)?;
+        let share: (Vec<Scalar>, Scalar) =
+            decrypt(hasher, (self.index, &*self.key), &transcript, ciphertext)?;
+        self.ingest_share((&share.0, &share.1));
+        Ok(())
+    }
+
+    pub fn verify_share(&self) -> Result<bool, Error> {
+        let _phase = phase!("verify", n = self.n, t = self.t, k = self.g.len());
+        opcount::msms(1, self.g.len() + 1);
+        opcount::msms(1, self.t + 1);
+        self.ctx.install(|| match &self.dealer_proof {
             Some((_, cvals)) => match &self.share {
                 Some((fi, ri)) => {
                     let a = fi
                         .par_iter()
                         .zip(self.g.par_iter())
                         .map(|(fik, gk)| fik * gk)
-                        .reduce(|| self.g0 * ri, |acc, prod| acc + prod);
+                        .sum::<RistrettoPoint>()
+                        + self.g0 * ri;
 
                     let b = cvals
                         .par_iter()
                         .enumerate()
                         .map(|(t, c)| c * Scalar::from(self.index.pow(t as u32) as u64))
-                        .reduce(|| RistrettoPoint::identity(), |acc, prod| acc + prod);
+                        .sum::<RistrettoPoint>();
 
-                    Ok(a == b)
+                    Ok(verified!(a == b, "share"))
                 }
                 None => Err(UninitializedValue("party.share").into()),
             },
             None => Err(UninitializedValue("party.dealer_proof").into()),
-        }
+        })
     }
 
     pub fn verify_shares(&mut self) -> Result<bool, Error> {
-        match &self.dealer_proof {
+        let report = self.share_report()?;
+        self.validated_shares = report.valid();
+        Ok(verified!(report.is_sufficient(), "insufficient_shares"))
+    }
+
+    // Every party's share with its status, see `VerificationReport`.
+    pub fn share_report(&self) -> Result<VerificationReport, Error> {
+        let _phase = phase!("verify", n = self.n, t = self.t, k = self.g.len());
+        self.ctx.install(|| match &self.dealer_proof {
             Some((_, cvals)) => match &self.shares {
                 Some(shares) => {
-                    self.validated_shares = shares
+                    opcount::msms(shares.len(), self.g.len() + 1);
+                    opcount::msms(shares.len(), self.t + 1);
+                    let checks: Vec<ShareCheck> = shares
                         .iter()
                         .enumerate()
-                        .map(|(i, (fi, ri))| {
-                            let a = fi
-                                .par_iter()
-                                .zip(self.g.par_iter())
-                                .map(|(fik, gk)| fik * gk)
-                                .reduce(|| self.g0 * ri, |acc, prod| acc + prod);
-
-                            let b = cvals
-                                .par_iter()
-                                .enumerate()
-                                .map(|(t, c)| c * Scalar::from((i + 1).pow(t as u32) as u64))
-                                .reduce(|| RistrettoPoint::identity(), |acc, prod| acc + prod);
-
-                            if a == b { Some(i) } else { None }
-                        })
-                        .filter(Option::is_some)
-                        .map(|res| res.unwrap())
+                        .map(|(i, share)| self.check_share(cvals, i + 1, share))
                         .collect();
-                    Ok(self.validated_shares.len() > self.t)
+                    Ok(VerificationReport::new(self.n, self.t, None, checks))
                 }
                 None => Err(UninitializedValue("party.share").into()),
             },
             None => Err(UninitializedValue("party.dealer_proof").into()),
+        })
+    }
+
+    // Checks party `index`'s shares as they arrive, ours included, and keeps them with the
+    // others in `shares` if they hold. Parties that haven't sent, or whose shares failed, have
+    // none there, and a second message from a party is an error. True once, when more than t
+    // of them hold, see `ShareTracker`.
+    pub fn verify_share_from(
+        &mut self,
+        index: usize,
+        share: (Vec<Scalar>, Scalar),
+    ) -> Result<bool, Error> {
+        let _phase = phase!("verify", n = self.n, t = self.t, k = self.g.len());
+        if index == 0 || index > self.n {
+            return Err(InvalidPararmeterSet(self.n, self.t as isize, index).into());
+        }
+        if self
+            .tracker
+            .as_ref()
+            .is_some_and(|tracker| tracker.check(index).is_some())
+        {
+            return Err(DuplicateMessage(index).into());
+        }
+        let check = match &self.dealer_proof {
+            Some((_, cvals)) => {
+                opcount::msms(1, self.g.len() + 1);
+                opcount::msms(1, self.t + 1);
+                self.check_share(cvals, index, &share)
+            }
+            None => return Err(UninitializedValue("party.dealer_proof").into()),
+        };
+        let valid = check.status == ShareStatus::Valid;
+        let tracker = self
+            .tracker
+            .get_or_insert_with(|| ShareTracker::new(self.n, self.t, None));
+        let sufficient = tracker.record(check)?;
+        self.validated_shares = tracker.valid();
+        // only shares that hold are kept, the others stay placeholders
+        if valid {
+            self.shares
+                .get_or_insert_with(|| vec![(vec![], Scalar::ZERO); self.n])[index - 1] = share;
         }
+        Ok(sufficient)
+    }
+
+    // prod g_k^fi_k g0^ri = prod C_j^(i^j)
+    fn check_share(
+        &self,
+        cvals: &[RistrettoPoint],
+        index: usize,
+        (fi, ri): &(Vec<Scalar>, Scalar),
+    ) -> ShareCheck {
+        let a = fi
+            .par_iter()
+            .zip(self.g.par_iter())
+            .map(|(fik, gk)| fik * gk)
+            .sum::<RistrettoPoint>()
+            + self.g0 * ri;
+
+        let b = cvals
+            .par_iter()
+            .enumerate()
+            .map(|(t, c)| c * Scalar::from(index.pow(t as u32) as u64))
+            .sum::<RistrettoPoint>();
+
+        ShareCheck::checked(
+            index,
+            verified!(fi.len() == self.g.len() && a == b, "share"),
+            ShareStatus::CommitmentMismatch,
+        )
     }
 
     pub fn ingest_shares(
         &mut self,
         shares: (&Vec<Vec<Scalar>>, &Vec<Scalar>),
     ) -> Result<(), Error> {
+        let _phase = phase!("ingest", n = self.n, t = self.t, k = self.g.len());
         if shares.0.len() == self.n && shares.1.len() == self.n {
             self.shares = Some(
                 shares
@@ -170,6 +453,43 @@ impl Party {
             Err(CountMismatch(self.n, "parties", shares.0.len(), "ingestable shares").into())
         }
     }
+    // The dealer's commitments to the coefficients of the dealt polynomials, which must be
+    // under `g`, the generator the verification keys are wanted for.
+    pub fn ingest_key_commitments(
+        &mut self,
+        g: &RistrettoPoint,
+        keys: &KeyCommitments,
+    ) -> Result<(), Error> {
+        let _phase = phase!("ingest", n = self.n, t = self.t, k = keys.commitments.len());
+        self.key_commitments = Some((*g, keys.open(g, keys.commitments.len(), self.t)?));
+        Ok(())
+    }
+
+    // True if the key commitments give our validated shares as our verification keys, which
+    // ties them to the dealing.
+    pub fn verify_key_commitments(&self, x_pows: &[Vec<Scalar>]) -> Result<bool, Error> {
+        match (&self.key_commitments, &self.share) {
+            (Some((g, commitments)), Some(share)) => {
+                Ok(check_shares(g, commitments, x_pows, self.index, &share.0))
+            }
+            (None, _) => Err(UninitializedValue("party.key_commitments").into()),
+            (_, None) => Err(UninitializedValue("party.share").into()),
+        }
+    }
+
+    // The group public key and every party's verification key, for each secret.
+    pub fn verification_keys(
+        &self,
+        x_pows: &[Vec<Scalar>],
+    ) -> Result<Vec<VerificationKeys>, Error> {
+        match &self.key_commitments {
+            Some((_, commitments)) => Ok(commitments
+                .iter()
+                .map(|commitments| VerificationKeys::from_commitments(commitments, x_pows, self.n))
+                .collect()),
+            None => Err(UninitializedValue("party.key_commitments").into()),
+        }
+    }
 }
 
 pub fn generate_parties<R>(
@@ -1,17 +1,29 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod dealer;
 pub mod party;
+pub mod session;
 
 #[cfg(test)]
 
 mod tests {
+    use std::{env, process};
+
     use curve25519_dalek::{RistrettoPoint, ristretto::CompressedRistretto};
 
-    use crate::{dealer::Dealer, party::generate_parties};
+    use crate::{
+        dealer::Dealer,
+        party::{Party, generate_parties},
+    };
 
     use common::{
+        fixture::{Fixture, N, T},
         precompute::gen_powers,
         random::{random_point, random_points, random_scalars},
         secret_sharing::{reconstruct_secrets, select_qualified_set},
+        snapshot::Vault,
         utils::{compute_lagrange_bases, ingest_public_keys},
     };
 
@@ -64,6 +76,19 @@ mod tests {
                 "share verification failure"
             );
 
+            // party 1 restarts from its checkpoint once its share verified
+            if p.index == 1 {
+                let vault = Vault::from_key_handle(
+                    &env::temp_dir().join(format!("b_pi_f-party-{}", process::id())),
+                    &*p.key,
+                    (&[0u8; 32], p.index),
+                )
+                .unwrap();
+                p.checkpoint(&vault).unwrap();
+                *p = Party::resume(&vault, p.key.clone()).unwrap().unwrap();
+                vault.clear().unwrap();
+            }
+
             p.ingest_shares(&shares).unwrap();
 
             assert!(
@@ -89,4 +114,65 @@ mod tests {
             assert!(secrets == sec, "Invalid Reconstructed Secret");
         }
     }
+
+    #[test]
+    fn verification_keys() {
+        const K: usize = 3;
+
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g: generator,
+            xpows,
+        } = Fixture::new(N, T);
+
+        let g: Vec<RistrettoPoint> = random_points(&mut rng, K);
+        let g2: RistrettoPoint = random_point(&mut rng);
+
+        let mut parties = generate_parties(&generator, &g, &g2, &mut rng, N, T);
+        let public_keys: Vec<CompressedRistretto> =
+            parties.iter().map(|party| party.public_key.0).collect();
+        let mut dealer = Dealer::new(g, g2, N, T, &public_keys)
+            .unwrap()
+            .with_key_commitments(generator);
+
+        let secrets = random_scalars(&mut rng, K);
+        let (shares, (c_vals, z)) =
+            dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secrets);
+        let mut key_commitments = dealer.key_commitments.clone().unwrap();
+
+        for p in &mut parties {
+            p.ingest_dealer_proof((&c_vals, &z)).unwrap();
+            p.ingest_share(&shares[p.index - 1]);
+            assert!(p.verify_share(&mut hasher, &mut buf, &xpows).unwrap());
+
+            p.ingest_key_commitments(&generator, &key_commitments)
+                .unwrap();
+            assert!(p.verify_key_commitments(&xpows).unwrap());
+            let keys = p.verification_keys(&xpows).unwrap();
+            for (k, keys) in keys.iter().enumerate() {
+                assert_eq!(keys.group_key, generator * secrets[k]);
+                assert_eq!(
+                    keys.key(p.index),
+                    Some(&(generator * shares[p.index - 1][k]))
+                );
+            }
+        }
+
+        // the dealer swaps two secrets' commitments, no party's shares match them
+        key_commitments.commitments.swap(0, 1);
+        for p in &mut parties {
+            p.ingest_key_commitments(&generator, &key_commitments)
+                .unwrap();
+            assert!(!p.verify_key_commitments(&xpows).unwrap());
+        }
+        key_commitments.commitments.pop();
+        assert!(
+            parties[0]
+                .ingest_key_commitments(&generator, &key_commitments)
+                .is_ok()
+        );
+        assert!(!parties[0].verify_key_commitments(&xpows).unwrap());
+    }
 }
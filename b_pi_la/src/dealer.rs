@@ -1,19 +1,36 @@
+#[cfg(feature = "std")]
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use common::secret_sharing::generate_shares_batched;
 use common::{
+    committee::Committee,
+    dealings::transcript_digest,
+    encryption::{self, Ciphertext, Plaintext},
     error::{Error, ErrorKind::CountMismatch},
+    execution::ExecutionContext,
+    keys::KeyCommitments,
+    opcount,
+    parallel::prelude::*,
+    phase,
     polynomial::Polynomial,
-    secret_sharing::generate_shares_batched,
+    pop::{check_public_keys, verify_public_keys},
+    session::DEALER,
     utils::{batch_decompress_ristretto_points, compute_d_powers_from_hash_commitments},
 };
 use rand::{CryptoRng, RngCore};
 
 use blake3::Hasher;
 use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
-use rayon::prelude::*;
 
+#[derive(Clone)]
 pub struct Dealer {
     pub t: usize,
     pub public_keys: Vec<RistrettoPoint>,
     pub(crate) secrets: Option<Vec<Scalar>>,
+    // Feldman commitments of the last dealing, if the dealer publishes them
+    pub key_commitments: Option<KeyCommitments>,
+    pub ctx: ExecutionContext,
 }
 
 impl Dealer {
@@ -21,16 +38,75 @@ impl Dealer {
         if public_keys.len() != n {
             return Err(CountMismatch(n, "parties", public_keys.len(), "public keys").into());
         }
+        check_public_keys(public_keys)?;
         match batch_decompress_ristretto_points(public_keys) {
             Ok(pks) => Ok(Self {
                 t,
                 public_keys: pks,
                 secrets: None,
+                key_commitments: None,
+                ctx: ExecutionContext::default(),
             }),
             Err(x) => Err(x),
         }
     }
 
+    pub fn from_committee(committee: &Committee) -> Self {
+        Self {
+            t: committee.t,
+            public_keys: committee.points.clone(),
+            secrets: None,
+            key_commitments: None,
+            ctx: ExecutionContext::default(),
+        }
+    }
+
+    pub fn with_context(mut self, ctx: ExecutionContext) -> Self {
+        self.ctx = ctx;
+        self
+    }
+
+    // Also commits to the dealt polynomials' coefficients under `g` on every dealing, see
+    // `common::keys`.
+    pub fn with_key_commitments(mut self, g: RistrettoPoint) -> Self {
+        self.key_commitments = Some(KeyCommitments::new(g));
+        self
+    }
+
+    // Checks every party's proof of possession, run it before dealing.
+    pub fn verify_possession(
+        &self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        committee: &[u8],
+        proofs: &[(Scalar, Scalar)],
+    ) -> Result<(), Error> {
+        opcount::compressions(self.public_keys.len());
+        let public_keys: Vec<CompressedRistretto> =
+            self.public_keys.iter().map(|pk| pk.compress()).collect();
+        verify_public_keys(hasher, buf, g, committee, &public_keys, proofs).map(|_| ())
+    }
+
+    // Encrypts `shares[i - 1]` to party i under a key bound to `dealing`, as published in
+    // `session_id`.
+    pub fn encrypt_shares<R, S>(
+        &self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        g: &RistrettoPoint,
+        session_id: &[u8; 32],
+        dealing: (&Vec<[u8; 64]>, &Polynomial),
+        shares: &[S],
+    ) -> Result<Vec<Ciphertext>, Error>
+    where
+        R: CryptoRng + RngCore,
+        S: Plaintext,
+    {
+        let transcript = transcript_digest(hasher, &(DEALER, *session_id), &dealing);
+        encryption::encrypt_shares(rng, hasher, g, &self.public_keys, &transcript, shares)
+    }
+
     pub fn t(&self) -> usize {
         self.t
     }
@@ -43,6 +119,7 @@ impl Dealer {
         self.secrets.clone().unwrap()
     }
 
+    #[cfg(feature = "std")]
     pub fn deal_secrets<R>(
         &mut self,
         rng: &mut R,
@@ -54,26 +131,36 @@ impl Dealer {
     where
         R: CryptoRng + RngCore,
     {
-        // number of secrets to share
-        let k = secrets.len();
-
-        let (mut f_polynomials, f_evals) =
-            generate_shares_batched(self.public_keys.len(), self.t, x_pows, secrets);
-
-        let mut c_buf = vec![[0u8; 64]; self.public_keys.len()];
-
-        let z = self.generate_proof(
-            rng,
-            hasher,
-            buf,
-            &mut c_buf,
-            x_pows,
-            k,
-            &mut f_polynomials,
-            &f_evals,
+        let _phase = phase!(
+            "deal",
+            n = self.public_keys.len(),
+            t = self.t,
+            k = secrets.len()
         );
+        let ctx = self.ctx.clone();
+        let mut key_commitments = self.key_commitments.take();
+        let dealing = ctx.install_with_rng(rng, |rng| {
+            let (f_polynomials, f_evals) =
+                generate_shares_batched(self.public_keys.len(), self.t, x_pows, secrets);
+            if let Some(keys) = &mut key_commitments {
+                keys.commit(&f_polynomials);
+            }
+
+            let mut c_buf = vec![[0u8; 64]; self.public_keys.len()];
+
+            let z = self.prove(
+                rng,
+                (hasher, buf),
+                &mut c_buf,
+                x_pows,
+                &f_polynomials,
+                &f_evals,
+            );
 
-        (f_evals, (c_buf, z))
+            (f_evals, (c_buf, z))
+        });
+        self.key_commitments = key_commitments;
+        dealing
     }
 
     pub fn generate_proof<R>(
@@ -83,36 +170,55 @@ impl Dealer {
         buf: &mut [u8; 64],
         c_buf: &mut Vec<[u8; 64]>,
         x_pows: &Vec<Vec<Scalar>>,
-        k: usize,
-        f_polynomials: &Vec<Polynomial>,
-        f_evals: &Vec<Vec<Scalar>>,
+        f_polynomials: &[Polynomial],
+        f_evals: &[Vec<Scalar>],
     ) -> Polynomial
     where
         R: CryptoRng,
     {
+        self.ctx.install_with_rng(rng, |rng| {
+            self.prove(rng, (hasher, buf), c_buf, x_pows, f_polynomials, f_evals)
+        })
+    }
+
+    fn prove<R>(
+        &self,
+        rng: &mut R,
+        (hasher, buf): (&mut Hasher, &mut [u8; 64]),
+        c_buf: &mut Vec<[u8; 64]>,
+        x_pows: &Vec<Vec<Scalar>>,
+        f_polynomials: &[Polynomial],
+        f_evals: &[Vec<Scalar>],
+    ) -> Polynomial
+    where
+        R: CryptoRng,
+    {
+        let k = f_polynomials.len();
+        let _phase = phase!("prove", n = self.public_keys.len(), t = self.t, k = k);
         let mut r = Polynomial::sample(self.t, rng);
 
         let r_evals = r.evaluate_range_precomp(x_pows, 1, self.public_keys.len());
+        opcount::hashes(
+            self.public_keys.len(),
+            32 * (k + 1) * self.public_keys.len(),
+        );
 
         c_buf
             .par_iter_mut()
             .zip(f_evals.par_iter().zip(r_evals.par_iter()))
-            .for_each_init(
-                || Hasher::new(),
-                |l_hasher, (l_buf, (fi, ri))| {
-                    fi.iter().for_each(|fi_k| {
-                        l_hasher.update(fi_k.as_bytes());
-                    });
-
-                    l_hasher.update(ri.as_bytes());
-
-                    l_hasher.finalize_xof().fill(l_buf);
-                    l_hasher.reset();
-                },
-            );
+            .for_each_init(Hasher::new, |l_hasher, (l_buf, (fi, ri))| {
+                fi.iter().for_each(|fi_k| {
+                    l_hasher.update(fi_k.as_bytes());
+                });
+
+                l_hasher.update(ri.as_bytes());
+
+                l_hasher.finalize_xof().fill(l_buf);
+                l_hasher.reset();
+            });
 
         // [d, d^2, ..., d^k]
-        let d_vals = compute_d_powers_from_hash_commitments(hasher, buf, &c_buf, k);
+        let d_vals = compute_d_powers_from_hash_commitments(hasher, buf, c_buf, k);
 
         // z == r += ( ∑ d_j * f_j )
         r.compute_z(f_polynomials, &d_vals);
@@ -1,17 +1,30 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod dealer;
 pub mod party;
+pub mod session;
 
 #[cfg(test)]
 
 mod tests {
+    use std::{env, process};
+
     use curve25519_dalek::{RistrettoPoint, ristretto::CompressedRistretto};
 
-    use crate::{dealer::Dealer, party::generate_parties};
+    use crate::{
+        dealer::Dealer,
+        party::{Party, generate_parties},
+    };
 
     use common::{
+        error::ErrorKind::InvalidCiphertext,
+        fixture::{Fixture, N, T},
         precompute::gen_powers,
         random::{random_point, random_scalars},
         secret_sharing::{reconstruct_secrets, select_qualified_set},
+        snapshot::Vault,
         utils::{compute_lagrange_bases, ingest_public_keys},
     };
 
@@ -64,6 +77,19 @@ mod tests {
                 "share verification failure"
             );
 
+            // party 1 restarts from its checkpoint once its share verified
+            if p.index == 1 {
+                let vault = Vault::from_key_handle(
+                    &env::temp_dir().join(format!("b_pi_la-party-{}", process::id())),
+                    &*p.key,
+                    (&[0u8; 32], p.index),
+                )
+                .unwrap();
+                p.checkpoint(&vault).unwrap();
+                *p = Party::resume(&vault, p.key.clone()).unwrap().unwrap();
+                vault.clear().unwrap();
+            }
+
             p.ingest_shares(&shares).unwrap();
 
             let verif_result = p.verify_shares(&mut hasher, &mut buf, &xpows).unwrap();
@@ -88,4 +114,56 @@ mod tests {
             assert!(secrets == sec, "Invalid Reconstructed Secret");
         }
     }
+
+    #[test]
+    fn encrypted_shares() {
+        const K: usize = 3;
+
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            xpows,
+        } = Fixture::new(N, T);
+        let session_id = [5u8; 32];
+
+        let mut parties = generate_parties(&g, &mut rng, N, T);
+        let public_keys: Vec<CompressedRistretto> =
+            parties.iter().map(|party| party.public_key.0).collect();
+        let mut dealer = Dealer::new(N, T, &public_keys).unwrap();
+
+        let secrets = random_scalars(&mut rng, K);
+        let (shares, (c_vals, z)) =
+            dealer.deal_secrets(&mut rng, &mut hasher, &mut buf, &xpows, &secrets);
+        let mut ciphertexts = dealer
+            .encrypt_shares(
+                &mut rng,
+                &mut hasher,
+                &g,
+                &session_id,
+                (&c_vals, &z),
+                &shares,
+            )
+            .unwrap();
+        ciphertexts[0].body[0] ^= 1;
+
+        for p in &mut parties {
+            p.ingest_dealer_proof((&c_vals, &z)).unwrap();
+
+            let ingested =
+                p.ingest_encrypted_share(&mut hasher, &session_id, &ciphertexts[p.index - 1]);
+            if p.index == 1 {
+                assert!(matches!(
+                    ingested.err().unwrap().kind(),
+                    InvalidCiphertext(1)
+                ));
+                assert!(p.share.is_none());
+            } else {
+                ingested.unwrap();
+                assert_eq!(p.share.as_ref(), Some(&shares[p.index - 1]));
+                assert!(p.verify_share(&mut hasher, &mut buf, &xpows).unwrap());
+            }
+        }
+    }
 }
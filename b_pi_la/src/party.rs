@@ -1,22 +1,42 @@
+use alloc::{format, vec, vec::Vec};
 use blake3::Hasher;
 use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
 use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
+#[cfg(feature = "std")]
+use common::snapshot::Vault;
 use common::{
+    committee::Committee,
+    dealings::transcript_digest,
+    encryption::{Ciphertext, decrypt},
     error::{
         Error,
-        ErrorKind::{CountMismatch, InvalidPararmeterSet, InvalidProof, UninitializedValue},
+        ErrorKind::{
+            CountMismatch, DuplicateMessage, ForeignPublicKey, InvalidPararmeterSet, InvalidProof,
+            SnapshotError, UninitializedValue,
+        },
     },
+    execution::ExecutionContext,
+    keys::{KeyCommitments, VerificationKeys, check_shares},
+    keystore::{MemoryKey, SharedKey},
+    opcount,
+    parallel::prelude::*,
+    phase,
     polynomial::Polynomial,
-    random::random_scalar,
-    utils::compute_d_powers_from_hash_commitments,
+    pop::prove_key_possession,
+    report::{ShareCheck, ShareStatus, ShareTracker, VerificationReport},
+    session::DEALER,
+    utils::{
+        compute_d_from_hash_commitments, compute_d_powers, compute_d_powers_from_hash_commitments,
+    },
+    verification_failure, verified,
 };
-use rayon::prelude::*;
 
 #[derive(Clone)]
 pub struct Party {
-    pub private_key: Scalar,
+    pub key: SharedKey,
     pub public_key: (CompressedRistretto, RistrettoPoint),
     pub index: usize,
     pub n: usize,
@@ -24,9 +44,33 @@ pub struct Party {
     pub public_keys: Option<Vec<RistrettoPoint>>,
     pub dealer_proof: Option<(Vec<[u8; 64]>, Polynomial)>,
     pub validated_shares: Vec<usize>,
+    // the running valid set while shares are checked on arrival
+    pub tracker: Option<ShareTracker>,
+    pub share: Option<Vec<Scalar>>,
+    pub shares: Option<Vec<Vec<Scalar>>>,
+    pub qualified_set: Option<Vec<(usize, Vec<Scalar>)>>,
+    // (generator, decompressed commitments) the dealer published, see `common::keys`
+    pub key_commitments: Option<(RistrettoPoint, Vec<Vec<RistrettoPoint>>)>,
+    pub ctx: ExecutionContext,
+}
+
+// Everything a party holds but its key and execution context, its private share included,
+// so snapshots only go into a `Vault`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PartySnapshot {
+    pub public_key: CompressedRistretto,
+    pub index: usize,
+    pub n: usize,
+    pub t: usize,
+    pub public_keys: Option<Vec<RistrettoPoint>>,
+    // (c_vals, z) as the dealer sent them, serde stops at 32 byte arrays
+    pub dealer_proof: Option<(Vec<Vec<u8>>, Polynomial)>,
+    pub validated_shares: Vec<usize>,
+    pub tracker: Option<ShareTracker>,
     pub share: Option<Vec<Scalar>>,
     pub shares: Option<Vec<Vec<Scalar>>>,
     pub qualified_set: Option<Vec<(usize, Vec<Scalar>)>>,
+    pub key_commitments: Option<(RistrettoPoint, Vec<Vec<RistrettoPoint>>)>,
 }
 
 impl Party {
@@ -40,13 +84,15 @@ impl Party {
     where
         R: CryptoRng + RngCore,
     {
-        let private_key = random_scalar(rng);
-        let public_key = g * &private_key;
+        Self::with_key(MemoryKey::generate(rng, g).shared(), n, t, index)
+    }
 
-        if index <= n && t < n && t as f32 == ((n - 1) as f32 / 2.0).floor() {
+    // A party around an existing key.
+    pub fn with_key(key: SharedKey, n: usize, t: usize, index: usize) -> Result<Self, Error> {
+        if index <= n && t < n && t == (n - 1) / 2 {
             Ok(Self {
-                private_key,
-                public_key: (public_key.compress(), public_key),
+                public_key: *key.public_key(),
+                key,
                 index,
                 n,
                 t,
@@ -54,15 +100,127 @@ impl Party {
                 share: None,
                 public_keys: None,
                 validated_shares: vec![],
+                tracker: None,
                 shares: None,
                 qualified_set: None,
+                key_commitments: None,
+                ctx: ExecutionContext::default(),
             })
         } else {
             Err(InvalidPararmeterSet(n, t as isize, index).into())
         }
     }
 
+    pub fn from_committee(
+        committee: &Committee,
+        key: SharedKey,
+        index: usize,
+    ) -> Result<Self, Error> {
+        committee.check_key(&key.public_key().1, index)?;
+
+        Ok(Self {
+            public_key: (
+                committee.public_keys[index - 1],
+                committee.points[index - 1],
+            ),
+            key,
+            index,
+            n: committee.n,
+            t: committee.t,
+            dealer_proof: None,
+            share: None,
+            public_keys: Some(committee.points.clone()),
+            validated_shares: vec![],
+            tracker: None,
+            shares: None,
+            qualified_set: None,
+            key_commitments: None,
+            ctx: ExecutionContext::default(),
+        })
+    }
+
+    pub fn with_context(mut self, ctx: ExecutionContext) -> Self {
+        self.ctx = ctx;
+        self
+    }
+
+    pub fn snapshot(&self) -> PartySnapshot {
+        PartySnapshot {
+            public_key: self.public_key.0,
+            index: self.index,
+            n: self.n,
+            t: self.t,
+            public_keys: self.public_keys.clone(),
+            dealer_proof: self
+                .dealer_proof
+                .as_ref()
+                .map(|(c_vals, z)| (c_vals.iter().map(|c| c.to_vec()).collect(), z.clone())),
+            validated_shares: self.validated_shares.clone(),
+            tracker: self.tracker.clone(),
+            share: self.share.clone(),
+            shares: self.shares.clone(),
+            qualified_set: self.qualified_set.clone(),
+            key_commitments: self.key_commitments.clone(),
+        }
+    }
+
+    // Picks a party back up from its snapshot, `key` must be the key it was taken with.
+    pub fn restore(snapshot: PartySnapshot, key: SharedKey) -> Result<Self, Error> {
+        if key.public_key().0 != snapshot.public_key {
+            return Err(ForeignPublicKey(snapshot.index).into());
+        }
+        let mut party = Self::with_key(key, snapshot.n, snapshot.t, snapshot.index)?;
+        party.public_keys = snapshot.public_keys;
+        if let Some((c_vals, z)) = snapshot.dealer_proof {
+            let c_vals = c_vals
+                .into_iter()
+                .map(|c| c.try_into())
+                .collect::<Result<Vec<[u8; 64]>, _>>()
+                .map_err(|_| Error::from(SnapshotError("malformed c_vals".into())))?;
+            party.ingest_dealer_proof((&c_vals, &z))?;
+        }
+        party.validated_shares = snapshot.validated_shares;
+        party.tracker = snapshot.tracker;
+        party.share = snapshot.share;
+        party.shares = snapshot.shares;
+        party.qualified_set = snapshot.qualified_set;
+        party.key_commitments = snapshot.key_commitments;
+        Ok(party)
+    }
+
+    // Saves the party's state, call it after each phase and before sending what the phase
+    // produced.
+    #[cfg(feature = "std")]
+    pub fn checkpoint(&self, vault: &Vault) -> Result<(), Error> {
+        vault.save(&self.snapshot())
+    }
+
+    // The party as last checkpointed in `vault`, `None` if it never was.
+    #[cfg(feature = "std")]
+    pub fn resume(vault: &Vault, key: SharedKey) -> Result<Option<Self>, Error> {
+        match vault.load()? {
+            Some(snapshot) => Self::restore(snapshot, key).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // Proof of knowledge of our private key for registering `public_key` under `index`.
+    pub fn prove_possession<R>(
+        &self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        committee: &[u8],
+    ) -> Result<(Scalar, Scalar), Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        prove_key_possession(rng, hasher, buf, g, &*self.key, self.index, committee)
+    }
+
     pub fn ingest_share(&mut self, share: &Vec<Scalar>) {
+        let _phase = phase!("ingest", n = self.n, t = self.t);
         self.share = Some(share.clone());
     }
 
@@ -70,9 +228,12 @@ impl Party {
         &mut self,
         proof: (&Vec<[u8; 64]>, &Polynomial),
     ) -> Result<(), Error> {
+        let _phase = phase!("ingest", n = self.n, t = self.t);
         if proof.1.len() != self.t + 1 {
+            verification_failure!("malformed_proof");
             Err(InvalidProof(format!("z len: {}, t: {}", proof.1.len(), self.t)).into())
         } else if proof.0.len() != self.n {
+            verification_failure!("malformed_proof");
             Err(InvalidProof(format!("c_vals len: {}, n: {}", proof.0.len(), self.n)).into())
         } else {
             self.dealer_proof = Some((proof.0.clone(), proof.1.clone()));
@@ -80,13 +241,46 @@ impl Party {
         }
     }
 
+    // Transcript digest of the ingested public dealing, as the dealer published it in
+    // `session_id`. The private shares aren't part of it.
+    pub fn transcript_digest(
+        &self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+    ) -> Result<[u8; 32], Error> {
+        match &self.dealer_proof {
+            Some((compressed_cvals, z)) => Ok(transcript_digest(
+                hasher,
+                &(DEALER, *session_id),
+                &(compressed_cvals, z),
+            )),
+            None => Err(UninitializedValue("party.dealer_proof").into()),
+        }
+    }
+
+    // Decrypts our share of the ingested dealing published in `session_id` and ingests it,
+    // a share that doesn't authenticate under that dealing is rejected.
+    pub fn ingest_encrypted_share(
+        &mut self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+        ciphertext: &Ciphertext,
+    ) -> Result<(), Error> {
+        let transcript = self.transcript_digest(hasher, session_id)?;
+        let share: Vec<Scalar> =
+            decrypt(hasher, (self.index, &*self.key), &transcript, ciphertext)?;
+        self.ingest_share(&share);
+        Ok(())
+    }
+
     pub fn verify_share(
         &mut self,
         hasher: &mut Hasher,
         buf: &mut [u8; 64],
         x_pows: &Vec<Vec<Scalar>>,
     ) -> Result<bool, Error> {
-        match &self.dealer_proof {
+        let _phase = phase!("verify", n = self.n, t = self.t);
+        self.ctx.install(|| match &self.dealer_proof {
             Some((cvals, z)) => match &self.share {
                 Some(share) => {
                     let k = share.len();
@@ -94,6 +288,7 @@ impl Party {
                     let d_vals = compute_d_powers_from_hash_commitments(hasher, buf, &cvals, k);
 
                     let mut l_hasher = Hasher::new();
+                    opcount::hashes(1, 32 * (k + 1));
 
                     let z_eval = z.evaluate_precomp(x_pows, self.index);
                     let r_val = Polynomial::compute_r_eval(&z_eval, &share, &d_vals);
@@ -109,12 +304,12 @@ impl Party {
 
                     let check_bit = cvals[self.index - 1] == *buf;
                     buf.zeroize();
-                    Ok(check_bit)
+                    Ok(verified!(check_bit, "share"))
                 }
                 None => Err(UninitializedValue("party.share").into()),
             },
             None => Err(UninitializedValue("party.dealer_proof").into()),
-        }
+        })
     }
 
     pub fn verify_shares(
@@ -123,7 +318,20 @@ impl Party {
         buf: &mut [u8; 64],
         x_pows: &Vec<Vec<Scalar>>,
     ) -> Result<bool, Error> {
-        match &self.dealer_proof {
+        let report = self.share_report(hasher, buf, x_pows)?;
+        self.validated_shares = report.valid();
+        Ok(verified!(report.is_sufficient(), "insufficient_shares"))
+    }
+
+    // Every party's share with its status, see `VerificationReport`.
+    pub fn share_report(
+        &self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        x_pows: &Vec<Vec<Scalar>>,
+    ) -> Result<VerificationReport, Error> {
+        let _phase = phase!("verify", n = self.n, t = self.t);
+        self.ctx.install(|| match &self.dealer_proof {
             Some((cvals, z)) => match &self.shares {
                 Some(shares) => {
                     let k = shares[0].len();
@@ -131,41 +339,92 @@ impl Party {
                     let d_vals = compute_d_powers_from_hash_commitments(hasher, buf, &cvals, k);
 
                     let z_evals = z.evaluate_range_precomp(x_pows, 1, self.n);
+                    opcount::hashes(self.n, 32 * (k + 1) * self.n);
 
-                    self.validated_shares = (0..self.n)
+                    let checks: Vec<ShareCheck> = (0..self.n)
                         .into_par_iter()
                         .map_init(
                             || (Hasher::new(), [0u8; 64]),
                             |(l_hasher, l_buf), i| {
-                                let r_val =
-                                    Polynomial::compute_r_eval(&z_evals[i], &shares[i], &d_vals);
-
-                                shares[i].iter().for_each(|fi_k| {
-                                    l_hasher.update(fi_k.as_bytes());
-                                });
-
-                                l_hasher.update(r_val.as_bytes());
-
-                                l_hasher.finalize_xof().fill(l_buf);
-                                l_hasher.reset();
-
-                                let check_bit = cvals[i] == *l_buf;
-                                l_buf.zeroize();
-                                if check_bit { Some(i) } else { None }
+                                check_share(
+                                    l_hasher,
+                                    l_buf,
+                                    &cvals[i],
+                                    &d_vals,
+                                    &z_evals[i],
+                                    i + 1,
+                                    &shares[i],
+                                )
                             },
                         )
-                        .filter(Option::is_some)
-                        .map(|res| res.unwrap())
                         .collect();
-                    Ok(self.validated_shares.len() > self.t)
+                    Ok(VerificationReport::new(
+                        self.n,
+                        self.t,
+                        d_vals.first().copied(),
+                        checks,
+                    ))
                 }
                 None => Err(UninitializedValue("party.share").into()),
             },
             None => Err(UninitializedValue("party.dealer_proof").into()),
+        })
+    }
+
+    // Checks party `index`'s shares as they arrive, ours included, and keeps them with the
+    // others in `shares` if they hold. Parties that haven't sent, or whose shares failed, have
+    // none there, and a second message from a party is an error. True once, when more than t
+    // of them hold, see `ShareTracker`.
+    pub fn verify_share_from(
+        &mut self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        x_pows: &Vec<Vec<Scalar>>,
+        index: usize,
+        share: Vec<Scalar>,
+    ) -> Result<bool, Error> {
+        let _phase = phase!("verify", n = self.n, t = self.t);
+        if index == 0 || index > self.n {
+            return Err(InvalidPararmeterSet(self.n, self.t as isize, index).into());
+        }
+        if self
+            .tracker
+            .as_ref()
+            .is_some_and(|tracker| tracker.check(index).is_some())
+        {
+            return Err(DuplicateMessage(index).into());
         }
+        let (check, d) = match &self.dealer_proof {
+            Some((cvals, z)) => {
+                let d = match self.tracker.as_ref().and_then(|tracker| tracker.challenge) {
+                    Some(d) => d,
+                    None => compute_d_from_hash_commitments(hasher, buf, cvals),
+                };
+                let d_vals = compute_d_powers(share.len(), &d);
+                let zi = z.evaluate_precomp(x_pows, index);
+                opcount::hashes(1, 32 * (share.len() + 1));
+                (
+                    check_share(hasher, buf, &cvals[index - 1], &d_vals, &zi, index, &share),
+                    d,
+                )
+            }
+            None => return Err(UninitializedValue("party.dealer_proof").into()),
+        };
+        let valid = check.status == ShareStatus::Valid;
+        let tracker = self
+            .tracker
+            .get_or_insert_with(|| ShareTracker::new(self.n, self.t, Some(d)));
+        let sufficient = tracker.record(check)?;
+        self.validated_shares = tracker.valid();
+        // only shares that hold are kept, the others stay placeholders
+        if valid {
+            self.shares.get_or_insert_with(|| vec![vec![]; self.n])[index - 1] = share;
+        }
+        Ok(sufficient)
     }
 
     pub fn ingest_shares(&mut self, shares: &Vec<Vec<Scalar>>) -> Result<(), Error> {
+        let _phase = phase!("ingest", n = self.n, t = self.t);
         if shares.len() == self.n {
             self.shares = Some(shares.clone());
             Ok(())
@@ -173,6 +432,70 @@ impl Party {
             Err(CountMismatch(self.n, "parties", shares.len(), "ingestable shares").into())
         }
     }
+    // The dealer's commitments to the coefficients of the dealt polynomials, which must be
+    // under `g`, the generator the verification keys are wanted for.
+    pub fn ingest_key_commitments(
+        &mut self,
+        g: &RistrettoPoint,
+        keys: &KeyCommitments,
+    ) -> Result<(), Error> {
+        let _phase = phase!("ingest", n = self.n, t = self.t, k = keys.commitments.len());
+        self.key_commitments = Some((*g, keys.open(g, keys.commitments.len(), self.t)?));
+        Ok(())
+    }
+
+    // True if the key commitments give our validated shares as our verification keys, which
+    // ties them to the dealing.
+    pub fn verify_key_commitments(&self, x_pows: &[Vec<Scalar>]) -> Result<bool, Error> {
+        match (&self.key_commitments, &self.share) {
+            (Some((g, commitments)), Some(share)) => {
+                Ok(check_shares(g, commitments, x_pows, self.index, share))
+            }
+            (None, _) => Err(UninitializedValue("party.key_commitments").into()),
+            (_, None) => Err(UninitializedValue("party.share").into()),
+        }
+    }
+
+    // The group public key and every party's verification key, for each secret.
+    pub fn verification_keys(
+        &self,
+        x_pows: &[Vec<Scalar>],
+    ) -> Result<Vec<VerificationKeys>, Error> {
+        match &self.key_commitments {
+            Some((_, commitments)) => Ok(commitments
+                .iter()
+                .map(|commitments| VerificationKeys::from_commitments(commitments, x_pows, self.n))
+                .collect()),
+            None => Err(UninitializedValue("party.key_commitments").into()),
+        }
+    }
+}
+
+// c_i = H(fi_1, ..., fi_k, r_i) with r_i = z(i) - sum d^k fi_k
+fn check_share(
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    c: &[u8; 64],
+    d_vals: &[Scalar],
+    zi: &Scalar,
+    index: usize,
+    fi: &[Scalar],
+) -> ShareCheck {
+    fi.iter().for_each(|fi_k| {
+        hasher.update(fi_k.as_bytes());
+    });
+    hasher.update(Polynomial::compute_r_eval(zi, fi, d_vals).as_bytes());
+
+    hasher.finalize_xof().fill(buf);
+    hasher.reset();
+
+    let check_bit = c == buf;
+    buf.zeroize();
+    ShareCheck::checked(
+        index,
+        verified!(check_bit, "share"),
+        ShareStatus::CommitmentMismatch,
+    )
 }
 
 pub fn generate_parties<R>(g: &RistrettoPoint, rng: &mut R, n: usize, t: usize) -> Vec<Party>
@@ -1,17 +1,29 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod dealer;
 pub mod party;
+pub mod session;
 
 #[cfg(test)]
 
 mod tests {
-    use curve25519_dalek::{RistrettoPoint, ristretto::CompressedRistretto};
+    use std::{env, process};
 
-    use crate::{dealer::Dealer, party::generate_parties};
+    use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+
+    use crate::{
+        dealer::Dealer,
+        party::{Party, generate_parties},
+    };
 
     use common::{
         precompute::gen_powers,
         random::{random_point, random_points, random_scalars},
-        secret_sharing::{reconstruct_secrets, select_qualified_set},
+        report::ShareStatus,
+        secret_sharing::{Selection, reconstruct, reconstruct_secrets, select_qualified_set},
+        snapshot::Vault,
         utils::{compute_lagrange_bases, ingest_public_keys},
     };
 
@@ -65,6 +77,19 @@ mod tests {
                 "share verification failure"
             );
 
+            // party 1 restarts from its checkpoint once its share verified
+            if p.index == 1 {
+                let vault = Vault::from_key_handle(
+                    &env::temp_dir().join(format!("b_pi_p-party-{}", process::id())),
+                    &*p.key,
+                    (&[0u8; 32], p.index),
+                )
+                .unwrap();
+                p.checkpoint(&vault).unwrap();
+                *p = Party::resume(&vault, p.key.clone()).unwrap().unwrap();
+                vault.clear().unwrap();
+            }
+
             p.ingest_shares((&shares, &g)).unwrap();
 
             assert!(
@@ -99,5 +124,44 @@ mod tests {
 
             assert!(secrets == sec, "Invalid Reconstructed Secret");
         }
+
+        // the shares one at a time with a bad one along the way, the (t + 1)-th valid one is
+        // enough
+        let p = &mut parties[0];
+        p.shares = None;
+        p.tracker = None;
+        for i in 1..=N {
+            let gamma = if i == 3 {
+                g[i - 1] + Scalar::ONE
+            } else {
+                g[i - 1]
+            };
+            let sufficient = p
+                .verify_share_from(
+                    &mut hasher,
+                    &mut buf,
+                    &xpows,
+                    i,
+                    (shares[i - 1].clone(), gamma),
+                )
+                .unwrap();
+            assert_eq!(sufficient, i == T + 2);
+        }
+        let tracker = p.tracker.as_ref().unwrap();
+        assert!(tracker.is_complete());
+        assert_eq!(
+            tracker.report().misbehaving(),
+            vec![(3, ShareStatus::CommitmentMismatch)]
+        );
+        let f_shares: Vec<Vec<Scalar>> = p
+            .shares
+            .iter()
+            .flatten()
+            .map(|(fi, _)| fi.clone())
+            .collect();
+        assert_eq!(
+            reconstruct(&mut rng, T, &f_shares, &p.validated_shares, Selection::All).unwrap(),
+            secrets
+        );
     }
 }
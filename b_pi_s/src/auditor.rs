@@ -0,0 +1,372 @@
+// Public verification of a batched pi_s dealing, for anyone holding the committee and
+// nothing else. The checks are the ones parties run, `Party` calls the same functions on
+// its own copy of the dealing.
+
+use alloc::{format, vec, vec::Vec};
+
+use blake3::Hasher;
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto, traits::Identity};
+use zeroize::Zeroize;
+
+use common::{
+    committee::Committee,
+    error::{
+        Error,
+        ErrorKind::{CountMismatch, InvalidProof},
+    },
+    opcount,
+    parallel::prelude::*,
+    phase,
+    polynomial::Polynomial,
+    report::{ShareCheck, ShareStatus, VerificationReport},
+    secret_sharing::reconstruct_secrets_exponent,
+    stream::Sink,
+    utils::{
+        batch_decompress_batched_ristretto_points, compute_d_powers, compute_lagrange_bases,
+        finalize_d,
+    },
+    verified,
+};
+
+// (compressed, decompressed) encrypted shares, k of them per party
+pub type EncryptedShares<'a> = (&'a [Vec<CompressedRistretto>], &'a [Vec<RistrettoPoint>]);
+
+// True if (d, z) proves that every party's k `encrypted_shares` encrypt evaluations of k
+// polynomials of degree t to its key in `public_keys`.
+pub fn verify_dealing(
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    x_pows: &Vec<Vec<Scalar>>,
+    public_keys: &[RistrettoPoint],
+    encrypted_shares: EncryptedShares,
+    (d, z): (&Scalar, &Polynomial),
+) -> bool {
+    hasher.reset();
+    buf.zeroize();
+    let k = encrypted_shares.0[0].len();
+
+    let d_vals = compute_d_powers(k, d);
+
+    let z_evals = z.evaluate_range_precomp(x_pows, 1, public_keys.len());
+    opcount::point_muls(public_keys.len());
+    opcount::msms(public_keys.len(), k);
+    opcount::compressions(public_keys.len());
+    opcount::hashes(1, 32 * public_keys.len() * (k + 1));
+
+    let suite: Vec<CompressedRistretto> = z_evals
+        .iter()
+        .zip(public_keys.iter().zip(encrypted_shares.1.iter()))
+        .map(|(z_eval, (public_key, encrypted_shares_i))| {
+            ((z_eval * public_key)
+                - d_vals
+                    .iter()
+                    .zip(encrypted_shares_i)
+                    .map(|(d_val, encrypted_shares_i_k)| encrypted_shares_i_k * d_val)
+                    .fold(RistrettoPoint::identity(), |acc, x| acc + x))
+            .compress()
+        })
+        .collect();
+
+    encrypted_shares
+        .0
+        .iter()
+        .flatten()
+        .chain(suite.iter())
+        .for_each(|x| {
+            hasher.update(x.as_bytes());
+        });
+
+    hasher.finalize_xof().fill(buf);
+    hasher.reset();
+
+    let d_comp = Scalar::from_bytes_mod_order_wide(buf);
+    buf.zeroize();
+
+    verified!(*d == d_comp, "dealer_proof")
+}
+
+// `verify_dealing` over encrypted shares that arrive a chunk of parties at a time, in index
+// order, e.g. replayed from where a streaming dealer wrote them. Keeps one point per party,
+// not k, and evaluates z without an x-power table.
+pub struct DealingVerifier<'a> {
+    pub public_keys: &'a [RistrettoPoint],
+    pub d_vals: Vec<Scalar>,
+    pub z: &'a Polynomial,
+    // z(i) pk_i - sum of d^k E_ik for the parties so far
+    pub suite: Vec<CompressedRistretto>,
+    hasher: Hasher,
+}
+
+impl<'a> DealingVerifier<'a> {
+    pub fn new(
+        public_keys: &'a [RistrettoPoint],
+        k: usize,
+        (d, z): (&Scalar, &'a Polynomial),
+    ) -> Self {
+        Self {
+            public_keys,
+            d_vals: compute_d_powers(k, d),
+            z,
+            suite: Vec::with_capacity(public_keys.len()),
+            hasher: Hasher::new(),
+        }
+    }
+
+    // True if the dealing holds, once every party's shares went through `write`.
+    pub fn finish(mut self, buf: &mut [u8; 64]) -> Result<bool, Error> {
+        if self.suite.len() != self.public_keys.len() {
+            return Err(CountMismatch(
+                self.public_keys.len(),
+                "parties",
+                self.suite.len(),
+                "streamed encrypted shares",
+            )
+            .into());
+        }
+        opcount::hashes(1, 32 * self.public_keys.len() * (self.d_vals.len() + 1));
+        self.suite.iter().for_each(|x| {
+            self.hasher.update(x.as_bytes());
+        });
+        let d_comp = finalize_d(&mut self.hasher, buf);
+        Ok(verified!(self.d_vals[0] == d_comp, "dealer_proof"))
+    }
+}
+
+impl Sink<Vec<CompressedRistretto>> for DealingVerifier<'_> {
+    fn write(&mut self, from: usize, chunk: &[Vec<CompressedRistretto>]) -> Result<(), Error> {
+        if from != self.suite.len() + 1 || from + chunk.len() > self.public_keys.len() + 1 {
+            return Err(
+                CountMismatch(self.suite.len() + 1, "next party", from, "chunk start").into(),
+            );
+        }
+        if let Some(shares) = chunk
+            .iter()
+            .find(|shares| shares.len() != self.d_vals.len())
+        {
+            return Err(CountMismatch(
+                self.d_vals.len(),
+                "secrets",
+                shares.len(),
+                "encrypted shares",
+            )
+            .into());
+        }
+        let to = from + chunk.len() - 1;
+        let encrypted_shares = batch_decompress_batched_ristretto_points(chunk)?;
+        let z_evals = self.z.evaluate_range(from, to);
+        opcount::point_muls(chunk.len());
+        opcount::msms(chunk.len(), self.d_vals.len());
+        opcount::compressions(chunk.len());
+
+        chunk.iter().flatten().for_each(|x| {
+            self.hasher.update(x.as_bytes());
+        });
+        let suite: Vec<CompressedRistretto> = z_evals
+            .par_iter()
+            .zip(self.public_keys[from - 1..to].par_iter())
+            .zip(encrypted_shares.par_iter())
+            .map(|((z_eval, public_key), encrypted_shares_i)| {
+                ((z_eval * public_key)
+                    - self
+                        .d_vals
+                        .iter()
+                        .zip(encrypted_shares_i)
+                        .map(|(d_val, encrypted_shares_i_k)| encrypted_shares_i_k * d_val)
+                        .fold(RistrettoPoint::identity(), |acc, x| acc + x))
+                .compress()
+            })
+            .collect();
+        self.suite.extend(suite);
+        Ok(())
+    }
+}
+
+// Every party's DLEQ proofs checked against its decrypted shares, in index order. A
+// party's check carries the challenge of its first failing proof, or of its first one.
+pub fn verify_decryptions(
+    g: &RistrettoPoint,
+    public_keys: &[RistrettoPoint],
+    encrypted_shares: &[Vec<RistrettoPoint>],
+    decrypted_shares: &[Vec<RistrettoPoint>],
+    proofs: &[Vec<(Scalar, Scalar)>],
+) -> Vec<ShareCheck> {
+    let checks = decrypted_shares.iter().map(Vec::len).sum::<usize>();
+    opcount::point_muls(4 * checks);
+    opcount::compressions(4 * checks);
+    opcount::hashes(checks, 4 * 32 * checks);
+    decrypted_shares
+        .par_iter()
+        .zip(
+            proofs
+                .par_iter()
+                .zip(public_keys.par_iter().zip(encrypted_shares.par_iter())),
+        )
+        .enumerate()
+        .map(|(i, (dec_share, (proof, (public_key, enc_share))))| {
+            // a party owes one proof per share
+            if dec_share.len() != enc_share.len() || proof.len() != enc_share.len() {
+                return ShareCheck::checked(i + 1, false, ShareStatus::Missing);
+            }
+            let challenges: Vec<(Scalar, Scalar)> = dec_share
+                .par_iter()
+                .zip(proof.par_iter().zip(enc_share.par_iter()))
+                .map_init(
+                    || (blake3::Hasher::new(), [0u8; 64]),
+                    |(hasher, buf), (dec_share_k, ((d, z), enc_share_k))| {
+                        let num1 = g * z;
+                        let num2 = dec_share_k * z;
+
+                        let denom1 = public_key * d;
+                        let denom2 = enc_share_k * d;
+
+                        hasher.update(public_key.compress().as_bytes());
+                        hasher.update(enc_share_k.compress().as_bytes());
+                        hasher.update((num1 - denom1).compress().as_bytes());
+                        hasher.update((num2 - denom2).compress().as_bytes());
+                        hasher.finalize_xof().fill(buf);
+
+                        let reconstructed_d = Scalar::from_bytes_mod_order_wide(buf);
+
+                        hasher.reset();
+                        buf.zeroize();
+
+                        (*d, reconstructed_d)
+                    },
+                )
+                .collect();
+            let failed = challenges.iter().find(|(d, reconstructed_d)| {
+                !verified!(*d == *reconstructed_d, "decryption_proof")
+            });
+            let check = ShareCheck::checked(i + 1, failed.is_none(), ShareStatus::WrongChallenge);
+            match failed.or(challenges.first()) {
+                Some(challenge) => check.with_challenge(*challenge),
+                None => check,
+            }
+        })
+        .collect()
+}
+
+// every party's (decrypted shares, DLEQ proofs), in index order
+pub type Decryptions<'a> = (&'a [Vec<CompressedRistretto>], &'a [Vec<(Scalar, Scalar)>]);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Audit {
+    // the dealing's proof holds
+    pub dealing: bool,
+    // every party's decrypted shares, if they were given
+    pub report: Option<VerificationReport>,
+    // g^secret for each of the k secrets, once the dealing holds and more than t parties do
+    pub secrets: Option<Vec<RistrettoPoint>>,
+}
+
+pub struct Auditor<'a> {
+    pub committee: &'a Committee,
+}
+
+impl<'a> Auditor<'a> {
+    pub fn new(committee: &'a Committee) -> Self {
+        Self { committee }
+    }
+
+    // Checks a dealing (encrypted shares, (d, z)) and, if given, every party's
+    // (decrypted shares, DLEQ proofs) in index order. Input that can't even be checked,
+    // wrong counts or points that don't decode, is an `Err`.
+    pub fn audit(
+        &self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        dealing: (&[Vec<CompressedRistretto>], (&Scalar, &Polynomial)),
+        decryptions: Option<Decryptions>,
+    ) -> Result<Audit, Error> {
+        let committee = self.committee;
+        let (encrypted_shares, (d, z)) = dealing;
+        let k = encrypted_shares.first().map_or(0, Vec::len);
+        let _phase = phase!("audit", n = committee.n, t = committee.t, k = k);
+        if encrypted_shares.len() != committee.n {
+            return Err(CountMismatch(
+                committee.n,
+                "parties",
+                encrypted_shares.len(),
+                "encrypted shares",
+            )
+            .into());
+        }
+        if let Some(shares) = encrypted_shares.iter().find(|shares| shares.len() != k) {
+            return Err(CountMismatch(k, "secrets", shares.len(), "encrypted shares").into());
+        }
+        if k == 0 || z.len() != committee.t + 1 {
+            return Err(InvalidProof(format!("k: {k}, z len: {}", z.len())).into());
+        }
+        let points = batch_decompress_batched_ristretto_points(encrypted_shares)?;
+
+        let dealing = verify_dealing(
+            hasher,
+            buf,
+            &committee.x_pows,
+            &committee.points,
+            (encrypted_shares, &points),
+            (d, z),
+        );
+        let mut audit = Audit {
+            dealing,
+            report: None,
+            secrets: None,
+        };
+
+        if let Some((decrypted_shares, proofs)) = decryptions {
+            for (len, what) in [
+                (decrypted_shares.len(), "decrypted shares"),
+                (proofs.len(), "proofs"),
+            ] {
+                if len != committee.n {
+                    return Err(CountMismatch(committee.n, "parties", len, what).into());
+                }
+            }
+            // shares that don't decode are checked as the identity and blamed for it
+            opcount::decompressions(decrypted_shares.iter().map(Vec::len).sum());
+            let decompressed: Vec<Option<Vec<RistrettoPoint>>> = decrypted_shares
+                .par_iter()
+                .map(|shares| shares.iter().map(CompressedRistretto::decompress).collect())
+                .collect();
+            let decrypted_shares: Vec<Vec<RistrettoPoint>> = decompressed
+                .iter()
+                .zip(decrypted_shares)
+                .map(|(shares, compressed)| {
+                    shares
+                        .clone()
+                        .unwrap_or_else(|| vec![RistrettoPoint::default(); compressed.len()])
+                })
+                .collect();
+            let mut checks = verify_decryptions(
+                &committee.g,
+                &committee.points,
+                &points,
+                &decrypted_shares,
+                proofs,
+            );
+            checks
+                .iter_mut()
+                .zip(&decompressed)
+                .filter(|(check, shares)| shares.is_none() && check.status != ShareStatus::Missing)
+                .for_each(|(check, _)| check.status = ShareStatus::BadDecompression);
+            let report = VerificationReport::new(committee.n, committee.t, Some(*d), checks);
+            let valid = report.valid();
+            audit.report = Some(report);
+
+            // any t + 1 valid parties give the same secrets once the dealing holds
+            if dealing && valid.len() > committee.t {
+                let qualified_set: Vec<(usize, Vec<RistrettoPoint>)> = valid
+                    .iter()
+                    .take(committee.t + 1)
+                    .map(|i| (i + 1, decrypted_shares[*i].clone()))
+                    .collect();
+                let indices: Vec<usize> = qualified_set.iter().map(|(i, _)| *i).collect();
+                audit.secrets = Some(reconstruct_secrets_exponent(
+                    &Some(qualified_set),
+                    &compute_lagrange_bases(&indices),
+                )?);
+            }
+        }
+        Ok(audit)
+    }
+}
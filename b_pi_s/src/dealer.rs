@@ -1,19 +1,54 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+#[cfg(feature = "std")]
+use common::secret_sharing::generate_encrypted_shares_batched;
+#[cfg(feature = "std")]
+use common::snapshot::Vault;
 use common::{
+    committee::Committee,
     error::{Error, ErrorKind::CountMismatch},
+    execution::ExecutionContext,
+    opcount,
+    parallel::prelude::*,
+    phase,
     polynomial::Polynomial,
-    secret_sharing::generate_encrypted_shares_batched,
-    utils::{batch_decompress_ristretto_points, compute_d_powers_from_point_commitments},
+    pop::{check_public_keys, verify_public_keys},
+    utils::{batch_decompress_ristretto_points, compute_d_powers, finalize_d},
 };
-use rand::{CryptoRng, RngCore};
+#[cfg(feature = "std")]
+use common::{
+    error::ErrorKind::{ConflictingDealing, UninitializedValue},
+    session::DEALER,
+    stream::{Sink, chunks},
+};
+use rand::CryptoRng;
+#[cfg(feature = "std")]
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
 
 use blake3::Hasher;
 use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
-use rayon::prelude::*;
 
+// (encrypted shares, (d, z))
+pub type Dealing = (Vec<Vec<CompressedRistretto>>, (Scalar, Polynomial));
+// (secrets, dealing) of a session the dealer dealt
+pub type DealtSession = (Vec<Scalar>, Dealing);
+
+// The dealer's state with the dealing of every session it dealt. A resumed dealer sends
+// those dealings again rather than dealing second, different ones.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DealerSnapshot {
+    pub t: usize,
+    pub public_keys: Vec<RistrettoPoint>,
+    pub sessions: Vec<([u8; 32], DealtSession)>,
+}
+
+#[derive(Clone)]
 pub struct Dealer {
     pub t: usize,
     pub public_keys: Vec<RistrettoPoint>,
-    pub(crate) secrets: Option<Vec<Scalar>>,
+    // by session id, see `deal_session`
+    sessions: BTreeMap<[u8; 32], DealtSession>,
+    pub ctx: ExecutionContext,
 }
 
 impl Dealer {
@@ -21,16 +56,117 @@ impl Dealer {
         if public_keys.len() != n {
             return Err(CountMismatch(n, "parties", public_keys.len(), "public keys").into());
         }
+        check_public_keys(public_keys)?;
         match batch_decompress_ristretto_points(public_keys) {
             Ok(pks) => Ok(Self {
                 t,
                 public_keys: pks,
-                secrets: None,
+                sessions: BTreeMap::new(),
+                ctx: ExecutionContext::default(),
             }),
             Err(x) => Err(x),
         }
     }
 
+    pub fn from_committee(committee: &Committee) -> Self {
+        Self {
+            t: committee.t,
+            public_keys: committee.points.clone(),
+            sessions: BTreeMap::new(),
+            ctx: ExecutionContext::default(),
+        }
+    }
+
+    pub fn with_context(mut self, ctx: ExecutionContext) -> Self {
+        self.ctx = ctx;
+        self
+    }
+
+    pub fn snapshot(&self) -> DealerSnapshot {
+        DealerSnapshot {
+            t: self.t,
+            public_keys: self.public_keys.clone(),
+            sessions: self
+                .sessions
+                .iter()
+                .map(|(id, session)| (*id, session.clone()))
+                .collect(),
+        }
+    }
+
+    pub fn restore(snapshot: DealerSnapshot) -> Self {
+        Self {
+            t: snapshot.t,
+            public_keys: snapshot.public_keys,
+            sessions: snapshot.sessions.into_iter().collect(),
+            ctx: ExecutionContext::default(),
+        }
+    }
+
+    // Saves the dealer and its sessions, call it before a session's dealing is sent.
+    #[cfg(feature = "std")]
+    pub fn checkpoint(&self, vault: &Vault) -> Result<(), Error> {
+        vault.save(&self.snapshot())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn resume(vault: &Vault) -> Result<Option<Self>, Error> {
+        Ok(vault.load()?.map(Self::restore))
+    }
+
+    // Deals `secrets` in session `session_id` and keeps them with their dealing. Dealing the
+    // same session again hands back that dealing, dealing it other secrets fails.
+    #[cfg(feature = "std")]
+    pub fn deal_session<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        x_pows: &Vec<Vec<Scalar>>,
+        session_id: &[u8; 32],
+        secrets: &Vec<Scalar>,
+    ) -> Result<Dealing, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        if let Some((dealt, dealing)) = self.sessions.get(session_id) {
+            return if dealt == secrets {
+                Ok(dealing.clone())
+            } else {
+                Err(ConflictingDealing(DEALER).into())
+            };
+        }
+        let dealing = self.deal_secrets(rng, hasher, buf, x_pows, secrets);
+        self.sessions
+            .insert(*session_id, (secrets.clone(), dealing.clone()));
+        Ok(dealing)
+    }
+
+    // The secrets and dealing of session `session_id`, if it was dealt.
+    pub fn session(&self, session_id: &[u8; 32]) -> Option<&DealtSession> {
+        self.sessions.get(session_id)
+    }
+
+    // Forgets session `session_id` once it is over.
+    pub fn finish(&mut self, session_id: &[u8; 32]) -> Option<DealtSession> {
+        self.sessions.remove(session_id)
+    }
+
+    // Checks every party's proof of possession, run it before dealing.
+    pub fn verify_possession(
+        &self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        committee: &[u8],
+        proofs: &[(Scalar, Scalar)],
+    ) -> Result<(), Error> {
+        opcount::compressions(self.public_keys.len());
+        let public_keys: Vec<CompressedRistretto> =
+            self.public_keys.iter().map(|pk| pk.compress()).collect();
+        verify_public_keys(hasher, buf, g, committee, &public_keys, proofs).map(|_| ())
+    }
+
     pub fn t(&self) -> usize {
         self.t
     }
@@ -39,10 +175,8 @@ impl Dealer {
         &self.public_keys[0]
     }
 
-    pub fn publish_f0(&self) -> Vec<Scalar> {
-        self.secrets.clone().unwrap()
-    }
-
+    // A dealing outside of any session, the dealer keeps nothing of it.
+    #[cfg(feature = "std")]
     pub fn deal_secrets<R>(
         &mut self,
         rng: &mut R,
@@ -50,19 +184,101 @@ impl Dealer {
         buf: &mut [u8; 64],
         x_pows: &Vec<Vec<Scalar>>,
         secrets: &Vec<Scalar>,
-    ) -> (Vec<Vec<CompressedRistretto>>, (Scalar, Polynomial))
+    ) -> Dealing
+    where
+        R: CryptoRng + RngCore,
+    {
+        let _phase = phase!(
+            "deal",
+            n = self.public_keys.len(),
+            t = self.t,
+            k = secrets.len()
+        );
+        let ctx = self.ctx.clone();
+        ctx.install_with_rng(rng, |rng| {
+            let (f_polynomials, f_evals) =
+                generate_encrypted_shares_batched(self.t, x_pows, &self.public_keys, secrets);
+
+            let (d, z) = self.prove(rng, (hasher, buf), x_pows, &f_polynomials, &f_evals);
+
+            (f_evals, (d, z))
+        })
+    }
+
+    // `deal_secrets` for committees too large to hold the whole dealing, the encrypted shares
+    // of at most `chunk_size` parties at a time. They go to `sink` in index order, and into
+    // the challenge hash, as they're made; what stays in memory is the polynomials and one
+    // commitment to r per party. Evaluates without an x-power table.
+    #[cfg(feature = "std")]
+    pub fn deal_secrets_streaming<R, S>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        chunk_size: usize,
+        secrets: &Vec<Scalar>,
+        sink: &mut S,
+    ) -> Result<(Scalar, Polynomial), Error>
     where
         R: CryptoRng + RngCore,
+        S: Sink<Vec<CompressedRistretto>> + Send,
     {
-        // number of secrets to share
+        let n = self.public_keys.len();
         let k = secrets.len();
+        if k == 0 {
+            return Err(UninitializedValue("dealer.secrets").into());
+        }
+        let _phase = phase!("deal", n = n, t = self.t, k = k);
+        let ctx = self.ctx.clone();
+        ctx.install_with_rng(rng, |rng| {
+            let f_polynomials = {
+                let _phase = phase!("sample", t = self.t, k = k);
+                Polynomial::sample_n_set_f0(k, self.t, secrets)?
+            };
+            let mut r = Polynomial::sample(self.t, rng);
+
+            hasher.reset();
+            let mut r_commitments: Vec<CompressedRistretto> = Vec::with_capacity(n);
+            for (from, to) in chunks(n, chunk_size) {
+                let public_keys = &self.public_keys[from - 1..to];
+                let f_evals = Polynomial::evaluate_many_range(&f_polynomials, from, to);
+                opcount::point_muls(public_keys.len() * (k + 1));
+                opcount::compressions(public_keys.len() * (k + 1));
+                let encrypted_shares: Vec<Vec<CompressedRistretto>> = f_evals
+                    .par_iter()
+                    .zip(public_keys.par_iter())
+                    .map(|(fk, pub_key)| {
+                        fk.iter()
+                            .map(|f_eval| (f_eval * pub_key).compress())
+                            .collect()
+                    })
+                    .collect();
 
-        let (mut f_polynomials, f_evals) =
-            generate_encrypted_shares_batched(self.t, &x_pows, &self.public_keys, secrets);
+                encrypted_shares.iter().flatten().for_each(|c| {
+                    hasher.update(c.as_bytes());
+                });
+                sink.write(from, &encrypted_shares)?;
+
+                let commitments: Vec<CompressedRistretto> = r
+                    .evaluate_range(from, to)
+                    .par_iter()
+                    .zip(public_keys.par_iter())
+                    .map(|(r_eval, pub_key)| (r_eval * pub_key).compress())
+                    .collect();
+                r_commitments.extend(commitments);
+            }
 
-        let (d, z) = self.generate_proof(rng, hasher, buf, x_pows, k, &mut f_polynomials, &f_evals);
+            opcount::hashes(1, 32 * n * (k + 1));
+            r_commitments.iter().for_each(|c| {
+                hasher.update(c.as_bytes());
+            });
+            let d_vals = compute_d_powers(k, &finalize_d(hasher, buf));
 
-        (f_evals, (d, z))
+            // z == r += ( ∑ d_j * f_j )
+            r.compute_z(&f_polynomials, &d_vals);
+
+            Ok((d_vals[0], r))
+        })
     }
 
     pub fn generate_proof<R>(
@@ -71,29 +287,51 @@ impl Dealer {
         hasher: &mut Hasher,
         buf: &mut [u8; 64],
         x_pows: &Vec<Vec<Scalar>>,
-        k: usize,
         f_polynomials: &Vec<Polynomial>,
         f_evals: &Vec<Vec<CompressedRistretto>>,
     ) -> (Scalar, Polynomial)
     where
         R: CryptoRng,
     {
+        self.ctx.install_with_rng(rng, |rng| {
+            self.prove(rng, (hasher, buf), x_pows, f_polynomials, f_evals)
+        })
+    }
+
+    fn prove<R>(
+        &self,
+        rng: &mut R,
+        (hasher, buf): (&mut Hasher, &mut [u8; 64]),
+        x_pows: &Vec<Vec<Scalar>>,
+        f_polynomials: &[Polynomial],
+        f_evals: &[Vec<CompressedRistretto>],
+    ) -> (Scalar, Polynomial)
+    where
+        R: CryptoRng,
+    {
+        let k = f_polynomials.len();
+        let _phase = phase!("prove", n = self.public_keys.len(), t = self.t, k = k);
         let mut r = Polynomial::sample(self.t, rng);
         let r_evals = r.evaluate_range_precomp(x_pows, 1, self.public_keys.len());
+        opcount::point_muls(self.public_keys.len());
+        opcount::compressions(self.public_keys.len());
 
-        let commitments: Vec<CompressedRistretto> = f_evals
-            .clone()
-            .into_par_iter()
-            .flatten()
-            .chain(
-                r_evals
-                    .par_iter()
-                    .zip(self.public_keys.par_iter())
-                    .map(|(r_eval, pub_key)| (r_eval * pub_key).compress()),
-            )
+        let r_commitments: Vec<CompressedRistretto> = r_evals
+            .par_iter()
+            .zip(self.public_keys.par_iter())
+            .map(|(r_eval, pub_key)| (r_eval * pub_key).compress())
             .collect();
 
-        let d_vals = compute_d_powers_from_point_commitments(hasher, buf, &commitments, k);
+        // the encrypted shares are hashed in place, then the commitments to r
+        opcount::hashes(1, 32 * self.public_keys.len() * (k + 1));
+        f_evals
+            .iter()
+            .flatten()
+            .chain(r_commitments.iter())
+            .for_each(|c| {
+                hasher.update(c.as_bytes());
+            });
+        let d_vals = compute_d_powers(k, &finalize_d(hasher, buf));
 
         // z == r += ( ∑ d_j * f_j )
         r.compute_z(f_polynomials, &d_vals);
@@ -1,20 +1,34 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod auditor;
 pub mod dealer;
 pub mod party;
+pub mod session;
 
 #[cfg(test)]
 
 mod tests {
     use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
 
-    use crate::{dealer::Dealer, party::generate_parties};
+    use crate::{
+        auditor::{Auditor, DealingVerifier},
+        dealer::Dealer,
+        party::generate_parties,
+    };
 
     use common::{
+        committee::Committee,
+        fixture::{Fixture, N, T},
+        parallel::prelude::*,
         precompute::gen_powers,
         random::{random_point, random_scalars},
+        report::ShareStatus,
         secret_sharing::{reconstruct_secrets_exponent, select_qualified_set},
+        stream::{PointReader, PointWriter, Sink},
         utils::{compute_lagrange_bases, ingest_public_keys},
     };
-    use rayon::prelude::*;
 
     #[test]
     fn end_to_end() {
@@ -88,6 +102,8 @@ mod tests {
             })
             .collect();
 
+        // checks the decrypted shares one at a time instead
+        let mut incremental = parties[0].clone();
         for p in &mut parties {
             let (mut decrypted_shares, mut share_proofs) =
                 (decrypted_shares.clone(), share_proofs.clone());
@@ -98,6 +114,10 @@ mod tests {
                 .unwrap();
 
             assert!(p.verify_decrypted_shares(&g).unwrap());
+            for (k, keys) in p.verification_keys().unwrap().iter().enumerate() {
+                assert_eq!(keys.group_key, g * secrets[k]);
+                assert_eq!(keys.key(p.index), p.decrypted_share.as_ref().map(|d| &d[k]));
+            }
 
             p.qualified_set = Some(
                 select_qualified_set(&mut rng, p.t, &p.decrypted_shares, &p.validated_shares)
@@ -120,5 +140,207 @@ mod tests {
                 .zip(secrets.iter())
                 .for_each(|(secret, dealer_secret)| assert_eq!(g * dealer_secret, *secret));
         }
+
+        // one bad share along the way, the (t + 1)-th valid one is enough
+        let p = &mut incremental;
+        let mut bad_proofs = share_proofs[1].clone();
+        bad_proofs[0].1 += Scalar::ONE;
+        for i in 1..=N {
+            let proofs = if i == 2 {
+                &bad_proofs
+            } else {
+                &share_proofs[i - 1]
+            };
+            let sufficient = p
+                .verify_decrypted_shares_from(&g, i, (&decrypted_shares[i - 1], proofs))
+                .unwrap();
+            assert_eq!(sufficient, i == T + 2);
+        }
+        let tracker = p.tracker.as_ref().unwrap();
+        assert!(tracker.is_complete());
+        assert_eq!(
+            tracker.report().misbehaving(),
+            vec![(2, ShareStatus::WrongChallenge)]
+        );
+        // the bad shares aren't kept, and a validated party resending garbage is turned away
+        assert!(p.decrypted_shares.as_ref().unwrap()[1].is_empty());
+        assert!(
+            p.verify_decrypted_shares_from(&g, 1, (&decrypted_shares[2], &bad_proofs))
+                .is_err()
+        );
+        let kept: Vec<CompressedRistretto> = p.decrypted_shares.as_ref().unwrap()[0]
+            .iter()
+            .map(|ds| ds.compress())
+            .collect();
+        assert_eq!(kept, decrypted_shares[0]);
+        let qualified_set = Some(
+            select_qualified_set(&mut rng, T, &p.decrypted_shares, &p.validated_shares).unwrap(),
+        );
+        let indices: Vec<usize> = qualified_set.iter().flatten().map(|(i, _)| *i).collect();
+        let sec = reconstruct_secrets_exponent(&qualified_set, &compute_lagrange_bases(&indices))
+            .unwrap();
+        assert_eq!(sec, secrets.iter().map(|s| g * s).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn public_audit() {
+        const K: usize = 3;
+
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            ..
+        } = Fixture::new(N, T);
+        let label = b"audit";
+
+        let mut parties = generate_parties(&g, &mut rng, N, T);
+        let public_keys: Vec<CompressedRistretto> =
+            parties.iter().map(|party| party.public_key.0).collect();
+        let proofs: Vec<(Scalar, Scalar)> = parties
+            .iter()
+            .map(|p| {
+                p.prove_possession(&mut rng, &mut hasher, &mut buf, &g, label)
+                    .unwrap()
+            })
+            .collect();
+        let committee =
+            Committee::new(&mut hasher, &mut buf, &g, T, label, (&public_keys, &proofs)).unwrap();
+
+        let mut dealer = Dealer::from_committee(&committee);
+        let secrets = random_scalars(&mut rng, K);
+        let (encrypted_shares, (d, z)) =
+            dealer.deal_secrets(&mut rng, &mut hasher, &mut buf, &committee.x_pows, &secrets);
+        let (mut decrypted_shares, share_proofs): (Vec<_>, Vec<_>) = parties
+            .iter_mut()
+            .map(|p| {
+                p.ingest_encrypted_shares(&encrypted_shares).unwrap();
+                p.decrypt_shares().unwrap();
+                p.dleq_share(&g, &mut rng, &mut hasher, &mut buf).unwrap();
+                (
+                    p.decrypted_share
+                        .clone()
+                        .unwrap()
+                        .iter()
+                        .map(|ds| ds.compress())
+                        .collect::<Vec<_>>(),
+                    p.share_proof.clone().unwrap(),
+                )
+            })
+            .collect();
+
+        // one party drops a share, it alone is blamed
+        decrypted_shares[4].pop();
+        let audit = Auditor::new(&committee)
+            .audit(
+                &mut hasher,
+                &mut buf,
+                (&encrypted_shares, (&d, &z)),
+                Some((&decrypted_shares, &share_proofs)),
+            )
+            .unwrap();
+        assert!(audit.dealing);
+        assert_eq!(
+            audit.report.unwrap().misbehaving(),
+            vec![(5, ShareStatus::Missing)]
+        );
+        assert_eq!(audit.secrets, Some(secrets.iter().map(|s| g * s).collect()));
+    }
+
+    #[test]
+    fn streaming() {
+        const K: usize = 3;
+        const CHUNK: usize = 5;
+
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            ..
+        } = Fixture::new(N, T);
+        let label = b"stream";
+
+        let mut parties = generate_parties(&g, &mut rng, N, T);
+        let public_keys: Vec<CompressedRistretto> =
+            parties.iter().map(|party| party.public_key.0).collect();
+        let proofs: Vec<(Scalar, Scalar)> = parties
+            .iter()
+            .map(|p| {
+                p.prove_possession(&mut rng, &mut hasher, &mut buf, &g, label)
+                    .unwrap()
+            })
+            .collect();
+        let committee =
+            Committee::new(&mut hasher, &mut buf, &g, T, label, (&public_keys, &proofs)).unwrap();
+
+        // the dealing goes out a chunk at a time, to a writer
+        let mut dealer = Dealer::from_committee(&committee);
+        let secrets = random_scalars(&mut rng, K);
+        let mut writer = PointWriter(Vec::new());
+        let (d, z) = dealer
+            .deal_secrets_streaming(
+                &mut rng,
+                &mut hasher,
+                &mut buf,
+                CHUNK,
+                &secrets,
+                &mut writer,
+            )
+            .unwrap();
+
+        // and is verified a chunk at a time, in chunks of its own size
+        let mut verifier = DealingVerifier::new(&committee.points, K, (&d, &z));
+        PointReader::new(writer.0.as_slice(), K)
+            .replay(N, 3, &mut verifier)
+            .unwrap();
+        assert!(verifier.finish(&mut buf).unwrap());
+
+        // it's the dealing `verify_dealing` checks in memory
+        let mut encrypted_shares = Vec::new();
+        PointReader::new(writer.0.as_slice(), K)
+            .replay(N, N, &mut encrypted_shares)
+            .unwrap();
+        let (decrypted_shares, share_proofs): (Vec<_>, Vec<_>) = parties
+            .iter_mut()
+            .map(|p| {
+                p.ingest_encrypted_shares(&encrypted_shares).unwrap();
+                p.decrypt_shares().unwrap();
+                p.dleq_share(&g, &mut rng, &mut hasher, &mut buf).unwrap();
+                (
+                    p.decrypted_share
+                        .clone()
+                        .unwrap()
+                        .iter()
+                        .map(|ds| ds.compress())
+                        .collect::<Vec<_>>(),
+                    p.share_proof.clone().unwrap(),
+                )
+            })
+            .collect();
+        let audit = Auditor::new(&committee)
+            .audit(
+                &mut hasher,
+                &mut buf,
+                (&encrypted_shares, (&d, &z)),
+                Some((&decrypted_shares, &share_proofs)),
+            )
+            .unwrap();
+        assert!(audit.dealing);
+        assert_eq!(audit.secrets, Some(secrets.iter().map(|s| g * s).collect()));
+
+        // a share swapped between two parties is caught
+        encrypted_shares.swap(3, 4);
+        let mut verifier = DealingVerifier::new(&committee.points, K, (&d, &z));
+        verifier.write(1, &encrypted_shares).unwrap();
+        assert!(!verifier.finish(&mut buf).unwrap());
+
+        // as is a dealing cut short
+        let mut verifier = DealingVerifier::new(&committee.points, K, (&d, &z));
+        PointReader::new(&writer.0[..32 * K * (N - 1)], K)
+            .replay(N - 1, CHUNK, &mut verifier)
+            .unwrap();
+        assert!(verifier.finish(&mut buf).is_err());
     }
 }
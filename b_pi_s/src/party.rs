@@ -1,22 +1,45 @@
+use alloc::{format, vec, vec::Vec};
 use blake3::Hasher;
-use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto, traits::Identity};
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
 use rand::{CryptoRng, RngCore};
-use zeroize::Zeroize;
+use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "std")]
+use common::snapshot::Vault;
+
+use crate::auditor::{verify_dealing, verify_decryptions};
 use common::{
+    committee::Committee,
+    dealings::transcript_digest,
     error::{
         Error,
-        ErrorKind::{CountMismatch, InvalidPararmeterSet, InvalidProof, UninitializedValue},
+        ErrorKind::{
+            CountMismatch, DuplicateMessage, ForeignPublicKey, InsufficientShares,
+            InvalidPararmeterSet, InvalidProof, UninitializedValue,
+        },
     },
+    execution::ExecutionContext,
+    keys::VerificationKeys,
+    keystore::{MemoryKey, SharedKey},
+    message::{Signed, open_all},
+    opcount, phase,
     polynomial::Polynomial,
-    random::random_scalar,
-    utils::{batch_decompress_batched_ristretto_points, compute_d_powers},
+    pop::prove_key_possession,
+    report::{ShareCheck, ShareStatus, ShareTracker, VerificationReport},
+    session::DEALER,
+    utils::{batch_decompress_batched_ristretto_points, batch_decompress_ristretto_points},
+    verification_failure, verified,
 };
-use rayon::prelude::*;
+
+// (encrypted shares, (d, z)) as signed by the dealer
+pub type SignedDealing = Signed<(Vec<Vec<CompressedRistretto>>, (Scalar, Polynomial))>;
+// (decrypted share, DLEQ proof) as signed by its party
+pub type SignedDecryptedShare = Signed<(Vec<CompressedRistretto>, Vec<(Scalar, Scalar)>)>;
 
 #[derive(Clone)]
 pub struct Party {
-    pub private_key: Scalar,
+    // the private key never leaves it
+    pub key: SharedKey,
     pub public_key: (CompressedRistretto, RistrettoPoint),
     pub index: usize,
     pub n: usize,
@@ -24,6 +47,8 @@ pub struct Party {
     pub public_keys: Option<Vec<RistrettoPoint>>,
     pub dealer_proof: Option<(Scalar, Polynomial)>,
     pub validated_shares: Vec<usize>,
+    // the running valid set while decrypted shares are checked on arrival
+    pub tracker: Option<ShareTracker>,
     pub encrypted_share: Option<Vec<RistrettoPoint>>,
     pub decrypted_share: Option<Vec<RistrettoPoint>>,
     pub encrypted_shares: Option<(Vec<Vec<CompressedRistretto>>, Vec<Vec<RistrettoPoint>>)>,
@@ -33,6 +58,29 @@ pub struct Party {
     pub share_proofs: Option<Vec<Vec<(Scalar, Scalar)>>>,
     pub shares: Option<Vec<Vec<Scalar>>>,
     pub qualified_set: Option<Vec<(usize, Vec<RistrettoPoint>)>>,
+    pub ctx: ExecutionContext,
+}
+
+// Everything a party holds but its key and execution context.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PartySnapshot {
+    pub public_key: CompressedRistretto,
+    pub index: usize,
+    pub n: usize,
+    pub t: usize,
+    pub public_keys: Option<Vec<RistrettoPoint>>,
+    pub dealer_proof: Option<(Scalar, Polynomial)>,
+    pub validated_shares: Vec<usize>,
+    pub tracker: Option<ShareTracker>,
+    pub encrypted_share: Option<Vec<RistrettoPoint>>,
+    pub decrypted_share: Option<Vec<RistrettoPoint>>,
+    pub encrypted_shares: Option<Vec<Vec<CompressedRistretto>>>,
+    pub decrypted_shares: Option<Vec<Vec<RistrettoPoint>>>,
+    pub share: Option<Vec<Scalar>>,
+    pub share_proof: Option<Vec<(Scalar, Scalar)>>,
+    pub share_proofs: Option<Vec<Vec<(Scalar, Scalar)>>>,
+    pub shares: Option<Vec<Vec<Scalar>>>,
+    pub qualified_set: Option<Vec<(usize, Vec<RistrettoPoint>)>>,
 }
 
 impl Party {
@@ -46,13 +94,15 @@ impl Party {
     where
         R: CryptoRng + RngCore,
     {
-        let private_key = random_scalar(rng);
-        let public_key = g * &private_key;
+        Self::with_key(MemoryKey::generate(rng, g).shared(), n, t, index)
+    }
 
-        if index <= n && t < n && t as f32 == ((n - 1) as f32 / 2.0).floor() {
+    // A party around an existing key.
+    pub fn with_key(key: SharedKey, n: usize, t: usize, index: usize) -> Result<Self, Error> {
+        if index <= n && t < n && t == (n - 1) / 2 {
             Ok(Self {
-                private_key,
-                public_key: (public_key.compress(), public_key),
+                public_key: *key.public_key(),
+                key,
                 index,
                 n,
                 t,
@@ -60,6 +110,7 @@ impl Party {
                 share: None,
                 public_keys: None,
                 validated_shares: vec![],
+                tracker: None,
                 encrypted_shares: None,
                 decrypted_shares: None,
                 encrypted_share: None,
@@ -68,20 +119,147 @@ impl Party {
                 share_proofs: None,
                 shares: None,
                 qualified_set: None,
+                ctx: ExecutionContext::default(),
             })
         } else {
             Err(InvalidPararmeterSet(n, t as isize, index).into())
         }
     }
 
+    pub fn from_committee(
+        committee: &Committee,
+        key: SharedKey,
+        index: usize,
+    ) -> Result<Self, Error> {
+        committee.check_key(&key.public_key().1, index)?;
+
+        Ok(Self {
+            public_key: (
+                committee.public_keys[index - 1],
+                committee.points[index - 1],
+            ),
+            key,
+            index,
+            n: committee.n,
+            t: committee.t,
+            dealer_proof: None,
+            share: None,
+            public_keys: Some(committee.points.clone()),
+            validated_shares: vec![],
+            tracker: None,
+            encrypted_shares: None,
+            decrypted_shares: None,
+            encrypted_share: None,
+            decrypted_share: None,
+            share_proof: None,
+            share_proofs: None,
+            shares: None,
+            qualified_set: None,
+            ctx: ExecutionContext::default(),
+        })
+    }
+
+    pub fn with_context(mut self, ctx: ExecutionContext) -> Self {
+        self.ctx = ctx;
+        self
+    }
+
+    pub fn snapshot(&self) -> PartySnapshot {
+        PartySnapshot {
+            public_key: self.public_key.0,
+            index: self.index,
+            n: self.n,
+            t: self.t,
+            public_keys: self.public_keys.clone(),
+            dealer_proof: self.dealer_proof.clone(),
+            validated_shares: self.validated_shares.clone(),
+            tracker: self.tracker.clone(),
+            encrypted_share: self.encrypted_share.clone(),
+            decrypted_share: self.decrypted_share.clone(),
+            encrypted_shares: self.encrypted_shares.as_ref().map(|(c, _)| c.clone()),
+            decrypted_shares: self.decrypted_shares.clone(),
+            share: self.share.clone(),
+            share_proof: self.share_proof.clone(),
+            share_proofs: self.share_proofs.clone(),
+            shares: self.shares.clone(),
+            qualified_set: self.qualified_set.clone(),
+        }
+    }
+
+    // Picks a party back up from its snapshot, `key` must be the key it was taken with.
+    pub fn restore(snapshot: PartySnapshot, key: SharedKey) -> Result<Self, Error> {
+        if key.public_key().0 != snapshot.public_key {
+            return Err(ForeignPublicKey(snapshot.index).into());
+        }
+        let mut party = Self::with_key(key, snapshot.n, snapshot.t, snapshot.index)?;
+        party.public_keys = snapshot.public_keys;
+        if let Some(encrypted_shares) = snapshot.encrypted_shares {
+            party.ingest_encrypted_shares(&encrypted_shares)?;
+        }
+        party.dealer_proof = snapshot.dealer_proof;
+        party.validated_shares = snapshot.validated_shares;
+        party.tracker = snapshot.tracker;
+        party.encrypted_share = snapshot.encrypted_share;
+        party.decrypted_share = snapshot.decrypted_share;
+        party.decrypted_shares = snapshot.decrypted_shares;
+        party.share = snapshot.share;
+        party.share_proof = snapshot.share_proof;
+        party.share_proofs = snapshot.share_proofs;
+        party.shares = snapshot.shares;
+        party.qualified_set = snapshot.qualified_set;
+        Ok(party)
+    }
+
+    // Saves the party's state, call it after each phase and before sending what the phase
+    // produced.
+    #[cfg(feature = "std")]
+    pub fn checkpoint(&self, vault: &Vault) -> Result<(), Error> {
+        vault.save(&self.snapshot())
+    }
+
+    // The party as last checkpointed in `vault`, `None` if it never was.
+    #[cfg(feature = "std")]
+    pub fn resume(vault: &Vault, key: SharedKey) -> Result<Option<Self>, Error> {
+        match vault.load()? {
+            Some(snapshot) => Self::restore(snapshot, key).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // Proof of knowledge of our private key for registering `public_key` under `index`.
+    pub fn prove_possession<R>(
+        &self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        committee: &[u8],
+    ) -> Result<(Scalar, Scalar), Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        prove_key_possession(rng, hasher, buf, g, &*self.key, self.index, committee)
+    }
+
     pub fn ingest_share(&mut self, share: &Vec<Scalar>) {
+        let _phase = phase!("ingest", n = self.n, t = self.t);
         self.share = Some(share.clone());
     }
 
     pub fn ingest_dealer_proof(&mut self, proof: (&Scalar, &Polynomial)) -> Result<(), Error> {
+        let _phase = phase!("ingest", n = self.n, t = self.t);
         if proof.1.len() != self.t + 1 {
+            verification_failure!("malformed_proof");
             Err(InvalidProof(format!("z len: {}, t: {}", proof.1.len(), self.t)).into())
         } else {
+            // the tracker derives its batch challenge from `d`
+            if self
+                .dealer_proof
+                .as_ref()
+                .is_some_and(|(d, _)| d != proof.0)
+            {
+                self.forget_dealing();
+            }
             self.dealer_proof = Some((proof.0.clone(), proof.1.clone()));
             Ok(())
         }
@@ -93,51 +271,17 @@ impl Party {
         buf: &mut [u8; 64],
         x_pows: &Vec<Vec<Scalar>>,
     ) -> Result<bool, Error> {
-        match &self.dealer_proof {
+        let _phase = phase!("verify", n = self.n, t = self.t);
+        self.ctx.install(|| match &self.dealer_proof {
             Some((d, z)) => match (&self.encrypted_shares, &self.public_keys) {
-                (Some(encrypted_shares), Some(public_keys)) => {
-                    hasher.reset();
-                    buf.zeroize();
-                    let k = encrypted_shares.0[0].len();
-
-                    let d_vals = compute_d_powers(k, d);
-
-                    let z_evals = z.evaluate_range_precomp(x_pows, 1, public_keys.len());
-
-                    let suite: Vec<CompressedRistretto> = z_evals
-                        .iter()
-                        .zip(public_keys.iter().zip(encrypted_shares.1.iter()))
-                        .map(|(z_eval, (public_key, encrypted_shares_i))| {
-                            ((z_eval * public_key)
-                                - d_vals
-                                    .iter()
-                                    .zip(encrypted_shares_i)
-                                    .map(|(d_val, encrypted_shares_i_k)| {
-                                        encrypted_shares_i_k * d_val
-                                    })
-                                    // .reduce(|| RistrettoPoint::identity(), |acc, x| acc + x))
-                                    .fold(RistrettoPoint::identity(), |acc, x| acc + x))
-                            .compress()
-                        })
-                        .collect();
-
-                    encrypted_shares
-                        .0
-                        .iter()
-                        .flatten()
-                        .chain(suite.iter())
-                        .for_each(|x| {
-                            hasher.update(x.as_bytes());
-                        });
-
-                    hasher.finalize_xof().fill(buf);
-                    hasher.reset();
-
-                    let d_comp = Scalar::from_bytes_mod_order_wide(buf);
-                    buf.zeroize();
-
-                    Ok(*d == d_comp)
-                }
+                (Some(encrypted_shares), Some(public_keys)) => Ok(verify_dealing(
+                    hasher,
+                    buf,
+                    x_pows,
+                    public_keys,
+                    (&encrypted_shares.0, &encrypted_shares.1),
+                    (d, z),
+                )),
                 (Some(_), None) => Err(UninitializedValue("party.public_keys").into()),
                 (None, Some(_)) => Err(UninitializedValue("party.encrypted_shares").into()),
                 (None, None) => {
@@ -145,23 +289,19 @@ impl Party {
                 }
             },
             None => Err(UninitializedValue("party.dealer_proof").into()),
-        }
+        })
     }
 
     pub fn decrypt_shares(&mut self) -> Result<(), Error> {
-        let inv_private_key = self.private_key.invert();
-        match &self.encrypted_share {
+        let _phase = phase!("decrypt", n = self.n, t = self.t);
+        self.ctx.install(|| match &self.encrypted_share {
             Some(encrypted_share) => {
-                self.decrypted_share = Some(
-                    encrypted_share
-                        .par_iter()
-                        .map(|enc_share| enc_share * inv_private_key)
-                        .collect(),
-                );
+                opcount::point_muls(encrypted_share.len());
+                self.decrypted_share = Some(self.key.decrypt(encrypted_share)?);
                 Ok(())
             }
             None => Err(UninitializedValue("party.encrypted_share").into()),
-        }
+        })
     }
 
     pub fn dleq_share<R>(
@@ -174,32 +314,32 @@ impl Party {
     where
         R: CryptoRng + RngCore,
     {
+        let _phase = phase!("prove", n = self.n, t = self.t);
+        // proofs that may already be out are resent as they were, matching the transcript,
+        // fresh nonces would make a second set safe but not the same messages
+        if self.share_proof.is_some() {
+            return Ok(());
+        }
         match (&self.decrypted_share, &self.encrypted_share) {
             (Some(decrypted_shares), Some(encrypted_shares)) => {
+                opcount::point_muls(2 * decrypted_shares.len());
+                opcount::scalar_muls(decrypted_shares.len());
+                opcount::compressions(3 * decrypted_shares.len());
+                opcount::hashes(decrypted_shares.len(), 4 * 32 * decrypted_shares.len());
                 self.share_proof = Some(
                     decrypted_shares
                         .iter()
                         .zip(encrypted_shares)
                         .map(|(decrypted_share, encrypted_share)| {
-                            let r = common::random::random_scalar(rng);
-                            let c1 = (g * &r).compress();
-                            let c2 = (decrypted_share * r).compress();
-
-                            hasher.update(self.public_key.0.as_bytes());
-                            hasher.update(encrypted_share.compress().as_bytes());
-                            hasher.update(c1.as_bytes());
-                            hasher.update(c2.as_bytes());
-
-                            hasher.finalize_xof().fill(buf);
-
-                            let d = Scalar::from_bytes_mod_order_wide(buf);
-                            let z = r + d * self.private_key;
-                            hasher.reset();
-                            buf.zeroize();
-
-                            (d, z)
+                            self.key.prove_dleq(
+                                rng,
+                                hasher,
+                                buf,
+                                g,
+                                (decrypted_share, &encrypted_share.compress()),
+                            )
                         })
-                        .collect(),
+                        .collect::<Result<Vec<(Scalar, Scalar)>, Error>>()?,
                 );
 
                 Ok(())
@@ -214,130 +354,312 @@ impl Party {
 
     pub fn ingest_encrypted_shares(
         &mut self,
-        encrypted_shares: &Vec<Vec<CompressedRistretto>>,
+        encrypted_shares: &[Vec<CompressedRistretto>],
     ) -> Result<(), Error> {
-        if encrypted_shares.len() == self.n {
-            match batch_decompress_batched_ristretto_points(encrypted_shares) {
-                Ok(enc_shares) => {
-                    self.encrypted_share = Some(enc_shares[self.index - 1].clone());
-                    self.encrypted_shares = Some((encrypted_shares.to_vec(), enc_shares));
-                    Ok(())
-                }
-                Err(x) => Err(x),
-            }
-        } else {
-            Err(CountMismatch(
+        let _phase = phase!("ingest", n = self.n, t = self.t);
+        if encrypted_shares.len() != self.n {
+            return Err(CountMismatch(
                 self.n,
                 "parties",
                 encrypted_shares.len(),
                 "encrypted shares",
             )
-            .into())
+            .into());
+        }
+        let enc_shares = self
+            .ctx
+            .install(|| batch_decompress_batched_ristretto_points(encrypted_shares))?;
+        let encrypted_share = &enc_shares[self.index - 1];
+        // decryptions and proofs are only kept for the shares they were made for
+        if self.encrypted_share.as_ref() != Some(encrypted_share) {
+            self.decrypted_share = None;
+            self.share_proof = None;
+        }
+        if self
+            .encrypted_shares
+            .as_ref()
+            .is_some_and(|(shares, _)| shares.as_slice() != encrypted_shares)
+        {
+            self.forget_dealing();
         }
+        self.encrypted_share = Some(encrypted_share.clone());
+        self.encrypted_shares = Some((encrypted_shares.to_vec(), enc_shares));
+        Ok(())
+    }
+
+    // Drops what we verified of the other parties' shares, it belongs to the previous dealing.
+    fn forget_dealing(&mut self) {
+        self.tracker = None;
+        self.decrypted_shares = None;
+        self.share_proofs = None;
+        self.validated_shares.clear();
+        self.qualified_set = None;
     }
 
     pub fn ingest_decrypted_shares_and_proofs(
         &mut self,
-        decrypted_shares: &Vec<Vec<CompressedRistretto>>,
+        decrypted_shares: &[Vec<CompressedRistretto>],
         proofs: Vec<Vec<(Scalar, Scalar)>>,
     ) -> Result<(), Error> {
-        if decrypted_shares.len() == self.n - 1 {
-            if proofs.len() == decrypted_shares.len() {
-                match batch_decompress_batched_ristretto_points(decrypted_shares) {
-                    Ok(mut dec_shares) => match (&self.decrypted_share, &self.share_proof) {
-                        (Some(own_dec_share), Some(own_proof)) => {
-                            dec_shares.insert(self.index - 1, own_dec_share.clone());
-                            self.decrypted_shares = Some(dec_shares);
-                            let mut proofs = proofs;
-                            proofs.insert(self.index - 1, own_proof.clone());
-                            self.share_proofs = Some(proofs);
-                            Ok(())
-                        }
-                        (None, Some(_)) => Err(UninitializedValue("party.decrypted_share").into()),
-                        (Some(_), None) => Err(UninitializedValue("party.share_proof").into()),
-                        (None, None) => {
-                            Err(UninitializedValue("party.{decrypted_share, share_proof}").into())
-                        }
-                    },
-                    Err(x) => Err(x),
+        let _phase = phase!("ingest", n = self.n, t = self.t);
+        self.ctx.install(|| {
+            if decrypted_shares.len() == self.n - 1 {
+                if proofs.len() == decrypted_shares.len() {
+                    match batch_decompress_batched_ristretto_points(decrypted_shares) {
+                        Ok(mut dec_shares) => match (&self.decrypted_share, &self.share_proof) {
+                            (Some(own_dec_share), Some(own_proof)) => {
+                                dec_shares.insert(self.index - 1, own_dec_share.clone());
+                                self.decrypted_shares = Some(dec_shares);
+                                let mut proofs = proofs;
+                                proofs.insert(self.index - 1, own_proof.clone());
+                                self.share_proofs = Some(proofs);
+                                Ok(())
+                            }
+                            (None, Some(_)) => {
+                                Err(UninitializedValue("party.decrypted_share").into())
+                            }
+                            (Some(_), None) => Err(UninitializedValue("party.share_proof").into()),
+                            (None, None) => {
+                                Err(UninitializedValue("party.{decrypted_share, share_proof}")
+                                    .into())
+                            }
+                        },
+                        Err(x) => Err(x),
+                    }
+                } else {
+                    Err(CountMismatch(self.n, "parties", proofs.len(), "proofs").into())
                 }
             } else {
-                Err(CountMismatch(self.n, "parties", proofs.len(), "proofs").into())
+                Err(CountMismatch(
+                    self.n,
+                    "parties",
+                    decrypted_shares.len(),
+                    "decrypted shares",
+                )
+                .into())
             }
-        } else {
-            Err(CountMismatch(
-                self.n,
-                "parties",
-                decrypted_shares.len(),
-                "decrypted shares",
-            )
-            .into())
+        })
+    }
+
+    // Opens a dealing signed under `dealer_public_key` and ingests it.
+    pub fn ingest_signed_dealing(
+        &mut self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        dealer_public_key: &CompressedRistretto,
+        session: (&[u8; 32], u64),
+        dealing: SignedDealing,
+    ) -> Result<(), Error> {
+        let (encrypted_shares, (d, z)) =
+            dealing.open(hasher, buf, g, dealer_public_key, session)?;
+        self.ingest_encrypted_shares(&encrypted_shares)?;
+        self.ingest_dealer_proof((&d, &z))
+    }
+
+    // Signs this party's decrypted share and proof for the other parties.
+    pub fn sign_decrypted_share<R>(
+        &self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        session: (&[u8; 32], u64),
+    ) -> Result<SignedDecryptedShare, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        match (&self.decrypted_share, &self.share_proof) {
+            (Some(decrypted_share), Some(share_proof)) => Signed::sign_with(
+                rng,
+                hasher,
+                buf,
+                g,
+                &*self.key,
+                (session.0, session.1, self.index),
+                (
+                    decrypted_share
+                        .iter()
+                        .map(|share| share.compress())
+                        .collect(),
+                    share_proof.clone(),
+                ),
+            ),
+            (None, _) => Err(UninitializedValue("party.decrypted_share").into()),
+            (_, None) => Err(UninitializedValue("party.share_proof").into()),
+        }
+    }
+
+    // `ingest_decrypted_shares_and_proofs` for signed messages, each placed by its sender
+    // rather than by its position in `messages`.
+    pub fn ingest_signed_decrypted_shares_and_proofs(
+        &mut self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        session: (&[u8; 32], u64),
+        messages: Vec<SignedDecryptedShare>,
+    ) -> Result<(), Error> {
+        match &self.public_keys {
+            Some(public_keys) => {
+                opcount::compressions(public_keys.len());
+                let public_keys: Vec<CompressedRistretto> =
+                    public_keys.iter().map(|pk| pk.compress()).collect();
+                let senders: Vec<usize> = (1..=self.n).filter(|i| *i != self.index).collect();
+
+                let (decrypted_shares, proofs): (Vec<_>, Vec<_>) =
+                    open_all(hasher, buf, g, &public_keys, session, &senders, messages)?
+                        .into_iter()
+                        .unzip();
+                self.ingest_decrypted_shares_and_proofs(&decrypted_shares, proofs)
+            }
+            None => Err(UninitializedValue("party.public_keys").into()),
+        }
+    }
+
+    // Transcript digest of the ingested dealing, as the dealer published it in `session_id`.
+    pub fn transcript_digest(
+        &self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+    ) -> Result<[u8; 32], Error> {
+        match (&self.encrypted_shares, &self.dealer_proof) {
+            (Some((encrypted_shares, _)), Some((d, z))) => Ok(transcript_digest(
+                hasher,
+                &(DEALER, *session_id),
+                &(encrypted_shares, (d, z)),
+            )),
+            (None, _) => Err(UninitializedValue("party.encrypted_shares").into()),
+            (_, None) => Err(UninitializedValue("party.dealer_proof").into()),
         }
     }
 
     pub fn verify_decrypted_shares(&mut self, g: &RistrettoPoint) -> Result<bool, Error> {
-        match (&self.public_keys, &self.encrypted_shares) {
+        let report = self.decrypted_share_report(g)?;
+        self.validated_shares = report.valid();
+        Ok(verified!(report.is_sufficient(), "insufficient_shares"))
+    }
+
+    // Checks party `index`'s (decrypted shares, DLEQ proofs) as they arrive, ours included, and
+    // keeps them with the others in `decrypted_shares` and `share_proofs` if they hold. Parties
+    // that haven't sent, or whose shares failed, have none there, and a second message from a
+    // party is an error. True once, when more than t of them hold, see `ShareTracker`.
+    pub fn verify_decrypted_shares_from(
+        &mut self,
+        g: &RistrettoPoint,
+        index: usize,
+        shares: (&[CompressedRistretto], &[(Scalar, Scalar)]),
+    ) -> Result<bool, Error> {
+        let _phase = phase!("verify", n = self.n, t = self.t, k = shares.0.len());
+        if index == 0 || index > self.n {
+            return Err(InvalidPararmeterSet(self.n, self.t as isize, index).into());
+        }
+        if self
+            .tracker
+            .as_ref()
+            .is_some_and(|tracker| tracker.check(index).is_some())
+        {
+            return Err(DuplicateMessage(index).into());
+        }
+        let (decrypted_shares, proofs) = shares;
+        let (check, dec_shares) = match (&self.public_keys, &self.encrypted_shares) {
             (Some(public_keys), Some(enc_shares)) => {
-                match (&self.decrypted_shares, &self.share_proofs) {
-                    (Some(dec_shares), Some(proofs)) => {
-                        self.validated_shares = dec_shares
-                            .par_iter()
-                            .zip(
-                                proofs
-                                    .par_iter()
-                                    .zip(public_keys.par_iter().zip(enc_shares.1.par_iter())),
+                match batch_decompress_ristretto_points(decrypted_shares) {
+                    Ok(dec_shares) => {
+                        let proofs = proofs.to_vec();
+                        let mut check = self.ctx.install(|| {
+                            verify_decryptions(
+                                g,
+                                &public_keys[index - 1..index],
+                                &enc_shares.1[index - 1..index],
+                                core::slice::from_ref(&dec_shares),
+                                core::slice::from_ref(&proofs),
                             )
-                            .enumerate()
-                            .map(|(i, (dec_share, (proof, (public_key, enc_share))))| {
-                                if dec_share
-                                    .par_iter()
-                                    .zip(proof.par_iter().zip(enc_share.par_iter()))
-                                    .map_init(
-                                        || (blake3::Hasher::new(), [0u8; 64]),
-                                        |(hasher, buf), (dec_share_k, ((d, z), enc_share_k))| {
-                                            let num1 = g * z;
-                                            let num2 = dec_share_k * z;
-
-                                            let denom1 = public_key * d;
-                                            let denom2 = enc_share_k * d;
-
-                                            hasher.update(public_key.compress().as_bytes());
-                                            hasher.update(enc_share_k.compress().as_bytes());
-                                            hasher.update((num1 - denom1).compress().as_bytes());
-                                            hasher.update((num2 - denom2).compress().as_bytes());
-                                            hasher.finalize_xof().fill(buf);
-
-                                            let reconstructed_d =
-                                                Scalar::from_bytes_mod_order_wide(buf);
-
-                                            hasher.reset();
-                                            buf.zeroize();
-
-                                            *d == reconstructed_d
-                                        },
-                                    )
-                                    .reduce(|| true, |acc, res| acc && res)
-                                {
-                                    Some(i)
-                                } else {
-                                    None
-                                }
-                            })
-                            .filter(Option::is_some)
-                            .map(|res| res.unwrap())
-                            .collect();
-                        Ok(self.validated_shares.len() > self.t)
+                            .remove(0)
+                        });
+                        check.index = index;
+                        (check, Some((dec_shares, proofs)))
+                    }
+                    Err(_) => {
+                        verification_failure!("point_decompression");
+                        (
+                            ShareCheck::checked(index, false, ShareStatus::BadDecompression),
+                            None,
+                        )
                     }
-                    (None, Some(_)) => Err(UninitializedValue("party.decrypted_shares").into()),
-                    (Some(_), None) => Err(UninitializedValue("party.share_proofs").into()),
-                    (None, None) => {
-                        Err(UninitializedValue("party.{decrypted_shares, share_proofs}").into())
+                }
+            }
+            (None, _) => return Err(UninitializedValue("party.public_keys").into()),
+            (_, None) => return Err(UninitializedValue("party.encrypted_shares").into()),
+        };
+
+        let valid = check.status == ShareStatus::Valid;
+        let (n, t, d) = (self.n, self.t, self.dealer_proof.as_ref().map(|(d, _)| *d));
+        let tracker = self
+            .tracker
+            .get_or_insert_with(|| ShareTracker::new(n, t, d));
+        let sufficient = tracker.record(check)?;
+        self.validated_shares = tracker.valid();
+        // only shares that hold are kept, the others stay placeholders
+        if let (true, Some((dec_shares, proofs))) = (valid, dec_shares) {
+            self.decrypted_shares
+                .get_or_insert_with(|| vec![vec![]; self.n])[index - 1] = dec_shares;
+            self.share_proofs
+                .get_or_insert_with(|| vec![vec![]; self.n])[index - 1] = proofs;
+        }
+        Ok(sufficient)
+    }
+
+    pub fn decrypted_share_report(&self, g: &RistrettoPoint) -> Result<VerificationReport, Error> {
+        let _phase = phase!("verify", n = self.n, t = self.t);
+        self.ctx
+            .install(|| match (&self.public_keys, &self.encrypted_shares) {
+                (Some(public_keys), Some(enc_shares)) => {
+                    match (&self.decrypted_shares, &self.share_proofs) {
+                        (Some(dec_shares), Some(proofs)) => Ok(VerificationReport::new(
+                            self.n,
+                            self.t,
+                            self.dealer_proof.as_ref().map(|(d, _)| *d),
+                            verify_decryptions(g, public_keys, &enc_shares.1, dec_shares, proofs),
+                        )),
+                        (None, Some(_)) => Err(UninitializedValue("party.decrypted_shares").into()),
+                        (Some(_), None) => Err(UninitializedValue("party.share_proofs").into()),
+                        (None, None) => {
+                            Err(UninitializedValue("party.{decrypted_shares, share_proofs}").into())
+                        }
                     }
                 }
+                (None, Some(_)) => Err(UninitializedValue("party.encrypted_shares").into()),
+                (Some(_), None) => Err(UninitializedValue("party.public_keys").into()),
+                (None, None) => {
+                    Err(UninitializedValue("party.{public_keys, encrypted_shares}").into())
+                }
+            })
+    }
+
+    // The group public key and every party's verification key for each secret, interpolated
+    // from the first t + 1 validated decrypted shares, each of which is g^f_k(i) already.
+    pub fn verification_keys(&self) -> Result<Vec<VerificationKeys>, Error> {
+        match &self.decrypted_shares {
+            Some(dec_shares) if self.validated_shares.len() > self.t => {
+                let qualified: Vec<usize> = self
+                    .validated_shares
+                    .iter()
+                    .take(self.t + 1)
+                    .copied()
+                    .collect();
+                Ok((0..dec_shares[qualified[0]].len())
+                    .map(|k| {
+                        let shares: Vec<(usize, RistrettoPoint)> = qualified
+                            .iter()
+                            .map(|i| (i + 1, dec_shares[*i][k]))
+                            .collect();
+                        VerificationKeys::from_shares(&shares, self.n)
+                    })
+                    .collect())
             }
-            (None, Some(_)) => Err(UninitializedValue("party.encrypted_shares").into()),
-            (Some(_), None) => Err(UninitializedValue("party.public_keys").into()),
-            (None, None) => Err(UninitializedValue("party.{public_keys, encrypted_shares}").into()),
+            Some(_) => Err(InsufficientShares(self.validated_shares.len(), self.t).into()),
+            None => Err(UninitializedValue("party.decrypted_shares").into()),
         }
     }
 }
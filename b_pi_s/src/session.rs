@@ -0,0 +1,164 @@
+use alloc::vec::Vec;
+
+use blake3::Hasher;
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+use rand::{CryptoRng, RngCore};
+
+use common::{
+    encryption::Ciphertext,
+    error::{Error, ErrorKind::UninitializedValue},
+    keystore::SharedKey,
+    polynomial::Polynomial,
+    report::ShareTracker,
+    secret_sharing::{reconstruct_secrets_exponent, select_qualified_set},
+    session::{self, SessionParty},
+    utils::compute_lagrange_bases,
+};
+
+#[cfg(feature = "std")]
+use common::session::SessionDealer;
+
+#[cfg(feature = "std")]
+use crate::dealer::{Dealer, Dealing};
+use crate::party::Party;
+
+#[cfg(feature = "std")]
+pub type DealerSession<'a> = session::DealerSession<'a, Dealer>;
+pub type PartySession<'a> = session::PartySession<'a, Party>;
+// the dealing is (encrypted shares, (d, z)), the reveal (decrypted shares, DLEQ proofs) of
+// its sender
+pub type Message = session::PartyMessage<Party>;
+
+#[cfg(feature = "std")]
+impl SessionDealer for Dealer {
+    type Party = Party;
+    type Secret = Vec<Scalar>;
+
+    // The shares are public, encrypted in the dealing itself.
+    fn deal<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        _g: &RistrettoPoint,
+        xpows: &Vec<Vec<Scalar>>,
+        session_id: &[u8; 32],
+        secrets: &Vec<Scalar>,
+    ) -> Result<(Dealing, Vec<Ciphertext>), Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let dealing = self.deal_session(rng, hasher, buf, xpows, session_id, secrets)?;
+        Ok((dealing, Vec::new()))
+    }
+}
+
+impl SessionParty for Party {
+    type Dealing = (Vec<Vec<CompressedRistretto>>, (Scalar, Polynomial));
+    type Reveal = (Vec<CompressedRistretto>, Vec<(Scalar, Scalar)>);
+    // g^secrets
+    type Outcome = Vec<RistrettoPoint>;
+    type Evidence = core::convert::Infallible;
+
+    const PRIVATE_SHARES: bool = false;
+    const INVALID_DEALING: &'static str = "dealer_proof";
+
+    fn index(&self) -> usize {
+        self.index
+    }
+
+    fn n(&self) -> usize {
+        self.n
+    }
+
+    fn key(&self) -> &SharedKey {
+        &self.key
+    }
+
+    fn public_keys(&self) -> Option<&Vec<RistrettoPoint>> {
+        self.public_keys.as_ref()
+    }
+
+    fn tracker(&self) -> Option<&ShareTracker> {
+        self.tracker.as_ref()
+    }
+
+    fn ingest_dealing(&mut self, dealing: Self::Dealing) -> Result<(), Error> {
+        let (encrypted_shares, (d, z)) = dealing;
+        self.ingest_encrypted_shares(&encrypted_shares)?;
+        self.ingest_dealer_proof((&d, &z))
+    }
+
+    fn transcript_digest(
+        &self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+    ) -> Result<[u8; 32], Error> {
+        Party::transcript_digest(self, hasher, session_id)
+    }
+
+    fn verify_dealing<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        xpows: &Vec<Vec<Scalar>>,
+        _session_id: &[u8; 32],
+        _ciphertext: Option<&Ciphertext>,
+    ) -> Result<Option<Self::Reveal>, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        if !self.verify_encrypted_shares(hasher, buf, xpows)? {
+            return Ok(None);
+        }
+        self.decrypt_shares()?;
+        self.dleq_share(g, rng, hasher, buf)?;
+        match (&self.decrypted_share, &self.share_proof) {
+            (Some(decrypted_share), Some(share_proof)) => Ok(Some((
+                decrypted_share
+                    .iter()
+                    .map(|share| share.compress())
+                    .collect(),
+                share_proof.clone(),
+            ))),
+            (None, _) => Err(UninitializedValue("party.decrypted_share").into()),
+            (_, None) => Err(UninitializedValue("party.share_proof").into()),
+        }
+    }
+
+    fn check_reveal(
+        &mut self,
+        _hasher: &mut Hasher,
+        _buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        _xpows: &Vec<Vec<Scalar>>,
+        sender: usize,
+        reveal: &Self::Reveal,
+    ) -> Result<bool, Error> {
+        let (decrypted_shares, proofs) = reveal;
+        self.verify_decrypted_shares_from(g, sender, (decrypted_shares, proofs))
+    }
+
+    fn reconstruct<R>(&mut self, rng: &mut R) -> Result<Vec<RistrettoPoint>, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        self.qualified_set = Some(select_qualified_set(
+            rng,
+            self.t,
+            &self.decrypted_shares,
+            &self.validated_shares,
+        )?);
+        let indices: Vec<usize> = self
+            .qualified_set
+            .iter()
+            .flatten()
+            .map(|(index, _)| *index)
+            .collect();
+        let lagrange_bases = compute_lagrange_bases(&indices);
+
+        reconstruct_secrets_exponent(&self.qualified_set, &lagrange_bases)
+    }
+}
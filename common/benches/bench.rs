@@ -2,6 +2,7 @@ use blake3::Hasher;
 use common::{
     BENCH_K, BENCH_N_T,
     error::ErrorKind::PointDecompressionError,
+    parallel::prelude::*,
     polynomial::Polynomial,
     precompute::{XPowTable, gen_powers},
     random::{random_points, random_scalar, random_scalars},
@@ -15,7 +16,6 @@ use common::{
 use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
 use curve25519_dalek::{RistrettoPoint, Scalar};
 use rand::RngCore;
-use rayon::prelude::*;
 use zeroize::Zeroize;
 
 fn add_mul(c: &mut Criterion) {
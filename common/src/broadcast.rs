@@ -0,0 +1,256 @@
+// Bracha's reliable broadcast of the dealing digest.
+// The dealer sends its dealing to every party, which echoes the transcript digest of the
+// dealing it got. A party sends ready for a digest once enough parties echoed it, or once
+// f + 1 parties are ready for it, and the digest is delivered on 2f + 1 readies. With at
+// most f = (n - 1) / 3 corrupt parties every honest party delivers the same digest or
+// none, so a dealer that hands out different dealings can't get two of them verified.
+//
+// The VSS schemes themselves tolerate t = (n - 1) / 2 corrupt parties, more than f. A
+// session that agrees on its dealing through this broadcast only keeps the guarantee
+// above while at most f parties are corrupt, between f and t a dealer working with the
+// corrupt parties can get honest parties to deliver different digests or none at all.
+//
+// Votes travel as `Signed<Vote>` in `BROADCAST_ROUND`, each party votes once of each kind.
+
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+use blake3::Hasher;
+use curve25519_dalek::{RistrettoPoint, ristretto::CompressedRistretto};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{
+        Error,
+        ErrorKind::{DuplicateMessage, UnexpectedMessage},
+    },
+    keystore::KeyHandle,
+    message::{Encode, Signed},
+    session::BROADCAST_ROUND,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Vote {
+    Echo([u8; 32]),
+    Ready([u8; 32]),
+}
+
+pub type SignedVote = Signed<Vote>;
+
+impl Encode for Vote {
+    fn encode(&self, hasher: &mut Hasher) {
+        match self {
+            Vote::Echo(digest) => {
+                hasher.update(&[0]);
+                hasher.update(digest);
+            }
+            Vote::Ready(digest) => {
+                hasher.update(&[1]);
+                hasher.update(digest);
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ReliableBroadcast {
+    pub n: usize,
+    // corrupt parties tolerated
+    pub f: usize,
+    pub index: usize,
+    // digest voted for by party, our own included
+    pub echoes: BTreeMap<usize, [u8; 32]>,
+    pub readies: BTreeMap<usize, [u8; 32]>,
+    pub delivered: Option<[u8; 32]>,
+}
+
+impl ReliableBroadcast {
+    pub fn new(n: usize, index: usize) -> Self {
+        Self {
+            n,
+            f: (n - 1) / 3,
+            index,
+            echoes: BTreeMap::new(),
+            readies: BTreeMap::new(),
+            delivered: None,
+        }
+    }
+
+    // The digest of the dealing we got from the dealer, answered with our echo.
+    pub fn propose(&mut self, digest: [u8; 32]) -> Vec<Vote> {
+        if self.echoes.contains_key(&self.index) {
+            return vec![];
+        }
+        self.echoes.insert(self.index, digest);
+
+        let mut votes = vec![Vote::Echo(digest)];
+        votes.extend(self.step());
+        votes
+    }
+
+    // Records `sender`'s vote and returns the votes it triggers on our side.
+    pub fn ingest(&mut self, sender: usize, vote: Vote) -> Result<Vec<Vote>, Error> {
+        if sender == 0 || sender == self.index || sender > self.n {
+            return Err(UnexpectedMessage(sender, "voting on the dealing").into());
+        }
+        let (votes, digest) = match vote {
+            Vote::Echo(digest) => (&mut self.echoes, digest),
+            Vote::Ready(digest) => (&mut self.readies, digest),
+        };
+        if votes.contains_key(&sender) {
+            return Err(DuplicateMessage(sender).into());
+        }
+        votes.insert(sender, digest);
+
+        Ok(self.step())
+    }
+
+    // `ingest` for a vote signed under `public_keys[sender - 1]`.
+    pub fn ingest_signed(
+        &mut self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        public_keys: &[CompressedRistretto],
+        session_id: &[u8; 32],
+        vote: SignedVote,
+    ) -> Result<Vec<Vote>, Error> {
+        let sender = vote.sender;
+        if sender == 0 || sender > public_keys.len() {
+            return Err(UnexpectedMessage(sender, "voting on the dealing").into());
+        }
+        let vote = vote.open(
+            hasher,
+            buf,
+            g,
+            &public_keys[sender - 1],
+            (session_id, BROADCAST_ROUND),
+        )?;
+        self.ingest(sender, vote)
+    }
+
+    // Signs our `votes` with `signer` = (key handle, session id).
+    pub fn sign<R>(
+        &self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        signer: (&dyn KeyHandle, &[u8; 32]),
+        votes: Vec<Vote>,
+    ) -> Result<Vec<SignedVote>, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        votes
+            .into_iter()
+            .map(|vote| {
+                Signed::sign_with(
+                    rng,
+                    hasher,
+                    buf,
+                    g,
+                    signer.0,
+                    (signer.1, BROADCAST_ROUND, self.index),
+                    vote,
+                )
+            })
+            .collect()
+    }
+
+    pub fn delivered(&self) -> Option<&[u8; 32]> {
+        self.delivered.as_ref()
+    }
+
+    fn count(votes: &BTreeMap<usize, [u8; 32]>, digest: &[u8; 32]) -> usize {
+        votes.values().filter(|d| *d == digest).count()
+    }
+
+    // The digest most votes agree on, ties don't matter as only one can reach a threshold.
+    fn leader(votes: &BTreeMap<usize, [u8; 32]>) -> Option<([u8; 32], usize)> {
+        votes
+            .values()
+            .map(|digest| (*digest, Self::count(votes, digest)))
+            .max_by_key(|(_, count)| *count)
+    }
+
+    fn step(&mut self) -> Vec<Vote> {
+        let mut votes = vec![];
+
+        if !self.readies.contains_key(&self.index) {
+            let echoed = Self::leader(&self.echoes)
+                .filter(|(_, count)| *count > (self.n + self.f) / 2)
+                .map(|(digest, _)| digest);
+            let amplified = Self::leader(&self.readies)
+                .filter(|(_, count)| *count > self.f)
+                .map(|(digest, _)| digest);
+
+            if let Some(digest) = echoed.or(amplified) {
+                self.readies.insert(self.index, digest);
+                votes.push(Vote::Ready(digest));
+            }
+        }
+
+        if self.delivered.is_none() {
+            self.delivered = Self::leader(&self.readies)
+                .filter(|(_, count)| *count > 2 * self.f)
+                .map(|(digest, _)| digest);
+        }
+        votes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{vec, vec::Vec};
+
+    use crate::{
+        broadcast::{ReliableBroadcast, Vote},
+        error::ErrorKind::DuplicateMessage,
+    };
+
+    // Delivers every vote to every other party until nothing moves.
+    fn run(parties: &mut [ReliableBroadcast], proposals: &[Option<[u8; 32]>]) {
+        let mut in_flight: Vec<(usize, Vote)> = vec![];
+        for (party, proposal) in parties.iter_mut().zip(proposals) {
+            if let Some(digest) = proposal {
+                let index = party.index;
+                in_flight.extend(party.propose(*digest).into_iter().map(|v| (index, v)));
+            }
+        }
+        while let Some((sender, vote)) = in_flight.pop() {
+            for party in parties.iter_mut().filter(|p| p.index != sender) {
+                let index = party.index;
+                let votes = party.ingest(sender, vote).unwrap();
+                in_flight.extend(votes.into_iter().map(|v| (index, v)));
+            }
+        }
+    }
+
+    #[test]
+    fn reliable_broadcast() {
+        const N: usize = 7;
+        let (a, b) = ([1u8; 32], [2u8; 32]);
+
+        // one party never got the dealing and still delivers
+        let mut parties: Vec<ReliableBroadcast> =
+            (1..=N).map(|i| ReliableBroadcast::new(N, i)).collect();
+        let mut proposals = vec![Some(a); N];
+        proposals[3] = None;
+        run(&mut parties, &proposals);
+        assert!(parties.iter().all(|p| p.delivered() == Some(&a)));
+
+        // a dealer splitting the parties gets nothing delivered
+        let mut parties: Vec<ReliableBroadcast> =
+            (1..=N).map(|i| ReliableBroadcast::new(N, i)).collect();
+        let proposals: Vec<Option<[u8; 32]>> =
+            (0..N).map(|i| Some(if i < 4 { a } else { b })).collect();
+        run(&mut parties, &proposals);
+        assert!(parties.iter().all(|p| p.delivered().is_none()));
+
+        assert!(matches!(
+            parties[0].ingest(2, Vote::Echo(b)).err().unwrap().kind(),
+            DuplicateMessage(2)
+        ));
+    }
+}
@@ -0,0 +1,262 @@
+// The roster every dealer and party of one committee is built from.
+// It fixes index i + 1 <-> public_keys[i], only admits keys with a valid proof of
+// possession, and caches the decompressed keys and the x_pows table.
+//
+// The fingerprint hashes the label, n, t, g and the keys in index order, two parties
+// holding the same fingerprint agree on the whole roster.
+
+use alloc::vec::Vec;
+
+use blake3::Hasher;
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+use rand::{CryptoRng, RngCore};
+
+use crate::{
+    error::{
+        Error,
+        ErrorKind::{ForeignPublicKey, InvalidPararmeterSet, UninitializedValue},
+    },
+    opcount,
+    pop::verify_public_keys,
+    precompute::gen_powers,
+    schnorr, verification_failure,
+};
+
+const DOMAIN: &[u8] = b"pi_vss/committee";
+
+#[derive(Clone)]
+pub struct Committee {
+    pub label: Vec<u8>,
+    pub n: usize,
+    pub t: usize,
+    // generator of the parties' public keys
+    pub g: RistrettoPoint,
+    pub public_keys: Vec<CompressedRistretto>,
+    pub points: Vec<RistrettoPoint>,
+    pub x_pows: Vec<Vec<Scalar>>,
+    pub fingerprint: [u8; 32],
+    // (administrator public key, signature over the fingerprint)
+    pub admin_signature: Option<(CompressedRistretto, (Scalar, Scalar))>,
+}
+
+impl Committee {
+    pub fn new(
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        t: usize,
+        label: &[u8],
+        registrations: (&[CompressedRistretto], &[(Scalar, Scalar)]),
+    ) -> Result<Self, Error> {
+        let n = registrations.0.len();
+        if t >= n || t != (n - 1) / 2 {
+            return Err(InvalidPararmeterSet(n, t as isize, 0).into());
+        }
+
+        let points = verify_public_keys(hasher, buf, g, label, registrations.0, registrations.1)?;
+        let fingerprint = fingerprint(hasher, g, t, label, registrations.0);
+
+        Ok(Self {
+            label: label.to_vec(),
+            n,
+            t,
+            g: *g,
+            public_keys: registrations.0.to_vec(),
+            points,
+            x_pows: gen_powers(n, t),
+            fingerprint,
+            admin_signature: None,
+        })
+    }
+
+    pub fn index_of(&self, public_key: &CompressedRistretto) -> Option<usize> {
+        self.public_keys
+            .iter()
+            .position(|pk| pk == public_key)
+            .map(|i| i + 1)
+    }
+
+    // Checks that `private_key` is the one registered under `index`.
+    pub fn check_membership(&self, private_key: &Scalar, index: usize) -> Result<(), Error> {
+        if index == 0 || index > self.n {
+            return Err(InvalidPararmeterSet(self.n, self.t as isize, index).into());
+        }
+        opcount::point_muls(1);
+        if self.g * private_key == self.points[index - 1] {
+            Ok(())
+        } else {
+            Err(ForeignPublicKey(index).into())
+        }
+    }
+
+    // `check_membership` for a key behind a handle, by its public key.
+    pub fn check_key(&self, public_key: &RistrettoPoint, index: usize) -> Result<(), Error> {
+        if index == 0 || index > self.n {
+            return Err(InvalidPararmeterSet(self.n, self.t as isize, index).into());
+        }
+        if *public_key == self.points[index - 1] {
+            Ok(())
+        } else {
+            Err(ForeignPublicKey(index).into())
+        }
+    }
+
+    pub fn sign<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        admin_private_key: &Scalar,
+    ) where
+        R: CryptoRng + RngCore,
+    {
+        opcount::point_muls(1);
+        opcount::compressions(1);
+        let admin_public_key = (self.g * admin_private_key).compress();
+        let signature = schnorr::sign(
+            rng,
+            hasher,
+            buf,
+            DOMAIN,
+            &self.g,
+            admin_private_key,
+            &self.fingerprint,
+        );
+        self.admin_signature = Some((admin_public_key, signature));
+    }
+
+    // True if the roster carries a valid signature by `admin_public_key`.
+    pub fn verify_signature(
+        &self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        admin_public_key: &CompressedRistretto,
+    ) -> Result<bool, Error> {
+        match &self.admin_signature {
+            Some((signer, signature)) => {
+                let valid = signer == admin_public_key
+                    && schnorr::verify(
+                        hasher,
+                        buf,
+                        DOMAIN,
+                        &self.g,
+                        signer,
+                        &self.fingerprint,
+                        signature,
+                    )?;
+                if !valid {
+                    verification_failure!("admin_signature");
+                }
+                Ok(valid)
+            }
+            None => Err(UninitializedValue("committee.admin_signature").into()),
+        }
+    }
+}
+
+fn fingerprint(
+    hasher: &mut Hasher,
+    g: &RistrettoPoint,
+    t: usize,
+    label: &[u8],
+    public_keys: &[CompressedRistretto],
+) -> [u8; 32] {
+    opcount::compressions(1);
+    opcount::hashes(
+        1,
+        DOMAIN.len() + 24 + label.len() + 32 * (public_keys.len() + 1),
+    );
+    hasher.update(DOMAIN);
+    hasher.update(&(label.len() as u64).to_le_bytes());
+    hasher.update(label);
+    hasher.update(&(public_keys.len() as u64).to_le_bytes());
+    hasher.update(&(t as u64).to_le_bytes());
+    hasher.update(g.compress().as_bytes());
+    public_keys.iter().for_each(|pk| {
+        hasher.update(pk.as_bytes());
+    });
+
+    let fingerprint = *hasher.finalize().as_bytes();
+    hasher.reset();
+    fingerprint
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use blake3::Hasher;
+    use curve25519_dalek::{Scalar, ristretto::CompressedRistretto};
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::{
+        committee::Committee,
+        error::ErrorKind::{ForeignPublicKey, InvalidPararmeterSet},
+        pop::prove_possession,
+        random::{random_point, random_scalar, random_scalars},
+    };
+
+    #[test]
+    fn committee_roster() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut hasher = Hasher::new();
+        let mut buf = [0u8; 64];
+        let g = random_point(&mut rng);
+        let label = b"committee";
+
+        let sks = random_scalars(&mut rng, 5);
+        let pks: Vec<CompressedRistretto> = sks.iter().map(|sk| (g * sk).compress()).collect();
+        let proofs: Vec<(Scalar, Scalar)> = sks
+            .iter()
+            .enumerate()
+            .map(|(i, sk)| prove_possession(&mut rng, &mut hasher, &mut buf, &g, sk, i + 1, label))
+            .collect();
+
+        let mut committee =
+            Committee::new(&mut hasher, &mut buf, &g, 2, label, (&pks, &proofs)).unwrap();
+        assert_eq!(committee.index_of(&pks[3]), Some(4));
+        assert!(committee.check_membership(&sks[3], 4).is_ok());
+        assert!(matches!(
+            committee.check_membership(&sks[3], 3).unwrap_err().kind(),
+            ForeignPublicKey(3)
+        ));
+
+        // same roster, same fingerprint
+        let other = Committee::new(&mut hasher, &mut buf, &g, 2, label, (&pks, &proofs)).unwrap();
+        assert_eq!(committee.fingerprint, other.fingerprint);
+
+        assert!(matches!(
+            Committee::new(&mut hasher, &mut buf, &g, 1, label, (&pks, &proofs))
+                .err()
+                .unwrap()
+                .kind(),
+            InvalidPararmeterSet(5, 1, 0)
+        ));
+
+        let admin = random_scalar(&mut rng);
+        let admin_pk = (g * admin).compress();
+        assert!(
+            committee
+                .verify_signature(&mut hasher, &mut buf, &admin_pk)
+                .is_err()
+        );
+        committee.sign(&mut rng, &mut hasher, &mut buf, &admin);
+        assert!(
+            committee
+                .verify_signature(&mut hasher, &mut buf, &admin_pk)
+                .unwrap()
+        );
+        assert!(
+            !committee
+                .verify_signature(&mut hasher, &mut buf, &pks[0])
+                .unwrap()
+        );
+
+        committee.fingerprint[0] ^= 1;
+        assert!(
+            !committee
+                .verify_signature(&mut hasher, &mut buf, &admin_pk)
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,149 @@
+// Complaint round of the privately verifiable schemes (pi_p, pi_la, pi_f).
+// A party whose share fails `verify_share` complains publicly, the dealer answers every
+// complaint by revealing that party's share, and every party checks the revealed shares
+// against the published commitments c_i.
+//
+// The verdict only depends on the public complaints, answers and commitments, so every
+// honest party reaches the same one: the dealer is disqualified if more than t parties
+// complained, a complaint went unanswered or a revealed share doesn't open its
+// commitment. Otherwise the dealing stands and complaining parties adopt their revealed
+// share.
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+
+use crate::{
+    error::{
+        Error,
+        ErrorKind::{DuplicateMessage, UnexpectedMessage},
+    },
+    session::DEALER,
+    verification_failure,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Accept,
+    // with the reason
+    Disqualify(&'static str),
+}
+
+#[derive(Clone)]
+pub struct Complaints<S> {
+    pub n: usize,
+    pub t: usize,
+    pub complaints: BTreeSet<usize>,
+    // revealed share by complaining party
+    pub answers: BTreeMap<usize, S>,
+}
+
+impl<S: Clone> Complaints<S> {
+    pub fn new(n: usize, t: usize) -> Self {
+        Self {
+            n,
+            t,
+            complaints: BTreeSet::new(),
+            answers: BTreeMap::new(),
+        }
+    }
+
+    pub fn complain(&mut self, index: usize) -> Result<(), Error> {
+        if index == DEALER || index > self.n {
+            return Err(UnexpectedMessage(index, "collecting complaints").into());
+        }
+        if !self.complaints.insert(index) {
+            return Err(DuplicateMessage(index).into());
+        }
+        Ok(())
+    }
+
+    // Dealer side: the shares to reveal out of all of them, `shares[i - 1]` for party i.
+    pub fn reveal(&self, shares: &[S]) -> Vec<(usize, S)> {
+        self.complaints
+            .iter()
+            .filter_map(|i| shares.get(i - 1).map(|share| (*i, share.clone())))
+            .collect()
+    }
+
+    // The dealer's answer, one revealed share per complaint.
+    pub fn answer(&mut self, answers: Vec<(usize, S)>) -> Result<(), Error> {
+        for (index, share) in answers {
+            if !self.complaints.contains(&index) {
+                return Err(UnexpectedMessage(DEALER, "revealing an uncontested share").into());
+            }
+            if self.answers.insert(index, share).is_some() {
+                return Err(DuplicateMessage(DEALER).into());
+            }
+        }
+        Ok(())
+    }
+
+    // Checks every complaint in index order, `check(i, share)` opens c_i with a revealed
+    // share.
+    pub fn resolve<F>(&self, mut check: F) -> Result<Verdict, Error>
+    where
+        F: FnMut(usize, &S) -> Result<bool, Error>,
+    {
+        if self.complaints.len() > self.t {
+            verification_failure!("too_many_complaints");
+            return Ok(Verdict::Disqualify("too_many_complaints"));
+        }
+        for index in &self.complaints {
+            match self.answers.get(index) {
+                Some(share) => {
+                    if !check(*index, share)? {
+                        return Ok(Verdict::Disqualify("invalid_reveal"));
+                    }
+                }
+                None => {
+                    verification_failure!("unanswered_complaint");
+                    return Ok(Verdict::Disqualify("unanswered_complaint"));
+                }
+            }
+        }
+        Ok(Verdict::Accept)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use crate::complaint::{Complaints, Verdict};
+
+    #[test]
+    fn complaint_verdicts() {
+        let shares = [10u64, 20, 30, 40, 50];
+        let valid = |i: usize, s: &u64| Ok(*s == 10 * i as u64);
+
+        let mut complaints: Complaints<u64> = Complaints::new(5, 2);
+        assert_eq!(complaints.resolve(valid).unwrap(), Verdict::Accept);
+
+        complaints.complain(2).unwrap();
+        complaints.complain(4).unwrap();
+        assert!(complaints.complain(4).is_err());
+        assert_eq!(
+            complaints.resolve(valid).unwrap(),
+            Verdict::Disqualify("unanswered_complaint")
+        );
+
+        let mut forged = complaints.clone();
+        forged.answer(vec![(2, 20), (4, 41)]).unwrap();
+        assert_eq!(
+            forged.resolve(valid).unwrap(),
+            Verdict::Disqualify("invalid_reveal")
+        );
+
+        assert!(complaints.clone().answer(vec![(3, 30)]).is_err());
+        complaints.answer(complaints.reveal(&shares)).unwrap();
+        assert_eq!(complaints.resolve(valid).unwrap(), Verdict::Accept);
+
+        complaints.complain(5).unwrap();
+        assert_eq!(
+            complaints.resolve(valid).unwrap(),
+            Verdict::Disqualify("too_many_complaints")
+        );
+    }
+}
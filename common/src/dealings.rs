@@ -0,0 +1,163 @@
+// Many in-flight dealings side by side.
+// Dealers and parties hold the state of exactly one dealing, so a `Dealings` keeps one
+// copy of that state per (dealer id, session id), each cloned from a template that has
+// keys but no dealing yet. The clones share the template's key handle, the private key
+// itself is never copied. A party tracks every dealing it receives, a dealer tracks
+// its own sessions under its own id unless it keeps them itself, as the pi_s dealers do.
+//
+// Each tracked dealing is identified by its transcript digest. A second dealing under
+// the same id with another digest is an equivocating dealer, not an update.
+
+use alloc::collections::BTreeMap;
+
+use blake3::Hasher;
+
+use crate::{
+    error::{Error, ErrorKind::ConflictingDealing},
+    message::Encode,
+    verification_failure,
+};
+
+const DOMAIN: &[u8] = b"pi_vss/dealing";
+
+// (dealer id, session id)
+pub type DealingId = (usize, [u8; 32]);
+
+// Canonical digest of everything the dealer published in one session.
+pub fn transcript_digest<T: Encode>(hasher: &mut Hasher, id: &DealingId, dealing: &T) -> [u8; 32] {
+    hasher.update(DOMAIN);
+    id.0.encode(hasher);
+    hasher.update(&id.1);
+    dealing.encode(hasher);
+
+    let digest = *hasher.finalize().as_bytes();
+    hasher.reset();
+    digest
+}
+
+#[derive(Clone)]
+pub struct Dealings<S> {
+    pub template: S,
+    // (transcript digest once the dealing is known, state)
+    pub in_flight: BTreeMap<DealingId, (Option<[u8; 32]>, S)>,
+}
+
+impl<S: Clone> Dealings<S> {
+    pub fn new(template: S) -> Self {
+        Self {
+            template,
+            in_flight: BTreeMap::new(),
+        }
+    }
+
+    // The state of `id`, started from the template if it isn't tracked yet.
+    pub fn entry(&mut self, id: DealingId) -> &mut S {
+        &mut self
+            .in_flight
+            .entry(id)
+            .or_insert_with(|| (None, self.template.clone()))
+            .1
+    }
+
+    // Records `dealing` as the one published under `id` and returns its digest and state.
+    // Fails if a different dealing was already recorded under `id`.
+    pub fn track<T: Encode>(
+        &mut self,
+        hasher: &mut Hasher,
+        id: DealingId,
+        dealing: &T,
+    ) -> Result<([u8; 32], &mut S), Error> {
+        let digest = transcript_digest(hasher, &id, dealing);
+        let (recorded, state) = self
+            .in_flight
+            .entry(id)
+            .or_insert_with(|| (None, self.template.clone()));
+
+        match recorded {
+            Some(recorded) if *recorded != digest => {
+                verification_failure!("conflicting_dealing");
+                Err(ConflictingDealing(id.0).into())
+            }
+            _ => {
+                *recorded = Some(digest);
+                Ok((digest, state))
+            }
+        }
+    }
+
+    pub fn get(&self, id: &DealingId) -> Option<&S> {
+        self.in_flight.get(id).map(|(_, state)| state)
+    }
+
+    pub fn get_mut(&mut self, id: &DealingId) -> Option<&mut S> {
+        self.in_flight.get_mut(id).map(|(_, state)| state)
+    }
+
+    pub fn digest(&self, id: &DealingId) -> Option<&[u8; 32]> {
+        self.in_flight
+            .get(id)
+            .and_then(|(digest, _)| digest.as_ref())
+    }
+
+    // Stops tracking `id` and hands back its state.
+    pub fn finish(&mut self, id: &DealingId) -> Option<S> {
+        self.in_flight.remove(id).map(|(_, state)| state)
+    }
+
+    pub fn len(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.in_flight.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{vec, vec::Vec};
+
+    use blake3::Hasher;
+    use curve25519_dalek::Scalar;
+
+    use crate::{dealings::Dealings, error::ErrorKind::ConflictingDealing};
+
+    #[test]
+    fn dealings_are_tracked_per_dealer_and_session() {
+        let mut hasher = Hasher::new();
+        let mut dealings: Dealings<Vec<usize>> = Dealings::new(vec![]);
+
+        let first = vec![Scalar::ONE, Scalar::ZERO];
+        let second = vec![Scalar::ZERO, Scalar::ONE];
+
+        let (d1, state) = dealings.track(&mut hasher, (1, [0; 32]), &first).unwrap();
+        state.push(1);
+        let (d2, state) = dealings.track(&mut hasher, (2, [0; 32]), &first).unwrap();
+        state.push(2);
+        let (d3, _) = dealings.track(&mut hasher, (1, [1; 32]), &first).unwrap();
+
+        // the same dealing under another dealer or session has another digest
+        assert!(d1 != d2 && d1 != d3 && d2 != d3);
+        assert_eq!(dealings.len(), 3);
+        assert_eq!(dealings.get(&(1, [0; 32])), Some(&vec![1]));
+        assert_eq!(dealings.get(&(2, [0; 32])), Some(&vec![2]));
+
+        // redelivery is fine, equivocation isn't
+        assert_eq!(
+            dealings.track(&mut hasher, (1, [0; 32]), &first).unwrap().0,
+            d1
+        );
+        assert!(matches!(
+            dealings
+                .track(&mut hasher, (1, [0; 32]), &second)
+                .err()
+                .unwrap()
+                .kind(),
+            ConflictingDealing(1)
+        ));
+
+        assert_eq!(dealings.finish(&(1, [0; 32])), Some(vec![1]));
+        assert!(dealings.digest(&(1, [0; 32])).is_none());
+        assert_eq!(dealings.digest(&(2, [0; 32])), Some(&d2));
+    }
+}
@@ -0,0 +1,292 @@
+// Hybrid encryption of the private shares of pi_p, pi_la, pi_f and their batched versions.
+// The dealer encrypts party i's share to its registered key pk_i = g^sk_i with a fresh
+// ephemeral key e: the shared point pk_i^e = E^sk_i is hashed with the transcript digest
+// of the public dealing, i, pk_i and E = g^e into a one-time ChaCha20-Poly1305 key. A
+// share that decrypts was therefore meant for this party in this very dealing, one
+// replayed from another dealing or aimed at another party doesn't authenticate.
+//
+// Keys are never reused, so the nonce is fixed.
+
+use alloc::vec::Vec;
+
+use blake3::Hasher;
+use chacha20poly1305::{
+    ChaCha20Poly1305, KeyInit, Nonce,
+    aead::{Aead, Payload},
+};
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::{
+    error::{Error, ErrorKind::InvalidCiphertext},
+    keystore::KeyHandle,
+    message::Encode,
+    opcount,
+    random::random_scalar,
+    verification_failure,
+};
+
+const DOMAIN: &[u8] = b"pi_vss/share-encryption";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ciphertext {
+    pub ephemeral: CompressedRistretto,
+    // the sealed share and its tag
+    pub body: Vec<u8>,
+}
+
+impl Encode for Ciphertext {
+    fn encode(&self, hasher: &mut Hasher) {
+        self.ephemeral.encode(hasher);
+        self.body.len().encode(hasher);
+        hasher.update(&self.body);
+    }
+}
+
+// Byte encoding of a share, scalars back to back.
+pub trait Plaintext: Sized {
+    fn write_bytes(&self, bytes: &mut Vec<u8>);
+    fn read_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+impl Plaintext for Scalar {
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(self.as_bytes());
+    }
+
+    fn read_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: [u8; 32] = bytes.try_into().ok()?;
+        Scalar::from_canonical_bytes(bytes).into()
+    }
+}
+
+impl Plaintext for Vec<Scalar> {
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        self.iter().for_each(|s| s.write_bytes(bytes));
+    }
+
+    fn read_bytes(bytes: &[u8]) -> Option<Self> {
+        if !bytes.len().is_multiple_of(32) {
+            return None;
+        }
+        bytes.chunks_exact(32).map(Scalar::read_bytes).collect()
+    }
+}
+
+// (share, blinding) of the pedersen-style schemes, the blinding goes last.
+impl<A: Plaintext> Plaintext for (A, Scalar) {
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        self.0.write_bytes(bytes);
+        self.1.write_bytes(bytes);
+    }
+
+    fn read_bytes(bytes: &[u8]) -> Option<Self> {
+        let (a, b) = bytes.split_at(bytes.len().checked_sub(32)?);
+        Some((A::read_bytes(a)?, Scalar::read_bytes(b)?))
+    }
+}
+
+fn key_schedule(
+    hasher: &mut Hasher,
+    transcript: &[u8; 32],
+    recipient: (usize, &CompressedRistretto),
+    ephemeral: &CompressedRistretto,
+    shared: &CompressedRistretto,
+) -> [u8; 32] {
+    opcount::hashes(1, DOMAIN.len() + 8 + 4 * 32);
+    hasher.update(DOMAIN);
+    hasher.update(transcript);
+    recipient.0.encode(hasher);
+    hasher.update(recipient.1.as_bytes());
+    hasher.update(ephemeral.as_bytes());
+    hasher.update(shared.as_bytes());
+
+    let key = *hasher.finalize().as_bytes();
+    hasher.reset();
+    key
+}
+
+// transcript digest and recipient, bound to the ciphertext
+fn associated_data(transcript: &[u8; 32], index: usize) -> [u8; 40] {
+    let mut aad = [0u8; 40];
+    aad[..32].copy_from_slice(transcript);
+    aad[32..].copy_from_slice(&(index as u64).to_le_bytes());
+    aad
+}
+
+// Encrypts `share` to party `recipient` = (index, public key) under the dealing whose
+// transcript digest is `transcript`.
+pub fn encrypt<R, S>(
+    rng: &mut R,
+    hasher: &mut Hasher,
+    g: &RistrettoPoint,
+    recipient: (usize, &RistrettoPoint),
+    transcript: &[u8; 32],
+    share: &S,
+) -> Result<Ciphertext, Error>
+where
+    R: CryptoRng + RngCore,
+    S: Plaintext,
+{
+    opcount::point_muls(2);
+    opcount::compressions(3);
+    let (index, public_key) = recipient;
+    let mut e = random_scalar(rng);
+    let ephemeral = (g * e).compress();
+    let shared = (public_key * e).compress();
+    e.zeroize();
+
+    let mut key = key_schedule(
+        hasher,
+        transcript,
+        (index, &public_key.compress()),
+        &ephemeral,
+        &shared,
+    );
+    let cipher = ChaCha20Poly1305::new(&key.into());
+    key.zeroize();
+
+    let mut plaintext = Vec::new();
+    share.write_bytes(&mut plaintext);
+    let body = cipher
+        .encrypt(
+            &Nonce::default(),
+            Payload {
+                msg: &plaintext,
+                aad: &associated_data(transcript, index),
+            },
+        )
+        .map_err(|_| InvalidCiphertext(index));
+    plaintext.zeroize();
+
+    Ok(Ciphertext {
+        ephemeral,
+        body: body?,
+    })
+}
+
+// `shares[i - 1]` encrypted to party i under `public_keys[i - 1]`.
+pub fn encrypt_shares<R, S>(
+    rng: &mut R,
+    hasher: &mut Hasher,
+    g: &RistrettoPoint,
+    public_keys: &[RistrettoPoint],
+    transcript: &[u8; 32],
+    shares: &[S],
+) -> Result<Vec<Ciphertext>, Error>
+where
+    R: CryptoRng + RngCore,
+    S: Plaintext,
+{
+    public_keys
+        .iter()
+        .zip(shares)
+        .enumerate()
+        .map(|(i, (public_key, share))| {
+            encrypt(rng, hasher, g, (i + 1, public_key), transcript, share)
+        })
+        .collect()
+}
+
+// Opens a share sent to `recipient` = (index, its key) under the dealing whose transcript
+// digest is `transcript`. Fails unless it authenticates and decodes.
+pub fn decrypt<S: Plaintext>(
+    hasher: &mut Hasher,
+    recipient: (usize, &dyn KeyHandle),
+    transcript: &[u8; 32],
+    ciphertext: &Ciphertext,
+) -> Result<S, Error> {
+    let (index, handle) = recipient;
+    opcount::decompressions(1);
+    let ephemeral = match ciphertext.ephemeral.decompress() {
+        Some(point) if point != RistrettoPoint::default() => point,
+        _ => {
+            verification_failure!("share_decryption");
+            return Err(InvalidCiphertext(index).into());
+        }
+    };
+    opcount::point_muls(1);
+    opcount::compressions(1);
+    let shared = handle.diffie_hellman(&ephemeral)?.compress();
+
+    let mut key = key_schedule(
+        hasher,
+        transcript,
+        (index, &handle.public_key().0),
+        &ciphertext.ephemeral,
+        &shared,
+    );
+    let cipher = ChaCha20Poly1305::new(&key.into());
+    key.zeroize();
+
+    let plaintext = cipher.decrypt(
+        &Nonce::default(),
+        Payload {
+            msg: &ciphertext.body,
+            aad: &associated_data(transcript, index),
+        },
+    );
+    match plaintext.ok().and_then(|mut plaintext| {
+        let share = S::read_bytes(&plaintext);
+        plaintext.zeroize();
+        share
+    }) {
+        Some(share) => Ok(share),
+        None => {
+            verification_failure!("share_decryption");
+            Err(InvalidCiphertext(index).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{vec, vec::Vec};
+
+    use blake3::Hasher;
+    use curve25519_dalek::Scalar;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::{
+        encryption::{decrypt, encrypt},
+        error::{Error, ErrorKind::InvalidCiphertext},
+        keystore::{KeyHandle, MemoryKey},
+        random::{random_point, random_scalar},
+    };
+
+    #[test]
+    fn share_encryption() {
+        let mut rng = StdRng::seed_from_u64(39);
+        let mut hasher = Hasher::new();
+        let g = random_point(&mut rng);
+        let key = MemoryKey::generate(&mut rng, &g);
+        let pk = key.public_key().1;
+        let recipient: (usize, &dyn KeyHandle) = (3, &key);
+        let (transcript, other) = ([1u8; 32], [2u8; 32]);
+
+        let share = (vec![random_scalar(&mut rng); 3], random_scalar(&mut rng));
+        let ciphertext = encrypt(&mut rng, &mut hasher, &g, (3, &pk), &transcript, &share).unwrap();
+        let opened: (Vec<Scalar>, Scalar) =
+            decrypt(&mut hasher, recipient, &transcript, &ciphertext).unwrap();
+        assert_eq!(opened, share);
+
+        // another dealing, another party or a tampered body don't authenticate
+        let rejected = |result: Result<(Vec<Scalar>, Scalar), Error>, index: usize| matches!(result.err().unwrap().kind(), InvalidCiphertext(i) if *i == index);
+        assert!(rejected(
+            decrypt(&mut hasher, recipient, &other, &ciphertext),
+            3
+        ));
+        assert!(rejected(
+            decrypt(&mut hasher, (4, &key), &transcript, &ciphertext),
+            4
+        ));
+        let mut tampered = ciphertext.clone();
+        tampered.body[0] ^= 1;
+        assert!(rejected(
+            decrypt(&mut hasher, recipient, &transcript, &tampered),
+            3
+        ));
+    }
+}
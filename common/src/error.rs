@@ -1,31 +1,157 @@
-use error_chain::error_chain;
+use alloc::string::String;
+use core::fmt;
 
-error_chain! {
-    errors{
-        PointDecompressionError(t: String) {
-            description("Unable to Decompress Compressed ristretto Point")
-            display("Unable to Decompress Compressed ristretto Point: '{}'", t)
-        }
-        CountMismatch(c1: usize, c1_type: &'static str, c2: usize, c2_type: &'static str) {
-            description("The number of {c1_type} does not match the number of {c2_type}.")
-            display("The number of {c1_type} does not match the number of {c2_type}.\nHave {c1} {c1_type} but the number of {c2} is {c2_type}.")
-        }
-        InsufficientShares(count: usize, t: usize){
-            description("The number of validated shares is less than the required threshold.")
-            display("The number of validated shares is {count}. This is less than the required t+1 shares (t+1 = {}).", t+1)
+#[derive(Debug)]
+pub enum ErrorKind {
+    PointDecompressionError(String),
+    CountMismatch(usize, &'static str, usize, &'static str),
+    InsufficientShares(usize, usize),
+    EmptyQualifiedSet,
+    InconsistentShares(usize),
+    UninitializedValue(&'static str),
+    InvalidPararmeterSet(usize, isize, usize),
+    InvalidProof(String),
+    ThreadPoolBuildError(String),
+    IdentityPublicKey(usize),
+    DuplicatePublicKey(usize, usize),
+    InvalidPossessionProof(usize),
+    ForeignPublicKey(usize),
+    InvalidSignature(usize),
+    WrongSession(usize, u64),
+    DuplicateMessage(usize),
+    ConflictingDealing(usize),
+    UnexpectedMessage(usize, &'static str),
+    TransportError(String),
+    InvalidCiphertext(usize),
+    KeyStoreError(String),
+    SnapshotError(String),
+    TranscriptError(String),
+    StreamError(String),
+    UnknownSender(usize),
+}
 
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::PointDecompressionError(t) => {
+                write!(
+                    f,
+                    "Unable to Decompress Compressed ristretto Point: '{}'",
+                    t
+                )
+            }
+            ErrorKind::CountMismatch(c1, c1_type, c2, c2_type) => write!(
+                f,
+                "The number of {c1_type} does not match the number of {c2_type}.\nHave {c1} {c1_type} but the number of {c2} is {c2_type}."
+            ),
+            ErrorKind::InsufficientShares(count, t) => write!(
+                f,
+                "The number of validated shares is {count}. This is less than the required t+1 shares (t+1 = {}).",
+                t + 1
+            ),
+            ErrorKind::EmptyQualifiedSet => {
+                write!(f, "Attempted to reconstruct from an empty qualified set")
+            }
+            ErrorKind::InconsistentShares(index) => write!(
+                f,
+                "The share of party {} does not lie on the polynomial the other shares interpolate",
+                index
+            ),
+            ErrorKind::UninitializedValue(t) => {
+                write!(f, "Attempted to operate on an unititalized value {}", t)
+            }
+            ErrorKind::InvalidPararmeterSet(n, t, index) => write!(
+                f,
+                "Invalid Parameter Set: n = {}, t = {}, index = {}.\n Valid params: n > t, index <= n, t => (n+1)/2",
+                n, t, index
+            ),
+            ErrorKind::InvalidProof(t) => write!(f, "Invalid Dealer Proof: {}", t),
+            ErrorKind::ThreadPoolBuildError(t) => {
+                write!(f, "Unable to build thread pool: {}", t)
+            }
+            ErrorKind::IdentityPublicKey(index) => {
+                write!(
+                    f,
+                    "Party {} registered the identity as its public key",
+                    index
+                )
+            }
+            ErrorKind::DuplicatePublicKey(first, second) => write!(
+                f,
+                "Parties {} and {} registered the same public key",
+                first, second
+            ),
+            ErrorKind::InvalidPossessionProof(index) => {
+                write!(f, "Invalid proof of possession for party {}", index)
+            }
+            ErrorKind::ForeignPublicKey(index) => {
+                write!(
+                    f,
+                    "The public key registered under index {} is not ours",
+                    index
+                )
+            }
+            ErrorKind::InvalidSignature(sender) => {
+                write!(f, "Invalid signature on a message from party {}", sender)
+            }
+            ErrorKind::WrongSession(sender, round) => write!(
+                f,
+                "Message from party {} (round {}) does not belong to this session and round",
+                sender, round
+            ),
+            ErrorKind::DuplicateMessage(sender) => {
+                write!(f, "Party {} sent more than one message this round", sender)
+            }
+            ErrorKind::UnexpectedMessage(sender, phase) => write!(
+                f,
+                "Unexpected message from party {} while {}",
+                sender, phase
+            ),
+            ErrorKind::ConflictingDealing(dealer) => write!(
+                f,
+                "Dealer {} published two different dealings in the same session",
+                dealer
+            ),
+            ErrorKind::TransportError(t) => write!(f, "Transport failure: {}", t),
+            ErrorKind::InvalidCiphertext(index) => {
+                write!(f, "Encrypted share for party {} doesn't decrypt", index)
+            }
+            ErrorKind::KeyStoreError(t) => write!(f, "Key store failure: {}", t),
+            ErrorKind::SnapshotError(t) => write!(f, "Snapshot failure: {}", t),
+            ErrorKind::TranscriptError(t) => write!(f, "Transcript failure: {}", t),
+            ErrorKind::StreamError(t) => write!(f, "Stream failure: {}", t),
+            ErrorKind::UnknownSender(sender) => {
+                write!(f, "No public key is registered for party {}", sender)
+            }
         }
-        UninitializedValue(t: &'static str) {
-            description("Attempted to operate on an unititalized value")
-            display("Attempted to operate on an unititalized value {}", t)
-        }
-        InvalidPararmeterSet(n: usize, t: isize, index: usize){
-            description("Invalid Parameter Set")
-            display("Invalid Parameter Set: n = {}, t = {}, index = {}.\n Valid params: n > t, index <= n, t => (n+1)/2", n, t, index)
-        }
-        InvalidProof(t: String) {
-            description("Invalid Dealer Proof")
-            display("Invalid Dealer Proof: {}", t)
-        }
     }
 }
+
+#[derive(Debug)]
+pub struct Error(pub ErrorKind);
+
+impl Error {
+    pub fn from_kind(kind: ErrorKind) -> Self {
+        Self(kind)
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.0
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Self(kind)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl core::error::Error for Error {}
+
+pub type Result<T> = core::result::Result<T, Error>;
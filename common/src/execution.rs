@@ -0,0 +1,180 @@
+#[cfg(feature = "parallel")]
+use alloc::{format, sync::Arc};
+#[cfg(feature = "parallel")]
+use core::cell::Cell;
+
+use rand::{CryptoRng, RngCore, SeedableRng, rngs::StdRng};
+#[cfg(feature = "parallel")]
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use crate::error::Error;
+#[cfg(feature = "parallel")]
+use crate::error::ErrorKind::ThreadPoolBuildError;
+
+// Where the parallel iterators of a dealer or party run.
+// Without the `parallel` feature every variant runs sequentially on the caller.
+#[derive(Clone, Default)]
+pub enum ExecutionContext {
+    // rayon's global pool
+    #[default]
+    Global,
+    // a dedicated pool, shared with whoever else holds it
+    #[cfg(feature = "parallel")]
+    Pool(Arc<ThreadPool>),
+    // a single worker thread of its own, every par_iter runs in order,
+    // clones share it but other contexts never queue behind it
+    Sequential(#[cfg(feature = "parallel")] Arc<ThreadPool>),
+}
+
+#[cfg(feature = "parallel")]
+thread_local! {
+    static GRANULARITY: Cell<usize> = const { Cell::new(1) };
+}
+
+// Minimum number of items a parallel job is split into on this thread.
+// Only pools built by `ExecutionContext::with_threads` set it.
+pub fn granularity() -> usize {
+    #[cfg(feature = "parallel")]
+    {
+        GRANULARITY.with(Cell::get)
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        1
+    }
+}
+
+impl ExecutionContext {
+    #[cfg(feature = "parallel")]
+    pub fn with_threads(threads: usize, granularity: usize) -> Result<Self, Error> {
+        let granularity = granularity.max(1);
+        match ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .start_handler(move |_| GRANULARITY.with(|g| g.set(granularity)))
+            .build()
+        {
+            Ok(pool) => Ok(Self::Pool(Arc::new(pool))),
+            Err(x) => Err(ThreadPoolBuildError(format!("{x}")).into()),
+        }
+    }
+
+    pub fn sequential() -> Result<Self, Error> {
+        #[cfg(feature = "parallel")]
+        {
+            match ThreadPoolBuilder::new()
+                .num_threads(1)
+                .thread_name(|_| "pi_vss-sequential".into())
+                .build()
+            {
+                Ok(pool) => Ok(Self::Sequential(Arc::new(pool))),
+                Err(x) => Err(ThreadPoolBuildError(format!("{x}")).into()),
+            }
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            Ok(Self::Sequential())
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    pub fn with_pool(pool: Arc<ThreadPool>) -> Self {
+        Self::Pool(pool)
+    }
+
+    pub fn threads(&self) -> usize {
+        match self {
+            #[cfg(feature = "parallel")]
+            Self::Global => rayon::current_num_threads(),
+            #[cfg(feature = "parallel")]
+            Self::Pool(pool) => pool.current_num_threads(),
+            _ => 1,
+        }
+    }
+
+    pub fn install<F, T>(&self, op: F) -> T
+    where
+        F: FnOnce() -> T + Send,
+        T: Send,
+    {
+        // keep the caller's span as parent of whatever the pool thread opens
+        #[cfg(all(feature = "parallel", feature = "tracing"))]
+        let op = {
+            let span = tracing::Span::current();
+            move || span.in_scope(op)
+        };
+
+        match self {
+            #[cfg(feature = "parallel")]
+            Self::Pool(pool) => pool.install(op),
+            #[cfg(feature = "parallel")]
+            Self::Sequential(pool) => pool.install(op),
+            _ => op(),
+        }
+    }
+
+    // Thread-local rngs can't follow the work onto the pool,
+    // so it gets a CSPRNG seeded from the caller's instead.
+    pub fn install_with_rng<R, F, T>(&self, rng: &mut R, op: F) -> T
+    where
+        R: CryptoRng + RngCore,
+        F: FnOnce(&mut StdRng) -> T + Send,
+        T: Send,
+    {
+        let mut rng = StdRng::from_rng(rng);
+        self.install(move || op(&mut rng))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::{execution::ExecutionContext, polynomial::Polynomial, precompute::gen_powers};
+
+    #[test]
+    fn same_result_on_every_context() {
+        let n = 64;
+        let t = 31;
+        let x_pows = gen_powers(n, t);
+
+        let contexts = [
+            ExecutionContext::Global,
+            ExecutionContext::sequential().unwrap(),
+            ExecutionContext::with_threads(2, 8).unwrap(),
+        ];
+
+        let evals: Vec<_> = contexts
+            .iter()
+            .map(|ctx| {
+                let mut rng = StdRng::seed_from_u64(42);
+                ctx.install_with_rng(&mut rng, |rng| {
+                    Polynomial::sample(t, rng).evaluate_range_precomp(&x_pows, 1, n)
+                })
+            })
+            .collect();
+
+        assert_eq!(contexts[1].threads(), 1);
+        assert_eq!(contexts[2].threads(), 2);
+        assert!(evals.windows(2).all(|w| w[0] == w[1]));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn sequential_contexts_do_not_share_a_worker() {
+        let worker = |ctx: &ExecutionContext| ctx.install(|| std::thread::current().id());
+
+        let a = ExecutionContext::sequential().unwrap();
+        let b = ExecutionContext::sequential().unwrap();
+
+        // concurrent callers on different contexts each get their own thread
+        let (on_a, on_b) = std::thread::scope(|s| {
+            let on_a = s.spawn(|| worker(&a));
+            let on_b = s.spawn(|| worker(&b));
+            (on_a.join().unwrap(), on_b.join().unwrap())
+        });
+
+        assert_ne!(on_a, on_b);
+        assert_eq!(worker(&a.clone()), on_a);
+        assert_ne!(on_a, std::thread::current().id());
+    }
+}
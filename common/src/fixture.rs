@@ -0,0 +1,139 @@
+// Setup the schemes' tests share.
+// `Fixture` is what nearly every test starts from: an rng, the scratch hasher and buffer the
+// checks run with, a generator and the x powers of an n-party committee, `N` and `T` unless
+// the test needs a larger one. Tests destructure it and borrow the parts independently.
+//
+// A scheme's parties get each other's keys through `distribute_public_keys`, over a marker
+// `committee!` declares for its `Party`, and `sessions` wraps them and a dealer in session
+// drivers.
+
+use std::vec::Vec;
+
+use blake3::Hasher;
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+use rand::{CryptoRng, RngCore, rngs::ThreadRng};
+
+use crate::{
+    keystore::MemoryKey,
+    precompute::gen_powers,
+    random::random_point,
+    session::{DealerSession, PartySession, SessionDealer},
+    utils::ingest_public_keys,
+};
+
+pub const N: usize = 16;
+pub const T: usize = 7;
+
+pub struct Fixture {
+    pub rng: ThreadRng,
+    pub hasher: Hasher,
+    pub buf: [u8; 64],
+    pub g: RistrettoPoint,
+    pub xpows: Vec<Vec<Scalar>>,
+}
+
+impl Fixture {
+    pub fn new(n: usize, t: usize) -> Self {
+        let mut rng = rand::rng();
+        let g = random_point(&mut rng);
+        Self {
+            rng,
+            hasher: Hasher::new(),
+            buf: [0u8; 64],
+            g,
+            xpows: gen_powers(n, t),
+        }
+    }
+}
+
+// The parties of one scheme as the fixture sees them. Tests implement it on a marker type of
+// their own with `committee!`, the scheme's `Party` may be foreign to them.
+pub trait Committee {
+    type Party;
+
+    fn index(party: &Self::Party) -> usize;
+    fn public_key(party: &Self::Party) -> &(CompressedRistretto, RistrettoPoint);
+    fn set_public_keys(party: &mut Self::Party, public_keys: Vec<RistrettoPoint>);
+}
+
+// Declares the marker `$scheme` and implements `Committee` for it over a `$party` with the
+// usual `index`, `public_key` and `public_keys` fields.
+#[macro_export]
+macro_rules! committee {
+    ($scheme:ident, $party:ty) => {
+        struct $scheme;
+
+        impl $crate::fixture::Committee for $scheme {
+            type Party = $party;
+
+            fn index(party: &$party) -> usize {
+                party.index
+            }
+
+            fn public_key(
+                party: &$party,
+            ) -> &(
+                curve25519_dalek::ristretto::CompressedRistretto,
+                curve25519_dalek::RistrettoPoint,
+            ) {
+                &party.public_key
+            }
+
+            fn set_public_keys(
+                party: &mut $party,
+                public_keys: Vec<curve25519_dalek::RistrettoPoint>,
+            ) {
+                party.public_keys = Some(public_keys);
+            }
+        }
+    };
+}
+
+// Hands every party everyone else's key and returns all of them in party order, for the dealer.
+pub fn distribute_public_keys<C: Committee>(parties: &mut [C::Party]) -> Vec<CompressedRistretto> {
+    let public_keys: Vec<CompressedRistretto> =
+        parties.iter().map(|party| C::public_key(party).0).collect();
+
+    for party in parties.iter_mut() {
+        let (own, own_point) = *C::public_key(party);
+        let others: Vec<CompressedRistretto> = public_keys
+            .iter()
+            .filter(|pk| **pk != own)
+            .copied()
+            .collect();
+
+        let ingested =
+            ingest_public_keys(public_keys.len(), &own_point, C::index(party), &others).unwrap();
+        C::set_public_keys(party, ingested);
+    }
+
+    public_keys
+}
+
+// A dealer and party sessions around a committee whose parties hold each other's keys
+// already, under `g`, the generator of those keys. The dealer signs with a fresh key.
+pub fn sessions<'a, D, R>(
+    rng: &mut R,
+    g: RistrettoPoint,
+    xpows: &'a Vec<Vec<Scalar>>,
+    dealer: D,
+    parties: Vec<D::Party>,
+) -> (DealerSession<'a, D>, Vec<PartySession<'a, D::Party>>)
+where
+    D: SessionDealer,
+    R: CryptoRng + RngCore,
+{
+    let session_id = [7u8; 32];
+    let key = MemoryKey::generate(rng, &g).shared();
+    let dealer_public_key = key.public_key().0;
+    let sessions = parties
+        .into_iter()
+        .map(|party| PartySession::new(party, g, dealer_public_key, session_id, xpows))
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    (
+        DealerSession::new(dealer, key, g, session_id, xpows),
+        sessions,
+    )
+}
@@ -0,0 +1,215 @@
+// Verification keys of a dealing: the group public key g^f(0) and every party's g^f(i),
+// what threshold schemes built on the VSS check partial results against.
+// Dealers of the schemes with plain shares can publish Feldman commitments g^a_j to the
+// coefficients of their polynomials next to the scheme's own commitments. Every party
+// checks its validated share against them, so once more than t honest parties accept, they
+// commit to the dealt polynomials and anyone derives the keys from them. pi_s parties
+// derive the keys from the decrypted shares instead.
+
+use alloc::{vec, vec::Vec};
+
+use curve25519_dalek::{
+    RistrettoPoint, Scalar,
+    ristretto::CompressedRistretto,
+    traits::{Identity, VartimeMultiscalarMul},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{
+        Error,
+        ErrorKind::{CountMismatch, InvalidProof},
+    },
+    opcount,
+    parallel::prelude::*,
+    polynomial::Polynomial,
+    utils::{
+        batch_decompress_batched_ristretto_points, compute_barycentric_weights,
+        compute_lagrange_bases_at,
+    },
+    verification_failure, verified,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyCommitments {
+    // generator of the keys
+    pub g: RistrettoPoint,
+    // commitments[k][j] = g^a_j for the k-th dealt polynomial
+    pub commitments: Vec<Vec<CompressedRistretto>>,
+}
+
+impl KeyCommitments {
+    pub fn new(g: RistrettoPoint) -> Self {
+        Self {
+            g,
+            commitments: vec![],
+        }
+    }
+
+    // Commits to the coefficients of `polynomials`, replacing the last dealing's.
+    pub fn commit(&mut self, polynomials: &[Polynomial]) {
+        let coefficients = polynomials.iter().map(Polynomial::len).sum();
+        opcount::point_muls(coefficients);
+        opcount::compressions(coefficients);
+        self.commitments = polynomials
+            .par_iter()
+            .map(|f| f.mul_with_point_compress(&self.g))
+            .collect();
+    }
+
+    // The commitments to k polynomials of degree t under `g`, decompressed. Commitments
+    // under another generator or to the identity are rejected, under g = 1 every share
+    // would check out and the group key would be the identity.
+    pub fn open(
+        &self,
+        g: &RistrettoPoint,
+        k: usize,
+        t: usize,
+    ) -> Result<Vec<Vec<RistrettoPoint>>, Error> {
+        if self.g != *g {
+            verification_failure!("key_commitments");
+            return Err(InvalidProof("key commitments under another generator".into()).into());
+        }
+        if self.commitments.len() != k {
+            return Err(
+                CountMismatch(k, "secrets", self.commitments.len(), "key commitments").into(),
+            );
+        }
+        if let Some(commitments) = self.commitments.iter().find(|c| c.len() != t + 1) {
+            return Err(
+                CountMismatch(t + 1, "coefficients", commitments.len(), "key commitments").into(),
+            );
+        }
+        let commitments = batch_decompress_batched_ristretto_points(&self.commitments)?;
+        if commitments
+            .iter()
+            .flatten()
+            .any(|c| *c == RistrettoPoint::identity())
+        {
+            verification_failure!("key_commitments");
+            return Err(InvalidProof("identity key commitment".into()).into());
+        }
+        Ok(commitments)
+    }
+}
+
+// g^f(index) of the polynomial `commitments` commit to.
+pub fn evaluate_commitments(
+    commitments: &[RistrettoPoint],
+    x_pows: &[Vec<Scalar>],
+    index: usize,
+) -> RistrettoPoint {
+    let size = commitments.len().min(x_pows[index].len());
+    opcount::msms(1, size);
+    RistrettoPoint::vartime_multiscalar_mul(&x_pows[index][..size], &commitments[..size])
+}
+
+// True if g^shares[k] is the k-th verification key of party `index`, how a party ties the
+// key commitments to the shares it validated.
+pub fn check_shares(
+    g: &RistrettoPoint,
+    commitments: &[Vec<RistrettoPoint>],
+    x_pows: &[Vec<Scalar>],
+    index: usize,
+    shares: &[Scalar],
+) -> bool {
+    opcount::point_muls(shares.len());
+    let holds = commitments.len() == shares.len()
+        && commitments
+            .par_iter()
+            .zip(shares.par_iter())
+            .all(|(c, share)| g * share == evaluate_commitments(c, x_pows, index));
+    verified!(holds, "key_commitments")
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationKeys {
+    // g^f(0)
+    pub group_key: RistrettoPoint,
+    // g^f(i) for party i at i - 1
+    pub keys: Vec<RistrettoPoint>,
+}
+
+impl VerificationKeys {
+    // The keys of parties 1..=n from commitments to the coefficients of f.
+    pub fn from_commitments(
+        commitments: &[RistrettoPoint],
+        x_pows: &[Vec<Scalar>],
+        n: usize,
+    ) -> Self {
+        Self {
+            group_key: commitments[0],
+            keys: (1..=n)
+                .into_par_iter()
+                .map(|i| evaluate_commitments(commitments, x_pows, i))
+                .collect(),
+        }
+    }
+
+    // The keys of parties 1..=n interpolated in the exponent from t + 1 of them, (index,
+    // g^f(index)).
+    pub fn from_shares(shares: &[(usize, RistrettoPoint)], n: usize) -> Self {
+        let (indices, points): (Vec<usize>, Vec<RistrettoPoint>) = shares.iter().copied().unzip();
+        let weights = compute_barycentric_weights(&indices);
+        let key = |x: usize| match shares.iter().find(|(i, _)| *i == x) {
+            Some((_, share)) => *share,
+            None => {
+                opcount::msms(1, shares.len());
+                RistrettoPoint::vartime_multiscalar_mul(
+                    compute_lagrange_bases_at(x, &indices, &weights),
+                    &points,
+                )
+            }
+        };
+        Self {
+            group_key: key(0),
+            keys: (1..=n).into_par_iter().map(key).collect(),
+        }
+    }
+
+    pub fn key(&self, index: usize) -> Option<&RistrettoPoint> {
+        index.checked_sub(1).and_then(|i| self.keys.get(i))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use curve25519_dalek::RistrettoPoint;
+
+    use crate::{
+        keys::{KeyCommitments, VerificationKeys, check_shares},
+        precompute::gen_powers,
+        random::{random_scalar, random_scalars},
+        secret_sharing::generate_shares_batched,
+    };
+
+    #[test]
+    fn keys_from_commitments_and_shares() {
+        let (n, t) = (9, 4);
+        let mut rng = rand::rng();
+        let g = RistrettoPoint::mul_base(&random_scalar(&mut rng));
+        let x_pows = gen_powers(n, t);
+        let secrets = random_scalars(&mut rng, 2);
+        let (polynomials, shares) = generate_shares_batched(n, t, &x_pows, &secrets);
+
+        let mut commitments = KeyCommitments::new(g);
+        commitments.commit(&polynomials);
+        let opened = commitments.open(&g, 2, t).unwrap();
+        assert!(commitments.open(&g, 2, t + 1).is_err());
+        assert!(check_shares(&g, &opened, &x_pows, 3, &shares[2]));
+        assert!(!check_shares(&g, &opened, &x_pows, 4, &shares[2]));
+
+        let keys = VerificationKeys::from_commitments(&opened[1], &x_pows, n);
+        assert_eq!(keys.group_key, g * secrets[1]);
+        assert_eq!(keys.key(5), Some(&(g * shares[4][1])));
+
+        // any t + 1 keys give the others
+        let some: Vec<(usize, RistrettoPoint)> = [2, 3, 5, 8, 9]
+            .iter()
+            .map(|i| (*i, keys.keys[i - 1]))
+            .collect();
+        assert_eq!(VerificationKeys::from_shares(&some, n), keys);
+    }
+}
@@ -0,0 +1,557 @@
+// Party private keys behind a handle.
+// A `KeyHandle` performs every operation the protocols need with a party's long-term key,
+// DH with a point, opening a share encrypted to it, DLEQ proofs and Schnorr signatures,
+// without handing the key out. Parties hold a `SharedKey`, so where the key lives is up to
+// whoever builds the party:
+// - `MemoryKey` keeps it in process memory and is what `Party::new` generates,
+// - `FileKey` keeps it in a file sealed under a password (Argon2id and ChaCha20-Poly1305),
+// - `SoftToken` stands in for a PKCS#11 token, its keys are objects inside the token that
+//   can only be used through their handles while a session is logged in.
+// Existing keys are imported with `MemoryKey::import`, `FileKey::create` or
+// `SoftToken::import_key`.
+
+use alloc::{sync::Arc, vec::Vec};
+
+use blake3::Hasher;
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+use rand::{CryptoRng, RngCore};
+use zeroize::Zeroize;
+
+use crate::{
+    error::{Error, ErrorKind::IdentityPublicKey},
+    parallel::prelude::*,
+    random::random_scalar,
+    schnorr,
+};
+
+pub trait KeyHandle: Send + Sync {
+    // (compressed, point) of g^sk, for the generator the key was made with
+    fn public_key(&self) -> &(CompressedRistretto, RistrettoPoint);
+
+    // point^sk
+    fn diffie_hellman(&self, point: &RistrettoPoint) -> Result<RistrettoPoint, Error>;
+
+    // point^(1/sk) for every point, opening shares encrypted to pk
+    fn decrypt(&self, encrypted: &[RistrettoPoint]) -> Result<Vec<RistrettoPoint>, Error>;
+
+    // (d, z) showing log_g pk == log_base image, with d over (pk, image, g^r, base^r)
+    fn prove_dleq(
+        &self,
+        rng: &mut dyn CryptoRng,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        statement: (&RistrettoPoint, &CompressedRistretto),
+    ) -> Result<(Scalar, Scalar), Error>;
+
+    // `schnorr::sign` of `message` under `domain`
+    fn sign(
+        &self,
+        rng: &mut dyn CryptoRng,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        domain: &[u8],
+        g: &RistrettoPoint,
+        message: &[u8],
+    ) -> Result<(Scalar, Scalar), Error>;
+}
+
+pub type SharedKey = Arc<dyn KeyHandle>;
+
+pub struct MemoryKey {
+    private_key: Scalar,
+    public_key: (CompressedRistretto, RistrettoPoint),
+}
+
+impl MemoryKey {
+    pub fn generate<R>(rng: &mut R, g: &RistrettoPoint) -> Self
+    where
+        R: CryptoRng + RngCore,
+    {
+        let private_key = random_scalar(rng);
+        let public_key = g * private_key;
+        Self {
+            private_key,
+            public_key: (public_key.compress(), public_key),
+        }
+    }
+
+    // Takes over an existing key, the zero key is rejected.
+    pub fn import(g: &RistrettoPoint, private_key: &Scalar) -> Result<Self, Error> {
+        if *private_key == Scalar::ZERO {
+            return Err(IdentityPublicKey(0).into());
+        }
+        let public_key = g * private_key;
+        Ok(Self {
+            private_key: *private_key,
+            public_key: (public_key.compress(), public_key),
+        })
+    }
+
+    pub fn shared(self) -> SharedKey {
+        Arc::new(self)
+    }
+}
+
+impl Drop for MemoryKey {
+    fn drop(&mut self) {
+        self.private_key.zeroize();
+    }
+}
+
+impl KeyHandle for MemoryKey {
+    fn public_key(&self) -> &(CompressedRistretto, RistrettoPoint) {
+        &self.public_key
+    }
+
+    fn diffie_hellman(&self, point: &RistrettoPoint) -> Result<RistrettoPoint, Error> {
+        Ok(point * self.private_key)
+    }
+
+    fn decrypt(&self, encrypted: &[RistrettoPoint]) -> Result<Vec<RistrettoPoint>, Error> {
+        let mut inverse = self.private_key.invert();
+        let decrypted = encrypted.par_iter().map(|e| e * inverse).collect();
+        inverse.zeroize();
+        Ok(decrypted)
+    }
+
+    fn prove_dleq(
+        &self,
+        mut rng: &mut dyn CryptoRng,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        statement: (&RistrettoPoint, &CompressedRistretto),
+    ) -> Result<(Scalar, Scalar), Error> {
+        let (base, image) = statement;
+        let mut r = random_scalar(&mut rng);
+        let c1 = (g * r).compress();
+        let c2 = (base * r).compress();
+
+        hasher.update(self.public_key.0.as_bytes());
+        hasher.update(image.as_bytes());
+        hasher.update(c1.as_bytes());
+        hasher.update(c2.as_bytes());
+        hasher.finalize_xof().fill(buf);
+        hasher.reset();
+
+        let d = Scalar::from_bytes_mod_order_wide(buf);
+        buf.zeroize();
+        let z = r + d * self.private_key;
+        r.zeroize();
+        Ok((d, z))
+    }
+
+    fn sign(
+        &self,
+        mut rng: &mut dyn CryptoRng,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        domain: &[u8],
+        g: &RistrettoPoint,
+        message: &[u8],
+    ) -> Result<(Scalar, Scalar), Error> {
+        Ok(schnorr::sign(
+            &mut rng,
+            hasher,
+            buf,
+            domain,
+            g,
+            &self.private_key,
+            message,
+        ))
+    }
+}
+
+#[cfg(feature = "std")]
+pub use store::{FileKey, SoftToken, TokenKey};
+
+#[cfg(feature = "std")]
+mod store {
+    use std::{
+        fs,
+        path::Path,
+        string::{String, ToString},
+        sync::{Arc, Mutex},
+        vec::Vec,
+    };
+
+    use argon2::Argon2;
+    use blake3::Hasher;
+    use chacha20poly1305::{
+        ChaCha20Poly1305, KeyInit, Nonce,
+        aead::{Aead, Payload},
+    };
+    use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+    use rand::{CryptoRng, RngCore};
+    use serde::{Deserialize, Serialize};
+    use zeroize::Zeroize;
+
+    use crate::{
+        error::{Error, ErrorKind::KeyStoreError},
+        keystore::{KeyHandle, MemoryKey},
+    };
+
+    fn key_store_error(e: impl ToString) -> Error {
+        KeyStoreError(e.to_string()).into()
+    }
+
+    // The sealed key as written to disk. The sealing key comes from Argon2id over the
+    // password and a fresh salt, so it is never reused and the nonce is fixed.
+    #[derive(Serialize, Deserialize)]
+    struct KeyFile {
+        public_key: CompressedRistretto,
+        salt: [u8; 16],
+        sealed: Vec<u8>,
+    }
+
+    fn sealing_key(password: &[u8], salt: &[u8; 16]) -> Result<ChaCha20Poly1305, Error> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(password, salt, &mut key)
+            .map_err(key_store_error)?;
+        let cipher = ChaCha20Poly1305::new(&key.into());
+        key.zeroize();
+        Ok(cipher)
+    }
+
+    pub struct FileKey(MemoryKey);
+
+    impl FileKey {
+        // Seals `private_key` into a new file at `path`, refusing to overwrite one.
+        pub fn create<R>(
+            rng: &mut R,
+            path: &Path,
+            password: &[u8],
+            g: &RistrettoPoint,
+            private_key: &Scalar,
+        ) -> Result<Self, Error>
+        where
+            R: CryptoRng + RngCore,
+        {
+            let key = MemoryKey::import(g, private_key)?;
+            let mut salt = [0u8; 16];
+            rng.fill_bytes(&mut salt);
+
+            let sealed = sealing_key(password, &salt)?
+                .encrypt(
+                    &Nonce::default(),
+                    Payload {
+                        msg: private_key.as_bytes(),
+                        aad: key.public_key.0.as_bytes(),
+                    },
+                )
+                .map_err(key_store_error)?;
+            let file = KeyFile {
+                public_key: key.public_key.0,
+                salt,
+                sealed,
+            };
+            let contents = serde_json::to_vec(&file).map_err(key_store_error)?;
+            fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(path)
+                .and_then(|mut f| std::io::Write::write_all(&mut f, &contents))
+                .map_err(key_store_error)?;
+            Ok(Self(key))
+        }
+
+        pub fn generate<R>(
+            rng: &mut R,
+            path: &Path,
+            password: &[u8],
+            g: &RistrettoPoint,
+        ) -> Result<Self, Error>
+        where
+            R: CryptoRng + RngCore,
+        {
+            let mut private_key = crate::random::random_scalar(rng);
+            let key = Self::create(rng, path, password, g, &private_key);
+            private_key.zeroize();
+            key
+        }
+
+        // Unseals the key at `path`, failing on a wrong password or a tampered file.
+        pub fn open(path: &Path, password: &[u8], g: &RistrettoPoint) -> Result<Self, Error> {
+            let contents = fs::read(path).map_err(key_store_error)?;
+            let file: KeyFile = serde_json::from_slice(&contents).map_err(key_store_error)?;
+
+            let mut opened = sealing_key(password, &file.salt)?
+                .decrypt(
+                    &Nonce::default(),
+                    Payload {
+                        msg: &file.sealed,
+                        aad: file.public_key.as_bytes(),
+                    },
+                )
+                .map_err(|_| key_store_error("wrong password or corrupt key file"))?;
+            let private_key = <[u8; 32]>::try_from(opened.as_slice())
+                .ok()
+                .and_then(|bytes| Option::<Scalar>::from(Scalar::from_canonical_bytes(bytes)));
+            opened.zeroize();
+
+            let key = match private_key {
+                Some(mut private_key) => {
+                    let key = MemoryKey::import(g, &private_key);
+                    private_key.zeroize();
+                    key?
+                }
+                None => return Err(key_store_error("corrupt key file")),
+            };
+            if key.public_key.0 != file.public_key {
+                return Err(key_store_error("key file made for another generator"));
+            }
+            Ok(Self(key))
+        }
+    }
+
+    impl KeyHandle for FileKey {
+        fn public_key(&self) -> &(CompressedRistretto, RistrettoPoint) {
+            self.0.public_key()
+        }
+
+        fn diffie_hellman(&self, point: &RistrettoPoint) -> Result<RistrettoPoint, Error> {
+            self.0.diffie_hellman(point)
+        }
+
+        fn decrypt(&self, encrypted: &[RistrettoPoint]) -> Result<Vec<RistrettoPoint>, Error> {
+            self.0.decrypt(encrypted)
+        }
+
+        fn prove_dleq(
+            &self,
+            rng: &mut dyn CryptoRng,
+            hasher: &mut Hasher,
+            buf: &mut [u8; 64],
+            g: &RistrettoPoint,
+            statement: (&RistrettoPoint, &CompressedRistretto),
+        ) -> Result<(Scalar, Scalar), Error> {
+            self.0.prove_dleq(rng, hasher, buf, g, statement)
+        }
+
+        fn sign(
+            &self,
+            rng: &mut dyn CryptoRng,
+            hasher: &mut Hasher,
+            buf: &mut [u8; 64],
+            domain: &[u8],
+            g: &RistrettoPoint,
+            message: &[u8],
+        ) -> Result<(Scalar, Scalar), Error> {
+            self.0.sign(rng, hasher, buf, domain, g, message)
+        }
+    }
+
+    struct Token {
+        pin: String,
+        logged_in: bool,
+        objects: Vec<MemoryKey>,
+    }
+
+    // In-process stand-in for a PKCS#11 token. Keys are created inside it and never
+    // exported, every operation goes through the token and fails while logged out.
+    #[derive(Clone)]
+    pub struct SoftToken(Arc<Mutex<Token>>);
+
+    pub struct TokenKey {
+        token: SoftToken,
+        object: usize,
+        public_key: (CompressedRistretto, RistrettoPoint),
+    }
+
+    impl SoftToken {
+        pub fn new(pin: &str) -> Self {
+            Self(Arc::new(Mutex::new(Token {
+                pin: pin.into(),
+                logged_in: false,
+                objects: Vec::new(),
+            })))
+        }
+
+        pub fn login(&self, pin: &str) -> Result<(), Error> {
+            self.with(false, |token| {
+                if token.pin == pin {
+                    token.logged_in = true;
+                    Ok(())
+                } else {
+                    Err(key_store_error("incorrect PIN"))
+                }
+            })
+        }
+
+        pub fn logout(&self) {
+            let _ = self.with(false, |token| {
+                token.logged_in = false;
+                Ok(())
+            });
+        }
+
+        pub fn generate_key<R>(&self, rng: &mut R, g: &RistrettoPoint) -> Result<TokenKey, Error>
+        where
+            R: CryptoRng + RngCore,
+        {
+            self.store(MemoryKey::generate(rng, g))
+        }
+
+        pub fn import_key(
+            &self,
+            g: &RistrettoPoint,
+            private_key: &Scalar,
+        ) -> Result<TokenKey, Error> {
+            self.store(MemoryKey::import(g, private_key)?)
+        }
+
+        fn store(&self, key: MemoryKey) -> Result<TokenKey, Error> {
+            let public_key = key.public_key;
+            let object = self.with(true, |token| {
+                token.objects.push(key);
+                Ok(token.objects.len() - 1)
+            })?;
+            Ok(TokenKey {
+                token: self.clone(),
+                object,
+                public_key,
+            })
+        }
+
+        fn with<T>(
+            &self,
+            logged_in: bool,
+            f: impl FnOnce(&mut Token) -> Result<T, Error>,
+        ) -> Result<T, Error> {
+            let mut token = self
+                .0
+                .lock()
+                .map_err(|_| key_store_error("token poisoned"))?;
+            if logged_in && !token.logged_in {
+                return Err(key_store_error("token not logged in"));
+            }
+            f(&mut token)
+        }
+
+        fn use_key<T>(
+            &self,
+            object: usize,
+            f: impl FnOnce(&MemoryKey) -> Result<T, Error>,
+        ) -> Result<T, Error> {
+            self.with(true, |token| match token.objects.get(object) {
+                Some(key) => f(key),
+                None => Err(key_store_error("no such key object")),
+            })
+        }
+    }
+
+    impl KeyHandle for TokenKey {
+        fn public_key(&self) -> &(CompressedRistretto, RistrettoPoint) {
+            &self.public_key
+        }
+
+        fn diffie_hellman(&self, point: &RistrettoPoint) -> Result<RistrettoPoint, Error> {
+            self.token
+                .use_key(self.object, |key| key.diffie_hellman(point))
+        }
+
+        fn decrypt(&self, encrypted: &[RistrettoPoint]) -> Result<Vec<RistrettoPoint>, Error> {
+            self.token
+                .use_key(self.object, |key| key.decrypt(encrypted))
+        }
+
+        fn prove_dleq(
+            &self,
+            rng: &mut dyn CryptoRng,
+            hasher: &mut Hasher,
+            buf: &mut [u8; 64],
+            g: &RistrettoPoint,
+            statement: (&RistrettoPoint, &CompressedRistretto),
+        ) -> Result<(Scalar, Scalar), Error> {
+            self.token.use_key(self.object, |key| {
+                key.prove_dleq(rng, hasher, buf, g, statement)
+            })
+        }
+
+        fn sign(
+            &self,
+            rng: &mut dyn CryptoRng,
+            hasher: &mut Hasher,
+            buf: &mut [u8; 64],
+            domain: &[u8],
+            g: &RistrettoPoint,
+            message: &[u8],
+        ) -> Result<(Scalar, Scalar), Error> {
+            self.token.use_key(self.object, |key| {
+                key.sign(rng, hasher, buf, domain, g, message)
+            })
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use std::{env, process};
+
+    use blake3::Hasher;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::{
+        error::ErrorKind::KeyStoreError,
+        keystore::{FileKey, KeyHandle, MemoryKey, SoftToken},
+        random::{random_point, random_scalar},
+        schnorr,
+    };
+
+    #[test]
+    fn key_handles() {
+        let mut rng = StdRng::seed_from_u64(40);
+        let mut hasher = Hasher::new();
+        let mut buf = [0u8; 64];
+        let g = random_point(&mut rng);
+        let sk = random_scalar(&mut rng);
+        let point = random_point(&mut rng);
+
+        let path = env::temp_dir().join(format!("pi_vss-key-{}", process::id()));
+        let _ = std::fs::remove_file(&path);
+        FileKey::create(&mut rng, &path, b"hunter2", &g, &sk).unwrap();
+        assert!(FileKey::create(&mut rng, &path, b"hunter2", &g, &sk).is_err());
+        assert!(FileKey::open(&path, b"hunter3", &g).is_err());
+        let file_key = FileKey::open(&path, b"hunter2", &g).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let token = SoftToken::new("1234");
+        assert!(token.import_key(&g, &sk).is_err());
+        assert!(token.login("4321").is_err());
+        token.login("1234").unwrap();
+        let token_key = token.import_key(&g, &sk).unwrap();
+
+        // the same imported key behind every kind of handle
+        let memory_key = MemoryKey::import(&g, &sk).unwrap();
+        let handles: [&dyn KeyHandle; 3] = [&memory_key, &file_key, &token_key];
+        for key in handles {
+            assert_eq!(key.public_key().1, g * sk);
+            assert_eq!(key.diffie_hellman(&point).unwrap(), point * sk);
+            assert_eq!(key.decrypt(&[point * sk]).unwrap(), [point]);
+
+            let signature = key
+                .sign(&mut rng, &mut hasher, &mut buf, b"test", &g, b"message")
+                .unwrap();
+            assert!(
+                schnorr::verify(
+                    &mut hasher,
+                    &mut buf,
+                    b"test",
+                    &g,
+                    &key.public_key().0,
+                    b"message",
+                    &signature
+                )
+                .unwrap()
+            );
+        }
+
+        token.logout();
+        assert!(matches!(
+            token_key.diffie_hellman(&point).err().unwrap().kind(),
+            KeyStoreError(_)
+        ));
+    }
+}
@@ -1,8 +1,35 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod broadcast;
+pub mod committee;
+pub mod complaint;
+pub mod dealings;
+pub mod encryption;
 pub mod error;
+pub mod execution;
+#[cfg(feature = "std")]
+pub mod fixture;
+pub mod keys;
+pub mod keystore;
+pub mod message;
+pub mod opcount;
+pub mod parallel;
 pub mod polynomial;
+pub mod pop;
 pub mod precompute;
 pub mod random;
+#[cfg(feature = "std")]
+pub mod record;
+pub mod report;
+pub mod schnorr;
 pub mod secret_sharing;
+pub mod session;
+#[cfg(feature = "std")]
+pub mod snapshot;
+pub mod stream;
+pub mod telemetry;
 pub mod utils;
 
 // pub const BENCH_N_T: [(usize, usize); 9] = [
@@ -30,3 +57,6 @@ pub const BENCH_N_T: [(usize, usize); 2] = [
 
 // pub const BENCH_K: [usize; 8] = [1, 10, 50, 100, 250, 500, 1000, 10000];
 pub const BENCH_K: [usize; 3] = [1, 10, 50];
+
+// pub const BENCH_THREADS: [usize; 6] = [1, 2, 4, 8, 16, 32];
+pub const BENCH_THREADS: [usize; 3] = [1, 2, 4];
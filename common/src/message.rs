@@ -0,0 +1,397 @@
+// Authenticated protocol messages.
+// A `Signed<T>` carries the session id, the round and the sender's index next to the
+// payload, all of it covered by the sender's Schnorr signature under its registered key.
+// A verified message is attributable to its sender, can't be replayed into another
+// session or round, and receivers place its payload by `sender` rather than by its
+// position in whatever the transport delivered.
+
+use alloc::vec::Vec;
+
+use blake3::Hasher;
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{
+        Error,
+        ErrorKind::{
+            CountMismatch, DuplicateMessage, InvalidSignature, UnknownSender, WrongSession,
+        },
+    },
+    keystore::KeyHandle,
+    polynomial::Polynomial,
+    schnorr, verification_failure,
+};
+
+const DOMAIN: &[u8] = b"pi_vss/message";
+
+// Canonical byte encoding of a payload, fed straight into the hasher.
+pub trait Encode {
+    fn encode(&self, hasher: &mut Hasher);
+}
+
+impl Encode for Scalar {
+    fn encode(&self, hasher: &mut Hasher) {
+        hasher.update(self.as_bytes());
+    }
+}
+
+impl Encode for CompressedRistretto {
+    fn encode(&self, hasher: &mut Hasher) {
+        hasher.update(self.as_bytes());
+    }
+}
+
+impl Encode for [u8; 32] {
+    fn encode(&self, hasher: &mut Hasher) {
+        hasher.update(self);
+    }
+}
+
+impl Encode for [u8; 64] {
+    fn encode(&self, hasher: &mut Hasher) {
+        hasher.update(self);
+    }
+}
+
+impl Encode for usize {
+    fn encode(&self, hasher: &mut Hasher) {
+        hasher.update(&(*self as u64).to_le_bytes());
+    }
+}
+
+impl Encode for Polynomial {
+    fn encode(&self, hasher: &mut Hasher) {
+        self.coefficients.encode(hasher);
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode(&self, hasher: &mut Hasher) {
+        (**self).encode(hasher);
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode(&self, hasher: &mut Hasher) {
+        self.len().encode(hasher);
+        self.iter().for_each(|x| x.encode(hasher));
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, hasher: &mut Hasher) {
+        self.as_slice().encode(hasher);
+    }
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+    fn encode(&self, hasher: &mut Hasher) {
+        self.0.encode(hasher);
+        self.1.encode(hasher);
+    }
+}
+
+impl<A: Encode, B: Encode, C: Encode> Encode for (A, B, C) {
+    fn encode(&self, hasher: &mut Hasher) {
+        self.0.encode(hasher);
+        self.1.encode(hasher);
+        self.2.encode(hasher);
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Signed<T> {
+    pub session_id: [u8; 32],
+    pub round: u64,
+    pub sender: usize,
+    pub payload: T,
+    pub signature: (Scalar, Scalar),
+}
+
+fn digest<T: Encode>(
+    hasher: &mut Hasher,
+    session_id: &[u8; 32],
+    round: u64,
+    sender: usize,
+    payload: &T,
+) -> [u8; 32] {
+    hasher.update(DOMAIN);
+    hasher.update(session_id);
+    hasher.update(&round.to_le_bytes());
+    sender.encode(hasher);
+    payload.encode(hasher);
+
+    let digest = *hasher.finalize().as_bytes();
+    hasher.reset();
+    digest
+}
+
+impl<T: Encode> Signed<T> {
+    pub fn sign<R>(
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        private_key: &Scalar,
+        context: (&[u8; 32], u64, usize),
+        payload: T,
+    ) -> Self
+    where
+        R: CryptoRng + RngCore,
+    {
+        let (session_id, round, sender) = context;
+        let digest = digest(hasher, session_id, round, sender, &payload);
+        let signature = schnorr::sign(rng, hasher, buf, DOMAIN, g, private_key, &digest);
+
+        Self {
+            session_id: *session_id,
+            round,
+            sender,
+            payload,
+            signature,
+        }
+    }
+
+    // `sign` with a key behind a handle.
+    pub fn sign_with<R>(
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        key: &dyn KeyHandle,
+        context: (&[u8; 32], u64, usize),
+        payload: T,
+    ) -> Result<Self, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let (session_id, round, sender) = context;
+        let digest = digest(hasher, session_id, round, sender, &payload);
+        let signature = key.sign(rng, hasher, buf, DOMAIN, g, &digest)?;
+
+        Ok(Self {
+            session_id: *session_id,
+            round,
+            sender,
+            payload,
+            signature,
+        })
+    }
+
+    pub fn verify(
+        &self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        public_key: &CompressedRistretto,
+    ) -> Result<bool, Error> {
+        let digest = digest(
+            hasher,
+            &self.session_id,
+            self.round,
+            self.sender,
+            &self.payload,
+        );
+        schnorr::verify(hasher, buf, DOMAIN, g, public_key, &digest, &self.signature)
+    }
+
+    // Checks the session, round and signature, and hands back the payload.
+    pub fn open(
+        self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        public_key: &CompressedRistretto,
+        session: (&[u8; 32], u64),
+    ) -> Result<T, Error> {
+        if &self.session_id != session.0 || self.round != session.1 {
+            verification_failure!("wrong_session");
+            Err(WrongSession(self.sender, self.round).into())
+        } else if self.verify(hasher, buf, g, public_key)? {
+            Ok(self.payload)
+        } else {
+            verification_failure!("signature");
+            Err(InvalidSignature(self.sender).into())
+        }
+    }
+}
+
+// Opens one message per sender in `senders`, signed under `public_keys[sender - 1]`,
+// and returns the payloads ordered by sender whatever order they arrived in. A sender with
+// no key in `public_keys`, or a message from anyone not in `senders`, is an `UnknownSender`
+// error.
+pub fn open_all<T: Encode>(
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    g: &RistrettoPoint,
+    public_keys: &[CompressedRistretto],
+    session: (&[u8; 32], u64),
+    senders: &[usize],
+    messages: Vec<Signed<T>>,
+) -> Result<Vec<T>, Error> {
+    if messages.len() != senders.len() {
+        return Err(CountMismatch(senders.len(), "senders", messages.len(), "messages").into());
+    }
+    if let Some(sender) = senders
+        .iter()
+        .find(|sender| **sender == 0 || **sender > public_keys.len())
+    {
+        return Err(UnknownSender(*sender).into());
+    }
+
+    let mut payloads: Vec<Option<T>> = senders.iter().map(|_| None).collect();
+    for message in messages {
+        let sender = message.sender;
+        let slot = match senders.iter().position(|s| *s == sender) {
+            Some(slot) => slot,
+            None => {
+                verification_failure!("unexpected_sender");
+                return Err(UnknownSender(sender).into());
+            }
+        };
+        if payloads[slot].is_some() {
+            verification_failure!("duplicate_message");
+            return Err(DuplicateMessage(sender).into());
+        }
+        payloads[slot] = Some(message.open(hasher, buf, g, &public_keys[sender - 1], session)?);
+    }
+
+    Ok(payloads.into_iter().map(Option::unwrap).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use blake3::Hasher;
+    use curve25519_dalek::{Scalar, ristretto::CompressedRistretto};
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::{
+        error::ErrorKind::{DuplicateMessage, InvalidSignature, UnknownSender, WrongSession},
+        message::{Signed, open_all},
+        random::{random_point, random_scalar, random_scalars},
+    };
+
+    #[test]
+    fn signed_messages() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut hasher = Hasher::new();
+        let mut buf = [0u8; 64];
+        let g = random_point(&mut rng);
+        let session = [7u8; 32];
+
+        let sks = random_scalars(&mut rng, 3);
+        let pks: Vec<CompressedRistretto> = sks.iter().map(|sk| (g * sk).compress()).collect();
+        let shares: Vec<Scalar> = random_scalars(&mut rng, 3);
+
+        let sign = |rng: &mut StdRng, hasher: &mut Hasher, buf: &mut [u8; 64], i: usize| {
+            Signed::sign(
+                rng,
+                hasher,
+                buf,
+                &g,
+                &sks[i - 1],
+                (&session, 2, i),
+                shares[i - 1],
+            )
+        };
+
+        // delivered out of order, returned by sender
+        let messages = [3, 1, 2]
+            .map(|i| sign(&mut rng, &mut hasher, &mut buf, i))
+            .to_vec();
+        let opened = open_all(
+            &mut hasher,
+            &mut buf,
+            &g,
+            &pks,
+            (&session, 2),
+            &[1, 2, 3],
+            messages.clone(),
+        )
+        .unwrap();
+        assert_eq!(opened, shares);
+
+        // another round of the same session
+        assert!(matches!(
+            messages[0]
+                .clone()
+                .open(&mut hasher, &mut buf, &g, &pks[2], (&session, 3))
+                .unwrap_err()
+                .kind(),
+            WrongSession(3, 2)
+        ));
+
+        // a payload swapped in by the transport
+        let mut swapped = messages[0].clone();
+        swapped.payload = random_scalar(&mut rng);
+        assert!(matches!(
+            swapped
+                .open(&mut hasher, &mut buf, &g, &pks[2], (&session, 2))
+                .unwrap_err()
+                .kind(),
+            InvalidSignature(3)
+        ));
+
+        let replayed = [1, 1, 2]
+            .map(|i| sign(&mut rng, &mut hasher, &mut buf, i))
+            .to_vec();
+        assert!(matches!(
+            open_all(
+                &mut hasher,
+                &mut buf,
+                &g,
+                &pks,
+                (&session, 2),
+                &[1, 2, 3],
+                replayed
+            )
+            .unwrap_err()
+            .kind(),
+            DuplicateMessage(1)
+        ));
+
+        // a sender with no key is an error, not a panic
+        let messages = [1, 2]
+            .map(|i| sign(&mut rng, &mut hasher, &mut buf, i))
+            .to_vec();
+        for senders in [[1, 4], [0, 1]] {
+            assert!(matches!(
+                open_all(
+                    &mut hasher,
+                    &mut buf,
+                    &g,
+                    &pks,
+                    (&session, 2),
+                    &senders,
+                    messages.clone()
+                )
+                .unwrap_err()
+                .kind(),
+                UnknownSender(_)
+            ));
+        }
+
+        // a message from outside `senders` is blamed on its sender, whatever its session
+        let messages = [1, 3]
+            .map(|i| sign(&mut rng, &mut hasher, &mut buf, i))
+            .to_vec();
+        assert!(matches!(
+            open_all(
+                &mut hasher,
+                &mut buf,
+                &g,
+                &pks,
+                (&session, 2),
+                &[1, 2],
+                messages
+            )
+            .unwrap_err()
+            .kind(),
+            UnknownSender(3)
+        ));
+    }
+}
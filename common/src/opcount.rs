@@ -0,0 +1,189 @@
+// Operation counters for comparing schemes against their theoretical cost.
+// Only the `op-count` feature makes the counters real, otherwise every call is a no-op
+// and `snapshot` returns zeroes.
+//
+// Counters are process-wide, so `measure` attributes everything that runs
+// concurrently with `op` to it. Measure one dealing or verification at a time.
+
+#[cfg(feature = "op-count")]
+use core::sync::atomic::{AtomicU64, Ordering::Relaxed};
+use core::{
+    fmt,
+    ops::{Add, Sub},
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OpCounts {
+    pub scalar_muls: u64,
+    // point multiplications outside of an msm
+    pub point_muls: u64,
+    // multi-scalar multiplications and their total number of terms
+    pub msms: u64,
+    pub msm_terms: u64,
+    pub compressions: u64,
+    pub decompressions: u64,
+    pub hashes: u64,
+    pub bytes_hashed: u64,
+}
+
+#[cfg(feature = "op-count")]
+struct Counters {
+    scalar_muls: AtomicU64,
+    point_muls: AtomicU64,
+    msms: AtomicU64,
+    msm_terms: AtomicU64,
+    compressions: AtomicU64,
+    decompressions: AtomicU64,
+    hashes: AtomicU64,
+    bytes_hashed: AtomicU64,
+}
+
+#[cfg(feature = "op-count")]
+static COUNTERS: Counters = Counters {
+    scalar_muls: AtomicU64::new(0),
+    point_muls: AtomicU64::new(0),
+    msms: AtomicU64::new(0),
+    msm_terms: AtomicU64::new(0),
+    compressions: AtomicU64::new(0),
+    decompressions: AtomicU64::new(0),
+    hashes: AtomicU64::new(0),
+    bytes_hashed: AtomicU64::new(0),
+};
+
+#[inline(always)]
+pub fn scalar_muls(count: usize) {
+    #[cfg(feature = "op-count")]
+    COUNTERS.scalar_muls.fetch_add(count as u64, Relaxed);
+    #[cfg(not(feature = "op-count"))]
+    let _ = count;
+}
+
+#[inline(always)]
+pub fn point_muls(count: usize) {
+    #[cfg(feature = "op-count")]
+    COUNTERS.point_muls.fetch_add(count as u64, Relaxed);
+    #[cfg(not(feature = "op-count"))]
+    let _ = count;
+}
+
+// `count` multi-scalar multiplications of `size` terms each
+#[inline(always)]
+pub fn msms(count: usize, size: usize) {
+    #[cfg(feature = "op-count")]
+    if count > 0 {
+        COUNTERS.msms.fetch_add(count as u64, Relaxed);
+        COUNTERS.msm_terms.fetch_add((count * size) as u64, Relaxed);
+    }
+    #[cfg(not(feature = "op-count"))]
+    let _ = (count, size);
+}
+
+#[inline(always)]
+pub fn compressions(count: usize) {
+    #[cfg(feature = "op-count")]
+    COUNTERS.compressions.fetch_add(count as u64, Relaxed);
+    #[cfg(not(feature = "op-count"))]
+    let _ = count;
+}
+
+#[inline(always)]
+pub fn decompressions(count: usize) {
+    #[cfg(feature = "op-count")]
+    COUNTERS.decompressions.fetch_add(count as u64, Relaxed);
+    #[cfg(not(feature = "op-count"))]
+    let _ = count;
+}
+
+// `count` hash invocations over `bytes` bytes in total
+#[inline(always)]
+pub fn hashes(count: usize, bytes: usize) {
+    #[cfg(feature = "op-count")]
+    {
+        COUNTERS.hashes.fetch_add(count as u64, Relaxed);
+        COUNTERS.bytes_hashed.fetch_add(bytes as u64, Relaxed);
+    }
+    #[cfg(not(feature = "op-count"))]
+    let _ = (count, bytes);
+}
+
+pub fn snapshot() -> OpCounts {
+    #[cfg(feature = "op-count")]
+    {
+        OpCounts {
+            scalar_muls: COUNTERS.scalar_muls.load(Relaxed),
+            point_muls: COUNTERS.point_muls.load(Relaxed),
+            msms: COUNTERS.msms.load(Relaxed),
+            msm_terms: COUNTERS.msm_terms.load(Relaxed),
+            compressions: COUNTERS.compressions.load(Relaxed),
+            decompressions: COUNTERS.decompressions.load(Relaxed),
+            hashes: COUNTERS.hashes.load(Relaxed),
+            bytes_hashed: COUNTERS.bytes_hashed.load(Relaxed),
+        }
+    }
+    #[cfg(not(feature = "op-count"))]
+    {
+        OpCounts::default()
+    }
+}
+
+// Runs `op` and returns what it counted.
+pub fn measure<T>(op: impl FnOnce() -> T) -> (T, OpCounts) {
+    let before = snapshot();
+    let res = op();
+    (res, snapshot() - before)
+}
+
+impl OpCounts {
+    pub const ENABLED: bool = cfg!(feature = "op-count");
+}
+
+impl Add for OpCounts {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            scalar_muls: self.scalar_muls + rhs.scalar_muls,
+            point_muls: self.point_muls + rhs.point_muls,
+            msms: self.msms + rhs.msms,
+            msm_terms: self.msm_terms + rhs.msm_terms,
+            compressions: self.compressions + rhs.compressions,
+            decompressions: self.decompressions + rhs.decompressions,
+            hashes: self.hashes + rhs.hashes,
+            bytes_hashed: self.bytes_hashed + rhs.bytes_hashed,
+        }
+    }
+}
+
+impl Sub for OpCounts {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            scalar_muls: self.scalar_muls - rhs.scalar_muls,
+            point_muls: self.point_muls - rhs.point_muls,
+            msms: self.msms - rhs.msms,
+            msm_terms: self.msm_terms - rhs.msm_terms,
+            compressions: self.compressions - rhs.compressions,
+            decompressions: self.decompressions - rhs.decompressions,
+            hashes: self.hashes - rhs.hashes,
+            bytes_hashed: self.bytes_hashed - rhs.bytes_hashed,
+        }
+    }
+}
+
+impl fmt::Display for OpCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "scalar mul: {}, point mul: {}, msm: {} ({} terms), compress: {}, decompress: {}, hash: {} ({} bytes)",
+            self.scalar_muls,
+            self.point_muls,
+            self.msms,
+            self.msm_terms,
+            self.compressions,
+            self.decompressions,
+            self.hashes,
+            self.bytes_hashed
+        )
+    }
+}
@@ -0,0 +1,95 @@
+// Drop-in replacement for `rayon::prelude`.
+// With the `parallel` feature this is rayon, without it the same method names
+// resolve to plain sequential iterators so call sites don't need to change.
+
+#[cfg(feature = "parallel")]
+pub mod prelude {
+    pub use rayon::prelude::*;
+}
+
+#[cfg(not(feature = "parallel"))]
+pub mod prelude {
+    use alloc::vec::Vec;
+
+    pub trait IntoParallelIterator: IntoIterator + Sized {
+        fn into_par_iter(self) -> Self::IntoIter {
+            self.into_iter()
+        }
+    }
+
+    impl<I: IntoIterator> IntoParallelIterator for I {}
+
+    pub trait IntoParallelRefIterator<'data> {
+        type Iter: Iterator;
+
+        fn par_iter(&'data self) -> Self::Iter;
+    }
+
+    impl<'data, I: 'data + ?Sized> IntoParallelRefIterator<'data> for I
+    where
+        &'data I: IntoIterator,
+    {
+        type Iter = <&'data I as IntoIterator>::IntoIter;
+
+        fn par_iter(&'data self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    pub trait IntoParallelRefMutIterator<'data> {
+        type Iter: Iterator;
+
+        fn par_iter_mut(&'data mut self) -> Self::Iter;
+    }
+
+    impl<'data, I: 'data + ?Sized> IntoParallelRefMutIterator<'data> for I
+    where
+        &'data mut I: IntoIterator,
+    {
+        type Iter = <&'data mut I as IntoIterator>::IntoIter;
+
+        fn par_iter_mut(&'data mut self) -> Self::Iter {
+            self.into_iter()
+        }
+    }
+
+    // The rayon-only adapters used across the workspace.
+    // `init` runs once since there is only one "thread".
+    pub trait ParallelIterator: Iterator + Sized {
+        fn map_init<T, INIT, F, R>(self, init: INIT, map_op: F) -> impl Iterator<Item = R>
+        where
+            INIT: Fn() -> T,
+            F: Fn(&mut T, Self::Item) -> R,
+        {
+            let mut state = init();
+            self.map(move |item| map_op(&mut state, item))
+        }
+
+        fn for_each_init<T, INIT, F>(self, init: INIT, op: F)
+        where
+            INIT: Fn() -> T,
+            F: Fn(&mut T, Self::Item),
+        {
+            let mut state = init();
+            self.for_each(|item| op(&mut state, item))
+        }
+
+        fn with_min_len(self, _min: usize) -> Self {
+            self
+        }
+
+        fn collect_into_vec(self, target: &mut Vec<Self::Item>) {
+            target.clear();
+            target.extend(self);
+        }
+
+        fn reduce_with<OP>(self, op: OP) -> Option<Self::Item>
+        where
+            OP: Fn(Self::Item, Self::Item) -> Self::Item,
+        {
+            self.reduce(op)
+        }
+    }
+
+    impl<I: Iterator> ParallelIterator for I {}
+}
@@ -1,15 +1,22 @@
+use alloc::{format, string::String, vec, vec::Vec};
+use core::fmt;
+
 use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
 
 use rand::{CryptoRng, RngCore};
-use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "std")]
+use crate::error::{Error, ErrorKind::CountMismatch};
 use crate::{
-    error::{Error, ErrorKind::CountMismatch},
+    execution::granularity,
+    opcount,
+    parallel::prelude::*,
     random::{random_scalar, random_scalars},
     utils::pointwise_op_in_place,
 };
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Polynomial {
     pub coefficients: Vec<Scalar>,
 }
@@ -79,6 +86,8 @@ impl Polynomial {
         )
     }
 
+    // these draw from a thread-local rng
+    #[cfg(feature = "std")]
     pub fn sample_n(n: usize, degree: usize) -> Vec<Self> {
         (0..n)
             .into_par_iter()
@@ -86,6 +95,7 @@ impl Polynomial {
             .collect()
     }
 
+    #[cfg(feature = "std")]
     pub fn sample_n_set_f0(
         n: usize,
         degree: usize,
@@ -126,8 +136,11 @@ impl Polynomial {
 
     // assuming all polynomials are of same degree, panics otherwise
     pub fn evaluate_many_range(polynomials: &[Self], from: usize, to: usize) -> Vec<Vec<Scalar>> {
-        (from..=to)
+        let len = polynomials[0].len();
+        opcount::scalar_muls((to + 1 - from) * (len.saturating_sub(2) + polynomials.len() * len));
+        (from..to + 1)
             .into_par_iter()
+            .with_min_len(granularity())
             .map(|i| {
                 let mut x_powers: Vec<Scalar> = vec![Scalar::ONE, Scalar::from(i as u64)];
 
@@ -157,8 +170,10 @@ impl Polynomial {
         from: usize,
         to: usize,
     ) -> Vec<Vec<Scalar>> {
-        (from..=to)
+        opcount::scalar_muls((to + 1 - from) * polynomials.len() * polynomials[0].len());
+        (from..to + 1)
             .into_par_iter()
+            .with_min_len(granularity())
             .map(|i| {
                 polynomials
                     .par_iter()
@@ -182,8 +197,10 @@ impl Polynomial {
         from: usize,
         to: usize,
     ) -> Vec<Scalar> {
-        (from..=to)
+        opcount::scalar_muls((to + 1 - from) * self.len());
+        (from..to + 1)
             .into_par_iter()
+            .with_min_len(granularity())
             .map(|i| {
                 self.coefficients
                     .par_iter()
@@ -196,8 +213,10 @@ impl Polynomial {
 
     // assuming all polynomials are of same degree, panics otherwise
     pub fn evaluate_range(&self, from: usize, to: usize) -> Vec<Scalar> {
-        (from..=to)
+        opcount::scalar_muls((to + 1 - from) * (self.len().saturating_sub(2) + self.len()));
+        (from..to + 1)
             .into_par_iter()
+            .with_min_len(granularity())
             .map(|i| {
                 let mut x_powers: Vec<Scalar> = vec![Scalar::ONE, Scalar::from(i as u64)];
 
@@ -215,6 +234,7 @@ impl Polynomial {
     }
 
     pub fn evaluate_precomp(&self, x_powers: &Vec<Vec<Scalar>>, x: usize) -> Scalar {
+        opcount::scalar_muls(self.len());
         self.coefficients
             .par_iter()
             .zip(&x_powers[x])
@@ -223,6 +243,7 @@ impl Polynomial {
     }
 
     pub fn evaluate(&self, x: usize) -> Scalar {
+        opcount::scalar_muls(self.len().saturating_sub(2) + self.len());
         let mut x_powers: Vec<Scalar> = vec![Scalar::ONE, Scalar::from(x as u64)];
 
         for i in 2..self.coefficients.len() {
@@ -242,8 +263,9 @@ impl Polynomial {
         from: usize,
         to: usize,
     ) -> (Vec<Scalar>, Vec<Scalar>) {
-        (from..=to)
+        (from..to + 1)
             .into_par_iter()
+            .with_min_len(granularity())
             .map(|i| {
                 let mut x_powers: Vec<Scalar> = vec![Scalar::ONE, Scalar::from(i as u64)];
 
@@ -329,20 +351,18 @@ impl Polynomial {
                     x_powers.push(x_powers[1] * x_powers[j - 1]);
                 }
 
-                let (f_val, r_val) = self
+                let f_val: Scalar = self
                     .coefficients
                     .par_iter()
-                    .zip(other.coefficients.par_iter())
-                    .zip(x_powers)
-                    .map(|((coef_f, coef_r), x_pow)| (coef_f * x_pow, coef_r * x_pow))
-                    .reduce(
-                        || (Scalar::ZERO, Scalar::ZERO),
-                        |(mut acc_f, mut acc_r), (fval, rval)| {
-                            acc_f += fval;
-                            acc_r += rval;
-                            (acc_f, acc_r)
-                        },
-                    );
+                    .zip(x_powers.par_iter())
+                    .map(|(coef_f, x_pow)| coef_f * x_pow)
+                    .sum();
+                let r_val: Scalar = other
+                    .coefficients
+                    .par_iter()
+                    .zip(x_powers.par_iter())
+                    .map(|(coef_r, x_pow)| coef_r * x_pow)
+                    .sum();
 
                 ((f_val * point).compress(), (r_val * point).compress())
             })
@@ -466,8 +486,10 @@ impl Polynomial {
     // The input here is &mut r(x), &[f1...fk] , &[d1...dk]
     // z = r + ( ∑ d_j * f_j )
     pub fn compute_z(&mut self, f_polynomials: &[Self], d_vals: &[Scalar]) {
+        opcount::scalar_muls(self.len() * f_polynomials.len());
         self.coef_mut()
             .into_par_iter()
+            .with_min_len(granularity())
             .enumerate()
             .for_each(|(i, r_coef)| {
                 *r_coef += f_polynomials
@@ -480,6 +502,7 @@ impl Polynomial {
 
     // The input here is &z(x), &[f1(x)...fk(x)] , &[d1...dk]
     pub fn compute_r_eval(z_eval: &Scalar, f_evals: &[Scalar], d_vals: &[Scalar]) -> Scalar {
+        opcount::scalar_muls(f_evals.len().min(d_vals.len()));
         z_eval
             - f_evals
                 .par_iter()
@@ -489,8 +512,8 @@ impl Polynomial {
     }
 }
 
-impl std::fmt::Display for Polynomial {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
             "{}",
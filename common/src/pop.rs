@@ -0,0 +1,213 @@
+// Schnorr proofs of possession for party public keys.
+// A proof (d, z) shows knowledge of sk with pk = g^sk and is bound to the party's
+// index and the committee label, so it can't be replayed under another index or
+// in another committee.
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use blake3::Hasher;
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto, traits::Identity};
+use rand::{CryptoRng, RngCore};
+
+use crate::{
+    error::{
+        Error,
+        ErrorKind::{CountMismatch, DuplicatePublicKey, IdentityPublicKey, InvalidPossessionProof},
+    },
+    keystore::KeyHandle,
+    schnorr,
+    utils::decompress_ristretto_point,
+    verification_failure,
+};
+
+const DOMAIN: &[u8] = b"pi_vss/proof-of-possession";
+
+fn message(index: usize, committee: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(16 + committee.len());
+    message.extend_from_slice(&(committee.len() as u64).to_le_bytes());
+    message.extend_from_slice(committee);
+    message.extend_from_slice(&(index as u64).to_le_bytes());
+    message
+}
+
+pub fn prove_possession<R>(
+    rng: &mut R,
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    g: &RistrettoPoint,
+    private_key: &Scalar,
+    index: usize,
+    committee: &[u8],
+) -> (Scalar, Scalar)
+where
+    R: CryptoRng + RngCore,
+{
+    schnorr::sign(
+        rng,
+        hasher,
+        buf,
+        DOMAIN,
+        g,
+        private_key,
+        &message(index, committee),
+    )
+}
+
+// `prove_possession` for a key behind a handle.
+pub fn prove_key_possession<R>(
+    rng: &mut R,
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    g: &RistrettoPoint,
+    key: &dyn KeyHandle,
+    index: usize,
+    committee: &[u8],
+) -> Result<(Scalar, Scalar), Error>
+where
+    R: CryptoRng + RngCore,
+{
+    key.sign(rng, hasher, buf, DOMAIN, g, &message(index, committee))
+}
+
+pub fn verify_possession(
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    g: &RistrettoPoint,
+    public_key: &CompressedRistretto,
+    index: usize,
+    committee: &[u8],
+    proof: &(Scalar, Scalar),
+) -> Result<bool, Error> {
+    schnorr::verify(
+        hasher,
+        buf,
+        DOMAIN,
+        g,
+        public_key,
+        &message(index, committee),
+        proof,
+    )
+}
+
+// Rejects the identity and any key registered twice.
+pub fn check_public_keys(public_keys: &[CompressedRistretto]) -> Result<(), Error> {
+    let mut seen = BTreeMap::new();
+    for (i, pk) in public_keys.iter().enumerate() {
+        if *pk == CompressedRistretto::identity() {
+            verification_failure!("identity_public_key");
+            return Err(IdentityPublicKey(i + 1).into());
+        }
+        if let Some(j) = seen.insert(pk.to_bytes(), i + 1) {
+            verification_failure!("duplicate_public_key");
+            return Err(DuplicatePublicKey(j, i + 1).into());
+        }
+    }
+    Ok(())
+}
+
+// Checks the whole committee's registrations, the key at position i belongs to party i + 1.
+pub fn verify_public_keys(
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    g: &RistrettoPoint,
+    committee: &[u8],
+    public_keys: &[CompressedRistretto],
+    proofs: &[(Scalar, Scalar)],
+) -> Result<Vec<RistrettoPoint>, Error> {
+    if public_keys.len() != proofs.len() {
+        return Err(CountMismatch(
+            public_keys.len(),
+            "public keys",
+            proofs.len(),
+            "possession proofs",
+        )
+        .into());
+    }
+    check_public_keys(public_keys)?;
+
+    public_keys
+        .iter()
+        .zip(proofs)
+        .enumerate()
+        .map(|(i, (pk, proof))| {
+            if verify_possession(hasher, buf, g, pk, i + 1, committee, proof)? {
+                decompress_ristretto_point(*pk)
+            } else {
+                verification_failure!("possession_proof");
+                Err(InvalidPossessionProof(i + 1).into())
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use blake3::Hasher;
+    use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::{
+        error::ErrorKind::{DuplicatePublicKey, IdentityPublicKey, InvalidPossessionProof},
+        pop::{prove_possession, verify_public_keys},
+        random::{random_point, random_scalars},
+    };
+
+    #[test]
+    fn possession_proofs() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut hasher = Hasher::new();
+        let mut buf = [0u8; 64];
+        let g = random_point(&mut rng);
+        let committee = b"committee";
+
+        let sks = random_scalars(&mut rng, 4);
+        let mut pks: Vec<CompressedRistretto> = sks.iter().map(|sk| (g * sk).compress()).collect();
+        let mut proofs: Vec<(Scalar, Scalar)> = sks
+            .iter()
+            .enumerate()
+            .map(|(i, sk)| {
+                prove_possession(&mut rng, &mut hasher, &mut buf, &g, sk, i + 1, committee)
+            })
+            .collect();
+
+        let decompressed =
+            verify_public_keys(&mut hasher, &mut buf, &g, committee, &pks, &proofs).unwrap();
+        assert_eq!(decompressed[2], g * sks[2]);
+
+        // bound to the committee
+        assert!(matches!(
+            verify_public_keys(&mut hasher, &mut buf, &g, b"other", &pks, &proofs)
+                .unwrap_err()
+                .kind(),
+            InvalidPossessionProof(1)
+        ));
+
+        // bound to the index
+        proofs.swap(1, 2);
+        pks.swap(1, 2);
+        assert!(matches!(
+            verify_public_keys(&mut hasher, &mut buf, &g, committee, &pks, &proofs)
+                .unwrap_err()
+                .kind(),
+            InvalidPossessionProof(2)
+        ));
+
+        pks[3] = pks[0];
+        assert!(matches!(
+            verify_public_keys(&mut hasher, &mut buf, &g, committee, &pks, &proofs)
+                .unwrap_err()
+                .kind(),
+            DuplicatePublicKey(1, 4)
+        ));
+
+        pks[0] = RistrettoPoint::default().compress();
+        assert!(matches!(
+            verify_public_keys(&mut hasher, &mut buf, &g, committee, &pks, &proofs)
+                .unwrap_err()
+                .kind(),
+            IdentityPublicKey(1)
+        ));
+    }
+}
@@ -1,13 +1,16 @@
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "std")]
 use std::{fs::File, io::Read};
 
 use curve25519_dalek::Scalar;
 use serde::{Deserialize, Serialize};
 
-use rayon::prelude::*;
+use crate::{execution::granularity, parallel::prelude::*};
 
 pub fn gen_powers(n: usize, t: usize) -> Vec<Vec<Scalar>> {
-    (0..=n)
+    (0..n + 1)
         .into_par_iter()
+        .with_min_len(granularity())
         .map(|i| {
             let mut x_powers: Vec<Scalar> = vec![Scalar::ONE, Scalar::from(i as u64)];
             for i in 2..(t + 1) {
@@ -46,6 +49,7 @@ impl XPowTable {
         }
     }
 
+    #[cfg(feature = "std")]
     pub fn from_file(path: &str) -> Self {
         let mut read_handle = File::open(path).unwrap();
 
@@ -55,6 +59,7 @@ impl XPowTable {
         serde_json::from_slice(&bytes).unwrap()
     }
 
+    #[cfg(feature = "std")]
     pub fn from_params(path: &str, n: usize, t: usize) -> Vec<Vec<Scalar>> {
         let table = Self::from_file(path);
         match (n, t) {
@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use curve25519_dalek::{RistrettoPoint, Scalar};
 use rand::*;
 
@@ -57,9 +59,9 @@ where
 
 #[cfg(test)]
 mod test {
+    use crate::parallel::prelude::*;
     use curve25519_dalek::Scalar;
     use rand::*;
-    use rayon::prelude::*;
 
     #[test]
     fn test_rand() {
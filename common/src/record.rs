@@ -0,0 +1,181 @@
+// Recording a party's run and replaying it offline.
+// `Recorder` wraps any `Session` and logs, for every message the party handles, the message
+// in its wire format (serde_json, as `net` sends it), the seed of the randomness the step
+// used and what came out of it: the outgoing messages, the error if it was rejected and the
+// phase it left the session in. Steps run on a `StdRng` seeded from the caller's rng, so a
+// `Transcript` pins down the whole run.
+//
+// `replay` feeds a recorded transcript to a session set up like the recorded one (same
+// party, same key, same session id) and reports the first step whose output differs.
+
+use std::{format, string::String, vec::Vec};
+
+use blake3::Hasher;
+use rand::{CryptoRng, RngCore, SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    error::{Error, ErrorKind::TranscriptError},
+    session::{Outgoing, Phase, Session},
+};
+
+// What one step produced, compared as a whole on replay.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Output {
+    // (recipient, message), `None` for a broadcast
+    pub outgoing: Vec<(Option<usize>, Vec<u8>)>,
+    pub error: Option<String>,
+    pub phase: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Step {
+    pub seed: [u8; 32],
+    pub input: Vec<u8>,
+    pub output: Output,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    // index into `Transcript::steps`
+    pub step: usize,
+    pub recorded: Output,
+    pub replayed: Output,
+}
+
+type Handled<M> = Result<Vec<Outgoing<M>>, Error>;
+
+fn encode<M: Serialize>(message: &M) -> Result<Vec<u8>, Error> {
+    serde_json::to_vec(message).map_err(|e| TranscriptError(e.to_string()).into())
+}
+
+fn decode<M: DeserializeOwned>(bytes: &[u8]) -> Result<M, Error> {
+    serde_json::from_slice(bytes).map_err(|e| TranscriptError(e.to_string()).into())
+}
+
+// Runs one step of `session` on `rng` seeded with `seed` and captures what it produced.
+// The session's own result is handed back untouched next to its recording.
+fn step<S>(
+    session: &mut S,
+    seed: [u8; 32],
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    message: S::Message,
+) -> Result<(Output, Handled<S::Message>), Error>
+where
+    S: Session,
+    S::Message: Serialize,
+{
+    let result = session.handle(&mut StdRng::from_seed(seed), hasher, buf, message);
+    let (outgoing, error) = match &result {
+        Ok(outgoing) => (
+            outgoing
+                .iter()
+                .map(|out| match out {
+                    Outgoing::Broadcast(m) => Ok((None, encode(m)?)),
+                    Outgoing::To(i, m) => Ok((Some(*i), encode(m)?)),
+                })
+                .collect::<Result<_, Error>>()?,
+            None,
+        ),
+        Err(e) => (Vec::new(), Some(format!("{e}"))),
+    };
+    let output = Output {
+        outgoing,
+        error,
+        phase: format!("{:?}", session.phase()),
+    };
+    Ok((output, result))
+}
+
+pub struct Recorder<S> {
+    pub session: S,
+    pub transcript: Transcript,
+}
+
+impl<S> Recorder<S>
+where
+    S: Session,
+    S::Message: Serialize,
+{
+    pub fn new(session: S) -> Self {
+        Self {
+            session,
+            transcript: Transcript::default(),
+        }
+    }
+
+    pub fn into_parts(self) -> (S, Transcript) {
+        (self.session, self.transcript)
+    }
+}
+
+impl<S> Session for Recorder<S>
+where
+    S: Session,
+    S::Message: Serialize,
+{
+    type Message = S::Message;
+    type Outcome = S::Outcome;
+
+    fn phase(&self) -> Phase {
+        self.session.phase()
+    }
+
+    fn outcome(&self) -> Option<&S::Outcome> {
+        self.session.outcome()
+    }
+
+    // A message that can't be encoded for the transcript isn't handed to the session.
+    fn handle<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        message: S::Message,
+    ) -> Result<Vec<Outgoing<S::Message>>, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        let input = encode(&message)?;
+
+        let (output, result) = step(&mut self.session, seed, hasher, buf, message)?;
+        self.transcript.steps.push(Step {
+            seed,
+            input,
+            output,
+        });
+        result
+    }
+}
+
+// Re-runs `session` on the inputs and randomness of `transcript`, `None` if every step
+// gives back what was recorded.
+pub fn replay<S>(session: &mut S, transcript: &Transcript) -> Result<Option<Divergence>, Error>
+where
+    S: Session,
+    S::Message: Serialize + DeserializeOwned,
+{
+    let mut hasher = Hasher::new();
+    let mut buf = [0u8; 64];
+
+    for (i, recorded) in transcript.steps.iter().enumerate() {
+        let message = decode(&recorded.input)?;
+        let (replayed, _) = step(session, recorded.seed, &mut hasher, &mut buf, message)?;
+        if replayed != recorded.output {
+            return Ok(Some(Divergence {
+                step: i,
+                recorded: recorded.output.clone(),
+                replayed,
+            }));
+        }
+    }
+    Ok(None)
+}
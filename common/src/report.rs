@@ -0,0 +1,251 @@
+// Per-party outcome of checking the shares of a dealing.
+// `verify_shares` / `verify_decrypted_shares` only keep the indices that passed, the
+// report says for every party whether its share holds and if not why, along with the
+// challenges it was checked under, so whoever runs the dealing can tell who misbehaved.
+// `ShareTracker` is the same for shares checked one at a time as they arrive.
+
+use alloc::{vec, vec::Vec};
+use core::fmt;
+
+use curve25519_dalek::Scalar;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{
+    Error,
+    ErrorKind::{DuplicateMessage, InvalidPararmeterSet},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShareStatus {
+    Valid,
+    // the share or its commitment isn't a valid point encoding
+    BadDecompression,
+    // the share doesn't open its commitment under the dealing's challenge
+    CommitmentMismatch,
+    // the DLEQ proof's challenge doesn't hash back to itself
+    WrongChallenge,
+    // no share, or fewer parts than the dealing has secrets
+    Missing,
+}
+
+impl fmt::Display for ShareStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareStatus::Valid => write!(f, "valid"),
+            ShareStatus::BadDecompression => write!(f, "share doesn't decompress"),
+            ShareStatus::CommitmentMismatch => write!(f, "share doesn't match its commitment"),
+            ShareStatus::WrongChallenge => write!(f, "DLEQ proof has the wrong challenge"),
+            ShareStatus::Missing => write!(f, "share is missing"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareCheck {
+    // 1-indexed party
+    pub index: usize,
+    pub status: ShareStatus,
+    // (challenge in the party's proof, challenge recomputed from it), for DLEQ proofs
+    pub challenge: Option<(Scalar, Scalar)>,
+}
+
+impl ShareCheck {
+    // `failure` unless `ok`.
+    pub fn checked(index: usize, ok: bool, failure: ShareStatus) -> Self {
+        Self {
+            index,
+            status: if ok { ShareStatus::Valid } else { failure },
+            challenge: None,
+        }
+    }
+
+    pub fn with_challenge(mut self, challenge: (Scalar, Scalar)) -> Self {
+        self.challenge = Some(challenge);
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub t: usize,
+    // the dealing's challenge d the shares were checked under, if the scheme has one
+    pub challenge: Option<Scalar>,
+    // one per party, in index order
+    pub checks: Vec<ShareCheck>,
+}
+
+impl VerificationReport {
+    // Parties 1..=n without a check in `checks` are reported `Missing`.
+    pub fn new(n: usize, t: usize, challenge: Option<Scalar>, checks: Vec<ShareCheck>) -> Self {
+        let mut checks = checks;
+        checks.sort_by_key(|check| check.index);
+        let mut checks = checks.into_iter().peekable();
+        let checks = (1..=n)
+            .map(|index| match checks.next_if(|check| check.index == index) {
+                Some(check) => check,
+                None => ShareCheck::checked(index, false, ShareStatus::Missing),
+            })
+            .collect();
+        Self {
+            t,
+            challenge,
+            checks,
+        }
+    }
+
+    // 0-indexed positions of the valid shares, as `validated_shares` keeps them.
+    pub fn valid(&self) -> Vec<usize> {
+        self.checks
+            .iter()
+            .filter(|check| check.status == ShareStatus::Valid)
+            .map(|check| check.index - 1)
+            .collect()
+    }
+
+    // (party, reason) for every share that didn't hold.
+    pub fn misbehaving(&self) -> Vec<(usize, ShareStatus)> {
+        self.checks
+            .iter()
+            .filter(|check| check.status != ShareStatus::Valid)
+            .map(|check| (check.index, check.status))
+            .collect()
+    }
+
+    // More than t shares hold, enough to reconstruct.
+    pub fn is_sufficient(&self) -> bool {
+        self.valid().len() > self.t
+    }
+}
+
+// The running valid set of a party checking shares on arrival, which says as soon as more
+// than t hold so reconstruction doesn't wait on stragglers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareTracker {
+    pub n: usize,
+    pub t: usize,
+    // the dealing's challenge d the shares are checked under, if the scheme has one
+    pub challenge: Option<Scalar>,
+    // in arrival order
+    pub checks: Vec<ShareCheck>,
+}
+
+impl ShareTracker {
+    pub fn new(n: usize, t: usize, challenge: Option<Scalar>) -> Self {
+        Self {
+            n,
+            t,
+            challenge,
+            checks: vec![],
+        }
+    }
+
+    // Records the check of a share that just arrived. True if it is the (t + 1)-th that
+    // holds, so the caller can reconstruct once and only once.
+    pub fn record(&mut self, check: ShareCheck) -> Result<bool, Error> {
+        if check.index == 0 || check.index > self.n {
+            return Err(InvalidPararmeterSet(self.n, self.t as isize, check.index).into());
+        }
+        if self.check(check.index).is_some() {
+            return Err(DuplicateMessage(check.index).into());
+        }
+        let valid = check.status == ShareStatus::Valid;
+        self.checks.push(check);
+        Ok(valid && self.valid().len() == self.t + 1)
+    }
+
+    pub fn check(&self, index: usize) -> Option<&ShareCheck> {
+        self.checks.iter().find(|check| check.index == index)
+    }
+
+    // 0-indexed positions of the valid shares in arrival order, as `validated_shares` keeps
+    // them.
+    pub fn valid(&self) -> Vec<usize> {
+        self.checks
+            .iter()
+            .filter(|check| check.status == ShareStatus::Valid)
+            .map(|check| check.index - 1)
+            .collect()
+    }
+
+    pub fn is_sufficient(&self) -> bool {
+        self.valid().len() > self.t
+    }
+
+    // Every party's share is in, nothing more will arrive.
+    pub fn is_complete(&self) -> bool {
+        self.checks.len() == self.n
+    }
+
+    // What arrived so far, the rest reported `Missing`.
+    pub fn report(&self) -> VerificationReport {
+        VerificationReport::new(self.n, self.t, self.challenge, self.checks.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use crate::{
+        error::ErrorKind::DuplicateMessage,
+        report::{ShareCheck, ShareStatus, ShareTracker, VerificationReport},
+    };
+
+    #[test]
+    fn report_fills_gaps() {
+        let report = VerificationReport::new(
+            4,
+            1,
+            None,
+            vec![
+                ShareCheck::checked(3, false, ShareStatus::CommitmentMismatch),
+                ShareCheck::checked(1, true, ShareStatus::CommitmentMismatch),
+                ShareCheck::checked(2, true, ShareStatus::CommitmentMismatch),
+            ],
+        );
+        assert_eq!(report.valid(), vec![0, 1]);
+        assert_eq!(
+            report.misbehaving(),
+            vec![
+                (3, ShareStatus::CommitmentMismatch),
+                (4, ShareStatus::Missing)
+            ]
+        );
+        assert!(report.is_sufficient());
+    }
+
+    #[test]
+    fn tracker_signals_once() {
+        let mut tracker = ShareTracker::new(5, 2, None);
+        let arrivals = [
+            (4, true, false),
+            (2, false, false),
+            (5, true, false),
+            (1, true, true),
+            (3, true, false),
+        ];
+        for (index, ok, signal) in arrivals {
+            let check = ShareCheck::checked(index, ok, ShareStatus::CommitmentMismatch);
+            assert_eq!(tracker.record(check).unwrap(), signal);
+            assert_eq!(tracker.is_sufficient(), index == 1 || index == 3);
+        }
+        assert_eq!(tracker.valid(), vec![3, 4, 0, 2]);
+        assert!(tracker.is_complete());
+        assert!(matches!(
+            tracker
+                .record(ShareCheck::checked(4, true, ShareStatus::Missing))
+                .unwrap_err()
+                .kind(),
+            DuplicateMessage(4)
+        ));
+        assert!(
+            tracker
+                .record(ShareCheck::checked(6, true, ShareStatus::Missing))
+                .is_err()
+        );
+        assert_eq!(
+            tracker.report().misbehaving(),
+            vec![(2, ShareStatus::CommitmentMismatch)]
+        );
+    }
+}
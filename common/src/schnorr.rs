@@ -0,0 +1,97 @@
+// Schnorr signatures (d, z) over ristretto, same shape as the DLEQ proofs.
+// The challenge covers a domain tag, the generator, the signer's key and the commitment,
+// so a signature made for one purpose doesn't verify for another.
+
+use blake3::Hasher;
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+use rand::{CryptoRng, RngCore};
+use zeroize::Zeroize;
+
+use crate::{error::Error, opcount, random::random_scalar, utils::decompress_ristretto_point};
+
+fn challenge(
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    domain: &[u8],
+    g: &CompressedRistretto,
+    public_key: &CompressedRistretto,
+    message: &[u8],
+    commitment: &CompressedRistretto,
+) -> Scalar {
+    opcount::hashes(1, 16 + domain.len() + message.len() + 3 * 32);
+    hasher.update(&(domain.len() as u64).to_le_bytes());
+    hasher.update(domain);
+    hasher.update(g.as_bytes());
+    hasher.update(public_key.as_bytes());
+    hasher.update(&(message.len() as u64).to_le_bytes());
+    hasher.update(message);
+    hasher.update(commitment.as_bytes());
+
+    hasher.finalize_xof().fill(buf);
+    hasher.reset();
+
+    let d = Scalar::from_bytes_mod_order_wide(buf);
+    buf.zeroize();
+    d
+}
+
+pub fn sign<R>(
+    rng: &mut R,
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    domain: &[u8],
+    g: &RistrettoPoint,
+    private_key: &Scalar,
+    message: &[u8],
+) -> (Scalar, Scalar)
+where
+    R: CryptoRng + RngCore,
+{
+    opcount::point_muls(2);
+    opcount::scalar_muls(1);
+    opcount::compressions(3);
+    let mut r = random_scalar(rng);
+    let commitment = (g * r).compress();
+    let public_key = (g * private_key).compress();
+
+    let d = challenge(
+        hasher,
+        buf,
+        domain,
+        &g.compress(),
+        &public_key,
+        message,
+        &commitment,
+    );
+    let z = r + d * private_key;
+    r.zeroize();
+
+    (d, z)
+}
+
+pub fn verify(
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    domain: &[u8],
+    g: &RistrettoPoint,
+    public_key: &CompressedRistretto,
+    message: &[u8],
+    signature: &(Scalar, Scalar),
+) -> Result<bool, Error> {
+    let pk = decompress_ristretto_point(*public_key)?;
+    opcount::msms(1, 2);
+    opcount::compressions(2);
+    // g^z * pk^-d == g^r
+    let commitment = (g * signature.1 - pk * signature.0).compress();
+
+    let d = challenge(
+        hasher,
+        buf,
+        domain,
+        &g.compress(),
+        public_key,
+        message,
+        &commitment,
+    );
+    Ok(d == signature.0)
+}
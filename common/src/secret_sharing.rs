@@ -1,15 +1,26 @@
+use alloc::vec::Vec;
+use core::time::Duration;
+
 use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
 use rand::{CryptoRng, RngCore, seq::SliceRandom};
 
 use crate::{
     error::{
         Error,
-        ErrorKind::{InsufficientShares, UninitializedValue},
+        ErrorKind::{
+            CountMismatch, EmptyQualifiedSet, InconsistentShares, InsufficientShares,
+            InvalidPararmeterSet, UninitializedValue,
+        },
     },
+    execution::granularity,
+    opcount,
+    parallel::prelude::*,
+    phase,
     polynomial::Polynomial,
+    utils::{compute_barycentric_weights, compute_lagrange_bases, compute_lagrange_bases_at},
 };
-use rayon::prelude::*;
 
+#[cfg(feature = "std")]
 pub fn generate_encrypted_shares_batched(
     t: usize,
     x_pows: &Vec<Vec<Scalar>>,
@@ -18,9 +29,13 @@ pub fn generate_encrypted_shares_batched(
 ) -> (Vec<Polynomial>, Vec<Vec<CompressedRistretto>>) {
     let (f_polynomials, f_evals) = generate_shares_batched(public_keys.len(), t, x_pows, secrets);
 
+    let _phase = phase!("encrypt", n = public_keys.len(), t = t, k = secrets.len());
+    opcount::point_muls(public_keys.len() * secrets.len());
+    opcount::compressions(public_keys.len() * secrets.len());
     let encrypted_shares = f_evals
         .par_iter()
         .zip(public_keys.par_iter())
+        .with_min_len(granularity())
         .map(|(fk, pub_key)| {
             fk.par_iter()
                 .map(|f_eval| (f_eval * pub_key).compress())
@@ -32,6 +47,7 @@ pub fn generate_encrypted_shares_batched(
 }
 
 pub fn decrypt_share(private_key: &Scalar, encrypted_share: &RistrettoPoint) -> RistrettoPoint {
+    opcount::point_muls(1);
     private_key.invert() * encrypted_share
 }
 
@@ -47,15 +63,20 @@ where
 {
     let (f_polynomial, f_evals) = generate_shares(rng, public_keys.len(), t, x_pows, secret);
 
+    let _phase = phase!("encrypt", n = public_keys.len(), t = t);
+    opcount::point_muls(public_keys.len());
+    opcount::compressions(public_keys.len());
     let encrypted_shares = f_evals
         .par_iter()
         .zip(public_keys.par_iter())
+        .with_min_len(granularity())
         .map(|(fi, pub_key)| (fi * pub_key).compress())
         .collect();
 
     (f_polynomial, encrypted_shares)
 }
 
+#[cfg(feature = "std")]
 pub fn generate_shares_batched(
     n: usize,
     t: usize,
@@ -63,8 +84,12 @@ pub fn generate_shares_batched(
     secrets: &Vec<Scalar>,
 ) -> (Vec<Polynomial>, Vec<Vec<Scalar>>) {
     // This contains k * f_polynomial
-    let f_polynomials = Polynomial::sample_n_set_f0(secrets.len(), t, secrets).unwrap();
+    let f_polynomials = {
+        let _phase = phase!("sample", t = t, k = secrets.len());
+        Polynomial::sample_n_set_f0(secrets.len(), t, secrets).unwrap()
+    };
     // evals is vec[vec[k]; n]
+    let _phase = phase!("evaluate", n = n, t = t, k = secrets.len());
     let f_evals = Polynomial::evaluate_many_range_precomp(x_pows, &f_polynomials, 1, n);
     (f_polynomials, f_evals)
 }
@@ -79,11 +104,203 @@ pub fn generate_shares<R>(
 where
     R: CryptoRng,
 {
-    let polynomial = Polynomial::sample_set_f0(t, rng, secret);
+    let polynomial = {
+        let _phase = phase!("sample", t = t);
+        Polynomial::sample_set_f0(t, rng, secret)
+    };
+    let _phase = phase!("evaluate", n = n, t = t);
     let evals = polynomial.evaluate_range_precomp(x_pows, 1, n);
     (polynomial, evals)
 }
 
+// Anything a secret is shared as: scalars, points when sharing in the exponent, or batches
+// of either. Reconstruction only ever takes linear combinations of shares.
+pub trait Share: Clone + PartialEq + Send + Sync {
+    // how many secrets one share carries
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn scale(&self, lambda: &Scalar) -> Self;
+
+    // fails for shares of different widths
+    fn combine(self, other: Self) -> Result<Self, Error>;
+
+    // counts a linear combination of `size` shares like this one
+    fn count_ops(&self, size: usize);
+}
+
+impl Share for Scalar {
+    fn scale(&self, lambda: &Scalar) -> Self {
+        self * lambda
+    }
+
+    fn combine(self, other: Self) -> Result<Self, Error> {
+        Ok(self + other)
+    }
+
+    fn count_ops(&self, size: usize) {
+        opcount::scalar_muls(size);
+    }
+}
+
+impl Share for RistrettoPoint {
+    fn scale(&self, lambda: &Scalar) -> Self {
+        self * lambda
+    }
+
+    fn combine(self, other: Self) -> Result<Self, Error> {
+        Ok(self + other)
+    }
+
+    fn count_ops(&self, size: usize) {
+        opcount::msms(1, size);
+    }
+}
+
+impl<T: Share> Share for Vec<T> {
+    fn width(&self) -> usize {
+        self.len()
+    }
+
+    fn scale(&self, lambda: &Scalar) -> Self {
+        self.iter().map(|share| share.scale(lambda)).collect()
+    }
+
+    fn combine(self, other: Self) -> Result<Self, Error> {
+        if self.len() != other.len() {
+            return Err(CountMismatch(
+                self.len(),
+                "secrets",
+                other.len(),
+                "secrets of another share",
+            )
+            .into());
+        }
+        self.into_iter()
+            .zip(other)
+            .map(|(share, other)| share.combine(other))
+            .collect()
+    }
+
+    fn count_ops(&self, size: usize) {
+        self.iter().for_each(|share| share.count_ops(size));
+    }
+}
+
+// Which of the validated shares to reconstruct from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Selection<'a> {
+    // t + 1 of them at random
+    Random,
+    // the t + 1 with the lowest indices
+    LowestIndices,
+    // the t + 1 that arrived first, latencies[i] is how long party i + 1's share took
+    LowestLatency(&'a [Duration]),
+    // all of them, checking they lie on one polynomial of degree t
+    All,
+}
+
+// (index, share) of the validated shares `selection` picks, at least t + 1 of them.
+// `validated_shares` are 0-based positions into `shares`.
+pub fn select_shares<R, T>(
+    rng: &mut R,
+    t: usize,
+    shares: &[T],
+    validated_shares: &[usize],
+    selection: Selection,
+) -> Result<Vec<(usize, T)>, Error>
+where
+    T: Clone,
+    R: CryptoRng + RngCore,
+{
+    if validated_shares.len() <= t {
+        return Err(InsufficientShares(validated_shares.len(), t).into());
+    }
+    if let Some(i) = validated_shares.iter().find(|i| **i >= shares.len()) {
+        return Err(InvalidPararmeterSet(shares.len(), t as isize, i + 1).into());
+    }
+
+    let mut selected = validated_shares.to_vec();
+    match selection {
+        Selection::Random => selected.shuffle(rng),
+        Selection::LowestIndices | Selection::All => selected.sort(),
+        Selection::LowestLatency(latencies) => {
+            selected.sort_by_key(|i| (latencies.get(*i).copied().unwrap_or(Duration::MAX), *i))
+        }
+    }
+    if selection != Selection::All {
+        selected.truncate(t + 1);
+    }
+
+    Ok(selected
+        .into_iter()
+        .map(|i| (i + 1, shares[i].clone()))
+        .collect())
+}
+
+// The secret shared as `shares`, from the validated ones `selection` picks. With
+// `Selection::All` every share past the first t + 1 must be what those interpolate at its
+// index, or this fails with the first that isn't.
+pub fn reconstruct<R, T>(
+    rng: &mut R,
+    t: usize,
+    shares: &[T],
+    validated_shares: &[usize],
+    selection: Selection,
+) -> Result<T, Error>
+where
+    T: Share,
+    R: CryptoRng + RngCore,
+{
+    let selected = select_shares(rng, t, shares, validated_shares, selection)?;
+    let (qualified_set, rest) = selected.split_at(t + 1);
+    let indices: Vec<usize> = qualified_set.iter().map(|(index, _)| *index).collect();
+
+    let weights = compute_barycentric_weights(&indices);
+    for (index, share) in rest {
+        let expected = interpolate(
+            qualified_set,
+            &compute_lagrange_bases_at(*index, &indices, &weights),
+        )?;
+        if expected != *share {
+            return Err(InconsistentShares(*index).into());
+        }
+    }
+    interpolate(qualified_set, &compute_lagrange_bases(&indices))
+}
+
+// sum of lambdas[i] * qualified_set[i], the polynomial through `qualified_set` wherever
+// `lambdas` interpolate it.
+pub fn interpolate<T>(qualified_set: &[(usize, T)], lambdas: &[Scalar]) -> Result<T, Error>
+where
+    T: Share,
+{
+    let (_, first) = qualified_set.first().ok_or(EmptyQualifiedSet)?;
+    if lambdas.len() != qualified_set.len() {
+        return Err(CountMismatch(
+            qualified_set.len(),
+            "shares",
+            lambdas.len(),
+            "Lagrange coefficients",
+        )
+        .into());
+    }
+    let _phase = phase!(
+        "reconstruct",
+        t = qualified_set.len() - 1,
+        k = first.width()
+    );
+    first.count_ops(qualified_set.len());
+
+    qualified_set
+        .par_iter()
+        .zip(lambdas.par_iter())
+        .map(|((_, share), lambda)| Ok(share.scale(lambda)))
+        .reduce_with(|acc: Result<T, Error>, share| acc?.combine(share?))
+        .ok_or(EmptyQualifiedSet)?
+}
+
 pub fn select_qualified_set<R, T>(
     rng: &mut R,
     t: usize,
@@ -95,114 +312,73 @@ where
     R: CryptoRng + RngCore,
 {
     match shares {
-        Some(shares) => {
-            if shares.len() > t {
-                let mut tmp = validated_shares.clone();
-                tmp.shuffle(rng);
-
-                Ok(tmp
-                    .into_iter()
-                    .take(t + 1)
-                    .map(|x| (x + 1, shares[x].clone()))
-                    .collect())
-            } else {
-                Err(InsufficientShares(validated_shares.len(), t).into())
-            }
-        }
+        Some(shares) => select_shares(rng, t, shares, validated_shares, Selection::Random),
         None => Err(UninitializedValue("party.{shares || decrypted_shares}").into()),
     }
 }
 
 pub fn reconstruct_secrets_exponent(
     qualified_set: &Option<Vec<(usize, Vec<RistrettoPoint>)>>,
-    lambdas: &Vec<Scalar>,
+    lambdas: &[Scalar],
 ) -> Result<Vec<RistrettoPoint>, Error> {
     match qualified_set {
-        Some(qualified_set) => {
-            let k = qualified_set[0].1.len();
-
-            Ok((0..k)
-                .into_par_iter()
-                .map(|k| {
-                    qualified_set
-                        .par_iter()
-                        .zip(lambdas.par_iter())
-                        .map(|((_, poly_share), lambda)| lambda * poly_share[k])
-                        .sum()
-                })
-                .collect())
-        }
+        Some(qualified_set) => interpolate(qualified_set, lambdas),
         None => Err(UninitializedValue("party.qualified_set").into()),
     }
 }
 
 pub fn reconstruct_secrets(
     qualified_set: &Option<Vec<(usize, Vec<Scalar>)>>,
-    lambdas: &Vec<Scalar>,
+    lambdas: &[Scalar],
 ) -> Result<Vec<Scalar>, Error> {
     match qualified_set {
-        Some(qualified_set) => {
-            let k = qualified_set[0].1.len();
-            Ok((0..k)
-                .into_par_iter()
-                .map(|k| {
-                    qualified_set
-                        .par_iter()
-                        .zip(lambdas.par_iter())
-                        .map(|((_, poly_share), lambda)| lambda * poly_share[k])
-                        .sum()
-                })
-                .collect())
-        }
+        Some(qualified_set) => interpolate(qualified_set, lambdas),
         None => Err(UninitializedValue("party.qualified_set").into()),
     }
 }
 
 pub fn reconstruct_secret(
     qualified_set: &Option<Vec<(usize, Scalar)>>,
-    lambdas: &Vec<Scalar>,
+    lambdas: &[Scalar],
 ) -> Result<Scalar, Error> {
     match qualified_set {
-        Some(qualified_set) => Ok(qualified_set
-            .par_iter()
-            .zip(lambdas.par_iter())
-            .map(|((_, decrypted_share), lambda)| lambda * decrypted_share)
-            .sum()),
+        Some(qualified_set) => interpolate(qualified_set, lambdas),
         None => Err(UninitializedValue("party.qualified_set").into()),
     }
 }
 
 pub fn reconstruct_secret_exponent(
     qualified_set: &Option<Vec<(usize, RistrettoPoint)>>,
-    lambdas: &Vec<Scalar>,
+    lambdas: &[Scalar],
 ) -> Result<RistrettoPoint, Error> {
     match qualified_set {
-        Some(qualified_set) => Ok(qualified_set
-            .par_iter()
-            .zip(lambdas.par_iter())
-            .map(|((_, decrypted_share), lambda)| lambda * decrypted_share)
-            .sum()),
+        Some(qualified_set) => interpolate(qualified_set, lambdas),
         None => Err(UninitializedValue("party.qualified_set").into()),
     }
 }
 
 #[cfg(test)]
 mod test {
-    use curve25519_dalek::RistrettoPoint;
+    use core::time::Duration;
+
+    use curve25519_dalek::{RistrettoPoint, Scalar};
 
     use crate::{
+        error::ErrorKind::{
+            CountMismatch, EmptyQualifiedSet, InconsistentShares, InsufficientShares,
+        },
         precompute::gen_powers,
         random::{random_scalar, random_scalars},
         secret_sharing::{
-            decrypt_share, generate_encrypted_shares, generate_encrypted_shares_batched,
-            generate_shares, generate_shares_batched, reconstruct_secret,
+            Selection, decrypt_share, generate_encrypted_shares, generate_encrypted_shares_batched,
+            generate_shares, generate_shares_batched, interpolate, reconstruct, reconstruct_secret,
             reconstruct_secret_exponent, reconstruct_secrets, reconstruct_secrets_exponent,
-            select_qualified_set,
+            select_qualified_set, select_shares,
         },
-        utils::compute_lagrange_bases,
+        utils::{compute_barycentric_weights, compute_lagrange_bases, compute_lagrange_bases_at},
     };
 
-    use rayon::prelude::*;
+    use crate::parallel::prelude::*;
 
     #[test]
     fn gen_shares() {
@@ -231,6 +407,84 @@ mod test {
 
         assert_eq!(secret, reconstruct_secret(&q, &lagrange_bases).unwrap());
     }
+    #[test]
+    fn selection_strategies() {
+        let (n, t, k) = (9, 4, 3);
+        let mut rng = rand::rng();
+        let x_pows = gen_powers(n, t);
+        let secrets = random_scalars(&mut rng, k);
+        let (_, mut shares) = generate_shares_batched(n, t, &x_pows, &secrets);
+        let validated: Vec<usize> = (0..n).filter(|i| *i != 1).collect();
+        let latencies: Vec<Duration> = (0..n as u64)
+            .map(|i| Duration::from_millis(100 - 10 * i))
+            .collect();
+
+        let lowest = select_shares(&mut rng, t, &shares, &validated, Selection::LowestIndices);
+        let indices: Vec<usize> = lowest.unwrap().iter().map(|(i, _)| *i).collect();
+        assert_eq!(indices, vec![1, 3, 4, 5, 6]);
+        let fastest = select_shares(
+            &mut rng,
+            t,
+            &shares,
+            &validated,
+            Selection::LowestLatency(&latencies),
+        );
+        let indices: Vec<usize> = fastest.unwrap().iter().map(|(i, _)| *i).collect();
+        assert_eq!(indices, vec![9, 8, 7, 6, 5]);
+        let weights = compute_barycentric_weights(&indices);
+        assert_eq!(
+            compute_lagrange_bases_at(0, &indices, &weights),
+            compute_lagrange_bases(&indices)
+        );
+
+        for selection in [
+            Selection::Random,
+            Selection::LowestIndices,
+            Selection::LowestLatency(&latencies),
+            Selection::All,
+        ] {
+            let reconstructed = reconstruct(&mut rng, t, &shares, &validated, selection);
+            assert_eq!(reconstructed.unwrap(), secrets);
+        }
+        assert!(matches!(
+            reconstruct(&mut rng, t, &shares, &validated[..t], Selection::Random)
+                .unwrap_err()
+                .kind(),
+            InsufficientShares(4, 4)
+        ));
+
+        // only using every share catches one that's off the polynomial
+        shares[7][1] += Scalar::ONE;
+        assert!(matches!(
+            reconstruct(&mut rng, t, &shares, &validated, Selection::All)
+                .unwrap_err()
+                .kind(),
+            InconsistentShares(8)
+        ));
+        assert!(reconstruct(&mut rng, t, &shares, &validated, Selection::LowestIndices).is_ok());
+
+        // shares of different widths don't combine
+        shares[7][1] -= Scalar::ONE;
+        shares[4].pop();
+        for selection in [Selection::LowestIndices, Selection::All] {
+            assert!(matches!(
+                reconstruct(&mut rng, t, &shares, &validated, selection)
+                    .unwrap_err()
+                    .kind(),
+                CountMismatch(..)
+            ));
+        }
+
+        assert!(matches!(
+            interpolate::<Scalar>(&[], &[]).unwrap_err().kind(),
+            EmptyQualifiedSet
+        ));
+        assert!(matches!(
+            reconstruct_secret(&Some(vec![]), &[]).unwrap_err().kind(),
+            EmptyQualifiedSet
+        ));
+    }
+
     #[test]
     fn gen_shares_batch() {
         let n = 128;
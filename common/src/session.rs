@@ -0,0 +1,628 @@
+// Round-based drivers for running a scheme over any transport.
+// `DealerSession` emits the dealing and `PartySession` implements `Session`: feed it every
+// message addressed to the party, send out whatever it returns, and read `phase` /
+// `outcome` in between. Both are generic over the scheme, whose `Dealer` and `Party`
+// implement `SessionDealer` and `SessionParty`, and each scheme crate's `session` module
+// names them for its types.
+// Messages are `Signed` under the sender's registered key, the dealer signs as `DEALER`.
+// Parties only verify a dealing once its digest was reliably broadcast, see `broadcast`,
+// and reconstruct as soon as more than t reveals hold.
+
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+use blake3::Hasher;
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    broadcast::{ReliableBroadcast, SignedVote, Vote},
+    encryption::Ciphertext,
+    error::{
+        Error,
+        ErrorKind::{DuplicateMessage, UnexpectedMessage, UninitializedValue},
+    },
+    keystore::SharedKey,
+    message::{Encode, Signed},
+    opcount,
+    report::{ShareStatus, ShareTracker},
+    verified,
+};
+
+// sender id of the dealer in signed messages, parties are 1-indexed
+pub const DEALER: usize = 0;
+
+// the dealer publishes its dealing
+pub const DEALING_ROUND: u64 = 1;
+// parties exchange what reconstruction needs
+pub const SHARES_ROUND: u64 = 2;
+// parties echo the digest of the dealing they got, see `broadcast`
+pub const BROADCAST_ROUND: u64 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    AwaitingDealing,
+    // the dealing is in but its digest isn't reliably delivered yet
+    AwaitingDelivery,
+    AwaitingShares,
+    Done,
+    // the dealing or the reconstruction failed, with the reason
+    Aborted(&'static str),
+}
+
+impl Phase {
+    pub fn is_over(&self) -> bool {
+        matches!(self, Phase::Done | Phase::Aborted(_))
+    }
+}
+
+#[derive(Clone)]
+pub enum Outgoing<M> {
+    Broadcast(M),
+    // (recipient index, message)
+    To(usize, M),
+}
+
+pub trait Session {
+    type Message;
+    type Outcome;
+
+    fn phase(&self) -> Phase;
+
+    fn outcome(&self) -> Option<&Self::Outcome>;
+
+    // An `Err` rejects `message` alone, the session keeps going and the message can be
+    // blamed on its sender. A failed dealing or reconstruction moves to `Phase::Aborted`.
+    fn handle<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        message: Self::Message,
+    ) -> Result<Vec<Outgoing<Self::Message>>, Error>
+    where
+        R: CryptoRng + RngCore;
+}
+
+// (recipient, its share encrypted to it)
+pub type SignedShare = Signed<(usize, Ciphertext)>;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Message<D, R> {
+    // broadcast by the dealer
+    Dealing(Signed<D>),
+    // sent by the dealer to its recipient only, for schemes with private shares
+    Share(SignedShare),
+    // broadcast by every party while agreeing on the dealing's digest
+    Vote(SignedVote),
+    // broadcast by every party once the dealing verified, what reconstruction needs from it
+    Reveal(Signed<R>),
+}
+
+// what a `PartySession` of `P` handles
+pub type PartyMessage<P> = Message<<P as SessionParty>::Dealing, <P as SessionParty>::Reveal>;
+
+type Dealing<D> = <<D as SessionDealer>::Party as SessionParty>::Dealing;
+
+// A scheme's dealer as `DealerSession` drives it.
+pub trait SessionDealer {
+    // the scheme's party, which the dealing is for
+    type Party: SessionParty;
+    type Secret: ?Sized;
+
+    // Deals `secret` in `session_id`, with every party's share encrypted to it if the
+    // scheme has private shares, `shares[i - 1]` for party i.
+    #[allow(clippy::too_many_arguments, clippy::ptr_arg)]
+    fn deal<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        xpows: &Vec<Vec<Scalar>>,
+        session_id: &[u8; 32],
+        secret: &Self::Secret,
+    ) -> Result<(Dealing<Self>, Vec<Ciphertext>), Error>
+    where
+        R: CryptoRng + RngCore;
+}
+
+// A scheme's party as `PartySession` drives it.
+pub trait SessionParty {
+    type Dealing: Encode + Clone;
+    type Reveal: Encode + Clone;
+    type Outcome;
+    // kept against the dealer or parties whose messages failed their checks
+    type Evidence;
+
+    // whether the dealer sends every party its own share next to the dealing
+    const PRIVATE_SHARES: bool;
+    // why the session aborts when the delivered dealing fails `verify_dealing`
+    const INVALID_DEALING: &'static str;
+
+    fn index(&self) -> usize;
+
+    fn n(&self) -> usize;
+
+    fn key(&self) -> &SharedKey;
+
+    fn public_keys(&self) -> Option<&Vec<RistrettoPoint>>;
+
+    fn tracker(&self) -> Option<&ShareTracker>;
+
+    fn ingest_dealing(&mut self, dealing: Self::Dealing) -> Result<(), Error>;
+
+    fn transcript_digest(
+        &self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+    ) -> Result<[u8; 32], Error>;
+
+    // True if the ingested dealing is the one whose digest was reliably broadcast.
+    fn verify_delivery(
+        &self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+        delivered: &[u8; 32],
+    ) -> Result<bool, Error> {
+        let digest = self.transcript_digest(hasher, session_id)?;
+        Ok(verified!(digest == *delivered, "undelivered_dealing"))
+    }
+
+    // Verifies the delivered dealing, with our own share decrypted from `ciphertext` if the
+    // scheme has private shares, and returns what to reveal, `None` if the dealing failed.
+    #[allow(clippy::too_many_arguments, clippy::ptr_arg)]
+    fn verify_dealing<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        xpows: &Vec<Vec<Scalar>>,
+        session_id: &[u8; 32],
+        ciphertext: Option<&Ciphertext>,
+    ) -> Result<Option<Self::Reveal>, Error>
+    where
+        R: CryptoRng + RngCore;
+
+    // Checks `sender`'s reveal, ours included, true once, when more than t of them hold.
+    #[allow(clippy::ptr_arg)]
+    fn check_reveal(
+        &mut self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        xpows: &Vec<Vec<Scalar>>,
+        sender: usize,
+        reveal: &Self::Reveal,
+    ) -> Result<bool, Error>;
+
+    fn reconstruct<R>(&mut self, rng: &mut R) -> Result<Self::Outcome, Error>
+    where
+        R: CryptoRng + RngCore;
+
+    // Evidence against the dealer out of its dealing that failed `verify_dealing`.
+    fn dealing_evidence(&self, _dealing: &Signed<Self::Dealing>) -> Option<Self::Evidence> {
+        None
+    }
+
+    // Evidence against a party out of its reveal that failed `check_reveal`.
+    fn reveal_evidence(
+        &self,
+        _dealing: &Signed<Self::Dealing>,
+        _reveal: &Signed<Self::Reveal>,
+    ) -> Option<Self::Evidence> {
+        None
+    }
+}
+
+pub struct DealerSession<'a, D> {
+    pub dealer: D,
+    pub key: SharedKey,
+    // generator of the registered keys
+    pub g: RistrettoPoint,
+    pub session_id: [u8; 32],
+    pub xpows: &'a Vec<Vec<Scalar>>,
+}
+
+impl<'a, D: SessionDealer> DealerSession<'a, D> {
+    pub fn new(
+        dealer: D,
+        key: SharedKey,
+        g: RistrettoPoint,
+        session_id: [u8; 32],
+        xpows: &'a Vec<Vec<Scalar>>,
+    ) -> Self {
+        Self {
+            dealer,
+            key,
+            g,
+            session_id,
+            xpows,
+        }
+    }
+
+    pub fn deal<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        secret: &D::Secret,
+    ) -> Result<Vec<Outgoing<PartyMessage<D::Party>>>, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let (dealing, ciphertexts) = self.dealer.deal(
+            rng,
+            hasher,
+            buf,
+            &self.g,
+            self.xpows,
+            &self.session_id,
+            secret,
+        )?;
+        let context = (&self.session_id, DEALING_ROUND, DEALER);
+
+        let mut outgoing = Vec::with_capacity(ciphertexts.len() + 1);
+        outgoing.push(Outgoing::Broadcast(Message::Dealing(Signed::sign_with(
+            rng, hasher, buf, &self.g, &*self.key, context, dealing,
+        )?)));
+        for (i, ciphertext) in ciphertexts.into_iter().enumerate() {
+            let share = Signed::sign_with(
+                rng,
+                hasher,
+                buf,
+                &self.g,
+                &*self.key,
+                context,
+                (i + 1, ciphertext),
+            )?;
+            outgoing.push(Outgoing::To(i + 1, Message::Share(share)));
+        }
+        Ok(outgoing)
+    }
+}
+
+pub struct PartySession<'a, P: SessionParty> {
+    pub party: P,
+    pub g: RistrettoPoint,
+    pub dealer_public_key: CompressedRistretto,
+    pub session_id: [u8; 32],
+    pub xpows: &'a Vec<Vec<Scalar>>,
+    pub phase: Phase,
+    // against the dealer or parties whose messages failed their checks
+    pub evidence: Vec<P::Evidence>,
+    // the committee's keys, compressed once for checking signatures
+    public_keys: Vec<CompressedRistretto>,
+    broadcast: ReliableBroadcast,
+    // the dealing as signed, kept for evidence
+    dealing: Option<Signed<P::Dealing>>,
+    // our encrypted share, only opened once the dealing it was encrypted under is delivered
+    ciphertext: Option<Ciphertext>,
+    // signed reveals by sender, may arrive before the dealing and are only checked once it
+    // is verified
+    received: BTreeMap<usize, Signed<P::Reveal>>,
+    outcome: Option<P::Outcome>,
+}
+
+impl<'a, P: SessionParty> PartySession<'a, P> {
+    // `party` must have ingested the committee's public keys already.
+    pub fn new(
+        party: P,
+        g: RistrettoPoint,
+        dealer_public_key: CompressedRistretto,
+        session_id: [u8; 32],
+        xpows: &'a Vec<Vec<Scalar>>,
+    ) -> Result<Self, Error> {
+        let public_keys = match party.public_keys() {
+            Some(public_keys) => {
+                opcount::compressions(public_keys.len());
+                public_keys.iter().map(|pk| pk.compress()).collect()
+            }
+            None => return Err(UninitializedValue("party.public_keys").into()),
+        };
+        let broadcast = ReliableBroadcast::new(party.n(), party.index());
+
+        Ok(Self {
+            party,
+            g,
+            dealer_public_key,
+            session_id,
+            xpows,
+            phase: Phase::AwaitingDealing,
+            evidence: vec![],
+            public_keys,
+            broadcast,
+            dealing: None,
+            ciphertext: None,
+            received: BTreeMap::new(),
+            outcome: None,
+        })
+    }
+
+    fn ingest_dealing<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        dealing: Signed<P::Dealing>,
+    ) -> Result<Vec<Outgoing<PartyMessage<P>>>, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let payload = dealing.clone().open(
+            hasher,
+            buf,
+            &self.g,
+            &self.dealer_public_key,
+            (&self.session_id, DEALING_ROUND),
+        )?;
+        self.party.ingest_dealing(payload)?;
+        self.dealing = Some(dealing);
+
+        let digest = self.party.transcript_digest(hasher, &self.session_id)?;
+        let votes = self.broadcast.propose(digest);
+        self.phase = Phase::AwaitingDelivery;
+        self.sign_votes(rng, hasher, buf, votes)
+    }
+
+    fn sign_votes<R>(
+        &self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        votes: Vec<Vote>,
+    ) -> Result<Vec<Outgoing<PartyMessage<P>>>, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        Ok(self
+            .broadcast
+            .sign(
+                rng,
+                hasher,
+                buf,
+                &self.g,
+                (&**self.party.key(), &self.session_id),
+                votes,
+            )?
+            .into_iter()
+            .map(|vote| Outgoing::Broadcast(Message::Vote(vote)))
+            .collect())
+    }
+
+    fn ingest_share(
+        &mut self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        share: SignedShare,
+    ) -> Result<(), Error> {
+        if self.ciphertext.is_some() {
+            return Err(UnexpectedMessage(DEALER, "the share is already in").into());
+        }
+        let (recipient, ciphertext) = share.open(
+            hasher,
+            buf,
+            &self.g,
+            &self.dealer_public_key,
+            (&self.session_id, DEALING_ROUND),
+        )?;
+        if recipient != self.party.index() {
+            return Err(UnexpectedMessage(DEALER, "the share is for another party").into());
+        }
+        self.ciphertext = Some(ciphertext);
+        Ok(())
+    }
+
+    // Verifies the dealing once its digest is delivered, and our share with it if the scheme
+    // has private ones, then reveals what reconstruction needs from us.
+    fn verify_dealing<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+    ) -> Result<Vec<Outgoing<PartyMessage<P>>>, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let delivered = match (self.phase, self.broadcast.delivered()) {
+            (Phase::AwaitingDelivery, Some(delivered))
+                if self.ciphertext.is_some() || !P::PRIVATE_SHARES =>
+            {
+                *delivered
+            }
+            _ => return Ok(vec![]),
+        };
+        if !self
+            .party
+            .verify_delivery(hasher, &self.session_id, &delivered)?
+        {
+            self.phase = Phase::Aborted("undelivered_dealing");
+            return Ok(vec![]);
+        }
+        let reveal = match self.party.verify_dealing(
+            rng,
+            hasher,
+            buf,
+            &self.g,
+            self.xpows,
+            &self.session_id,
+            self.ciphertext.as_ref(),
+        )? {
+            Some(reveal) => reveal,
+            None => {
+                self.phase = Phase::Aborted(P::INVALID_DEALING);
+                let evidence = self
+                    .dealing
+                    .as_ref()
+                    .and_then(|dealing| self.party.dealing_evidence(dealing));
+                self.evidence.extend(evidence);
+                return Ok(vec![]);
+            }
+        };
+
+        let index = self.party.index();
+        let reveal = Signed::sign_with(
+            rng,
+            hasher,
+            buf,
+            &self.g,
+            &**self.party.key(),
+            (&self.session_id, SHARES_ROUND, index),
+            reveal,
+        )?;
+        self.phase = Phase::AwaitingShares;
+        self.check_reveal(rng, hasher, buf, index, &reveal.payload)?;
+        let early: Vec<(usize, P::Reveal)> = self
+            .received
+            .iter()
+            .map(|(sender, reveal)| (*sender, reveal.payload.clone()))
+            .collect();
+        for (sender, payload) in early {
+            self.check_reveal(rng, hasher, buf, sender, &payload)?;
+        }
+
+        Ok(vec![Outgoing::Broadcast(Message::Reveal(reveal))])
+    }
+
+    fn ingest_reveal<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        reveal: Signed<P::Reveal>,
+    ) -> Result<(), Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let sender = reveal.sender;
+        if sender == DEALER || sender == self.party.index() || sender > self.party.n() {
+            return Err(UnexpectedMessage(sender, "collecting revealed shares").into());
+        }
+        if self.received.contains_key(&sender) {
+            return Err(DuplicateMessage(sender).into());
+        }
+
+        let payload = reveal.clone().open(
+            hasher,
+            buf,
+            &self.g,
+            &self.public_keys[sender - 1],
+            (&self.session_id, SHARES_ROUND),
+        )?;
+        self.received.insert(sender, reveal);
+        // until the dealing is verified there is nothing to check reveals against
+        if matches!(self.phase, Phase::AwaitingShares | Phase::Done) {
+            self.check_reveal(rng, hasher, buf, sender, &payload)?;
+        }
+        Ok(())
+    }
+
+    // Checks a reveal as it arrives and reconstructs as soon as more than t hold, without
+    // waiting on stragglers. Those are still checked after, for evidence.
+    fn check_reveal<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        sender: usize,
+        payload: &P::Reveal,
+    ) -> Result<(), Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let sufficient = self
+            .party
+            .check_reveal(hasher, buf, &self.g, self.xpows, sender, payload)?;
+
+        if let (Some(tracker), Some(dealing), Some(reveal)) = (
+            self.party.tracker(),
+            &self.dealing,
+            self.received.get(&sender),
+        ) && tracker
+            .check(sender)
+            .is_some_and(|check| check.status != ShareStatus::Valid)
+        {
+            self.evidence
+                .extend(self.party.reveal_evidence(dealing, reveal));
+        }
+
+        if self.phase != Phase::AwaitingShares {
+            return Ok(());
+        }
+        if sufficient {
+            self.outcome = Some(self.party.reconstruct(rng)?);
+            self.phase = Phase::Done;
+        } else if self.party.tracker().is_some_and(|tracker| {
+            tracker.is_complete() && !verified!(tracker.is_sufficient(), "insufficient_shares")
+        }) {
+            self.phase = Phase::Aborted("insufficient_shares");
+        }
+        Ok(())
+    }
+}
+
+impl<P: SessionParty> Session for PartySession<'_, P> {
+    type Message = PartyMessage<P>;
+    type Outcome = P::Outcome;
+
+    fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    fn outcome(&self) -> Option<&P::Outcome> {
+        self.outcome.as_ref()
+    }
+
+    fn handle<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        message: Self::Message,
+    ) -> Result<Vec<Outgoing<Self::Message>>, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        match (self.phase, message) {
+            (Phase::AwaitingDealing, Message::Dealing(dealing)) => {
+                let mut outgoing = self.ingest_dealing(rng, hasher, buf, dealing)?;
+                outgoing.extend(self.verify_dealing(rng, hasher, buf)?);
+                Ok(outgoing)
+            }
+            (_, Message::Share(_)) if !P::PRIVATE_SHARES => {
+                Err(UnexpectedMessage(DEALER, "the scheme's shares are public").into())
+            }
+            (Phase::AwaitingDealing | Phase::AwaitingDelivery, Message::Share(share)) => {
+                self.ingest_share(hasher, buf, share)?;
+                self.verify_dealing(rng, hasher, buf)
+            }
+            (phase, Message::Vote(vote)) if !phase.is_over() => {
+                let votes = self.broadcast.ingest_signed(
+                    hasher,
+                    buf,
+                    &self.g,
+                    &self.public_keys,
+                    &self.session_id,
+                    vote,
+                )?;
+                let mut outgoing = self.sign_votes(rng, hasher, buf, votes)?;
+                outgoing.extend(self.verify_dealing(rng, hasher, buf)?);
+                Ok(outgoing)
+            }
+            // reveals arriving after reconstruction are still checked, for evidence
+            (phase, Message::Reveal(reveal)) if !phase.is_over() || phase == Phase::Done => {
+                self.ingest_reveal(rng, hasher, buf, reveal)?;
+                Ok(vec![])
+            }
+            (_, Message::Dealing(_) | Message::Share(_)) => {
+                Err(UnexpectedMessage(DEALER, "the dealing is already in").into())
+            }
+            // late votes from parties still catching up
+            (_, Message::Vote(_)) => Ok(vec![]),
+            (_, Message::Reveal(reveal)) => {
+                Err(UnexpectedMessage(reveal.sender, "the session is over").into())
+            }
+        }
+    }
+}
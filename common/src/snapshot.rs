@@ -0,0 +1,223 @@
+// Encrypted, crash-safe snapshots of protocol state.
+// A `Vault` keeps one snapshot at one path. Every save seals the whole state with
+// XChaCha20-Poly1305 under the vault key and a fresh nonce, writes it next to the target,
+// syncs it and renames it over the previous snapshot, so a crash leaves either the old
+// snapshot or the new one and never a torn file. The session id and the index of whoever
+// saved it are sealed in as associated data, a snapshot only opens for the same session
+// and the same party, even where several share a vault key.
+//
+// Parties and dealers save after each phase and before sending anything the phase
+// produced. Whatever a resumed process sends is then exactly what the snapshot holds,
+// it never redoes a randomized step, a second DLEQ proof or a second dealing, whose
+// output may already be out there.
+//
+// Every scheme's `Party` has `checkpoint` and `resume`. Of the dealers only those of pi_s
+// and b_pi_s keep their dealings, by session. The others hold nothing between dealings,
+// so whoever runs them saves the dealing itself in a `Vault` before sending any of it.
+
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    string::ToString,
+    vec::Vec,
+};
+
+use chacha20poly1305::{
+    KeyInit, XChaCha20Poly1305, XNonce,
+    aead::{Aead, Payload},
+};
+use curve25519_dalek::RistrettoPoint;
+use rand::RngCore;
+use serde::{Serialize, de::DeserializeOwned};
+use zeroize::Zeroize;
+
+use crate::{
+    error::{Error, ErrorKind::SnapshotError},
+    keystore::KeyHandle,
+};
+
+const DOMAIN: &[u8] = b"pi_vss/snapshot";
+const NONCE_LEN: usize = 24;
+
+fn snapshot_error(e: impl ToString) -> Error {
+    SnapshotError(e.to_string()).into()
+}
+
+pub struct Vault {
+    path: PathBuf,
+    key: [u8; 32],
+    aad: Vec<u8>,
+}
+
+impl Vault {
+    // `context` is the session id and the party index, `DEALER` for a dealer.
+    pub fn new(path: &Path, key: [u8; 32], context: (&[u8; 32], usize)) -> Self {
+        let (session_id, index) = context;
+        let mut aad = DOMAIN.to_vec();
+        aad.extend_from_slice(session_id);
+        aad.extend_from_slice(&(index as u64).to_le_bytes());
+        Self {
+            path: path.to_path_buf(),
+            key,
+            aad,
+        }
+    }
+
+    // A vault keyed by a party's own key, only its handle can open the snapshots.
+    pub fn from_key_handle(
+        path: &Path,
+        key: &dyn KeyHandle,
+        context: (&[u8; 32], usize),
+    ) -> Result<Self, Error> {
+        let mut wide = [0u8; 64];
+        blake3::Hasher::new_derive_key("pi_vss/snapshot base")
+            .finalize_xof()
+            .fill(&mut wide);
+        let shared = key.diffie_hellman(&RistrettoPoint::from_uniform_bytes(&wide))?;
+
+        let mut hasher = blake3::Hasher::new_derive_key("pi_vss/snapshot key");
+        hasher.update(key.public_key().0.as_bytes());
+        hasher.update(shared.compress().as_bytes());
+        Ok(Self::new(path, *hasher.finalize().as_bytes(), context))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn save<T: Serialize>(&self, state: &T) -> Result<(), Error> {
+        let mut plaintext = serde_json::to_vec(state).map_err(snapshot_error)?;
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::rng().fill_bytes(&mut nonce);
+
+        let sealed = XChaCha20Poly1305::new(&self.key.into()).encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: &plaintext,
+                aad: &self.aad,
+            },
+        );
+        plaintext.zeroize();
+        let sealed = sealed.map_err(snapshot_error)?;
+
+        let mut contents = Vec::with_capacity(NONCE_LEN + sealed.len());
+        contents.extend_from_slice(&nonce);
+        contents.extend_from_slice(&sealed);
+        self.replace(&contents).map_err(snapshot_error)
+    }
+
+    // The last saved state, `None` if nothing was saved yet.
+    pub fn load<T: DeserializeOwned>(&self) -> Result<Option<T>, Error> {
+        let contents = match fs::read(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(snapshot_error(e)),
+        };
+        if contents.len() < NONCE_LEN {
+            return Err(snapshot_error("truncated snapshot"));
+        }
+        let (nonce, sealed) = contents.split_at(NONCE_LEN);
+
+        let mut plaintext = XChaCha20Poly1305::new(&self.key.into())
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: sealed,
+                    aad: &self.aad,
+                },
+            )
+            .map_err(|_| snapshot_error("wrong key or corrupt snapshot"))?;
+        let state = serde_json::from_slice(&plaintext).map_err(snapshot_error);
+        plaintext.zeroize();
+        state.map(Some)
+    }
+
+    // Drops the snapshot once the protocol is over.
+    pub fn clear(&self) -> Result<(), Error> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(snapshot_error(e)),
+            _ => Ok(()),
+        }
+    }
+
+    fn replace(&self, contents: &[u8]) -> std::io::Result<()> {
+        let mut staging = self.path.clone().into_os_string();
+        staging.push(".tmp");
+        let staging = PathBuf::from(staging);
+
+        let mut file = File::create(&staging)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&staging, &self.path)?;
+
+        // make the rename itself durable, not every platform lets a directory be synced
+        if let Some(dir) = self.path.parent().and_then(|dir| File::open(dir).ok()) {
+            let _ = dir.sync_all();
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Vault {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs, process};
+
+    use crate::{
+        keystore::MemoryKey,
+        random::{random_point, random_scalar},
+        snapshot::Vault,
+    };
+
+    #[test]
+    fn vault_round_trip() {
+        let mut rng = rand::rng();
+        let g = random_point(&mut rng);
+        let key = MemoryKey::import(&g, &random_scalar(&mut rng)).unwrap();
+        let other = MemoryKey::generate(&mut rng, &g);
+
+        let session_id = [5u8; 32];
+        let path = env::temp_dir().join(format!("pi_vss-snapshot-{}", process::id()));
+        let vault = Vault::from_key_handle(&path, &key, (&session_id, 3)).unwrap();
+        vault.clear().unwrap();
+        assert_eq!(vault.load::<Vec<u64>>().unwrap(), None);
+
+        vault.save(&vec![1u64, 2, 3]).unwrap();
+        vault.save(&vec![4u64]).unwrap();
+        assert_eq!(vault.load::<Vec<u64>>().unwrap(), Some(vec![4]));
+        assert!(
+            Vault::from_key_handle(&path, &key, (&session_id, 3))
+                .unwrap()
+                .load::<Vec<u64>>()
+                .unwrap()
+                .is_some()
+        );
+
+        // another key, session or party index or a flipped bit doesn't open it
+        for (key, context) in [
+            (&other, (&session_id, 3)),
+            (&key, (&[6u8; 32], 3)),
+            (&key, (&session_id, 4)),
+        ] {
+            assert!(
+                Vault::from_key_handle(&path, key, context)
+                    .unwrap()
+                    .load::<Vec<u64>>()
+                    .is_err()
+            );
+        }
+        let mut contents = fs::read(&path).unwrap();
+        *contents.last_mut().unwrap() ^= 1;
+        fs::write(&path, contents).unwrap();
+        assert!(vault.load::<Vec<u64>>().is_err());
+
+        vault.clear().unwrap();
+        assert!(!path.exists());
+    }
+}
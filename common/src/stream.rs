@@ -0,0 +1,151 @@
+// Dealing output produced and consumed a chunk of parties at a time, so dealings for very
+// large committees never hold every party's evaluations, ciphertexts or commitments at once.
+// A streaming dealer evaluates its polynomials at parties from..=to without an x-power
+// table, writes their output to a `Sink` in index order and feeds it to the challenge hash
+// as it goes. Verifiers take it back the same way, from a sink of their own or a
+// `PointReader` over wherever the dealer's output was written.
+
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::{
+    io::{Read, Write},
+    string::ToString,
+};
+
+#[cfg(feature = "std")]
+use curve25519_dalek::ristretto::CompressedRistretto;
+
+#[cfg(feature = "std")]
+use crate::error::ErrorKind::StreamError;
+use crate::error::{Error, ErrorKind::CountMismatch};
+
+pub trait Sink<T> {
+    // Takes the output of parties from, from + 1, ..., one element each, called in index
+    // order.
+    fn write(&mut self, from: usize, chunk: &[T]) -> Result<(), Error>;
+}
+
+// Collects everything, for tests and committees small enough to keep in memory.
+impl<T: Clone> Sink<T> for Vec<T> {
+    fn write(&mut self, from: usize, chunk: &[T]) -> Result<(), Error> {
+        if from != self.len() + 1 {
+            return Err(CountMismatch(self.len() + 1, "next party", from, "chunk start").into());
+        }
+        self.extend_from_slice(chunk);
+        Ok(())
+    }
+}
+
+// Parties 1..=n as (from, to) ranges of at most `size` parties, inclusive like
+// `Polynomial::evaluate_range`.
+pub fn chunks(n: usize, size: usize) -> impl Iterator<Item = (usize, usize)> {
+    let size = size.max(1);
+    (1..=n)
+        .step_by(size)
+        .map(move |from| (from, (from + size - 1).min(n)))
+}
+
+// Writes every party's k compressed points, 32 bytes each, back to back.
+#[cfg(feature = "std")]
+pub struct PointWriter<W: Write>(pub W);
+
+#[cfg(feature = "std")]
+impl<W: Write> Sink<Vec<CompressedRistretto>> for PointWriter<W> {
+    fn write(&mut self, _from: usize, chunk: &[Vec<CompressedRistretto>]) -> Result<(), Error> {
+        chunk
+            .iter()
+            .flatten()
+            .try_for_each(|point| self.0.write_all(point.as_bytes()))
+            .map_err(|e| StreamError(e.to_string()).into())
+    }
+}
+
+// Reads back what a `PointWriter` wrote, k points per party.
+#[cfg(feature = "std")]
+pub struct PointReader<R: Read> {
+    pub reader: R,
+    pub k: usize,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> PointReader<R> {
+    pub fn new(reader: R, k: usize) -> Self {
+        Self { reader, k }
+    }
+
+    // The points of the next `count` parties.
+    pub fn read(&mut self, count: usize) -> Result<Vec<Vec<CompressedRistretto>>, Error> {
+        let mut bytes = [0u8; 32];
+        (0..count)
+            .map(|_| {
+                (0..self.k)
+                    .map(|_| {
+                        self.reader
+                            .read_exact(&mut bytes)
+                            .map(|_| CompressedRistretto(bytes))
+                            .map_err(|e| StreamError(e.to_string()).into())
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // Feeds the points of parties 1..=n to `sink` in chunks of at most `size` parties.
+    pub fn replay<S>(&mut self, n: usize, size: usize, sink: &mut S) -> Result<(), Error>
+    where
+        S: Sink<Vec<CompressedRistretto>>,
+    {
+        chunks(n, size).try_for_each(|(from, to)| sink.write(from, &self.read(to + 1 - from)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{vec, vec::Vec};
+
+    use curve25519_dalek::ristretto::CompressedRistretto;
+
+    use crate::{
+        random::random_points,
+        stream::{PointReader, PointWriter, Sink, chunks},
+    };
+
+    #[test]
+    fn chunked_round_trip() {
+        assert_eq!(
+            chunks(10, 4).collect::<Vec<_>>(),
+            vec![(1, 4), (5, 8), (9, 10)]
+        );
+        assert_eq!(chunks(3, 8).collect::<Vec<_>>(), vec![(1, 3)]);
+
+        let mut rng = rand::rng();
+        let points: Vec<Vec<CompressedRistretto>> = (0..10)
+            .map(|_| {
+                random_points(&mut rng, 3)
+                    .iter()
+                    .map(|p| p.compress())
+                    .collect()
+            })
+            .collect();
+        let mut writer = PointWriter(Vec::new());
+        for (from, to) in chunks(10, 4) {
+            writer.write(from, &points[from - 1..to]).unwrap();
+        }
+        assert_eq!(writer.0.len(), 10 * 3 * 32);
+
+        let mut collected = Vec::new();
+        PointReader::new(writer.0.as_slice(), 3)
+            .replay(10, 3, &mut collected)
+            .unwrap();
+        assert_eq!(collected, points);
+
+        // out of order
+        assert!(collected.write(3, &points[..1]).is_err());
+        // past the end
+        assert!(
+            PointReader::new(&writer.0[..], 3)
+                .replay(11, 4, &mut Vec::new())
+                .is_err()
+        );
+    }
+}
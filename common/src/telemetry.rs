@@ -0,0 +1,120 @@
+// Per-phase tracing spans and metrics.
+// With neither the `tracing` nor the `metrics` feature `Phase` is an empty struct
+// and both macros expand to nothing that survives optimisation.
+//
+// Spans are named "phase" and carry the phase name plus whatever n, t, k fields
+// the call site passes. Histograms are `pi_vss_phase_seconds{phase}`, failures are
+// counted in `pi_vss_verification_failures_total{scheme, reason}`.
+
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+pub use tracing;
+
+#[cfg(feature = "tracing")]
+pub type SpanGuard = tracing::span::EnteredSpan;
+#[cfg(not(feature = "tracing"))]
+pub type SpanGuard = ();
+
+// Times a phase until dropped: `let _phase = phase!("prove", n = n, t = t, k = k);`
+#[macro_export]
+macro_rules! phase {
+    ($name:literal $(, $($fields:tt)*)?) => {
+        $crate::telemetry::Phase::enter($name, $crate::__phase_span!($name $(, $($fields)*)?))
+    };
+}
+
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __phase_span {
+    ($name:literal $(, $($fields:tt)*)?) => {
+        $crate::telemetry::tracing::debug_span!("phase", phase = $name $(, $($fields)*)?).entered()
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __phase_span {
+    ($name:literal $(, $($fields:tt)*)?) => {
+        ()
+    };
+}
+
+// Counts a rejected share or proof, labelled with the calling crate.
+#[macro_export]
+macro_rules! verification_failure {
+    ($reason:literal) => {
+        $crate::telemetry::verification_failure(module_path!(), $reason)
+    };
+}
+
+// Passes a check's outcome through, counting it if it failed.
+#[macro_export]
+macro_rules! verified {
+    ($check:expr, $reason:literal) => {{
+        let ok: bool = $check;
+        if !ok {
+            $crate::verification_failure!($reason);
+        }
+        ok
+    }};
+}
+
+pub struct Phase {
+    _span: SpanGuard,
+    #[cfg(feature = "metrics")]
+    name: &'static str,
+    #[cfg(feature = "metrics")]
+    start: std::time::Instant,
+}
+
+impl Phase {
+    #[inline(always)]
+    pub fn enter(name: &'static str, span: SpanGuard) -> Self {
+        #[cfg(not(feature = "metrics"))]
+        let _ = name;
+
+        Self {
+            _span: span,
+            #[cfg(feature = "metrics")]
+            name,
+            #[cfg(feature = "metrics")]
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl Drop for Phase {
+    fn drop(&mut self) {
+        metrics::histogram!("pi_vss_phase_seconds", "phase" => self.name)
+            .record(self.start.elapsed().as_secs_f64());
+    }
+}
+
+#[inline(always)]
+pub fn verification_failure(module: &'static str, reason: &'static str) {
+    #[cfg(any(feature = "tracing", feature = "metrics"))]
+    let scheme = module.split("::").next().unwrap_or(module);
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(scheme, reason, "verification failure");
+
+    #[cfg(feature = "metrics")]
+    metrics::counter!("pi_vss_verification_failures_total", "scheme" => scheme, "reason" => reason)
+        .increment(1);
+
+    #[cfg(not(any(feature = "tracing", feature = "metrics")))]
+    let _ = (module, reason);
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn verified_passes_the_check_through() {
+        let _phase = phase!("verify", n = 4, t = 1, k = 1);
+        assert!(verified!(1 + 1 == 2, "share"));
+        assert!(!verified!(1 + 1 == 3, "share"));
+    }
+}
@@ -1,13 +1,19 @@
-use std::ops::Mul;
+use alloc::{format, vec::Vec};
 
 use blake3::Hasher;
 use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
-use rayon::prelude::*;
 use zeroize::Zeroize;
 
-use crate::error::{
-    Error,
-    ErrorKind::{CountMismatch, PointDecompressionError},
+use crate::{
+    error::{
+        Error,
+        ErrorKind::{CountMismatch, ForeignPublicKey, PointDecompressionError},
+    },
+    execution::granularity,
+    opcount,
+    parallel::prelude::*,
+    phase,
+    pop::{check_public_keys, verify_public_keys},
 };
 
 pub fn pointwise_op_in_place(
@@ -25,16 +31,13 @@ pub fn compute_d_from_hash_commitments(
     buf: &mut [u8; 64],
     commitments: &[[u8; 64]],
 ) -> Scalar {
+    let _phase = phase!("hash", n = commitments.len());
+    opcount::hashes(1, commitments.len() * 64);
     commitments.iter().for_each(|c| {
         hasher.update(c);
     });
 
-    hasher.finalize_xof().fill(buf);
-    hasher.reset();
-
-    let d = Scalar::from_bytes_mod_order_wide(buf);
-    buf.zeroize();
-    d
+    finalize_d(hasher, buf)
 }
 
 pub fn compute_d_from_point_commitments(
@@ -42,10 +45,17 @@ pub fn compute_d_from_point_commitments(
     buf: &mut [u8; 64],
     commitments: &[CompressedRistretto],
 ) -> Scalar {
+    let _phase = phase!("hash", n = commitments.len());
+    opcount::hashes(1, commitments.len() * 32);
     commitments.iter().for_each(|c| {
         hasher.update(c.as_bytes());
     });
 
+    finalize_d(hasher, buf)
+}
+
+// d from a hasher that was fed the commitments already, e.g. a chunk at a time.
+pub fn finalize_d(hasher: &mut Hasher, buf: &mut [u8; 64]) -> Scalar {
     hasher.finalize_xof().fill(buf);
     hasher.reset();
 
@@ -77,6 +87,7 @@ pub fn compute_d_powers_from_point_commitments(
 }
 
 pub fn compute_d_powers(k: usize, d: &Scalar) -> Vec<Scalar> {
+    opcount::scalar_muls(k.saturating_sub(1));
     let mut d_vals: Vec<Scalar> = Vec::with_capacity(k);
     // [d^1,
     d_vals.push(*d);
@@ -90,8 +101,9 @@ pub fn compute_d_powers(k: usize, d: &Scalar) -> Vec<Scalar> {
 }
 
 pub fn precompute_lambda(n: usize, t: usize) -> Vec<Scalar> {
-    (1..=n)
+    (1..n + 1)
         .into_par_iter()
+        .with_min_len(granularity())
         .map(|i| {
             let zq_i = Scalar::from(i as u64);
             let mut lambda_i = Scalar::ONE;
@@ -114,6 +126,51 @@ pub fn compute_lagrange_bases(qualified_set: &Vec<usize>) -> Vec<Scalar> {
         .collect()
 }
 
+// w_i = 1 / prod over j != i of (i - j) for every i in `qualified_set`, with one batch
+// inversion. Computed once, they give the coefficients at any x in O(t), see
+// `compute_lagrange_bases_at`.
+pub fn compute_barycentric_weights(qualified_set: &[usize]) -> Vec<Scalar> {
+    let mut weights: Vec<Scalar> = qualified_set
+        .par_iter()
+        .map(|i| {
+            let zq_i = Scalar::from(*i as u64);
+            qualified_set
+                .iter()
+                .filter(|j| *j != i)
+                .map(|j| zq_i - Scalar::from(*j as u64))
+                .product()
+        })
+        .collect();
+    Scalar::batch_invert(&mut weights);
+    weights
+}
+
+// The coefficients that interpolate the polynomial through `qualified_set` at x, from the
+// set's `weights`: lambda_i(x) = w_i / (x - i) times the product of every (x - j).
+pub fn compute_lagrange_bases_at(
+    x: usize,
+    qualified_set: &[usize],
+    weights: &[Scalar],
+) -> Vec<Scalar> {
+    if let Some(position) = qualified_set.iter().position(|i| *i == x) {
+        return (0..qualified_set.len())
+            .map(|i| Scalar::from((i == position) as u64))
+            .collect();
+    }
+    let x = Scalar::from(x as u64);
+    let mut differences: Vec<Scalar> = qualified_set
+        .iter()
+        .map(|i| x - Scalar::from(*i as u64))
+        .collect();
+    let product: Scalar = differences.iter().product();
+    Scalar::batch_invert(&mut differences);
+    differences
+        .iter()
+        .zip(weights)
+        .map(|(difference, weight)| product * weight * difference)
+        .collect()
+}
+
 pub fn compute_lagrange_basis(i: usize, qualified_set: &Vec<usize>) -> Scalar {
     let zq_i = Scalar::from(i as u64);
 
@@ -127,12 +184,13 @@ pub fn compute_lagrange_basis(i: usize, qualified_set: &Vec<usize>) -> Scalar {
                 zq_j * ((zq_j - zq_i).invert())
             }
         })
-        .reduce(|| Scalar::ONE, Scalar::mul)
+        .product()
 }
 
 pub fn decompress_ristretto_point(
     compressed_point: CompressedRistretto,
 ) -> Result<RistrettoPoint, Error> {
+    opcount::decompressions(1);
     match compressed_point.decompress() {
         Some(decompressed_point) => Ok(decompressed_point),
         None => Err(Error::from_kind(PointDecompressionError(format!(
@@ -146,12 +204,13 @@ pub fn batch_decompress_ristretto_points(
 ) -> Result<Vec<RistrettoPoint>, Error> {
     compressed_points
         .par_iter()
+        .with_min_len(granularity())
         .map(|compressed_point| decompress_ristretto_point(*compressed_point))
         .collect()
 }
 pub fn batch_decompress_batched_ristretto_points(
     // vec[vec[_;k]; n]
-    batch_compressed_points: &Vec<Vec<CompressedRistretto>>,
+    batch_compressed_points: &[Vec<CompressedRistretto>],
 ) -> Result<Vec<Vec<RistrettoPoint>>, Error> {
     batch_compressed_points
         .par_iter()
@@ -166,6 +225,10 @@ pub fn ingest_public_keys(
     public_keys: &[CompressedRistretto],
 ) -> Result<Vec<RistrettoPoint>, Error> {
     if public_keys.len() == n - 1 {
+        let mut all_keys = public_keys.to_vec();
+        all_keys.insert(own_index - 1, own_public_key.compress());
+        check_public_keys(&all_keys)?;
+
         match batch_decompress_ristretto_points(public_keys) {
             Ok(mut pks) => {
                 pks.insert(own_index - 1, *own_public_key);
@@ -177,3 +240,22 @@ pub fn ingest_public_keys(
         Err(CountMismatch(n, "parties", public_keys.len(), "public_keys").into())
     }
 }
+
+// Same as `ingest_public_keys` but takes the whole committee's (public keys, proofs),
+// own key included, and only accepts them with a valid proof of possession each.
+pub fn ingest_proven_public_keys(
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    g: &RistrettoPoint,
+    committee: &[u8],
+    own_public_key: &CompressedRistretto,
+    own_index: usize,
+    registrations: (&[CompressedRistretto], &[(Scalar, Scalar)]),
+) -> Result<Vec<RistrettoPoint>, Error> {
+    let (public_keys, proofs) = registrations;
+    if public_keys.get(own_index.wrapping_sub(1)) != Some(own_public_key) {
+        Err(ForeignPublicKey(own_index).into())
+    } else {
+        verify_public_keys(hasher, buf, g, committee, public_keys, proofs)
+    }
+}
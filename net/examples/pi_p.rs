@@ -0,0 +1,138 @@
+// pi_p end to end with the dealer and every party in its own task.
+// Runs over in-memory channels, or over loopback TCP with `--tcp`:
+//   cargo run --release -p net --example pi_p -- --tcp
+
+use curve25519_dalek::{Scalar, ristretto::CompressedRistretto};
+use rand::{SeedableRng, rngs::StdRng};
+
+use common::{
+    committee::Committee,
+    keystore::MemoryKey,
+    pop::prove_possession,
+    random::{random_point, random_scalar, random_scalars},
+    session::{DEALER, Phase, Session},
+};
+use net::{Transport, drive, memory::network, tcp::loopback};
+use pi_p::{
+    dealer::Dealer,
+    party::Party,
+    session::{DealerSession, Message, PartySession},
+};
+
+const N: usize = 16;
+const T: usize = 7;
+
+#[tokio::main]
+async fn main() {
+    let mut rng = StdRng::from_rng(&mut rand::rng());
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+    let label = b"pi_p example";
+    let session_id = [2u8; 32];
+
+    let g = random_point(&mut rng);
+    let (g1, g2, g3) = (
+        random_point(&mut rng),
+        random_point(&mut rng),
+        random_point(&mut rng),
+    );
+    let private_keys = random_scalars(&mut rng, N);
+    let public_keys: Vec<CompressedRistretto> =
+        private_keys.iter().map(|sk| (g * sk).compress()).collect();
+    let proofs: Vec<(Scalar, Scalar)> = private_keys
+        .iter()
+        .enumerate()
+        .map(|(i, sk)| prove_possession(&mut rng, &mut hasher, &mut buf, &g, sk, i + 1, label))
+        .collect();
+    // sessions borrow the x_pows table for as long as the tasks run
+    let committee: &'static Committee = Box::leak(Box::new(
+        Committee::new(&mut hasher, &mut buf, &g, T, label, (&public_keys, &proofs)).unwrap(),
+    ));
+
+    let dealer_key = MemoryKey::generate(&mut rng, &g).shared();
+    let dealer_public_key = dealer_key.public_key().0;
+    let dealer = DealerSession::new(
+        Dealer::from_committee(g1, g2, g3, committee),
+        dealer_key,
+        g,
+        session_id,
+        &committee.x_pows,
+    );
+    let parties: Vec<PartySession<'static>> = private_keys
+        .iter()
+        .enumerate()
+        .map(|(i, sk)| {
+            let key = MemoryKey::import(&g, sk).unwrap().shared();
+            let party = Party::from_committee(committee, g1, g2, g3, key, i + 1).unwrap();
+            PartySession::new(party, g, dealer_public_key, session_id, &committee.x_pows).unwrap()
+        })
+        .collect();
+    let secret = random_scalar(&mut rng);
+
+    let reconstructed = if std::env::args().any(|arg| arg == "--tcp") {
+        println!("pi_p over loopback TCP, n = {N}, t = {T}");
+        run(loopback(N).await.unwrap(), dealer, parties, secret).await
+    } else {
+        println!("pi_p over in-memory channels, n = {N}, t = {T}");
+        run(network(N), dealer, parties, secret).await
+    };
+
+    for (i, outcome) in reconstructed.iter().enumerate() {
+        assert_eq!(outcome, &Some(secret), "party {} failed", i + 1);
+    }
+    println!("all {N} parties reconstructed the secret");
+}
+
+async fn run<T>(
+    mut endpoints: Vec<T>,
+    mut dealer: DealerSession<'static>,
+    parties: Vec<PartySession<'static>>,
+    secret: Scalar,
+) -> Vec<Option<Scalar>>
+where
+    T: Transport<Message> + 'static,
+{
+    let tasks: Vec<_> = endpoints
+        .drain(DEALER + 1..)
+        .zip(parties)
+        .map(|(mut transport, mut session)| {
+            tokio::spawn(async move {
+                let mut rng = StdRng::from_rng(&mut rand::rng());
+                let rejected = drive(&mut session, &mut transport, &mut rng).await.unwrap();
+                for (from, e) in rejected {
+                    println!(
+                        "party {} rejected a message from {from}: {e:?}",
+                        session.party.index
+                    );
+                }
+                if let Phase::Aborted(reason) = session.phase() {
+                    println!("party {} aborted: {reason}", session.party.index);
+                }
+                // stays connected until everyone is done
+                (session.outcome().copied(), transport)
+            })
+        })
+        .collect();
+
+    let mut transport = endpoints.pop().unwrap();
+    tokio::spawn(async move {
+        let mut rng = StdRng::from_rng(&mut rand::rng());
+        let mut hasher = blake3::Hasher::new();
+        let mut buf = [0u8; 64];
+        let dealing = dealer
+            .deal(&mut rng, &mut hasher, &mut buf, &secret)
+            .unwrap();
+        transport.dispatch(dealing).await.unwrap();
+    })
+    .await
+    .unwrap();
+
+    let mut outcomes = Vec::with_capacity(tasks.len());
+    let mut transports = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        let (outcome, transport) = task.await.unwrap();
+        outcomes.push(outcome);
+        transports.push(transport);
+    }
+    outcomes
+}
@@ -0,0 +1,101 @@
+// Moving protocol messages between the dealer and the parties.
+// Endpoints are numbered like the senders of signed messages, `DEALER` (0) for the dealer
+// and 1..=n for the parties. A transport only moves messages: authenticity and session
+// binding come from `Signed`, so a transport is free to reorder or drop them.
+
+use core::future::Future;
+
+use blake3::Hasher;
+use rand::{CryptoRng, RngCore};
+
+use common::{
+    error::Error,
+    session::{Outgoing, Session},
+};
+
+pub mod memory;
+pub mod tcp;
+
+pub trait Transport<M: Send>: Send {
+    // our own endpoint
+    fn index(&self) -> usize;
+
+    fn send(&mut self, to: usize, message: M) -> impl Future<Output = Result<(), Error>> + Send;
+
+    // to every party but ourselves, the dealer doesn't take part after dealing
+    fn broadcast(&mut self, message: M) -> impl Future<Output = Result<(), Error>> + Send;
+
+    // the next message and the endpoint it came from
+    fn recv(&mut self) -> impl Future<Output = Result<(usize, M), Error>> + Send;
+
+    // Sends out everything a session returned.
+    fn dispatch(
+        &mut self,
+        outgoing: Vec<Outgoing<M>>,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        async move {
+            for out in outgoing {
+                match out {
+                    Outgoing::Broadcast(message) => self.broadcast(message).await?,
+                    Outgoing::To(to, message) => self.send(to, message).await?,
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+// Feeds `session` everything that arrives on `transport` until it is done or aborted.
+// Rejected messages don't stop the session and are handed back for blaming their senders,
+// an `Err` means the transport itself failed.
+pub async fn drive<S, T, R>(
+    session: &mut S,
+    transport: &mut T,
+    rng: &mut R,
+) -> Result<Vec<(usize, Error)>, Error>
+where
+    S: Session,
+    S::Message: Send,
+    T: Transport<S::Message>,
+    R: CryptoRng + RngCore,
+{
+    let mut hasher = Hasher::new();
+    let mut buf = [0u8; 64];
+    let mut rejected = vec![];
+
+    while !session.phase().is_over() {
+        let (from, message) = transport.recv().await?;
+        match session.handle(rng, &mut hasher, &mut buf, message) {
+            Ok(outgoing) => transport.dispatch(outgoing).await?,
+            Err(e) => rejected.push((from, e)),
+        }
+    }
+    Ok(rejected)
+}
+
+#[cfg(test)]
+mod tests {
+    use common::session::DEALER;
+
+    use crate::{Transport, memory::network, tcp::loopback};
+
+    async fn exchange<T: Transport<u64>>(mut endpoints: Vec<T>) {
+        endpoints[DEALER].send(2, 7).await.unwrap();
+        endpoints[1].broadcast(11).await.unwrap();
+        assert!(endpoints[1].send(4, 0).await.is_err());
+
+        assert_eq!(endpoints[2].recv().await.unwrap(), (DEALER, 7));
+        assert_eq!(endpoints[2].recv().await.unwrap(), (1, 11));
+        assert_eq!(endpoints[3].recv().await.unwrap(), (1, 11));
+    }
+
+    #[tokio::test]
+    async fn memory_transport() {
+        exchange(network(3)).await;
+    }
+
+    #[tokio::test]
+    async fn tcp_transport() {
+        exchange(loopback(3).await.unwrap()).await;
+    }
+}
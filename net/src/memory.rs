@@ -0,0 +1,67 @@
+// In-process transport, one unbounded channel per endpoint.
+
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+use common::error::{Error, ErrorKind::TransportError};
+
+use crate::Transport;
+
+pub struct MemoryTransport<M> {
+    index: usize,
+    // by endpoint, without our own
+    peers: Vec<Option<UnboundedSender<(usize, M)>>>,
+    inbox: UnboundedReceiver<(usize, M)>,
+}
+
+// Endpoints for the dealer and `n` parties, indexed by endpoint.
+pub fn network<M>(n: usize) -> Vec<MemoryTransport<M>> {
+    let (senders, inboxes): (Vec<_>, Vec<_>) = (0..=n).map(|_| unbounded_channel()).unzip();
+
+    inboxes
+        .into_iter()
+        .enumerate()
+        .map(|(index, inbox)| MemoryTransport {
+            index,
+            peers: senders
+                .iter()
+                .enumerate()
+                .map(|(i, sender)| (i != index).then(|| sender.clone()))
+                .collect(),
+            inbox,
+        })
+        .collect()
+}
+
+impl<M> MemoryTransport<M> {
+    fn deliver(&self, to: usize, message: M) -> Result<(), Error> {
+        match self.peers.get(to) {
+            Some(Some(peer)) => peer
+                .send((self.index, message))
+                .map_err(|_| TransportError(format!("endpoint {to} hung up")).into()),
+            _ => Err(TransportError(format!("no endpoint {to}")).into()),
+        }
+    }
+}
+
+impl<M: Clone + Send> Transport<M> for MemoryTransport<M> {
+    fn index(&self) -> usize {
+        self.index
+    }
+
+    async fn send(&mut self, to: usize, message: M) -> Result<(), Error> {
+        self.deliver(to, message)
+    }
+
+    async fn broadcast(&mut self, message: M) -> Result<(), Error> {
+        (1..self.peers.len())
+            .filter(|i| *i != self.index)
+            .try_for_each(|i| self.deliver(i, message.clone()))
+    }
+
+    async fn recv(&mut self) -> Result<(usize, M), Error> {
+        self.inbox
+            .recv()
+            .await
+            .ok_or_else(|| TransportError("every endpoint hung up".into()).into())
+    }
+}
@@ -0,0 +1,181 @@
+// TCP transport, one connection per ordered pair of endpoints.
+// A connection opens with the sender's endpoint as a u64, then carries frames of a u32
+// length and the message as JSON, all little endian.
+
+use core::{marker::PhantomData, time::Duration};
+use std::net::SocketAddr;
+
+use serde::{Serialize, de::DeserializeOwned};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream, tcp::OwnedReadHalf},
+    sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
+    time::sleep,
+};
+
+use common::error::{Error, ErrorKind::TransportError};
+
+use crate::Transport;
+
+// a peer that isn't listening yet is retried this often
+const CONNECT_ATTEMPTS: usize = 50;
+const CONNECT_BACKOFF: Duration = Duration::from_millis(100);
+
+pub struct TcpTransport<M> {
+    index: usize,
+    // by endpoint, without our own
+    peers: Vec<Option<TcpStream>>,
+    inbox: UnboundedReceiver<(usize, Vec<u8>)>,
+    _message: PhantomData<fn() -> M>,
+}
+
+fn io_error(e: std::io::Error) -> Error {
+    TransportError(e.to_string()).into()
+}
+
+// Endpoints for the dealer and `n` parties on 127.0.0.1, indexed by endpoint.
+pub async fn loopback<M>(n: usize) -> Result<Vec<TcpTransport<M>>, Error> {
+    let mut listeners = Vec::with_capacity(n + 1);
+    for _ in 0..=n {
+        listeners.push(TcpListener::bind("127.0.0.1:0").await.map_err(io_error)?);
+    }
+    let addrs = listeners
+        .iter()
+        .map(|listener| listener.local_addr().map_err(io_error))
+        .collect::<Result<Vec<SocketAddr>, Error>>()?;
+
+    let mut endpoints = Vec::with_capacity(n + 1);
+    for (index, listener) in listeners.into_iter().enumerate() {
+        endpoints.push(TcpTransport::connect(index, listener, &addrs).await?);
+    }
+    Ok(endpoints)
+}
+
+impl<M> TcpTransport<M> {
+    // Accepts the other endpoints on `listener` and connects to each of `addrs`, which is
+    // indexed by endpoint and includes our own.
+    pub async fn connect(
+        index: usize,
+        listener: TcpListener,
+        addrs: &[SocketAddr],
+    ) -> Result<Self, Error> {
+        let (sender, inbox) = unbounded_channel();
+        tokio::spawn(accept(listener, addrs.len() - 1, sender));
+
+        let mut peers = Vec::with_capacity(addrs.len());
+        for (i, addr) in addrs.iter().enumerate() {
+            if i == index {
+                peers.push(None);
+                continue;
+            }
+            let mut stream = dial(addr).await?;
+            stream.set_nodelay(true).map_err(io_error)?;
+            stream
+                .write_all(&(index as u64).to_le_bytes())
+                .await
+                .map_err(io_error)?;
+            peers.push(Some(stream));
+        }
+
+        Ok(Self {
+            index,
+            peers,
+            inbox,
+            _message: PhantomData,
+        })
+    }
+}
+
+async fn dial(addr: &SocketAddr) -> Result<TcpStream, Error> {
+    let mut attempts = 0;
+    loop {
+        match TcpStream::connect(addr).await {
+            Ok(stream) => return Ok(stream),
+            Err(e) if attempts + 1 >= CONNECT_ATTEMPTS => return Err(io_error(e)),
+            Err(_) => {
+                attempts += 1;
+                sleep(CONNECT_BACKOFF).await;
+            }
+        }
+    }
+}
+
+async fn accept(listener: TcpListener, peers: usize, inbox: UnboundedSender<(usize, Vec<u8>)>) {
+    for _ in 0..peers {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let (reader, _) = stream.into_split();
+                tokio::spawn(read_frames(reader, inbox.clone()));
+            }
+            Err(_) => return,
+        }
+    }
+}
+
+// Forwards the frames of one peer until it hangs up or sends garbage.
+async fn read_frames(mut reader: OwnedReadHalf, inbox: UnboundedSender<(usize, Vec<u8>)>) {
+    let mut word = [0u8; 8];
+    if reader.read_exact(&mut word).await.is_err() {
+        return;
+    }
+    let from = u64::from_le_bytes(word) as usize;
+
+    loop {
+        let mut len = [0u8; 4];
+        if reader.read_exact(&mut len).await.is_err() {
+            return;
+        }
+        let mut frame = vec![0u8; u32::from_le_bytes(len) as usize];
+        if reader.read_exact(&mut frame).await.is_err() || inbox.send((from, frame)).is_err() {
+            return;
+        }
+    }
+}
+
+impl<M: Serialize + DeserializeOwned + Send> TcpTransport<M> {
+    async fn deliver(&mut self, to: usize, frame: &[u8]) -> Result<(), Error> {
+        match self.peers.get_mut(to) {
+            Some(Some(peer)) => {
+                peer.write_all(&(frame.len() as u32).to_le_bytes())
+                    .await
+                    .map_err(io_error)?;
+                peer.write_all(frame).await.map_err(io_error)
+            }
+            _ => Err(TransportError(format!("no endpoint {to}")).into()),
+        }
+    }
+}
+
+fn encode<M: Serialize>(message: &M) -> Result<Vec<u8>, Error> {
+    serde_json::to_vec(message).map_err(|e| TransportError(e.to_string()).into())
+}
+
+impl<M: Serialize + DeserializeOwned + Send> Transport<M> for TcpTransport<M> {
+    fn index(&self) -> usize {
+        self.index
+    }
+
+    async fn send(&mut self, to: usize, message: M) -> Result<(), Error> {
+        let frame = encode(&message)?;
+        self.deliver(to, &frame).await
+    }
+
+    async fn broadcast(&mut self, message: M) -> Result<(), Error> {
+        let frame = encode(&message)?;
+        for i in 1..self.peers.len() {
+            if i != self.index {
+                self.deliver(i, &frame).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn recv(&mut self) -> Result<(usize, M), Error> {
+        match self.inbox.recv().await {
+            Some((from, frame)) => serde_json::from_slice(&frame)
+                .map(|message| (from, message))
+                .map_err(|e| TransportError(format!("endpoint {from}: {e}")).into()),
+            None => Err(TransportError("every endpoint hung up".into()).into()),
+        }
+    }
+}
@@ -1,21 +1,36 @@
+use alloc::vec::Vec;
 use common::{
+    committee::Committee,
+    dealings::transcript_digest,
+    encryption::{self, Ciphertext, Plaintext},
     error::{Error, ErrorKind::CountMismatch},
+    execution::ExecutionContext,
+    keys::KeyCommitments,
+    opcount,
+    parallel::prelude::*,
+    phase,
     polynomial::Polynomial,
+    pop::{check_public_keys, verify_public_keys},
     secret_sharing::generate_shares,
+    session::DEALER,
     utils::{batch_decompress_ristretto_points, compute_d_from_point_commitments},
 };
+use core::slice;
 use rand::{CryptoRng, RngCore};
-use rayon::prelude::*;
 
 use blake3::Hasher;
 use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
 
+#[derive(Clone)]
 pub struct Dealer {
     pub t: usize,
     pub g1: RistrettoPoint,
     pub g2: RistrettoPoint,
     pub public_keys: Vec<RistrettoPoint>,
     pub(crate) secret: Option<Scalar>,
+    // Feldman commitments of the last dealing, if the dealer publishes them
+    pub key_commitments: Option<KeyCommitments>,
+    pub ctx: ExecutionContext,
 }
 
 impl Dealer {
@@ -29,18 +44,79 @@ impl Dealer {
         if public_keys.len() != n {
             return Err(CountMismatch(n, "parties", public_keys.len(), "public keys").into());
         }
+        check_public_keys(public_keys)?;
         match batch_decompress_ristretto_points(public_keys) {
             Ok(pks) => Ok(Self {
                 t,
                 public_keys: pks,
                 secret: None,
-                g1: g1.clone(),
-                g2: g2.clone(),
+                g1,
+                g2,
+                key_commitments: None,
+                ctx: ExecutionContext::default(),
             }),
             Err(x) => Err(x),
         }
     }
 
+    pub fn from_committee(g1: RistrettoPoint, g2: RistrettoPoint, committee: &Committee) -> Self {
+        Self {
+            t: committee.t,
+            public_keys: committee.points.clone(),
+            secret: None,
+            g1,
+            g2,
+            key_commitments: None,
+            ctx: ExecutionContext::default(),
+        }
+    }
+
+    pub fn with_context(mut self, ctx: ExecutionContext) -> Self {
+        self.ctx = ctx;
+        self
+    }
+
+    // Also commits to the dealt polynomials' coefficients under `g` on every dealing, see
+    // `common::keys`.
+    pub fn with_key_commitments(mut self, g: RistrettoPoint) -> Self {
+        self.key_commitments = Some(KeyCommitments::new(g));
+        self
+    }
+
+    // Checks every party's proof of possession, run it before dealing.
+    pub fn verify_possession(
+        &self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        committee: &[u8],
+        proofs: &[(Scalar, Scalar)],
+    ) -> Result<(), Error> {
+        opcount::compressions(self.public_keys.len());
+        let public_keys: Vec<CompressedRistretto> =
+            self.public_keys.iter().map(|pk| pk.compress()).collect();
+        verify_public_keys(hasher, buf, g, committee, &public_keys, proofs).map(|_| ())
+    }
+
+    // Encrypts `shares[i - 1]` to party i under a key bound to `dealing`, as published in
+    // `session_id`.
+    pub fn encrypt_shares<R, S>(
+        &self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        g: &RistrettoPoint,
+        session_id: &[u8; 32],
+        dealing: (&Vec<CompressedRistretto>, &Polynomial),
+        shares: &[S],
+    ) -> Result<Vec<Ciphertext>, Error>
+    where
+        R: CryptoRng + RngCore,
+        S: Plaintext,
+    {
+        let transcript = transcript_digest(hasher, &(DEALER, *session_id), &dealing);
+        encryption::encrypt_shares(rng, hasher, g, &self.public_keys, &transcript, shares)
+    }
+
     pub fn t(&self) -> usize {
         self.t
     }
@@ -56,14 +132,31 @@ impl Dealer {
     where
         R: CryptoRng + RngCore,
     {
-        let (f_polynomial, f_evals) =
-            generate_shares(rng, self.public_keys.len(), self.t, x_pows, secret);
+        let _phase = phase!("deal", n = self.public_keys.len(), t = self.t, k = 1);
+        let ctx = self.ctx.clone();
+        let mut key_commitments = self.key_commitments.take();
+        let dealing = ctx.install_with_rng(rng, |rng| {
+            let (f_polynomial, f_evals) =
+                generate_shares(rng, self.public_keys.len(), self.t, x_pows, secret);
+            if let Some(keys) = &mut key_commitments {
+                keys.commit(slice::from_ref(&f_polynomial));
+            }
 
-        let mut c_buf: Vec<CompressedRistretto> = Vec::with_capacity(self.public_keys.len());
+            let mut c_buf: Vec<CompressedRistretto> = Vec::with_capacity(self.public_keys.len());
 
-        let z = self.generate_proof(rng, hasher, buf, &mut c_buf, x_pows, f_polynomial, &f_evals);
+            let z = self.prove(
+                rng,
+                (hasher, buf),
+                &mut c_buf,
+                x_pows,
+                f_polynomial,
+                &f_evals,
+            );
 
-        (f_evals, (c_buf, z))
+            (f_evals, (c_buf, z))
+        });
+        self.key_commitments = key_commitments;
+        dealing
     }
 
     pub fn generate_proof<R>(
@@ -79,8 +172,28 @@ impl Dealer {
     where
         R: CryptoRng,
     {
+        self.ctx.install_with_rng(rng, |rng| {
+            self.prove(rng, (hasher, buf), c_buf, x_pows, f_polynomial, f_evals)
+        })
+    }
+
+    fn prove<R>(
+        &self,
+        rng: &mut R,
+        (hasher, buf): (&mut Hasher, &mut [u8; 64]),
+        c_buf: &mut Vec<CompressedRistretto>,
+        x_pows: &Vec<Vec<Scalar>>,
+        f_polynomial: Polynomial,
+        f_evals: &Vec<Scalar>,
+    ) -> Polynomial
+    where
+        R: CryptoRng,
+    {
+        let _phase = phase!("prove", n = self.public_keys.len(), t = self.t, k = 1);
         let mut r = Polynomial::sample(self.t, rng);
         let r_evals = r.evaluate_range_precomp(x_pows, 1, self.public_keys.len());
+        opcount::msms(self.public_keys.len(), 2);
+        opcount::compressions(self.public_keys.len());
 
         f_evals
             .par_iter()
@@ -88,7 +201,7 @@ impl Dealer {
             .map(|(fi, ri)| (self.g1 * fi + self.g2 * ri).compress())
             .collect_into_vec(c_buf);
 
-        let d = compute_d_from_point_commitments(hasher, buf, &c_buf);
+        let d = compute_d_from_point_commitments(hasher, buf, c_buf);
 
         // z == r +=  d * f
         r.compute_z(&[f_polynomial], &[d]);
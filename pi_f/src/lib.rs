@@ -1,17 +1,29 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod dealer;
 pub mod party;
+pub mod session;
 
 #[cfg(test)]
 
 mod tests {
+    use std::{env, process};
+
     use curve25519_dalek::{RistrettoPoint, ristretto::CompressedRistretto};
 
-    use crate::{dealer::Dealer, party::generate_parties};
+    use crate::{
+        dealer::Dealer,
+        party::{Party, generate_parties},
+    };
 
     use common::{
+        fixture::{Fixture, N, T},
         precompute::gen_powers,
         random::{random_point, random_scalar},
         secret_sharing::{reconstruct_secret, select_qualified_set},
+        snapshot::Vault,
         utils::{compute_lagrange_bases, ingest_public_keys},
     };
 
@@ -63,6 +75,19 @@ mod tests {
                 "share verification failure"
             );
 
+            // party 1 restarts from its checkpoint once its share verified
+            if p.index == 1 {
+                let vault = Vault::from_key_handle(
+                    &env::temp_dir().join(format!("pi_f-party-{}", process::id())),
+                    &*p.key,
+                    (&[0u8; 32], p.index),
+                )
+                .unwrap();
+                p.checkpoint(&vault).unwrap();
+                *p = Party::resume(&vault, p.key.clone()).unwrap().unwrap();
+                vault.clear().unwrap();
+            }
+
             p.ingest_shares(&shares).unwrap();
 
             assert!(
@@ -88,4 +113,48 @@ mod tests {
             assert!(secret == sec, "Invalid Reconstructed Secret");
         }
     }
+
+    #[test]
+    fn verification_keys() {
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            xpows,
+        } = Fixture::new(N, T);
+
+        let g1: RistrettoPoint = random_point(&mut rng);
+        let g2: RistrettoPoint = random_point(&mut rng);
+
+        let mut parties = generate_parties(&g, &g1, &g2, &mut rng, N, T);
+        let public_keys: Vec<CompressedRistretto> =
+            parties.iter().map(|party| party.public_key.0).collect();
+        let mut dealer = Dealer::new(g1, g2, N, T, &public_keys)
+            .unwrap()
+            .with_key_commitments(g);
+
+        let secret = random_scalar(&mut rng);
+        let (shares, (c_vals, z)) =
+            dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret);
+        let key_commitments = dealer.key_commitments.clone().unwrap();
+        // commitments to some other polynomial, published for the same dealing
+        dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret);
+        let unrelated = dealer.key_commitments.clone().unwrap();
+
+        for p in &mut parties {
+            p.ingest_dealer_proof((&c_vals, &z)).unwrap();
+            p.ingest_share(&shares[p.index - 1]);
+            assert!(p.verify_share(&mut hasher, &mut buf, &xpows).unwrap());
+
+            p.ingest_key_commitments(&g, &unrelated).unwrap();
+            assert!(!p.verify_key_commitments(&xpows).unwrap());
+
+            p.ingest_key_commitments(&g, &key_commitments).unwrap();
+            assert!(p.verify_key_commitments(&xpows).unwrap());
+            let keys = p.verification_keys(&xpows).unwrap();
+            assert_eq!(keys.group_key, g * secret);
+            assert_eq!(keys.key(p.index), Some(&(g * shares[p.index - 1])));
+        }
+    }
 }
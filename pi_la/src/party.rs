@@ -1,22 +1,41 @@
+use alloc::{format, vec, vec::Vec};
 use blake3::Hasher;
 use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
 use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
+#[cfg(feature = "std")]
+use common::snapshot::Vault;
 use common::{
+    committee::Committee,
+    complaint::{Complaints, Verdict},
+    dealings::transcript_digest,
+    encryption::{Ciphertext, decrypt},
     error::{
         Error,
-        ErrorKind::{CountMismatch, InvalidPararmeterSet, InvalidProof, UninitializedValue},
+        ErrorKind::{
+            CountMismatch, DuplicateMessage, ForeignPublicKey, InvalidPararmeterSet, InvalidProof,
+            SnapshotError, UninitializedValue,
+        },
     },
+    execution::ExecutionContext,
+    keys::{KeyCommitments, VerificationKeys, check_shares},
+    keystore::{MemoryKey, SharedKey},
+    opcount,
+    parallel::prelude::*,
+    phase,
     polynomial::Polynomial,
-    random::random_scalar,
+    pop::prove_key_possession,
+    report::{ShareCheck, ShareStatus, ShareTracker, VerificationReport},
+    session::DEALER,
     utils::compute_d_from_hash_commitments,
+    verification_failure, verified,
 };
-use rayon::prelude::*;
 
 #[derive(Clone)]
 pub struct Party {
-    pub private_key: Scalar,
+    pub key: SharedKey,
     pub public_key: (CompressedRistretto, RistrettoPoint),
     pub index: usize,
     pub n: usize,
@@ -24,10 +43,35 @@ pub struct Party {
     pub public_keys: Option<Vec<RistrettoPoint>>,
     pub dealer_proof: Option<(Vec<[u8; 64]>, Polynomial)>,
     pub validated_shares: Vec<usize>,
+    // the running valid set while shares are checked on arrival
+    pub tracker: Option<ShareTracker>,
     pub share: Option<Scalar>,
     pub d: Option<Scalar>,
     pub shares: Option<Vec<Scalar>>,
     pub qualified_set: Option<Vec<(usize, Scalar)>>,
+    // (generator, decompressed commitments) the dealer published, see `common::keys`
+    pub key_commitments: Option<(RistrettoPoint, Vec<Vec<RistrettoPoint>>)>,
+    pub ctx: ExecutionContext,
+}
+
+// Everything a party holds but its key and execution context, its private share included,
+// so snapshots only go into a `Vault`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PartySnapshot {
+    pub public_key: CompressedRistretto,
+    pub index: usize,
+    pub n: usize,
+    pub t: usize,
+    pub public_keys: Option<Vec<RistrettoPoint>>,
+    // (c_vals, z) as the dealer sent them, serde stops at 32 byte arrays
+    pub dealer_proof: Option<(Vec<Vec<u8>>, Polynomial)>,
+    pub validated_shares: Vec<usize>,
+    pub tracker: Option<ShareTracker>,
+    pub share: Option<Scalar>,
+    pub d: Option<Scalar>,
+    pub shares: Option<Vec<Scalar>>,
+    pub qualified_set: Option<Vec<(usize, Scalar)>>,
+    pub key_commitments: Option<(RistrettoPoint, Vec<Vec<RistrettoPoint>>)>,
 }
 
 impl Party {
@@ -41,13 +85,15 @@ impl Party {
     where
         R: CryptoRng + RngCore,
     {
-        let private_key = random_scalar(rng);
-        let public_key = g * &private_key;
+        Self::with_key(MemoryKey::generate(rng, g).shared(), n, t, index)
+    }
 
-        if index <= n && t < n && t as f32 == ((n - 1) as f32 / 2.0).floor() {
+    // A party around an existing key.
+    pub fn with_key(key: SharedKey, n: usize, t: usize, index: usize) -> Result<Self, Error> {
+        if index <= n && t < n && t == (n - 1) / 2 {
             Ok(Self {
-                private_key,
-                public_key: (public_key.compress(), public_key),
+                public_key: *key.public_key(),
+                key,
                 index,
                 n,
                 t,
@@ -55,16 +101,131 @@ impl Party {
                 share: None,
                 public_keys: None,
                 validated_shares: vec![],
+                tracker: None,
                 d: None,
                 shares: None,
                 qualified_set: None,
+                key_commitments: None,
+                ctx: ExecutionContext::default(),
             })
         } else {
             Err(InvalidPararmeterSet(n, t as isize, index).into())
         }
     }
 
+    pub fn from_committee(
+        committee: &Committee,
+        key: SharedKey,
+        index: usize,
+    ) -> Result<Self, Error> {
+        committee.check_key(&key.public_key().1, index)?;
+
+        Ok(Self {
+            public_key: (
+                committee.public_keys[index - 1],
+                committee.points[index - 1],
+            ),
+            key,
+            index,
+            n: committee.n,
+            t: committee.t,
+            dealer_proof: None,
+            share: None,
+            public_keys: Some(committee.points.clone()),
+            validated_shares: vec![],
+            tracker: None,
+            d: None,
+            shares: None,
+            qualified_set: None,
+            key_commitments: None,
+            ctx: ExecutionContext::default(),
+        })
+    }
+
+    pub fn with_context(mut self, ctx: ExecutionContext) -> Self {
+        self.ctx = ctx;
+        self
+    }
+
+    pub fn snapshot(&self) -> PartySnapshot {
+        PartySnapshot {
+            public_key: self.public_key.0,
+            index: self.index,
+            n: self.n,
+            t: self.t,
+            public_keys: self.public_keys.clone(),
+            dealer_proof: self
+                .dealer_proof
+                .as_ref()
+                .map(|(c_vals, z)| (c_vals.iter().map(|c| c.to_vec()).collect(), z.clone())),
+            validated_shares: self.validated_shares.clone(),
+            tracker: self.tracker.clone(),
+            share: self.share,
+            d: self.d,
+            shares: self.shares.clone(),
+            qualified_set: self.qualified_set.clone(),
+            key_commitments: self.key_commitments.clone(),
+        }
+    }
+
+    // Picks a party back up from its snapshot, `key` must be the key it was taken with.
+    pub fn restore(snapshot: PartySnapshot, key: SharedKey) -> Result<Self, Error> {
+        if key.public_key().0 != snapshot.public_key {
+            return Err(ForeignPublicKey(snapshot.index).into());
+        }
+        let mut party = Self::with_key(key, snapshot.n, snapshot.t, snapshot.index)?;
+        party.public_keys = snapshot.public_keys;
+        if let Some((c_vals, z)) = snapshot.dealer_proof {
+            let c_vals = c_vals
+                .into_iter()
+                .map(|c| c.try_into())
+                .collect::<Result<Vec<[u8; 64]>, _>>()
+                .map_err(|_| Error::from(SnapshotError("malformed c_vals".into())))?;
+            party.ingest_dealer_proof((&c_vals, &z))?;
+        }
+        party.validated_shares = snapshot.validated_shares;
+        party.tracker = snapshot.tracker;
+        party.share = snapshot.share;
+        party.d = snapshot.d;
+        party.shares = snapshot.shares;
+        party.qualified_set = snapshot.qualified_set;
+        party.key_commitments = snapshot.key_commitments;
+        Ok(party)
+    }
+
+    // Saves the party's state, call it after each phase and before sending what the phase
+    // produced.
+    #[cfg(feature = "std")]
+    pub fn checkpoint(&self, vault: &Vault) -> Result<(), Error> {
+        vault.save(&self.snapshot())
+    }
+
+    // The party as last checkpointed in `vault`, `None` if it never was.
+    #[cfg(feature = "std")]
+    pub fn resume(vault: &Vault, key: SharedKey) -> Result<Option<Self>, Error> {
+        match vault.load()? {
+            Some(snapshot) => Self::restore(snapshot, key).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // Proof of knowledge of our private key for registering `public_key` under `index`.
+    pub fn prove_possession<R>(
+        &self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        committee: &[u8],
+    ) -> Result<(Scalar, Scalar), Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        prove_key_possession(rng, hasher, buf, g, &*self.key, self.index, committee)
+    }
+
     pub fn ingest_share(&mut self, share: &Scalar) {
+        let _phase = phase!("ingest", n = self.n, t = self.t, k = 1);
         self.share = Some(share.clone());
     }
 
@@ -72,9 +233,12 @@ impl Party {
         &mut self,
         proof: (&Vec<[u8; 64]>, &Polynomial),
     ) -> Result<(), Error> {
+        let _phase = phase!("ingest", n = self.n, t = self.t, k = 1);
         if proof.1.len() != self.t + 1 {
+            verification_failure!("malformed_proof");
             Err(InvalidProof(format!("z len: {}, t: {}", proof.1.len(), self.t + 1)).into())
         } else if proof.0.len() != self.n {
+            verification_failure!("malformed_proof");
             Err(InvalidProof(format!("c_vals len: {}, n: {}", proof.0.len(), self.n)).into())
         } else {
             self.dealer_proof = Some((proof.0.clone(), proof.1.clone()));
@@ -82,16 +246,49 @@ impl Party {
         }
     }
 
+    // Transcript digest of the ingested public dealing, as the dealer published it in
+    // `session_id`. The private shares aren't part of it.
+    pub fn transcript_digest(
+        &self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+    ) -> Result<[u8; 32], Error> {
+        match &self.dealer_proof {
+            Some((compressed_cvals, z)) => Ok(transcript_digest(
+                hasher,
+                &(DEALER, *session_id),
+                &(compressed_cvals, z),
+            )),
+            None => Err(UninitializedValue("party.dealer_proof").into()),
+        }
+    }
+
+    // Decrypts our share of the ingested dealing published in `session_id` and ingests it,
+    // a share that doesn't authenticate under that dealing is rejected.
+    pub fn ingest_encrypted_share(
+        &mut self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+        ciphertext: &Ciphertext,
+    ) -> Result<(), Error> {
+        let transcript = self.transcript_digest(hasher, session_id)?;
+        let share: Scalar = decrypt(hasher, (self.index, &*self.key), &transcript, ciphertext)?;
+        self.ingest_share(&share);
+        Ok(())
+    }
+
     pub fn verify_share(
         &self,
         hasher: &mut Hasher,
         buf: &mut [u8; 64],
         x_pows: &Vec<Vec<Scalar>>,
     ) -> Result<bool, Error> {
-        match &self.dealer_proof {
+        let _phase = phase!("verify", n = self.n, t = self.t, k = 1);
+        self.ctx.install(|| match &self.dealer_proof {
             Some((c_vals, z)) => match &self.share {
                 Some(fi) => {
                     let d = compute_d_from_hash_commitments(hasher, buf, c_vals);
+                    opcount::hashes(1, 64);
 
                     hasher.update(fi.as_bytes());
                     hasher.update(
@@ -109,58 +306,168 @@ impl Party {
                     let check_bit = &c_vals[self.index - 1] == buf;
                     buf.zeroize();
 
-                    Ok(check_bit)
+                    Ok(verified!(check_bit, "share"))
                 }
                 None => Err(UninitializedValue("party.share").into()),
             },
             None => Err(UninitializedValue("party.dealer_proof").into()),
+        })
+    }
+
+    // Checks the fi the dealer revealed for party `index` against c_i.
+    pub fn verify_revealed_share(
+        &self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        x_pows: &Vec<Vec<Scalar>>,
+        index: usize,
+        fi: &Scalar,
+    ) -> Result<bool, Error> {
+        if index == 0 || index > self.n {
+            return Err(InvalidPararmeterSet(self.n, self.t as isize, index).into());
+        }
+        match &self.dealer_proof {
+            Some((c_vals, z)) => {
+                let d = compute_d_from_hash_commitments(hasher, buf, c_vals);
+                opcount::hashes(1, 64);
+
+                hasher.update(fi.as_bytes());
+                hasher.update(
+                    Polynomial::compute_r_eval(&z.evaluate_precomp(x_pows, index), &[*fi], &[d])
+                        .as_bytes(),
+                );
+
+                hasher.finalize_xof().fill(buf);
+                hasher.reset();
+
+                let check_bit = &c_vals[index - 1] == buf;
+                buf.zeroize();
+
+                Ok(verified!(check_bit, "revealed_share"))
+            }
+            None => Err(UninitializedValue("party.dealer_proof").into()),
         }
     }
 
+    // Settles the complaint round. If the dealing stands and we complained, the share
+    // the dealer revealed for us becomes our share.
+    pub fn resolve_complaints(
+        &mut self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        x_pows: &Vec<Vec<Scalar>>,
+        complaints: &Complaints<Scalar>,
+    ) -> Result<Verdict, Error> {
+        let verdict = complaints.resolve(|index, share| {
+            self.verify_revealed_share(hasher, buf, x_pows, index, share)
+        })?;
+        if verdict == Verdict::Accept
+            && let Some(share) = complaints.answers.get(&self.index)
+        {
+            self.share = Some(*share);
+        }
+        Ok(verdict)
+    }
+
     pub fn verify_shares(
         &mut self,
         hasher: &mut Hasher,
         buf: &mut [u8; 64],
         x_pows: &Vec<Vec<Scalar>>,
     ) -> Result<bool, Error> {
-        match &self.dealer_proof {
+        let report = self.share_report(hasher, buf, x_pows)?;
+        self.validated_shares = report.valid();
+        Ok(verified!(report.is_sufficient(), "insufficient_shares"))
+    }
+
+    // Every party's share with its status, see `VerificationReport`.
+    pub fn share_report(
+        &self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        x_pows: &Vec<Vec<Scalar>>,
+    ) -> Result<VerificationReport, Error> {
+        let _phase = phase!("verify", n = self.n, t = self.t, k = 1);
+        self.ctx.install(|| match &self.dealer_proof {
             Some((cvals, z)) => match &self.shares {
                 Some(shares) => {
                     let d = compute_d_from_hash_commitments(hasher, buf, cvals);
                     let z_evals = z.evaluate_range_precomp(x_pows, 1, self.n);
+                    opcount::hashes(shares.len(), 64 * shares.len());
 
-                    self.validated_shares = shares
+                    let checks: Vec<ShareCheck> = shares
                         .par_iter()
                         .zip(z_evals.par_iter())
                         .enumerate()
                         .map_init(
                             || (Hasher::new(), [0u8; 64]),
                             |(l_hasher, l_buf), (i, (fi, zi))| {
-                                l_hasher.update(fi.as_bytes());
-                                l_hasher.update(
-                                    (Polynomial::compute_r_eval(zi, &[*fi], &[d])).as_bytes(),
-                                );
-
-                                l_hasher.finalize_xof().fill(l_buf);
-                                l_hasher.reset();
-
-                                let check_bit = cvals[i] == *l_buf;
-                                l_buf.zeroize();
-                                if check_bit { Some(i) } else { None }
+                                check_share(l_hasher, l_buf, &cvals[i], &d, zi, i + 1, fi)
                             },
                         )
-                        .filter(Option::is_some)
-                        .map(|res| res.unwrap())
                         .collect();
-                    Ok(self.validated_shares.len() > self.t)
+                    Ok(VerificationReport::new(self.n, self.t, Some(d), checks))
                 }
                 None => Err(UninitializedValue("party.share").into()),
             },
             None => Err(UninitializedValue("party.dealer_proof").into()),
+        })
+    }
+
+    // Checks party `index`'s share as it arrives, ours included, and keeps it with the others
+    // in `shares` if it holds. Parties that haven't sent, or whose share failed, have zero
+    // there, and a second message from a party is an error. True once, when more than t of
+    // them hold, see `ShareTracker`.
+    pub fn verify_share_from(
+        &mut self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        x_pows: &Vec<Vec<Scalar>>,
+        index: usize,
+        share: Scalar,
+    ) -> Result<bool, Error> {
+        let _phase = phase!("verify", n = self.n, t = self.t, k = 1);
+        if index == 0 || index > self.n {
+            return Err(InvalidPararmeterSet(self.n, self.t as isize, index).into());
         }
+        if self
+            .tracker
+            .as_ref()
+            .is_some_and(|tracker| tracker.check(index).is_some())
+        {
+            return Err(DuplicateMessage(index).into());
+        }
+        let (check, d) = match &self.dealer_proof {
+            Some((cvals, z)) => {
+                let d = match self.tracker.as_ref().and_then(|tracker| tracker.challenge) {
+                    Some(d) => d,
+                    None => compute_d_from_hash_commitments(hasher, buf, cvals),
+                };
+                let zi = z.evaluate_precomp(x_pows, index);
+                opcount::hashes(1, 64);
+                (
+                    check_share(hasher, buf, &cvals[index - 1], &d, &zi, index, &share),
+                    d,
+                )
+            }
+            None => return Err(UninitializedValue("party.dealer_proof").into()),
+        };
+        let valid = check.status == ShareStatus::Valid;
+        let tracker = self
+            .tracker
+            .get_or_insert_with(|| ShareTracker::new(self.n, self.t, Some(d)));
+        let sufficient = tracker.record(check)?;
+        self.validated_shares = tracker.valid();
+        // only shares that hold are kept, the others stay placeholders
+        if valid {
+            self.shares
+                .get_or_insert_with(|| vec![Scalar::ZERO; self.n])[index - 1] = share;
+        }
+        Ok(sufficient)
     }
 
     pub fn ingest_shares(&mut self, shares: &Vec<Scalar>) -> Result<(), Error> {
+        let _phase = phase!("ingest", n = self.n, t = self.t, k = 1);
         if shares.len() == self.n {
             self.shares = Some(shares.clone());
             Ok(())
@@ -168,6 +475,66 @@ impl Party {
             Err(CountMismatch(self.n, "parties", shares.len(), "ingestable shares").into())
         }
     }
+    // The dealer's commitments to the coefficients of the dealt polynomials, which must be
+    // under `g`, the generator the verification keys are wanted for.
+    pub fn ingest_key_commitments(
+        &mut self,
+        g: &RistrettoPoint,
+        keys: &KeyCommitments,
+    ) -> Result<(), Error> {
+        let _phase = phase!("ingest", n = self.n, t = self.t, k = 1);
+        self.key_commitments = Some((*g, keys.open(g, 1, self.t)?));
+        Ok(())
+    }
+
+    // True if the key commitments give our validated share as our verification key, which
+    // ties them to the dealing.
+    pub fn verify_key_commitments(&self, x_pows: &[Vec<Scalar>]) -> Result<bool, Error> {
+        match (&self.key_commitments, &self.share) {
+            (Some((g, commitments)), Some(share)) => {
+                Ok(check_shares(g, commitments, x_pows, self.index, &[*share]))
+            }
+            (None, _) => Err(UninitializedValue("party.key_commitments").into()),
+            (_, None) => Err(UninitializedValue("party.share").into()),
+        }
+    }
+
+    // The group public key and every party's verification key.
+    pub fn verification_keys(&self, x_pows: &[Vec<Scalar>]) -> Result<VerificationKeys, Error> {
+        match &self.key_commitments {
+            Some((_, commitments)) => Ok(VerificationKeys::from_commitments(
+                &commitments[0],
+                x_pows,
+                self.n,
+            )),
+            None => Err(UninitializedValue("party.key_commitments").into()),
+        }
+    }
+}
+
+// c_i = H(fi, r_i) with r_i = z(i) - d fi
+fn check_share(
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    c: &[u8; 64],
+    d: &Scalar,
+    zi: &Scalar,
+    index: usize,
+    fi: &Scalar,
+) -> ShareCheck {
+    hasher.update(fi.as_bytes());
+    hasher.update(Polynomial::compute_r_eval(zi, &[*fi], &[*d]).as_bytes());
+
+    hasher.finalize_xof().fill(buf);
+    hasher.reset();
+
+    let check_bit = c == buf;
+    buf.zeroize();
+    ShareCheck::checked(
+        index,
+        verified!(check_bit, "share"),
+        ShareStatus::CommitmentMismatch,
+    )
 }
 
 pub fn generate_parties<R>(g: &RistrettoPoint, rng: &mut R, n: usize, t: usize) -> Vec<Party>
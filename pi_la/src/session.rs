@@ -0,0 +1,169 @@
+use alloc::vec::Vec;
+
+use blake3::Hasher;
+use curve25519_dalek::{RistrettoPoint, Scalar};
+use rand::{CryptoRng, RngCore};
+
+use common::{
+    encryption::Ciphertext,
+    error::{Error, ErrorKind::UninitializedValue},
+    keystore::SharedKey,
+    polynomial::Polynomial,
+    report::ShareTracker,
+    secret_sharing::{reconstruct_secret, select_qualified_set},
+    session::{self, SessionDealer, SessionParty},
+    utils::compute_lagrange_bases,
+};
+
+use crate::{dealer::Dealer, party::Party};
+
+pub type DealerSession<'a> = session::DealerSession<'a, Dealer>;
+pub type PartySession<'a> = session::PartySession<'a, Party>;
+// the dealing is (c_vals, z), the reveal f_i of its sender
+pub type Message = session::PartyMessage<Party>;
+
+// serde stops at 32 byte arrays, so the dealing carries each c_val as its halves, which
+// encode the same for signing and the transcript
+type Halves = Vec<([u8; 32], [u8; 32])>;
+
+impl SessionDealer for Dealer {
+    type Party = Party;
+    type Secret = Scalar;
+
+    fn deal<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        xpows: &Vec<Vec<Scalar>>,
+        session_id: &[u8; 32],
+        secret: &Scalar,
+    ) -> Result<((Halves, Polynomial), Vec<Ciphertext>), Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let (shares, (c_vals, z)) = self.deal_secret(rng, hasher, buf, xpows, secret);
+        let ciphertexts =
+            self.encrypt_shares(rng, hasher, g, session_id, (&c_vals, &z), &shares)?;
+        let halves = c_vals
+            .iter()
+            .map(|c| {
+                let (lo, hi) = c.split_at(32);
+                (lo.try_into().unwrap(), hi.try_into().unwrap())
+            })
+            .collect();
+        Ok(((halves, z), ciphertexts))
+    }
+}
+
+impl SessionParty for Party {
+    type Dealing = (Halves, Polynomial);
+    type Reveal = Scalar;
+    // the secret
+    type Outcome = Scalar;
+    type Evidence = core::convert::Infallible;
+
+    const PRIVATE_SHARES: bool = true;
+    // a share that doesn't decrypt under the delivered dealing is as bad as a wrong one
+    const INVALID_DEALING: &'static str = "share";
+
+    fn index(&self) -> usize {
+        self.index
+    }
+
+    fn n(&self) -> usize {
+        self.n
+    }
+
+    fn key(&self) -> &SharedKey {
+        &self.key
+    }
+
+    fn public_keys(&self) -> Option<&Vec<RistrettoPoint>> {
+        self.public_keys.as_ref()
+    }
+
+    fn tracker(&self) -> Option<&ShareTracker> {
+        self.tracker.as_ref()
+    }
+
+    fn ingest_dealing(&mut self, dealing: Self::Dealing) -> Result<(), Error> {
+        let (halves, z) = dealing;
+        let c_vals: Vec<[u8; 64]> = halves
+            .iter()
+            .map(|(lo, hi)| {
+                let mut c = [0u8; 64];
+                c[..32].copy_from_slice(lo);
+                c[32..].copy_from_slice(hi);
+                c
+            })
+            .collect();
+        self.ingest_dealer_proof((&c_vals, &z))
+    }
+
+    fn transcript_digest(
+        &self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+    ) -> Result<[u8; 32], Error> {
+        Party::transcript_digest(self, hasher, session_id)
+    }
+
+    fn verify_dealing<R>(
+        &mut self,
+        _rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        _g: &RistrettoPoint,
+        xpows: &Vec<Vec<Scalar>>,
+        session_id: &[u8; 32],
+        ciphertext: Option<&Ciphertext>,
+    ) -> Result<Option<Scalar>, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let ciphertext = ciphertext.ok_or(UninitializedValue("ciphertext"))?;
+        if self
+            .ingest_encrypted_share(hasher, session_id, ciphertext)
+            .is_err()
+            || !self.verify_share(hasher, buf, xpows)?
+        {
+            return Ok(None);
+        }
+        Ok(self.share)
+    }
+
+    fn check_reveal(
+        &mut self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        _g: &RistrettoPoint,
+        xpows: &Vec<Vec<Scalar>>,
+        sender: usize,
+        reveal: &Scalar,
+    ) -> Result<bool, Error> {
+        self.verify_share_from(hasher, buf, xpows, sender, *reveal)
+    }
+
+    fn reconstruct<R>(&mut self, rng: &mut R) -> Result<Scalar, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        self.qualified_set = Some(select_qualified_set(
+            rng,
+            self.t,
+            &self.shares,
+            &self.validated_shares,
+        )?);
+        let indices: Vec<usize> = self
+            .qualified_set
+            .iter()
+            .flatten()
+            .map(|(index, _)| *index)
+            .collect();
+        let lagrange_bases = compute_lagrange_bases(&indices);
+
+        reconstruct_secret(&self.qualified_set, &lagrange_bases)
+    }
+}
@@ -1,20 +1,41 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod dealer;
 pub mod party;
+pub mod reconstruction;
+pub mod session;
 
 #[cfg(test)]
 
 mod tests {
-    use curve25519_dalek::{RistrettoPoint, ristretto::CompressedRistretto};
+    use std::{env, process};
+
+    use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
 
-    use crate::{dealer::Dealer, party::generate_parties};
+    use crate::{
+        dealer::Dealer,
+        party::{Party, generate_parties},
+        reconstruction::verify_reconstruction,
+        session::{Message, PartySession},
+    };
 
     use common::{
+        error::ErrorKind::InvalidCiphertext,
+        fixture::{Fixture, N, T, distribute_public_keys, sessions},
         precompute::gen_powers,
         random::{random_point, random_scalar},
-        secret_sharing::{reconstruct_secret, select_qualified_set},
+        record::{Recorder, replay},
+        report::ShareStatus,
+        secret_sharing::{Selection, reconstruct, reconstruct_secret, select_qualified_set},
+        session::{DEALER, Outgoing, Phase, Session},
+        snapshot::Vault,
         utils::{compute_lagrange_bases, ingest_public_keys},
     };
 
+    common::committee!(PiP, Party);
+
     #[test]
     fn end_to_end() {
         const N: usize = 128;
@@ -64,6 +85,19 @@ mod tests {
                 "share verification failure"
             );
 
+            // party 1 restarts from its checkpoint once its share verified
+            if p.index == 1 {
+                let vault = Vault::from_key_handle(
+                    &env::temp_dir().join(format!("pi_p-party-{}", process::id())),
+                    &*p.key,
+                    (&[0u8; 32], p.index),
+                )
+                .unwrap();
+                p.checkpoint(&vault).unwrap();
+                *p = Party::resume(&vault, p.key.clone()).unwrap().unwrap();
+                vault.clear().unwrap();
+            }
+
             p.ingest_shares((&shares, &g)).unwrap();
 
             assert!(
@@ -98,5 +132,354 @@ mod tests {
 
             assert!(secret == sec, "Invalid Reconstructed Secret");
         }
+
+        // shares checked one at a time as they arrive, the last first and one of them bad
+        let mut p = parties[0].clone();
+        p.shares = None;
+        for i in (1..=N).rev() {
+            let mut share = (shares[i - 1], g[i - 1]);
+            if i == N - 2 {
+                share.1 += Scalar::ONE;
+            }
+            let sufficient = p
+                .verify_share_from(&mut hasher, &mut buf, &xpows, i, share)
+                .unwrap();
+            assert_eq!(sufficient, i == N - T - 1);
+        }
+        assert_eq!(
+            p.tracker.as_ref().unwrap().report().misbehaving(),
+            vec![(N - 2, ShareStatus::CommitmentMismatch)]
+        );
+        // a validated party resending garbage is turned away and its share kept
+        let validated = p.validated_shares.clone();
+        assert!(
+            p.verify_share_from(&mut hasher, &mut buf, &xpows, 1, (Scalar::ONE, Scalar::ONE))
+                .is_err()
+        );
+        assert_eq!(p.shares.as_ref().unwrap()[0], (shares[0], g[0]));
+        assert_eq!(p.validated_shares, validated);
+        let f_shares: Vec<Scalar> = p.shares.iter().flatten().map(|(fi, _)| *fi).collect();
+        assert_eq!(
+            reconstruct(&mut rng, T, &f_shares, &p.validated_shares, Selection::All).unwrap(),
+            secret
+        );
+
+        // a light client checks any party's result from the commitments alone
+        let (result, proof) = parties[0].prove_reconstruction().unwrap();
+        assert_eq!(result, secret);
+        let verify = |rng: &mut _, hasher: &mut _, buf: &mut _, result, proof| {
+            verify_reconstruction(
+                rng,
+                hasher,
+                buf,
+                (&g1, &g2, &g3),
+                T,
+                (&c_vals, &z),
+                (&result, &proof),
+            )
+            .unwrap()
+        };
+        assert!(verify(
+            &mut rng,
+            &mut hasher,
+            &mut buf,
+            result,
+            proof.clone()
+        ));
+        assert!(!verify(
+            &mut rng,
+            &mut hasher,
+            &mut buf,
+            result + Scalar::ONE,
+            proof.clone()
+        ));
+        // shifting f_i and gamma_i keeps the interpolation consistent but breaks c_i
+        let mut forged = proof.clone();
+        forged.openings[3].1.1 += Scalar::ONE;
+        assert!(!verify(&mut rng, &mut hasher, &mut buf, result, forged));
+        let mut short = proof;
+        short.openings.pop();
+        assert!(!verify(&mut rng, &mut hasher, &mut buf, result, short));
+    }
+
+    #[test]
+    fn session_driver() {
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            xpows,
+        } = Fixture::new(N, T);
+
+        let g1: RistrettoPoint = random_point(&mut rng);
+        let g2: RistrettoPoint = random_point(&mut rng);
+        let g3: RistrettoPoint = random_point(&mut rng);
+
+        let mut parties = generate_parties(&g, &g1, &g2, &g3, &mut rng, N, T);
+        let public_keys = distribute_public_keys::<PiP>(&mut parties);
+
+        let (mut dealer, mut sessions) = sessions(
+            &mut rng,
+            g,
+            &xpows,
+            Dealer::new(g1, g2, g3, N, T, &public_keys).unwrap(),
+            parties,
+        );
+
+        let secret = random_scalar(&mut rng);
+        let dealing = dealer
+            .deal(&mut rng, &mut hasher, &mut buf, &secret)
+            .unwrap();
+
+        // last in, first out: shares before proofs, reveals before either
+        let mut in_flight: Vec<(usize, Message)> = vec![];
+        let route = |in_flight: &mut Vec<(usize, Message)>,
+                     from: usize,
+                     outgoing: Vec<Outgoing<Message>>| {
+            for out in outgoing {
+                match out {
+                    Outgoing::Broadcast(m) => (1..=N)
+                        .filter(|i| *i != from)
+                        .for_each(|i| in_flight.push((i, m.clone()))),
+                    Outgoing::To(i, m) => in_flight.push((i, m)),
+                }
+            }
+        };
+        route(&mut in_flight, DEALER, dealing);
+
+        while let Some((to, message)) = in_flight.pop() {
+            let outgoing = sessions[to - 1]
+                .handle(&mut rng, &mut hasher, &mut buf, message)
+                .unwrap();
+            route(&mut in_flight, to, outgoing);
+        }
+
+        for session in &sessions {
+            assert_eq!(session.phase(), Phase::Done);
+            assert_eq!(session.outcome(), Some(&secret));
+        }
+    }
+
+    #[test]
+    fn stragglers() {
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            xpows,
+        } = Fixture::new(N, T);
+
+        let g1: RistrettoPoint = random_point(&mut rng);
+        let g2: RistrettoPoint = random_point(&mut rng);
+        let g3: RistrettoPoint = random_point(&mut rng);
+
+        let mut parties = generate_parties(&g, &g1, &g2, &g3, &mut rng, N, T);
+        let public_keys = distribute_public_keys::<PiP>(&mut parties);
+
+        let (mut dealer, mut sessions) = sessions(
+            &mut rng,
+            g,
+            &xpows,
+            Dealer::new(g1, g2, g3, N, T, &public_keys).unwrap(),
+            parties,
+        );
+
+        let secret = random_scalar(&mut rng);
+        let mut in_flight: Vec<(usize, Message)> = vec![];
+        for out in dealer
+            .deal(&mut rng, &mut hasher, &mut buf, &secret)
+            .unwrap()
+        {
+            match out {
+                Outgoing::Broadcast(m) => (1..=N).for_each(|i| in_flight.push((i, m.clone()))),
+                Outgoing::To(i, m) => in_flight.push((i, m)),
+            }
+        }
+        // the revealed shares of parties past t + 1 are held back
+        let mut held_back: Vec<(usize, Message)> = vec![];
+        while let Some((to, message)) = in_flight.pop() {
+            let outgoing = sessions[to - 1]
+                .handle(&mut rng, &mut hasher, &mut buf, message)
+                .unwrap();
+            for out in outgoing {
+                let Outgoing::Broadcast(m) = out else {
+                    unreachable!()
+                };
+                let queue = match &m {
+                    Message::Reveal(_) if to > T + 1 => &mut held_back,
+                    _ => &mut in_flight,
+                };
+                (1..=N)
+                    .filter(|i| *i != to)
+                    .for_each(|i| queue.push((i, m.clone())));
+            }
+        }
+
+        // everyone reconstructs from the first t + 1 revealed shares
+        for session in &sessions {
+            assert_eq!(session.phase(), Phase::Done);
+            assert_eq!(session.outcome(), Some(&secret));
+            let tracker = session.party.tracker.as_ref().unwrap();
+            assert!(tracker.is_sufficient() && !tracker.is_complete());
+        }
+
+        // the stragglers are checked all the same once they show up
+        for (to, message) in held_back {
+            sessions[to - 1]
+                .handle(&mut rng, &mut hasher, &mut buf, message)
+                .unwrap();
+        }
+        for session in &sessions {
+            let tracker = session.party.tracker.as_ref().unwrap();
+            assert!(tracker.is_complete());
+            assert_eq!(tracker.valid().len(), N);
+        }
+    }
+
+    #[test]
+    fn recorded_replay() {
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            xpows,
+        } = Fixture::new(N, T);
+
+        let g1: RistrettoPoint = random_point(&mut rng);
+        let g2: RistrettoPoint = random_point(&mut rng);
+        let g3: RistrettoPoint = random_point(&mut rng);
+
+        let mut parties = generate_parties(&g, &g1, &g2, &g3, &mut rng, N, T);
+        let public_keys = distribute_public_keys::<PiP>(&mut parties);
+        let recorded_party = parties[0].clone();
+
+        let (mut dealer, sessions) = sessions(
+            &mut rng,
+            g,
+            &xpows,
+            Dealer::new(g1, g2, g3, N, T, &public_keys).unwrap(),
+            parties,
+        );
+        let (dealer_public_key, session_id) =
+            (sessions[0].dealer_public_key, sessions[0].session_id);
+        let mut sessions: Vec<Recorder<PartySession>> =
+            sessions.into_iter().map(Recorder::new).collect();
+
+        let secret = random_scalar(&mut rng);
+        let mut in_flight: Vec<(usize, Message)> = vec![];
+        for out in dealer
+            .deal(&mut rng, &mut hasher, &mut buf, &secret)
+            .unwrap()
+        {
+            match out {
+                Outgoing::Broadcast(m) => (1..=N).for_each(|i| in_flight.push((i, m.clone()))),
+                Outgoing::To(i, m) => in_flight.push((i, m)),
+            }
+        }
+        while let Some((to, message)) = in_flight.pop() {
+            let outgoing = sessions[to - 1]
+                .handle(&mut rng, &mut hasher, &mut buf, message)
+                .unwrap();
+            for out in outgoing {
+                let Outgoing::Broadcast(m) = out else {
+                    unreachable!()
+                };
+                (1..=N)
+                    .filter(|i| *i != to)
+                    .for_each(|i| in_flight.push((i, m.clone())));
+            }
+        }
+        assert_eq!(sessions[0].outcome(), Some(&secret));
+
+        // the same party replays its run step for step, its encrypted share included
+        let (_, transcript) = sessions.swap_remove(0).into_parts();
+        let fresh = || {
+            PartySession::new(
+                recorded_party.clone(),
+                g,
+                dealer_public_key,
+                session_id,
+                &xpows,
+            )
+            .unwrap()
+        };
+        let mut replayed = fresh();
+        assert_eq!(replay(&mut replayed, &transcript).unwrap(), None);
+        assert_eq!(replayed.outcome(), Some(&secret));
+
+        // other randomness in a step that signs something shows up right there
+        let mut altered = transcript.clone();
+        let signing = altered
+            .steps
+            .iter()
+            .position(|step| !step.output.outgoing.is_empty())
+            .unwrap();
+        altered.steps[signing].seed[0] ^= 1;
+        let divergence = replay(&mut fresh(), &altered).unwrap().unwrap();
+        assert_eq!(divergence.step, signing);
+        assert_eq!(divergence.recorded, transcript.steps[signing].output);
+    }
+
+    #[test]
+    fn encrypted_shares() {
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            xpows,
+        } = Fixture::new(N, T);
+        let (session_id, other_session) = ([3u8; 32], [4u8; 32]);
+
+        let g1: RistrettoPoint = random_point(&mut rng);
+        let g2: RistrettoPoint = random_point(&mut rng);
+        let g3: RistrettoPoint = random_point(&mut rng);
+
+        let mut parties = generate_parties(&g, &g1, &g2, &g3, &mut rng, N, T);
+        let public_keys: Vec<CompressedRistretto> =
+            parties.iter().map(|party| party.public_key.0).collect();
+        let mut dealer = Dealer::new(g1, g2, g3, N, T, &public_keys).unwrap();
+
+        let secret = random_scalar(&mut rng);
+        let (shares, (gammas, c_vals, z)) =
+            dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret);
+        let shares: Vec<(Scalar, Scalar)> = shares.into_iter().zip(gammas).collect();
+        let ciphertexts = dealer
+            .encrypt_shares(
+                &mut rng,
+                &mut hasher,
+                &g,
+                &session_id,
+                (&c_vals, &z),
+                &shares,
+            )
+            .unwrap();
+
+        for p in &mut parties {
+            p.ingest_dealer_proof((&c_vals, &z)).unwrap();
+
+            // someone else's share, or ours replayed into another session, doesn't open
+            let other = &ciphertexts[p.index % N];
+            for (session, ciphertext) in [
+                (&session_id, other),
+                (&other_session, &ciphertexts[p.index - 1]),
+            ] {
+                assert!(matches!(
+                    p.ingest_encrypted_share(&mut hasher, session, ciphertext)
+                        .err()
+                        .unwrap()
+                        .kind(),
+                    InvalidCiphertext(i) if *i == p.index
+                ));
+            }
+
+            p.ingest_encrypted_share(&mut hasher, &session_id, &ciphertexts[p.index - 1])
+                .unwrap();
+            assert_eq!(p.share, Some(shares[p.index - 1]));
+            assert!(p.verify_share(&mut hasher, &mut buf, &xpows).unwrap());
+        }
     }
 }
@@ -1,27 +1,44 @@
+use alloc::{format, vec, vec::Vec};
 use blake3::Hasher;
 use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
 use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
 
+use crate::reconstruction::{ReconstructionProof, prove_reconstruction};
+#[cfg(feature = "std")]
+use common::snapshot::Vault;
 use common::{
+    committee::Committee,
+    complaint::{Complaints, Verdict},
+    dealings::transcript_digest,
+    encryption::{Ciphertext, decrypt},
     error::{
         Error,
         ErrorKind::{
-            CountMismatch, InvalidPararmeterSet, InvalidProof, PointDecompressionError,
-            UninitializedValue,
+            CountMismatch, DuplicateMessage, ForeignPublicKey, InvalidPararmeterSet, InvalidProof,
+            PointDecompressionError, UninitializedValue,
         },
     },
+    execution::ExecutionContext,
+    keys::{KeyCommitments, VerificationKeys, check_shares},
+    keystore::{MemoryKey, SharedKey},
+    opcount,
+    parallel::prelude::*,
+    phase,
     polynomial::Polynomial,
-    random::random_scalar,
+    pop::prove_key_possession,
+    report::{ShareCheck, ShareStatus, ShareTracker, VerificationReport},
+    session::DEALER,
     utils::compute_d_from_point_commitments,
+    verification_failure, verified,
 };
-use rayon::prelude::*;
 
 #[derive(Clone)]
 pub struct Party {
     pub g1: RistrettoPoint,
     pub g2: RistrettoPoint,
     pub g3: RistrettoPoint,
-    pub private_key: Scalar,
+    pub key: SharedKey,
     pub public_key: (CompressedRistretto, RistrettoPoint),
     pub index: usize,
     pub n: usize,
@@ -29,11 +46,39 @@ pub struct Party {
     pub public_keys: Option<Vec<RistrettoPoint>>,
     pub dealer_proof: Option<(Vec<CompressedRistretto>, Vec<RistrettoPoint>, Polynomial)>,
     pub validated_shares: Vec<usize>,
+    // the running valid set while shares are checked on arrival
+    pub tracker: Option<ShareTracker>,
     // (fi, gamma_i)
     pub share: Option<(Scalar, Scalar)>,
     pub d: Option<Scalar>,
     pub shares: Option<Vec<(Scalar, Scalar)>>,
     pub qualified_set: Option<Vec<(usize, Scalar)>>,
+    // (generator, decompressed commitments) the dealer published, see `common::keys`
+    pub key_commitments: Option<(RistrettoPoint, Vec<Vec<RistrettoPoint>>)>,
+    pub ctx: ExecutionContext,
+}
+
+// Everything a party holds but its key and execution context, its private share included,
+// so snapshots only go into a `Vault`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PartySnapshot {
+    pub g1: RistrettoPoint,
+    pub g2: RistrettoPoint,
+    pub g3: RistrettoPoint,
+    pub public_key: CompressedRistretto,
+    pub index: usize,
+    pub n: usize,
+    pub t: usize,
+    pub public_keys: Option<Vec<RistrettoPoint>>,
+    // (c_vals, z) as the dealer sent them
+    pub dealer_proof: Option<(Vec<CompressedRistretto>, Polynomial)>,
+    pub validated_shares: Vec<usize>,
+    pub tracker: Option<ShareTracker>,
+    pub share: Option<(Scalar, Scalar)>,
+    pub d: Option<Scalar>,
+    pub shares: Option<Vec<(Scalar, Scalar)>>,
+    pub qualified_set: Option<Vec<(usize, Scalar)>>,
+    pub key_commitments: Option<(RistrettoPoint, Vec<Vec<RistrettoPoint>>)>,
 }
 
 impl Party {
@@ -50,16 +95,34 @@ impl Party {
     where
         R: CryptoRng + RngCore,
     {
-        let private_key = random_scalar(rng);
-        let public_key = g * &private_key;
+        Self::with_key(
+            MemoryKey::generate(rng, g).shared(),
+            g1,
+            g2,
+            g3,
+            n,
+            t,
+            index,
+        )
+    }
 
-        if index <= n && t < n && t as f32 == ((n - 1) as f32 / 2.0).floor() {
+    // A party around an existing key.
+    pub fn with_key(
+        key: SharedKey,
+        g1: RistrettoPoint,
+        g2: RistrettoPoint,
+        g3: RistrettoPoint,
+        n: usize,
+        t: usize,
+        index: usize,
+    ) -> Result<Self, Error> {
+        if index <= n && t < n && t == (n - 1) / 2 {
             Ok(Self {
-                g1: g1.clone(),
-                g2: g2.clone(),
-                g3: g3.clone(),
-                private_key,
-                public_key: (public_key.compress(), public_key),
+                g1,
+                g2,
+                g3,
+                public_key: *key.public_key(),
+                key,
                 index,
                 n,
                 t,
@@ -67,16 +130,143 @@ impl Party {
                 share: None,
                 public_keys: None,
                 validated_shares: vec![],
+                tracker: None,
                 d: None,
                 shares: None,
                 qualified_set: None,
+                key_commitments: None,
+                ctx: ExecutionContext::default(),
             })
         } else {
             Err(InvalidPararmeterSet(n, t as isize, index).into())
         }
     }
 
+    pub fn from_committee(
+        committee: &Committee,
+        g1: RistrettoPoint,
+        g2: RistrettoPoint,
+        g3: RistrettoPoint,
+        key: SharedKey,
+        index: usize,
+    ) -> Result<Self, Error> {
+        committee.check_key(&key.public_key().1, index)?;
+
+        Ok(Self {
+            g1,
+            g2,
+            g3,
+            public_key: (
+                committee.public_keys[index - 1],
+                committee.points[index - 1],
+            ),
+            key,
+            index,
+            n: committee.n,
+            t: committee.t,
+            dealer_proof: None,
+            share: None,
+            public_keys: Some(committee.points.clone()),
+            validated_shares: vec![],
+            tracker: None,
+            d: None,
+            shares: None,
+            qualified_set: None,
+            key_commitments: None,
+            ctx: ExecutionContext::default(),
+        })
+    }
+
+    pub fn with_context(mut self, ctx: ExecutionContext) -> Self {
+        self.ctx = ctx;
+        self
+    }
+
+    pub fn snapshot(&self) -> PartySnapshot {
+        PartySnapshot {
+            g1: self.g1,
+            g2: self.g2,
+            g3: self.g3,
+            public_key: self.public_key.0,
+            index: self.index,
+            n: self.n,
+            t: self.t,
+            public_keys: self.public_keys.clone(),
+            dealer_proof: self
+                .dealer_proof
+                .as_ref()
+                .map(|(c_vals, _, z)| (c_vals.clone(), z.clone())),
+            validated_shares: self.validated_shares.clone(),
+            tracker: self.tracker.clone(),
+            share: self.share,
+            d: self.d,
+            shares: self.shares.clone(),
+            qualified_set: self.qualified_set.clone(),
+            key_commitments: self.key_commitments.clone(),
+        }
+    }
+
+    // Picks a party back up from its snapshot, `key` must be the key it was taken with.
+    pub fn restore(snapshot: PartySnapshot, key: SharedKey) -> Result<Self, Error> {
+        if key.public_key().0 != snapshot.public_key {
+            return Err(ForeignPublicKey(snapshot.index).into());
+        }
+        let mut party = Self::with_key(
+            key,
+            snapshot.g1,
+            snapshot.g2,
+            snapshot.g3,
+            snapshot.n,
+            snapshot.t,
+            snapshot.index,
+        )?;
+        party.public_keys = snapshot.public_keys;
+        if let Some((c_vals, z)) = snapshot.dealer_proof {
+            party.ingest_dealer_proof((&c_vals, &z))?;
+        }
+        party.validated_shares = snapshot.validated_shares;
+        party.tracker = snapshot.tracker;
+        party.share = snapshot.share;
+        party.d = snapshot.d;
+        party.shares = snapshot.shares;
+        party.qualified_set = snapshot.qualified_set;
+        party.key_commitments = snapshot.key_commitments;
+        Ok(party)
+    }
+
+    // Saves the party's state, call it after each phase and before sending what the phase
+    // produced.
+    #[cfg(feature = "std")]
+    pub fn checkpoint(&self, vault: &Vault) -> Result<(), Error> {
+        vault.save(&self.snapshot())
+    }
+
+    // The party as last checkpointed in `vault`, `None` if it never was.
+    #[cfg(feature = "std")]
+    pub fn resume(vault: &Vault, key: SharedKey) -> Result<Option<Self>, Error> {
+        match vault.load()? {
+            Some(snapshot) => Self::restore(snapshot, key).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // Proof of knowledge of our private key for registering `public_key` under `index`.
+    pub fn prove_possession<R>(
+        &self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        committee: &[u8],
+    ) -> Result<(Scalar, Scalar), Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        prove_key_possession(rng, hasher, buf, g, &*self.key, self.index, committee)
+    }
+
     pub fn ingest_share(&mut self, share: (&Scalar, &Scalar)) {
+        let _phase = phase!("ingest", n = self.n, t = self.t, k = 1);
         self.share = Some((share.0.clone(), share.1.clone()));
     }
 
@@ -84,17 +274,22 @@ impl Party {
         &mut self,
         proof: (&Vec<CompressedRistretto>, &Polynomial),
     ) -> Result<(), Error> {
+        let _phase = phase!("ingest", n = self.n, t = self.t, k = 1);
         if proof.1.len() != self.t + 1 {
+            verification_failure!("malformed_proof");
             Err(InvalidProof(format!("z len: {}, t: {}", proof.1.len(), self.t + 1)).into())
         } else if proof.0.len() != self.n {
+            verification_failure!("malformed_proof");
             Err(InvalidProof(format!("c_vals len: {}, n: {}", proof.0.len(), self.n)).into())
         } else {
             let mut decompressed_c_vals = Vec::with_capacity(self.n);
 
+            opcount::decompressions(proof.0.len());
             for c_i in proof.0 {
                 match c_i.decompress() {
                     Some(c) => decompressed_c_vals.push(c),
                     None => {
+                        verification_failure!("point_decompression");
                         return Err(Error::from_kind(PointDecompressionError(format!(
                             "{c_i:?}",
                         ))));
@@ -106,17 +301,62 @@ impl Party {
         }
     }
 
+    // Transcript digest of the ingested public dealing, as the dealer published it in
+    // `session_id`. The private shares aren't part of it.
+    pub fn transcript_digest(
+        &self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+    ) -> Result<[u8; 32], Error> {
+        match &self.dealer_proof {
+            Some((compressed_cvals, _, z)) => Ok(transcript_digest(
+                hasher,
+                &(DEALER, *session_id),
+                &(compressed_cvals, z),
+            )),
+            None => Err(UninitializedValue("party.dealer_proof").into()),
+        }
+    }
+
+    // True if the ingested dealing is the one whose digest was reliably broadcast.
+    pub fn verify_delivery(
+        &self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+        delivered: &[u8; 32],
+    ) -> Result<bool, Error> {
+        let digest = self.transcript_digest(hasher, session_id)?;
+        Ok(verified!(digest == *delivered, "undelivered_dealing"))
+    }
+
+    // Decrypts our share of the ingested dealing published in `session_id` and ingests it,
+    // a share that doesn't authenticate under that dealing is rejected.
+    pub fn ingest_encrypted_share(
+        &mut self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+        ciphertext: &Ciphertext,
+    ) -> Result<(), Error> {
+        let transcript = self.transcript_digest(hasher, session_id)?;
+        let share: (Scalar, Scalar) =
+            decrypt(hasher, (self.index, &*self.key), &transcript, ciphertext)?;
+        self.ingest_share((&share.0, &share.1));
+        Ok(())
+    }
+
     pub fn verify_share(
         &self,
         hasher: &mut Hasher,
         buf: &mut [u8; 64],
         x_pows: &Vec<Vec<Scalar>>,
     ) -> Result<bool, Error> {
-        match &self.dealer_proof {
+        let _phase = phase!("verify", n = self.n, t = self.t, k = 1);
+        self.ctx.install(|| match &self.dealer_proof {
             Some((compressed_cvals, cvals, z)) => match &self.share {
                 Some((fi, gi)) => {
                     let d = compute_d_from_point_commitments(hasher, buf, &compressed_cvals);
                     let zi = z.evaluate_precomp(x_pows, self.index);
+                    opcount::msms(1, 3);
 
                     let expected_c = cvals[self.index - 1];
 
@@ -124,12 +364,61 @@ impl Party {
                         + self.g2 * Polynomial::compute_r_eval(&zi, &[*fi], &[d])
                         + self.g3 * gi;
 
-                    Ok(expected_c == c)
+                    Ok(verified!(expected_c == c, "share"))
                 }
                 None => Err(UninitializedValue("party.share").into()),
             },
             None => Err(UninitializedValue("party.dealer_proof").into()),
+        })
+    }
+
+    // Checks the (fi, gamma_i) the dealer revealed for party `index` against c_i.
+    pub fn verify_revealed_share(
+        &self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        x_pows: &Vec<Vec<Scalar>>,
+        index: usize,
+        share: &(Scalar, Scalar),
+    ) -> Result<bool, Error> {
+        if index == 0 || index > self.n {
+            return Err(InvalidPararmeterSet(self.n, self.t as isize, index).into());
+        }
+        match &self.dealer_proof {
+            Some((compressed_cvals, cvals, z)) => {
+                let (fi, gi) = share;
+                let d = compute_d_from_point_commitments(hasher, buf, compressed_cvals);
+                let zi = z.evaluate_precomp(x_pows, index);
+                opcount::msms(1, 3);
+
+                let c = self.g1 * fi
+                    + self.g2 * Polynomial::compute_r_eval(&zi, &[*fi], &[d])
+                    + self.g3 * gi;
+
+                Ok(verified!(cvals[index - 1] == c, "revealed_share"))
+            }
+            None => Err(UninitializedValue("party.dealer_proof").into()),
+        }
+    }
+
+    // Settles the complaint round. If the dealing stands and we complained, the share
+    // the dealer revealed for us becomes our share.
+    pub fn resolve_complaints(
+        &mut self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        x_pows: &Vec<Vec<Scalar>>,
+        complaints: &Complaints<(Scalar, Scalar)>,
+    ) -> Result<Verdict, Error> {
+        let verdict = complaints.resolve(|index, share| {
+            self.verify_revealed_share(hasher, buf, x_pows, index, share)
+        })?;
+        if verdict == Verdict::Accept
+            && let Some(share) = complaints.answers.get(&self.index)
+        {
+            self.share = Some(*share);
         }
+        Ok(verdict)
     }
 
     pub fn verify_shares(
@@ -138,39 +427,116 @@ impl Party {
         buf: &mut [u8; 64],
         x_pows: &Vec<Vec<Scalar>>,
     ) -> Result<bool, Error> {
-        match &self.dealer_proof {
+        let report = self.share_report(hasher, buf, x_pows)?;
+        self.validated_shares = report.valid();
+        Ok(verified!(report.is_sufficient(), "insufficient_shares"))
+    }
+
+    // Every party's share with its status, see `VerificationReport`.
+    pub fn share_report(
+        &self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        x_pows: &Vec<Vec<Scalar>>,
+    ) -> Result<VerificationReport, Error> {
+        let _phase = phase!("verify", n = self.n, t = self.t, k = 1);
+        self.ctx.install(|| match &self.dealer_proof {
             Some((compressed_cvals, cvals, z)) => match &self.shares {
                 Some(shares) => {
                     let d = compute_d_from_point_commitments(hasher, buf, &compressed_cvals);
                     let z_evals = z.evaluate_range_precomp(x_pows, 1, self.n);
+                    opcount::msms(shares.len(), 3);
 
-                    self.validated_shares = shares
+                    let checks: Vec<ShareCheck> = shares
                         .par_iter()
                         .zip(z_evals.par_iter())
                         .enumerate()
-                        .map(|(i, ((fi, gi), zi))| {
-                            if cvals[i]
-                                == self.g1 * fi
-                                    + self.g2 * Polynomial::compute_r_eval(zi, &[*fi], &[d])
-                                    + self.g3 * gi
-                            {
-                                Some(i)
-                            } else {
-                                None
-                            }
-                        })
-                        .filter(Option::is_some)
-                        .map(|res| res.unwrap())
+                        .map(|(i, (share, zi))| self.check_share(&cvals[i], &d, zi, i + 1, share))
                         .collect();
-                    Ok(self.validated_shares.len() > self.t)
+                    Ok(VerificationReport::new(self.n, self.t, Some(d), checks))
                 }
                 None => Err(UninitializedValue("party.share").into()),
             },
             None => Err(UninitializedValue("party.dealer_proof").into()),
+        })
+    }
+
+    // Checks party `index`'s (fi, gamma_i) as it arrives, ours included, and keeps it with the
+    // others in `shares` if it holds. Parties that haven't sent, or whose share failed, have
+    // zero there, and a second message from a party is an error. True once, when more than t
+    // of them hold, see `ShareTracker`.
+    pub fn verify_share_from(
+        &mut self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        x_pows: &Vec<Vec<Scalar>>,
+        index: usize,
+        share: (Scalar, Scalar),
+    ) -> Result<bool, Error> {
+        let _phase = phase!("verify", n = self.n, t = self.t, k = 1);
+        if index == 0 || index > self.n {
+            return Err(InvalidPararmeterSet(self.n, self.t as isize, index).into());
+        }
+        if self
+            .tracker
+            .as_ref()
+            .is_some_and(|tracker| tracker.check(index).is_some())
+        {
+            return Err(DuplicateMessage(index).into());
+        }
+        let (check, d) = match &self.dealer_proof {
+            Some((compressed_cvals, cvals, z)) => {
+                let d = match self.tracker.as_ref().and_then(|tracker| tracker.challenge) {
+                    Some(d) => d,
+                    None => compute_d_from_point_commitments(hasher, buf, compressed_cvals),
+                };
+                let zi = z.evaluate_precomp(x_pows, index);
+                opcount::msms(1, 3);
+                (
+                    self.check_share(&cvals[index - 1], &d, &zi, index, &share),
+                    d,
+                )
+            }
+            None => return Err(UninitializedValue("party.dealer_proof").into()),
+        };
+        let valid = check.status == ShareStatus::Valid;
+        let tracker = self
+            .tracker
+            .get_or_insert_with(|| ShareTracker::new(self.n, self.t, Some(d)));
+        let sufficient = tracker.record(check)?;
+        self.validated_shares = tracker.valid();
+        // only shares that hold are kept, the others stay placeholders
+        if valid {
+            self.shares
+                .get_or_insert_with(|| vec![(Scalar::ZERO, Scalar::ZERO); self.n])[index - 1] =
+                share;
         }
+        Ok(sufficient)
+    }
+
+    // c_i = g1^fi g2^r_i g3^gamma_i with r_i = z(i) - d fi
+    fn check_share(
+        &self,
+        c: &RistrettoPoint,
+        d: &Scalar,
+        zi: &Scalar,
+        index: usize,
+        (fi, gi): &(Scalar, Scalar),
+    ) -> ShareCheck {
+        ShareCheck::checked(
+            index,
+            verified!(
+                *c == self.g1 * fi
+                    + self.g2 * Polynomial::compute_r_eval(zi, &[*fi], &[*d])
+                    + self.g3 * gi,
+                "share"
+            ),
+            ShareStatus::CommitmentMismatch,
+        )
     }
 
     pub fn ingest_shares(&mut self, shares: (&Vec<Scalar>, &Vec<Scalar>)) -> Result<(), Error> {
+        let _phase = phase!("ingest", n = self.n, t = self.t, k = 1);
         if shares.0.len() == self.n && shares.1.len() == self.n {
             self.shares = Some(
                 shares
@@ -185,6 +551,55 @@ impl Party {
             Err(CountMismatch(self.n, "parties", shares.0.len(), "ingestable shares").into())
         }
     }
+    // The dealer's commitments to the coefficients of the dealt polynomials, which must be
+    // under `g`, the generator the verification keys are wanted for.
+    pub fn ingest_key_commitments(
+        &mut self,
+        g: &RistrettoPoint,
+        keys: &KeyCommitments,
+    ) -> Result<(), Error> {
+        let _phase = phase!("ingest", n = self.n, t = self.t, k = 1);
+        self.key_commitments = Some((*g, keys.open(g, 1, self.t)?));
+        Ok(())
+    }
+
+    // True if the key commitments give our validated share as our verification key, which
+    // ties them to the dealing.
+    pub fn verify_key_commitments(&self, x_pows: &[Vec<Scalar>]) -> Result<bool, Error> {
+        match (&self.key_commitments, &self.share) {
+            (Some((g, commitments)), Some(share)) => {
+                Ok(check_shares(g, commitments, x_pows, self.index, &[share.0]))
+            }
+            (None, _) => Err(UninitializedValue("party.key_commitments").into()),
+            (_, None) => Err(UninitializedValue("party.share").into()),
+        }
+    }
+
+    // The group public key and every party's verification key.
+    pub fn verification_keys(&self, x_pows: &[Vec<Scalar>]) -> Result<VerificationKeys, Error> {
+        match &self.key_commitments {
+            Some((_, commitments)) => Ok(VerificationKeys::from_commitments(
+                &commitments[0],
+                x_pows,
+                self.n,
+            )),
+            None => Err(UninitializedValue("party.key_commitments").into()),
+        }
+    }
+
+    // The secret reconstructed from the qualified set, with a proof for light clients, see
+    // `reconstruction`.
+    pub fn prove_reconstruction(&self) -> Result<(Scalar, ReconstructionProof), Error> {
+        let _phase = phase!("reconstruct", t = self.t);
+        match (&self.qualified_set, &self.shares) {
+            (Some(qualified_set), Some(shares)) => {
+                let indices: Vec<usize> = qualified_set.iter().map(|(index, _)| *index).collect();
+                Ok(self.ctx.install(|| prove_reconstruction(&indices, shares)))
+            }
+            (None, _) => Err(UninitializedValue("party.qualified_set").into()),
+            (_, None) => Err(UninitializedValue("party.shares").into()),
+        }
+    }
 }
 
 pub fn generate_parties<R>(
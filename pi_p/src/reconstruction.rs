@@ -0,0 +1,125 @@
+// Proof that a secret is what a qualified set of shares reconstructs to, for light clients
+// that only know the dealing's commitments c_i and z. The aggregator opens t + 1 of them,
+// (f_i, gamma_i), and the client checks every c_i = g1^f_i g2^(z(i) - d f_i) g3^gamma_i in
+// one multiscalar multiplication under random weights, then interpolates the f_i. It doesn't
+// check the dealing itself, the parties did that before sharing.
+
+use alloc::vec::Vec;
+
+use blake3::Hasher;
+use curve25519_dalek::{
+    RistrettoPoint, Scalar,
+    ristretto::CompressedRistretto,
+    traits::{IsIdentity, VartimeMultiscalarMul},
+};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use common::{
+    error::Error,
+    opcount,
+    parallel::prelude::*,
+    polynomial::Polynomial,
+    random::random_scalars,
+    utils::{compute_d_from_point_commitments, compute_lagrange_bases},
+    verification_failure, verified,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconstructionProof {
+    // (index, (f_i, gamma_i)) of the qualified set, by increasing index
+    pub openings: Vec<(usize, (Scalar, Scalar))>,
+}
+
+// The secret reconstructed from the parties at `indices`, whose `shares` (f_i, gamma_i) in
+// index order all passed verification, with its proof.
+pub fn prove_reconstruction(
+    indices: &[usize],
+    shares: &[(Scalar, Scalar)],
+) -> (Scalar, ReconstructionProof) {
+    let mut indices = indices.to_vec();
+    indices.sort();
+    let lagrange_bases = compute_lagrange_bases(&indices);
+
+    let openings: Vec<(usize, (Scalar, Scalar))> =
+        indices.iter().map(|i| (*i, shares[i - 1])).collect();
+    opcount::scalar_muls(openings.len());
+    let secret = openings
+        .par_iter()
+        .zip(lagrange_bases.par_iter())
+        .map(|((_, (fi, _)), lambda)| fi * lambda)
+        .sum();
+
+    (secret, ReconstructionProof { openings })
+}
+
+// True if `secret` is f(0) for the f that t + 1 openings of the dealing's commitments lie on.
+// `dealing` is the commitments c_i, in index order, and z.
+pub fn verify_reconstruction<R>(
+    rng: &mut R,
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    (g1, g2, g3): (&RistrettoPoint, &RistrettoPoint, &RistrettoPoint),
+    t: usize,
+    (c_vals, z): (&[CompressedRistretto], &Polynomial),
+    (secret, proof): (&Scalar, &ReconstructionProof),
+) -> Result<bool, Error>
+where
+    R: CryptoRng + RngCore,
+{
+    let openings = &proof.openings;
+    if openings.len() != t + 1
+        || openings.first().is_some_and(|(index, _)| *index == 0)
+        || openings
+            .last()
+            .is_some_and(|(index, _)| *index > c_vals.len())
+        || openings.windows(2).any(|w| w[0].0 >= w[1].0)
+    {
+        verification_failure!("malformed_proof");
+        return Ok(false);
+    }
+
+    let indices: Vec<usize> = openings.iter().map(|(index, _)| *index).collect();
+    let lagrange_bases = compute_lagrange_bases(&indices);
+    opcount::scalar_muls(openings.len());
+    let interpolated: Scalar = openings
+        .par_iter()
+        .zip(lagrange_bases.par_iter())
+        .map(|((_, (fi, _)), lambda)| fi * lambda)
+        .sum();
+    if !verified!(interpolated == *secret, "reconstruction") {
+        return Ok(false);
+    }
+
+    opcount::decompressions(openings.len());
+    let commitments: Option<Vec<RistrettoPoint>> = indices
+        .par_iter()
+        .map(|i| c_vals[i - 1].decompress())
+        .collect();
+    let commitments = match commitments {
+        Some(commitments) => commitments,
+        None => {
+            verification_failure!("malformed_proof");
+            return Ok(false);
+        }
+    };
+
+    let d = compute_d_from_point_commitments(hasher, buf, c_vals);
+    let rho = random_scalars(rng, openings.len());
+
+    // sum of rho_i (f_i g1 + (z(i) - d f_i) g2 + gamma_i g3 - c_i)
+    let (mut s1, mut s2, mut s3) = (Scalar::ZERO, Scalar::ZERO, Scalar::ZERO);
+    for ((index, (fi, gi)), rho) in openings.iter().zip(rho.iter()) {
+        s1 += rho * fi;
+        s2 += rho * Polynomial::compute_r_eval(&z.evaluate(*index), &[*fi], &[d]);
+        s3 += rho * gi;
+    }
+    let scalars = [s1, s2, s3].into_iter().chain(rho.iter().map(|rho| -rho));
+    let bases = [*g1, *g2, *g3].into_iter().chain(commitments);
+
+    opcount::msms(1, openings.len() + 3);
+    Ok(verified!(
+        RistrettoPoint::vartime_multiscalar_mul(scalars, bases).is_identity(),
+        "reconstruction_proof"
+    ))
+}
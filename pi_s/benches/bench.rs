@@ -1,88 +1,102 @@
-use common::{BENCH_N_T, precompute::gen_powers, random::random_scalar, utils::ingest_public_keys};
+use common::{
+    BENCH_N_T, BENCH_THREADS, execution::ExecutionContext, precompute::gen_powers,
+    random::random_scalar, utils::ingest_public_keys,
+};
 use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
 use curve25519_dalek::{RistrettoPoint, ristretto::CompressedRistretto, scalar::Scalar};
 use pi_s::{dealer::Dealer, party::generate_parties};
 
 fn pvss(c: &mut Criterion) {
-    for (n, t) in BENCH_N_T {
-        let mut rng = rand::rng();
-        let mut hasher = blake3::Hasher::new();
-        let mut buf: [u8; 64] = [0u8; 64];
-
-        let g: RistrettoPoint = RistrettoPoint::mul_base(&random_scalar(&mut rng));
-
-        let mut parties = generate_parties(&g, &mut rng, n, t);
-        let xpows = gen_powers(n, t);
-
-        let public_keys: Vec<CompressedRistretto> =
-            parties.iter().map(|party| party.public_key.0).collect();
-        let mut dealer = Dealer::new(n, t, &public_keys).unwrap();
-
-        let public_keys: Vec<CompressedRistretto> =
-            parties.iter().map(|party| party.public_key.0).collect();
-
-        for party in &mut parties {
-            let public_keys: Vec<CompressedRistretto> = public_keys
-                .iter()
-                .filter(|pk| &party.public_key.0 != *pk)
-                .copied()
-                .collect();
+    for threads in BENCH_THREADS {
+        let ctx = ExecutionContext::with_threads(threads, 1).unwrap();
+
+        for (n, t) in BENCH_N_T {
+            let mut rng = rand::rng();
+            let mut hasher = blake3::Hasher::new();
+            let mut buf: [u8; 64] = [0u8; 64];
+
+            let g: RistrettoPoint = RistrettoPoint::mul_base(&random_scalar(&mut rng));
+
+            let mut parties = generate_parties(&g, &mut rng, n, t);
+            let xpows = gen_powers(n, t);
+
+            let public_keys: Vec<CompressedRistretto> =
+                parties.iter().map(|party| party.public_key.0).collect();
+            let mut dealer = Dealer::new(n, t, &public_keys)
+                .unwrap()
+                .with_context(ctx.clone());
+
+            let public_keys: Vec<CompressedRistretto> =
+                parties.iter().map(|party| party.public_key.0).collect();
+
+            for party in &mut parties {
+                party.ctx = ctx.clone();
+                let public_keys: Vec<CompressedRistretto> = public_keys
+                    .iter()
+                    .filter(|pk| &party.public_key.0 != *pk)
+                    .copied()
+                    .collect();
+
+                party.public_keys = Some(
+                    ingest_public_keys(n, &party.public_key.1, party.index, &public_keys).unwrap(),
+                );
+            }
+            let secret = random_scalar(&mut rng);
+
+            let (encrypted_shares, (d, z)) =
+                dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret);
+
+            c.bench_function(
+                &format!(
+                    "(n: {}, t: {}, threads: {}) | Pi_S PVSS | Dealer: Deal Secret",
+                    n, t, threads
+                ),
+                |b| {
+                    b.iter_batched(
+                        || (blake3::Hasher::new(), [0u8; 64]),
+                        |(mut hasher, mut buf)| {
+                            dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret)
+                        },
+                        BatchSize::PerIteration,
+                    )
+                },
+            );
 
-            party.public_keys = Some(
-                ingest_public_keys(n, &party.public_key.1, party.index, &public_keys).unwrap(),
+            for p in &mut parties {
+                p.ingest_encrypted_shares(&encrypted_shares).unwrap();
+                p.ingest_dealer_proof(d, z.clone()).unwrap();
+
+                let res = p
+                    .verify_encrypted_shares(&mut hasher, &mut buf, &xpows)
+                    .unwrap();
+
+                assert!(res, "encrypted share verification failure");
+            }
+
+            c.bench_function(
+                &format!(
+                    "(n: {}, t: {}, threads: {}) | Pi_S PVSS | Party: Verify Encrypted Shares",
+                    n, t, threads
+                ),
+                |b| {
+                    b.iter_batched(
+                        || (blake3::Hasher::new(), [0u8; 64]),
+                        |(mut hasher, mut buf)| {
+                            assert!(
+                                parties[0]
+                                    .verify_encrypted_shares(&mut hasher, &mut buf, &xpows)
+                                    .unwrap()
+                            )
+                        },
+                        BatchSize::PerIteration,
+                    )
+                },
             );
-        }
-        let secret = random_scalar(&mut rng);
-
-        let (encrypted_shares, (d, z)) =
-            dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret);
-
-        c.bench_function(
-            &format!("(n: {}, t: {}) | Pi_S PVSS | Dealer: Deal Secret", n, t),
-            |b| {
-                b.iter_batched(
-                    || (blake3::Hasher::new(), [0u8; 64]),
-                    |(mut hasher, mut buf)| {
-                        dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret)
-                    },
-                    BatchSize::PerIteration,
-                )
-            },
-        );
-
-        for p in &mut parties {
-            p.ingest_encrypted_shares(&encrypted_shares).unwrap();
-            p.ingest_dealer_proof(d, z.clone()).unwrap();
-
-            let res = p
-                .verify_encrypted_shares(&mut hasher, &mut buf, &xpows)
-                .unwrap();
-
-            assert!(res, "encrypted share verification failure");
-        }
 
-        c.bench_function(
-            &format!(
-                "(n: {}, t: {}) | Pi_S PVSS | Party: Verify Encrypted Shares",
-                n, t
-            ),
-            |b| {
-                b.iter_batched(
-                    || (blake3::Hasher::new(), [0u8; 64]),
-                    |(mut hasher, mut buf)| {
-                        assert!(
-                            parties[0]
-                                .verify_encrypted_shares(&mut hasher, &mut buf, &xpows)
-                                .unwrap()
-                        )
-                    },
-                    BatchSize::PerIteration,
-                )
-            },
-        );
-
-        let (decrypted_shares, share_proofs): (Vec<CompressedRistretto>, Vec<(Scalar, Scalar)>) =
-            parties
+            let (decrypted_shares, share_proofs): (
+                Vec<CompressedRistretto>,
+                Vec<(Scalar, Scalar)>,
+            ) = parties
                 .iter_mut()
                 .map(|p| {
                     p.decrypt_share().unwrap();
@@ -94,61 +108,74 @@ fn pvss(c: &mut Criterion) {
                 })
                 .collect();
 
-        c.bench_function(
-            &format!("(n: {}, t: {}) | Pi_S PVSS | Party: Decrypt Share", n, t),
-            |b| b.iter(|| parties[0].decrypt_share().unwrap()),
-        );
-
-        c.bench_function(
-            &format!("(n: {}, t: {}) | Pi_S PVSS | Party: Generate Proof", n, t),
-            |b| {
-                b.iter_batched(
-                    || (blake3::Hasher::new(), [0u8; 64]),
-                    |(mut hasher, mut buf)| {
-                        parties[0]
-                            .dleq_share(&g, &mut rng, &mut hasher, &mut buf)
-                            .unwrap()
-                    },
-                    BatchSize::PerIteration,
-                )
-            },
-        );
-
-        for p in &mut parties {
-            let (mut decrypted_shares, mut share_proofs) =
-                (decrypted_shares.clone(), share_proofs.clone());
-
-            decrypted_shares.remove(p.index - 1);
-            share_proofs.remove(p.index - 1);
-            p.ingest_decrypted_shares_and_proofs(&decrypted_shares, share_proofs)
-                .unwrap();
-        }
+            c.bench_function(
+                &format!(
+                    "(n: {}, t: {}, threads: {}) | Pi_S PVSS | Party: Decrypt Share",
+                    n, t, threads
+                ),
+                |b| b.iter(|| parties[0].decrypt_share().unwrap()),
+            );
 
-        c.bench_function(
-            &format!(
-                "(n: {}, t: {}) | Pi_S PVSS | Party: Verify Decrypted Shares",
-                n, t
-            ),
-            |b| {
-                b.iter(|| {
-                    parties[0].verify_decrypted_shares(&g).unwrap();
-                })
-            },
-        );
-
-        // let lambdas = precompute_lambda(n, t);
-
-        //     c.bench_function(
-        //         &format!(
-        //             "(n: {}, t: {}) | Pi_S PVSS | Party: Reconstruct Secret",
-        //             n, t
-        //         ),
-        //         |b| {
-        //             b.iter(|| {
-        //                 parties[0].reconstruct_secret(&lambdas).unwrap();
-        //             })
-        //         },
-        //     );
+            c.bench_function(
+                &format!(
+                    "(n: {}, t: {}, threads: {}) | Pi_S PVSS | Party: Generate Proof",
+                    n, t, threads
+                ),
+                |b| {
+                    b.iter_batched(
+                        || {
+                            let mut party = parties[0].clone();
+                            // a party that has its proof already doesn't make another
+                            party.share_proof = None;
+                            (party, blake3::Hasher::new(), [0u8; 64])
+                        },
+                        |(mut party, mut hasher, mut buf)| {
+                            party
+                                .dleq_share(&g, &mut rng, &mut hasher, &mut buf)
+                                .unwrap();
+                            party
+                        },
+                        BatchSize::PerIteration,
+                    )
+                },
+            );
+
+            for p in &mut parties {
+                let (mut decrypted_shares, mut share_proofs) =
+                    (decrypted_shares.clone(), share_proofs.clone());
+
+                decrypted_shares.remove(p.index - 1);
+                share_proofs.remove(p.index - 1);
+                p.ingest_decrypted_shares_and_proofs(&decrypted_shares, share_proofs)
+                    .unwrap();
+            }
+
+            c.bench_function(
+                &format!(
+                    "(n: {}, t: {}, threads: {}) | Pi_S PVSS | Party: Verify Decrypted Shares",
+                    n, t, threads
+                ),
+                |b| {
+                    b.iter(|| {
+                        parties[0].verify_decrypted_shares(&g).unwrap();
+                    })
+                },
+            );
+
+            // let lambdas = precompute_lambda(n, t);
+
+            //     c.bench_function(
+            //         &format!(
+            //             "(n: {}, t: {}) | Pi_S PVSS | Party: Reconstruct Secret",
+            //             n, t
+            //         ),
+            //         |b| {
+            //             b.iter(|| {
+            //                 parties[0].reconstruct_secret(&lambdas).unwrap();
+            //             })
+            //         },
+            //     );
+        }
     }
 }
 
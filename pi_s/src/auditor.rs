@@ -0,0 +1,228 @@
+// Public verification of a pi_s dealing, for anyone holding the committee and nothing else.
+// The checks are the ones parties run, `Party` calls the same functions on its own copy of
+// the dealing.
+
+use alloc::{format, vec::Vec};
+
+use blake3::Hasher;
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+use zeroize::Zeroize;
+
+use common::{
+    committee::Committee,
+    error::{
+        Error,
+        ErrorKind::{CountMismatch, InvalidProof},
+    },
+    opcount,
+    parallel::prelude::*,
+    phase,
+    polynomial::Polynomial,
+    report::{ShareCheck, ShareStatus, VerificationReport},
+    secret_sharing::reconstruct_secret_exponent,
+    utils::{batch_decompress_ristretto_points, compute_lagrange_bases},
+    verification_failure, verified,
+};
+
+// True if (d, z) proves that `encrypted_shares` (compressed, decompressed) encrypt
+// evaluations of one polynomial of degree t to `public_keys`.
+pub fn verify_dealing(
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    x_pows: &Vec<Vec<Scalar>>,
+    public_keys: &[RistrettoPoint],
+    encrypted_shares: (&[CompressedRistretto], &[RistrettoPoint]),
+    (d, z): (&Scalar, &Polynomial),
+) -> bool {
+    let n = public_keys.len();
+    let z_evals = z.evaluate_range_precomp(x_pows, 1, n);
+    opcount::point_muls(2 * n);
+    opcount::compressions(n);
+
+    let shares: Vec<CompressedRistretto> = z_evals
+        .par_iter()
+        .zip(public_keys)
+        .zip(encrypted_shares.1.par_iter())
+        .map(|((z_eval, pub_key), enc_share)| (z_eval * pub_key - (enc_share * d)).compress())
+        .collect();
+
+    let _phase = phase!("hash", n = encrypted_shares.0.len() + shares.len());
+    opcount::hashes(1, 32 * (encrypted_shares.0.len() + shares.len()));
+    encrypted_shares.0.iter().chain(&shares).for_each(|x| {
+        hasher.update(x.as_bytes());
+    });
+
+    hasher.finalize_xof().fill(buf);
+    let reconstructed_d = Scalar::from_bytes_mod_order_wide(buf);
+
+    hasher.reset();
+    buf.zeroize();
+    verified!(*d == reconstructed_d, "dealer_proof")
+}
+
+// Every party's DLEQ proof checked against its decrypted share, in index order.
+pub fn verify_decryptions(
+    g: &RistrettoPoint,
+    public_keys: &[RistrettoPoint],
+    encrypted_shares: &[RistrettoPoint],
+    decrypted_shares: &[RistrettoPoint],
+    proofs: &[(Scalar, Scalar)],
+) -> Vec<ShareCheck> {
+    opcount::point_muls(4 * decrypted_shares.len());
+    opcount::compressions(4 * decrypted_shares.len());
+    opcount::hashes(decrypted_shares.len(), 4 * 32 * decrypted_shares.len());
+    decrypted_shares
+        .par_iter()
+        .zip(
+            proofs
+                .par_iter()
+                .zip(public_keys.par_iter().zip(encrypted_shares.par_iter())),
+        )
+        .enumerate()
+        .map_init(
+            || (blake3::Hasher::new(), [0u8; 64]),
+            |(hasher, buf), (i, (dec_share, ((d, z), (public_key, enc_share))))| {
+                let num1 = g * z;
+                let num2 = dec_share * z;
+
+                let denom1 = public_key * d;
+                let denom2 = enc_share * d;
+
+                hasher.update(public_key.compress().as_bytes());
+                hasher.update(enc_share.compress().as_bytes());
+                hasher.update((num1 - denom1).compress().as_bytes());
+                hasher.update((num2 - denom2).compress().as_bytes());
+                hasher.finalize_xof().fill(buf);
+
+                let reconstructed_d = Scalar::from_bytes_mod_order_wide(buf);
+
+                hasher.reset();
+                buf.zeroize();
+
+                ShareCheck::checked(
+                    i + 1,
+                    verified!(*d == reconstructed_d, "decryption_proof"),
+                    ShareStatus::WrongChallenge,
+                )
+                .with_challenge((*d, reconstructed_d))
+            },
+        )
+        .collect()
+}
+
+// every party's (decrypted share, DLEQ proof), in index order
+pub type Decryptions<'a> = (&'a [CompressedRistretto], &'a [(Scalar, Scalar)]);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Audit {
+    // the dealing's proof holds
+    pub dealing: bool,
+    // every party's decrypted share, if they were given
+    pub report: Option<VerificationReport>,
+    // g^secret, once the dealing holds and more than t shares do
+    pub secret: Option<RistrettoPoint>,
+}
+
+pub struct Auditor<'a> {
+    pub committee: &'a Committee,
+}
+
+impl<'a> Auditor<'a> {
+    pub fn new(committee: &'a Committee) -> Self {
+        Self { committee }
+    }
+
+    // Checks a dealing (encrypted shares, (d, z)) and, if given, every party's
+    // (decrypted share, DLEQ proof) in index order. Input that can't even be checked,
+    // wrong counts or points that don't decode, is an `Err`.
+    pub fn audit(
+        &self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        dealing: (&[CompressedRistretto], (&Scalar, &Polynomial)),
+        decryptions: Option<Decryptions>,
+    ) -> Result<Audit, Error> {
+        let committee = self.committee;
+        let _phase = phase!("audit", n = committee.n, t = committee.t, k = 1);
+        let (encrypted_shares, (d, z)) = dealing;
+        if encrypted_shares.len() != committee.n {
+            return Err(CountMismatch(
+                committee.n,
+                "parties",
+                encrypted_shares.len(),
+                "encrypted shares",
+            )
+            .into());
+        }
+        if *d == Scalar::ZERO || z.len() != committee.t + 1 {
+            verification_failure!("malformed_proof");
+            return Err(InvalidProof(format!("d == {d:?}, z len: {}", z.len())).into());
+        }
+        let points = batch_decompress_ristretto_points(encrypted_shares)?;
+
+        let dealing = verify_dealing(
+            hasher,
+            buf,
+            &committee.x_pows,
+            &committee.points,
+            (encrypted_shares, &points),
+            (d, z),
+        );
+        let mut audit = Audit {
+            dealing,
+            report: None,
+            secret: None,
+        };
+
+        if let Some((decrypted_shares, proofs)) = decryptions {
+            for (len, what) in [
+                (decrypted_shares.len(), "decrypted shares"),
+                (proofs.len(), "proofs"),
+            ] {
+                if len != committee.n {
+                    return Err(CountMismatch(committee.n, "parties", len, what).into());
+                }
+            }
+            // a share that doesn't decode is checked as the identity and blamed for it
+            opcount::decompressions(decrypted_shares.len());
+            let decompressed: Vec<Option<RistrettoPoint>> = decrypted_shares
+                .par_iter()
+                .map(CompressedRistretto::decompress)
+                .collect();
+            let decrypted_shares: Vec<RistrettoPoint> = decompressed
+                .iter()
+                .map(|share| share.unwrap_or_default())
+                .collect();
+            let mut checks = verify_decryptions(
+                &committee.g,
+                &committee.points,
+                &points,
+                &decrypted_shares,
+                proofs,
+            );
+            checks
+                .iter_mut()
+                .zip(&decompressed)
+                .filter(|(_, share)| share.is_none())
+                .for_each(|(check, _)| check.status = ShareStatus::BadDecompression);
+            let report = VerificationReport::new(committee.n, committee.t, Some(*d), checks);
+            let valid = report.valid();
+            audit.report = Some(report);
+
+            // any t + 1 valid shares give the same secret once the dealing holds
+            if dealing && valid.len() > committee.t {
+                let qualified_set: Vec<(usize, RistrettoPoint)> = valid
+                    .iter()
+                    .take(committee.t + 1)
+                    .map(|i| (i + 1, decrypted_shares[*i]))
+                    .collect();
+                let indices: Vec<usize> = qualified_set.iter().map(|(i, _)| *i).collect();
+                audit.secret = Some(reconstruct_secret_exponent(
+                    &Some(qualified_set),
+                    &compute_lagrange_bases(&indices),
+                )?);
+            }
+        }
+        Ok(audit)
+    }
+}
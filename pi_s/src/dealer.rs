@@ -1,21 +1,51 @@
+use alloc::{collections::BTreeMap, vec::Vec};
 use common::{
-    error::{Error, ErrorKind::CountMismatch},
+    committee::Committee,
+    error::{
+        Error,
+        ErrorKind::{ConflictingDealing, CountMismatch},
+    },
+    execution::ExecutionContext,
+    opcount,
+    parallel::prelude::*,
+    phase,
     polynomial::Polynomial,
+    pop::{check_public_keys, verify_public_keys},
     secret_sharing::generate_shares,
+    session::DEALER,
 };
 
 use blake3::Hasher;
+#[cfg(feature = "std")]
+use common::snapshot::Vault;
 use common::utils::batch_decompress_ristretto_points;
 use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
 
 use rand::{CryptoRng, RngCore};
-use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
+// (encrypted shares, (d, z))
+pub type Dealing = (Vec<CompressedRistretto>, (Scalar, Polynomial));
+// (secret, dealing) of a session the dealer dealt
+pub type DealtSession = (Scalar, Dealing);
+
+// The dealer's state with the dealing of every session it dealt. A resumed dealer sends
+// those dealings again rather than dealing second, different ones.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DealerSnapshot {
+    pub t: usize,
+    pub public_keys: Vec<RistrettoPoint>,
+    pub sessions: Vec<([u8; 32], DealtSession)>,
+}
+
+#[derive(Clone)]
 pub struct Dealer {
     t: usize,
     public_keys: Vec<RistrettoPoint>,
-    pub(crate) secret: Option<Scalar>,
+    // by session id, see `deal_session`
+    sessions: BTreeMap<[u8; 32], DealtSession>,
+    ctx: ExecutionContext,
 }
 
 impl Dealer {
@@ -23,16 +53,117 @@ impl Dealer {
         if public_keys.len() != n {
             return Err(CountMismatch(n, "parties", public_keys.len(), "public keys").into());
         }
+        check_public_keys(public_keys)?;
         match batch_decompress_ristretto_points(public_keys) {
             Ok(pks) => Ok(Self {
                 t,
                 public_keys: pks.par_iter().map(|pk| *pk).collect(),
-                secret: None,
+                sessions: BTreeMap::new(),
+                ctx: ExecutionContext::default(),
             }),
             Err(x) => Err(x),
         }
     }
 
+    pub fn from_committee(committee: &Committee) -> Self {
+        Self {
+            t: committee.t,
+            public_keys: committee.points.clone(),
+            sessions: BTreeMap::new(),
+            ctx: ExecutionContext::default(),
+        }
+    }
+
+    pub fn with_context(mut self, ctx: ExecutionContext) -> Self {
+        self.ctx = ctx;
+        self
+    }
+
+    pub fn snapshot(&self) -> DealerSnapshot {
+        DealerSnapshot {
+            t: self.t,
+            public_keys: self.public_keys.clone(),
+            sessions: self
+                .sessions
+                .iter()
+                .map(|(id, session)| (*id, session.clone()))
+                .collect(),
+        }
+    }
+
+    pub fn restore(snapshot: DealerSnapshot) -> Self {
+        Self {
+            t: snapshot.t,
+            public_keys: snapshot.public_keys,
+            sessions: snapshot.sessions.into_iter().collect(),
+            ctx: ExecutionContext::default(),
+        }
+    }
+
+    // Saves the dealer and its sessions, call it before a session's dealing is sent.
+    #[cfg(feature = "std")]
+    pub fn checkpoint(&self, vault: &Vault) -> Result<(), Error> {
+        vault.save(&self.snapshot())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn resume(vault: &Vault) -> Result<Option<Self>, Error> {
+        Ok(vault.load()?.map(Self::restore))
+    }
+
+    // Deals `secret` in session `session_id` and keeps it with its dealing. Dealing the same
+    // session again hands back that dealing, dealing it another secret fails.
+    pub fn deal_session<R>(
+        &mut self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        x_pows: &Vec<Vec<Scalar>>,
+        session_id: &[u8; 32],
+        secret: &Scalar,
+    ) -> Result<Dealing, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        if let Some((dealt, dealing)) = self.sessions.get(session_id) {
+            return if dealt == secret {
+                Ok(dealing.clone())
+            } else {
+                Err(ConflictingDealing(DEALER).into())
+            };
+        }
+        let dealing = self.deal_secret(rng, hasher, buf, x_pows, secret);
+        self.sessions
+            .insert(*session_id, (*secret, dealing.clone()));
+        Ok(dealing)
+    }
+
+    // The secret and dealing of session `session_id`, if it was dealt.
+    pub fn session(&self, session_id: &[u8; 32]) -> Option<&DealtSession> {
+        self.sessions.get(session_id)
+    }
+
+    // Forgets session `session_id` once it is over.
+    pub fn finish(&mut self, session_id: &[u8; 32]) -> Option<DealtSession> {
+        self.sessions.remove(session_id)
+    }
+
+    // Checks every party's proof of possession, run it before dealing.
+    pub fn verify_possession(
+        &self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        committee: &[u8],
+        proofs: &[(Scalar, Scalar)],
+    ) -> Result<(), Error> {
+        opcount::compressions(self.public_keys.len());
+        let public_keys: Vec<CompressedRistretto> =
+            self.public_keys.iter().map(|pk| pk.compress()).collect();
+        verify_public_keys(hasher, buf, g, committee, &public_keys, proofs).map(|_| ())
+    }
+
+    // A dealing outside of any session, the dealer keeps nothing of it.
     pub fn deal_secret<R>(
         &mut self,
         rng: &mut R,
@@ -40,16 +171,19 @@ impl Dealer {
         buf: &mut [u8; 64],
         x_pows: &Vec<Vec<Scalar>>,
         secret: &Scalar,
-    ) -> (Vec<CompressedRistretto>, (Scalar, Polynomial))
+    ) -> Dealing
     where
         R: CryptoRng + RngCore,
     {
-        self.secret = Some(*secret);
-        let (f_polynomial, f_evals) = self.generate_encrypted_shares(rng, x_pows, secret);
+        let _phase = phase!("deal", n = self.public_keys.len(), t = self.t, k = 1);
+        let ctx = self.ctx.clone();
+        ctx.install_with_rng(rng, |rng| {
+            let (f_polynomial, f_evals) = self.encrypt_shares(rng, x_pows, secret);
 
-        let (d, z) = self.generate_proof(rng, hasher, buf, x_pows, f_polynomial, &f_evals);
+            let (d, z) = self.prove(rng, hasher, buf, x_pows, f_polynomial, &f_evals);
 
-        (f_evals, (d, z))
+            (f_evals, (d, z))
+        })
     }
 
     pub fn generate_encrypted_shares<R>(
@@ -58,12 +192,28 @@ impl Dealer {
         x_pows: &Vec<Vec<Scalar>>,
         secret: &Scalar,
     ) -> (Polynomial, Vec<CompressedRistretto>)
+    where
+        R: CryptoRng,
+    {
+        self.ctx
+            .install_with_rng(rng, |rng| self.encrypt_shares(rng, x_pows, secret))
+    }
+
+    fn encrypt_shares<R>(
+        &self,
+        rng: &mut R,
+        x_pows: &Vec<Vec<Scalar>>,
+        secret: &Scalar,
+    ) -> (Polynomial, Vec<CompressedRistretto>)
     where
         R: CryptoRng,
     {
         let (f_polynomial, f_evals) =
             generate_shares(rng, self.public_keys.len(), self.t, x_pows, secret);
 
+        let _phase = phase!("encrypt", n = self.public_keys.len(), t = self.t, k = 1);
+        opcount::point_muls(self.public_keys.len());
+        opcount::compressions(self.public_keys.len());
         let encrypted_shares = f_evals
             .par_iter()
             .zip(self.public_keys.par_iter())
@@ -85,8 +235,28 @@ impl Dealer {
     where
         R: CryptoRng,
     {
+        self.ctx.install_with_rng(rng, |rng| {
+            self.prove(rng, hasher, buf, x_pows, f_polynomial, f_evals)
+        })
+    }
+
+    fn prove<R>(
+        &self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        x_pows: &Vec<Vec<Scalar>>,
+        f_polynomial: Polynomial,
+        f_evals: &Vec<CompressedRistretto>,
+    ) -> (Scalar, Polynomial)
+    where
+        R: CryptoRng,
+    {
+        let _phase = phase!("prove", n = self.public_keys.len(), t = self.t, k = 1);
         let mut r = Polynomial::sample(self.t, rng);
         let r_evals = r.evaluate_range_precomp(x_pows, 1, self.public_keys.len());
+        opcount::point_muls(self.public_keys.len());
+        opcount::compressions(self.public_keys.len());
 
         let encrypted_r_evals: Vec<CompressedRistretto> = r_evals
             .par_iter()
@@ -94,6 +264,8 @@ impl Dealer {
             .map(|(f_eval, pub_key)| (f_eval * pub_key).compress())
             .collect();
 
+        let _phase = phase!("hash", n = f_evals.len() + encrypted_r_evals.len());
+        opcount::hashes(1, 32 * (f_evals.len() + encrypted_r_evals.len()));
         f_evals
             .iter()
             .chain(encrypted_r_evals.iter())
@@ -0,0 +1,157 @@
+// Transferable proof that a dealer or a party misbehaved in a pi_s session.
+// Evidence is the offending message as its sender signed it, with whatever it takes to
+// rerun the check it failed, so anyone holding the committee and the dealer's key can
+// convince themselves with `verify_evidence` without trusting whoever reported it.
+
+use alloc::boxed::Box;
+
+use blake3::Hasher;
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+use serde::{Deserialize, Serialize};
+
+use common::{
+    committee::Committee,
+    error::Error,
+    report::{ShareCheck, ShareStatus},
+    session::{DEALER, DEALING_ROUND, SHARES_ROUND},
+    utils::batch_decompress_ristretto_points,
+    verification_failure,
+};
+
+use crate::{
+    auditor::{verify_dealing, verify_decryptions},
+    party::{SignedDealing, SignedDecryptedShare},
+};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Evidence {
+    // a dealing whose proof (d, z) doesn't hold, or that can't even be checked
+    Dealing(SignedDealing),
+    // a party's decrypted share that fails its check against the dealing it decrypts,
+    // `check` is that failure as the reporting party saw it
+    Decryption {
+        dealing: SignedDealing,
+        share: Box<SignedDecryptedShare>,
+        check: ShareCheck,
+    },
+}
+
+impl Evidence {
+    // The party the evidence is against, `DEALER` for a dealing.
+    pub fn accused(&self) -> usize {
+        match self {
+            Evidence::Dealing(_) => DEALER,
+            Evidence::Decryption { share, .. } => share.sender,
+        }
+    }
+
+    // The session the misbehavior happened in.
+    pub fn session_id(&self) -> &[u8; 32] {
+        match self {
+            Evidence::Dealing(dealing) => &dealing.session_id,
+            Evidence::Decryption { share, .. } => &share.session_id,
+        }
+    }
+}
+
+// True if `evidence` proves its accused misbehaved: every message in it is signed by its
+// sender for the round it belongs to, and the check it reports fails the same way when
+// rerun. Evidence against an honest sender, or not signed by it, is false.
+pub fn verify_evidence(
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    committee: &Committee,
+    dealer_public_key: &CompressedRistretto,
+    evidence: &Evidence,
+) -> Result<bool, Error> {
+    match evidence {
+        Evidence::Dealing(dealing) => {
+            if !signed_dealing(hasher, buf, committee, dealer_public_key, dealing)? {
+                return Ok(false);
+            }
+            let (encrypted_shares, (d, z)) = &dealing.payload;
+            if encrypted_shares.len() != committee.n
+                || *d == Scalar::ZERO
+                || z.len() != committee.t + 1
+            {
+                return Ok(true);
+            }
+            match batch_decompress_ristretto_points(encrypted_shares) {
+                Ok(points) => Ok(!verify_dealing(
+                    hasher,
+                    buf,
+                    &committee.x_pows,
+                    &committee.points,
+                    (encrypted_shares, &points),
+                    (d, z),
+                )),
+                Err(_) => Ok(true),
+            }
+        }
+        Evidence::Decryption {
+            dealing,
+            share,
+            check,
+        } => {
+            let sender = share.sender;
+            if sender == DEALER
+                || sender > committee.n
+                || share.round != SHARES_ROUND
+                || share.session_id != dealing.session_id
+                || check.index != sender
+            {
+                verification_failure!("malformed_evidence");
+                return Ok(false);
+            }
+            if !signed_dealing(hasher, buf, committee, dealer_public_key, dealing)?
+                || !share.verify(
+                    hasher,
+                    buf,
+                    &committee.g,
+                    &committee.public_keys[sender - 1],
+                )?
+            {
+                return Ok(false);
+            }
+            // a party can't be blamed for a share the dealer never gave it
+            let encrypted_share = match dealing
+                .payload
+                .0
+                .get(sender - 1)
+                .map(|share| share.decompress())
+            {
+                Some(Some(encrypted_share)) => encrypted_share,
+                _ => return Ok(false),
+            };
+            let (decrypted_share, proof) = &share.payload;
+            let decompressed = decrypted_share.decompress();
+            let mut rerun = verify_decryptions(
+                &committee.g,
+                &committee.points[sender - 1..sender],
+                &[encrypted_share],
+                &[decompressed.unwrap_or(RistrettoPoint::default())],
+                &[*proof],
+            )
+            .remove(0);
+            rerun.index = sender;
+            if decompressed.is_none() {
+                rerun.status = ShareStatus::BadDecompression;
+            }
+            Ok(rerun.status != ShareStatus::Valid && rerun == *check)
+        }
+    }
+}
+
+fn signed_dealing(
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    committee: &Committee,
+    dealer_public_key: &CompressedRistretto,
+    dealing: &SignedDealing,
+) -> Result<bool, Error> {
+    if dealing.sender != DEALER || dealing.round != DEALING_ROUND {
+        verification_failure!("malformed_evidence");
+        return Ok(false);
+    }
+    dealing.verify(hasher, buf, &committee.g, dealer_public_key)
+}
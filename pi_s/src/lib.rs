@@ -1,18 +1,49 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod auditor;
 pub mod dealer;
+pub mod evidence;
 pub mod party;
+pub mod reconstruction;
+pub mod session;
 
 #[cfg(test)]
 mod tests {
+    use std::{env, process, sync::Arc};
+
     use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
 
-    use crate::{dealer::Dealer, party::generate_parties};
+    use crate::{
+        auditor::Auditor,
+        dealer::Dealer,
+        evidence::{Evidence, verify_evidence},
+        party::{Party, generate_parties},
+        reconstruction::verify_reconstruction,
+        session::{DealerSession, Message, PartySession},
+    };
 
     use common::{
+        committee::Committee,
+        dealings::{DealingId, Dealings},
+        error::ErrorKind::{KeyStoreError, UnexpectedMessage},
+        fixture::{Fixture, N, T, distribute_public_keys, sessions},
+        keystore::{MemoryKey, SoftToken},
+        message::Signed,
+        pop::prove_possession,
         precompute::gen_powers,
+        random::{random_point, random_scalars},
+        record::{Recorder, replay},
+        report::ShareStatus,
         secret_sharing::{reconstruct_secret_exponent, select_qualified_set},
+        session::{DEALER, DEALING_ROUND, Outgoing, Phase, SHARES_ROUND, Session},
+        snapshot::Vault,
         utils::{compute_lagrange_bases, ingest_public_keys},
     };
 
+    common::committee!(PiS, Party);
+
     #[test]
     fn end_to_end() {
         const N: usize = 128;
@@ -108,6 +139,944 @@ mod tests {
         }
         reconstructed_secrets
             .iter()
-            .for_each(|secret| assert_eq!(g * dealer.secret.unwrap(), *secret));
+            .for_each(|reconstructed| assert_eq!(g * secret, *reconstructed));
+
+        // a light client checks any party's result from the keys and the encrypted shares
+        let (result, proof) = parties[0].prove_reconstruction(&g).unwrap();
+        assert_eq!(result, g * secret);
+        let verify = |rng: &mut _, hasher: &mut _, buf: &mut _, result, proof| {
+            verify_reconstruction(
+                rng,
+                hasher,
+                buf,
+                &g,
+                T,
+                (&public_keys, &encrypted_shares),
+                (&result, &proof),
+            )
+            .unwrap()
+        };
+        assert!(verify(
+            &mut rng,
+            &mut hasher,
+            &mut buf,
+            result,
+            proof.clone()
+        ));
+        assert!(!verify(
+            &mut rng,
+            &mut hasher,
+            &mut buf,
+            result + g,
+            proof.clone()
+        ));
+        let mut forged = proof.clone();
+        forged.openings[3].response += Scalar::ONE;
+        assert!(!verify(&mut rng, &mut hasher, &mut buf, result, forged));
+        let mut short = proof;
+        short.openings.pop();
+        assert!(!verify(&mut rng, &mut hasher, &mut buf, result, short));
+    }
+
+    #[test]
+    fn token_keys() {
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            xpows,
+        } = Fixture::new(N, T);
+
+        // every party's key lives in the token and is only used through its handle
+        let token = SoftToken::new("0000");
+        token.login("0000").unwrap();
+        let mut parties: Vec<Party> = (1..=N)
+            .map(|i| {
+                let key = Arc::new(token.generate_key(&mut rng, &g).unwrap());
+                Party::with_key(key, N, T, i).unwrap()
+            })
+            .collect();
+        let public_keys: Vec<CompressedRistretto> =
+            parties.iter().map(|party| party.public_key.0).collect();
+
+        let mut dealer = Dealer::new(N, T, &public_keys).unwrap();
+        let secret = common::random::random_scalar(&mut rng);
+        let (encrypted_shares, (d, z)) =
+            dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret);
+
+        let (decrypted_shares, share_proofs): (Vec<CompressedRistretto>, Vec<(Scalar, Scalar)>) =
+            parties
+                .iter_mut()
+                .map(|p| {
+                    p.ingest_encrypted_shares(&encrypted_shares).unwrap();
+                    p.ingest_dealer_proof(d, z.clone()).unwrap();
+                    p.decrypt_share().unwrap();
+                    p.dleq_share(&g, &mut rng, &mut hasher, &mut buf).unwrap();
+                    (
+                        p.decrypted_share.unwrap().compress(),
+                        p.share_proof.unwrap(),
+                    )
+                })
+                .collect();
+
+        let p = &mut parties[0];
+        let public_keys: Vec<CompressedRistretto> = public_keys[1..].to_vec();
+        p.public_keys =
+            Some(ingest_public_keys(N, &p.public_key.1, p.index, &public_keys).unwrap());
+        p.ingest_decrypted_shares_and_proofs(&decrypted_shares[1..], share_proofs[1..].to_vec())
+            .unwrap();
+        assert!(p.verify_decrypted_shares(&g).unwrap());
+
+        token.logout();
+        assert!(matches!(
+            p.decrypt_share().err().unwrap().kind(),
+            KeyStoreError(_)
+        ));
+    }
+
+    #[test]
+    fn crash_recovery() {
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            xpows,
+        } = Fixture::new(N, T);
+
+        let mut parties = generate_parties(&g, &mut rng, N, T);
+        let public_keys = distribute_public_keys::<PiS>(&mut parties);
+
+        // the dealer dies right after dealing and comes back with the very same dealing
+        let dir = env::temp_dir();
+        let dealer_vault = Vault::new(
+            &dir.join(format!("pi_s-dealer-{}", process::id())),
+            [7u8; 32],
+            (&[0u8; 32], DEALER),
+        );
+        let mut dealer = Dealer::new(N, T, &public_keys).unwrap();
+        let secret = common::random::random_scalar(&mut rng);
+        let dealing = dealer
+            .deal_session(&mut rng, &mut hasher, &mut buf, &xpows, &[0u8; 32], &secret)
+            .unwrap();
+        dealer.checkpoint(&dealer_vault).unwrap();
+        drop(dealer);
+        let mut dealer = Dealer::resume(&dealer_vault).unwrap().unwrap();
+        let resent = dealer
+            .deal_session(&mut rng, &mut hasher, &mut buf, &xpows, &[0u8; 32], &secret)
+            .unwrap();
+        assert!(resent.0 == dealing.0 && resent.1.0 == dealing.1.0);
+        assert_eq!(dealer.session(&[0u8; 32]).unwrap().0, secret);
+        // the session was dealt already, another secret can't go out in it
+        let other = common::random::random_scalar(&mut rng);
+        assert!(
+            dealer
+                .deal_session(&mut rng, &mut hasher, &mut buf, &xpows, &[0u8; 32], &other)
+                .is_err()
+        );
+        dealer_vault.clear().unwrap();
+
+        let (encrypted_shares, (d, z)) = resent;
+        let (decrypted_shares, share_proofs): (Vec<CompressedRistretto>, Vec<(Scalar, Scalar)>) =
+            parties
+                .iter_mut()
+                .map(|p| {
+                    p.ingest_encrypted_shares(&encrypted_shares).unwrap();
+                    p.ingest_dealer_proof(d, z.clone()).unwrap();
+                    assert!(
+                        p.verify_encrypted_shares(&mut hasher, &mut buf, &xpows)
+                            .unwrap()
+                    );
+                    p.decrypt_share().unwrap();
+                    p.dleq_share(&g, &mut rng, &mut hasher, &mut buf).unwrap();
+                    (
+                        p.decrypted_share.unwrap().compress(),
+                        p.share_proof.unwrap(),
+                    )
+                })
+                .collect();
+
+        // party 1 dies once its proof is out and resumes from its last checkpoint
+        let p = parties.remove(0);
+        let key = p.key.clone();
+        let vault = Vault::from_key_handle(
+            &dir.join(format!("pi_s-party-{}", process::id())),
+            &*key,
+            (&[0u8; 32], p.index),
+        )
+        .unwrap();
+        p.checkpoint(&vault).unwrap();
+        drop(p);
+
+        let other = MemoryKey::generate(&mut rng, &g).shared();
+        assert!(Party::resume(&vault, other).is_err());
+        let mut p = Party::resume(&vault, key).unwrap().unwrap();
+        vault.clear().unwrap();
+
+        // redoing the phase keeps the proof that was sent
+        p.dleq_share(&g, &mut rng, &mut hasher, &mut buf).unwrap();
+        assert_eq!(p.share_proof, Some(share_proofs[0]));
+
+        p.ingest_decrypted_shares_and_proofs(&decrypted_shares[1..], share_proofs[1..].to_vec())
+            .unwrap();
+        assert!(p.verify_decrypted_shares(&g).unwrap());
+
+        // the same dealing again keeps the proof, another share for this party drops it
+        p.ingest_encrypted_shares(&encrypted_shares).unwrap();
+        assert_eq!(p.share_proof, Some(share_proofs[0]));
+        let mut swapped = encrypted_shares.clone();
+        swapped.swap(0, 1);
+        p.ingest_encrypted_shares(&swapped).unwrap();
+        assert!(p.decrypted_share.is_none() && p.share_proof.is_none());
+        p.decrypt_share().unwrap();
+        p.dleq_share(&g, &mut rng, &mut hasher, &mut buf).unwrap();
+        assert_ne!(p.share_proof, Some(share_proofs[0]));
+    }
+
+    #[test]
+    fn committee_dealing() {
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            ..
+        } = Fixture::new(N, T);
+        let label = b"committee";
+
+        let private_keys = random_scalars(&mut rng, N);
+        let public_keys: Vec<CompressedRistretto> =
+            private_keys.iter().map(|sk| (g * sk).compress()).collect();
+        let proofs: Vec<(Scalar, Scalar)> = private_keys
+            .iter()
+            .enumerate()
+            .map(|(i, sk)| prove_possession(&mut rng, &mut hasher, &mut buf, &g, sk, i + 1, label))
+            .collect();
+
+        let committee =
+            Committee::new(&mut hasher, &mut buf, &g, T, label, (&public_keys, &proofs)).unwrap();
+
+        let mut dealer = Dealer::from_committee(&committee);
+        let secret = common::random::random_scalar(&mut rng);
+        let (encrypted_shares, (d, z)) =
+            dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &committee.x_pows, &secret);
+
+        for (i, sk) in private_keys.iter().enumerate() {
+            let key = MemoryKey::import(&g, sk).unwrap().shared();
+            let mut p = Party::from_committee(&committee, key, i + 1).unwrap();
+            p.ingest_encrypted_shares(&encrypted_shares).unwrap();
+            p.ingest_dealer_proof(d, z.clone()).unwrap();
+
+            assert!(
+                p.verify_encrypted_shares(&mut hasher, &mut buf, &committee.x_pows)
+                    .unwrap()
+            );
+        }
+        let key = MemoryKey::import(&g, &private_keys[0]).unwrap().shared();
+        assert!(Party::from_committee(&committee, key, 2).is_err());
+    }
+
+    #[test]
+    fn public_audit() {
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            ..
+        } = Fixture::new(N, T);
+        let label = b"audit";
+
+        let mut parties = generate_parties(&g, &mut rng, N, T);
+        let public_keys: Vec<CompressedRistretto> =
+            parties.iter().map(|party| party.public_key.0).collect();
+        let proofs: Vec<(Scalar, Scalar)> = parties
+            .iter()
+            .map(|p| {
+                p.prove_possession(&mut rng, &mut hasher, &mut buf, &g, label)
+                    .unwrap()
+            })
+            .collect();
+        let committee =
+            Committee::new(&mut hasher, &mut buf, &g, T, label, (&public_keys, &proofs)).unwrap();
+
+        let mut dealer = Dealer::from_committee(&committee);
+        let secret = common::random::random_scalar(&mut rng);
+        let (encrypted_shares, (d, z)) =
+            dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &committee.x_pows, &secret);
+        let (mut decrypted_shares, share_proofs): (
+            Vec<CompressedRistretto>,
+            Vec<(Scalar, Scalar)>,
+        ) = parties
+            .iter_mut()
+            .map(|p| {
+                p.ingest_encrypted_shares(&encrypted_shares).unwrap();
+                p.decrypt_share().unwrap();
+                p.dleq_share(&g, &mut rng, &mut hasher, &mut buf).unwrap();
+                (
+                    p.decrypted_share.unwrap().compress(),
+                    p.share_proof.unwrap(),
+                )
+            })
+            .collect();
+
+        // nothing but the committee and the public messages
+        let auditor = Auditor::new(&committee);
+        let audit = auditor
+            .audit(
+                &mut hasher,
+                &mut buf,
+                (&encrypted_shares, (&d, &z)),
+                Some((&decrypted_shares, &share_proofs)),
+            )
+            .unwrap();
+        assert!(audit.dealing);
+        assert_eq!(audit.report.unwrap().valid(), (0..N).collect::<Vec<_>>());
+        assert_eq!(audit.secret, Some(g * secret));
+
+        // bad decryptions are pinned on their parties with the reason, the others still
+        // reconstruct
+        decrypted_shares.swap(2, 5);
+        decrypted_shares[8] = CompressedRistretto([0xff; 32]);
+        let audit = auditor
+            .audit(
+                &mut hasher,
+                &mut buf,
+                (&encrypted_shares, (&d, &z)),
+                Some((&decrypted_shares, &share_proofs)),
+            )
+            .unwrap();
+        let report = audit.report.unwrap();
+        assert_eq!(
+            report.misbehaving(),
+            vec![
+                (3, ShareStatus::WrongChallenge),
+                (6, ShareStatus::WrongChallenge),
+                (9, ShareStatus::BadDecompression)
+            ]
+        );
+        let (claimed, recomputed) = report.checks[2].challenge.unwrap();
+        assert!(claimed == share_proofs[2].0 && recomputed != claimed);
+        assert_eq!(audit.secret, Some(g * secret));
+
+        // a bad proof fails the dealing
+        let audit = auditor
+            .audit(
+                &mut hasher,
+                &mut buf,
+                (&encrypted_shares, (&(d + Scalar::ONE), &z)),
+                Some((&decrypted_shares, &share_proofs)),
+            )
+            .unwrap();
+        assert!(!audit.dealing && audit.secret.is_none());
+        assert!(
+            auditor
+                .audit(
+                    &mut hasher,
+                    &mut buf,
+                    (&encrypted_shares[1..], (&d, &z)),
+                    None
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn concurrent_dealings() {
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            xpows,
+        } = Fixture::new(N, T);
+
+        let mut parties = generate_parties(&g, &mut rng, N, T);
+        let public_keys = distribute_public_keys::<PiS>(&mut parties);
+        let mut stores: Vec<Dealings<Party>> = parties.into_iter().map(Dealings::new).collect();
+
+        // two dealers, the first one running two sessions
+        let mut dealers: Vec<Dealer> = (0..2)
+            .map(|_| Dealer::new(N, T, &public_keys).unwrap())
+            .collect();
+        let ids: [DealingId; 3] = [(1, [0; 32]), (1, [1; 32]), (2, [0; 32])];
+
+        for id in ids {
+            let secret = common::random::random_scalar(&mut rng);
+            let dealing = dealers[id.0 - 1]
+                .deal_session(&mut rng, &mut hasher, &mut buf, &xpows, &id.1, &secret)
+                .unwrap();
+
+            for store in &mut stores {
+                let (_, p) = store.track(&mut hasher, id, &dealing).unwrap();
+                p.ingest_encrypted_shares(&dealing.0).unwrap();
+                p.ingest_dealer_proof(dealing.1.0, dealing.1.1.clone())
+                    .unwrap();
+            }
+        }
+
+        assert!(dealers[0].session(&[1; 32]).is_some() && dealers[1].session(&[1; 32]).is_none());
+
+        // every party agrees on which dealings it got
+        let digests: Vec<[u8; 32]> = ids
+            .iter()
+            .map(|id| *stores[0].digest(id).unwrap())
+            .collect();
+        assert!(digests[0] != digests[1] && digests[1] != digests[2]);
+
+        for store in &stores {
+            assert_eq!(store.len(), 3);
+            for (id, digest) in ids.iter().zip(&digests) {
+                assert_eq!(store.digest(id), Some(digest));
+                assert!(
+                    store
+                        .get(id)
+                        .unwrap()
+                        .verify_encrypted_shares(&mut hasher, &mut buf, &xpows)
+                        .unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn session_driver() {
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            xpows,
+        } = Fixture::new(N, T);
+
+        let mut parties = generate_parties(&g, &mut rng, N, T);
+        let public_keys = distribute_public_keys::<PiS>(&mut parties);
+
+        let (mut dealer, mut sessions) = sessions(
+            &mut rng,
+            g,
+            &xpows,
+            Dealer::new(N, T, &public_keys).unwrap(),
+            parties,
+        );
+
+        let secret = common::random::random_scalar(&mut rng);
+        let dealing = dealer
+            .deal(&mut rng, &mut hasher, &mut buf, &secret)
+            .unwrap();
+
+        // last in, first out, so most parties see decrypted shares before the dealing
+        let mut in_flight: Vec<(usize, Message)> = vec![];
+        let route = |in_flight: &mut Vec<(usize, Message)>,
+                     from: usize,
+                     outgoing: Vec<Outgoing<Message>>| {
+            for out in outgoing {
+                match out {
+                    Outgoing::Broadcast(m) => (1..=N)
+                        .filter(|i| *i != from)
+                        .for_each(|i| in_flight.push((i, m.clone()))),
+                    Outgoing::To(i, m) => in_flight.push((i, m)),
+                }
+            }
+        };
+        route(&mut in_flight, DEALER, dealing);
+
+        while let Some((to, message)) = in_flight.pop() {
+            let outgoing = sessions[to - 1]
+                .handle(&mut rng, &mut hasher, &mut buf, message)
+                .unwrap();
+            route(&mut in_flight, to, outgoing);
+        }
+
+        for session in &mut sessions {
+            assert_eq!(session.phase(), Phase::Done);
+            assert_eq!(session.outcome(), Some(&(g * secret)));
+        }
+        // every party derives the same keys, its own among them
+        let keys = sessions[0].party.verification_keys().unwrap();
+        assert_eq!(keys.group_key, g * secret);
+        for session in &sessions {
+            let party = &session.party;
+            assert_eq!(party.verification_keys().unwrap(), keys);
+            assert_eq!(keys.key(party.index), party.decrypted_share.as_ref());
+        }
+
+        // nothing is accepted once the session is over
+        let late = dealer
+            .deal(&mut rng, &mut hasher, &mut buf, &secret)
+            .unwrap();
+        let Outgoing::Broadcast(late) = late.into_iter().next().unwrap() else {
+            unreachable!()
+        };
+        assert!(matches!(
+            sessions[0]
+                .handle(&mut rng, &mut hasher, &mut buf, late)
+                .err()
+                .unwrap()
+                .kind(),
+            UnexpectedMessage(DEALER, _)
+        ));
+    }
+
+    #[test]
+    fn stragglers() {
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            xpows,
+        } = Fixture::new(N, T);
+
+        let mut parties = generate_parties(&g, &mut rng, N, T);
+        let public_keys = distribute_public_keys::<PiS>(&mut parties);
+
+        let (mut dealer, mut sessions) = sessions(
+            &mut rng,
+            g,
+            &xpows,
+            Dealer::new(N, T, &public_keys).unwrap(),
+            parties,
+        );
+
+        let secret = common::random::random_scalar(&mut rng);
+        let mut in_flight: Vec<(usize, Message)> = vec![];
+        for out in dealer
+            .deal(&mut rng, &mut hasher, &mut buf, &secret)
+            .unwrap()
+        {
+            let Outgoing::Broadcast(m) = out else {
+                unreachable!()
+            };
+            (1..=N).for_each(|i| in_flight.push((i, m.clone())));
+        }
+        // the decrypted shares of parties past t + 1 are held back
+        let mut held_back: Vec<(usize, Message)> = vec![];
+        while let Some((to, message)) = in_flight.pop() {
+            let outgoing = sessions[to - 1]
+                .handle(&mut rng, &mut hasher, &mut buf, message)
+                .unwrap();
+            for out in outgoing {
+                let Outgoing::Broadcast(m) = out else {
+                    unreachable!()
+                };
+                let queue = match &m {
+                    Message::Reveal(_) if to > T + 1 => &mut held_back,
+                    _ => &mut in_flight,
+                };
+                (1..=N)
+                    .filter(|i| *i != to)
+                    .for_each(|i| queue.push((i, m.clone())));
+            }
+        }
+
+        // everyone reconstructs from the first t + 1 decrypted shares
+        for session in &sessions {
+            assert_eq!(session.phase(), Phase::Done);
+            assert_eq!(session.outcome(), Some(&(g * secret)));
+            let tracker = session.party.tracker.as_ref().unwrap();
+            assert!(tracker.is_sufficient() && !tracker.is_complete());
+        }
+
+        // the stragglers are checked all the same once they show up
+        for (to, message) in held_back {
+            sessions[to - 1]
+                .handle(&mut rng, &mut hasher, &mut buf, message)
+                .unwrap();
+        }
+        for session in &sessions {
+            let tracker = session.party.tracker.as_ref().unwrap();
+            assert!(tracker.is_complete());
+            assert_eq!(tracker.valid().len(), N);
+            assert!(session.evidence.is_empty());
+        }
+    }
+
+    #[test]
+    fn two_dealings() {
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            xpows,
+        } = Fixture::new(N, T);
+
+        let mut parties = generate_parties(&g, &mut rng, N, T);
+        let public_keys = distribute_public_keys::<PiS>(&mut parties);
+        let mut dealer = Dealer::new(N, T, &public_keys).unwrap();
+
+        // the same parties go through a second dealing after the first
+        for _ in 0..2 {
+            let secret = common::random::random_scalar(&mut rng);
+            let (encrypted_shares, (d, z)) =
+                dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret);
+            let shares: Vec<(CompressedRistretto, (Scalar, Scalar))> = parties
+                .iter_mut()
+                .map(|p| {
+                    p.ingest_encrypted_shares(&encrypted_shares).unwrap();
+                    p.ingest_dealer_proof(d, z.clone()).unwrap();
+                    assert!(
+                        p.verify_encrypted_shares(&mut hasher, &mut buf, &xpows)
+                            .unwrap()
+                    );
+                    p.decrypt_share().unwrap();
+                    p.dleq_share(&g, &mut rng, &mut hasher, &mut buf).unwrap();
+                    (
+                        p.decrypted_share.unwrap().compress(),
+                        p.share_proof.unwrap(),
+                    )
+                })
+                .collect();
+
+            for p in &mut parties {
+                for (i, (share, proof)) in shares.iter().enumerate() {
+                    let sufficient = p.verify_decrypted_share_from(&g, i + 1, (share, proof));
+                    assert_eq!(sufficient.unwrap(), i == T);
+                }
+                assert_eq!(p.validated_shares.len(), N);
+                let qualified_set =
+                    select_qualified_set(&mut rng, T, &p.decrypted_shares, &p.validated_shares)
+                        .unwrap();
+                let indices: Vec<usize> = qualified_set.iter().map(|(index, _)| *index).collect();
+                let reconstructed = reconstruct_secret_exponent(
+                    &Some(qualified_set),
+                    &compute_lagrange_bases(&indices),
+                )
+                .unwrap();
+                assert_eq!(reconstructed, g * secret);
+            }
+        }
+    }
+
+    #[test]
+    fn recorded_replay() {
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            xpows,
+        } = Fixture::new(N, T);
+
+        let mut parties = generate_parties(&g, &mut rng, N, T);
+        let public_keys = distribute_public_keys::<PiS>(&mut parties);
+        let recorded_party = parties[0].clone();
+
+        let (mut dealer, sessions) = sessions(
+            &mut rng,
+            g,
+            &xpows,
+            Dealer::new(N, T, &public_keys).unwrap(),
+            parties,
+        );
+        let (dealer_public_key, session_id) =
+            (sessions[0].dealer_public_key, sessions[0].session_id);
+        let mut sessions: Vec<Recorder<PartySession>> =
+            sessions.into_iter().map(Recorder::new).collect();
+
+        let secret = common::random::random_scalar(&mut rng);
+        let mut in_flight: Vec<(usize, Message)> = vec![];
+        for out in dealer
+            .deal(&mut rng, &mut hasher, &mut buf, &secret)
+            .unwrap()
+        {
+            let Outgoing::Broadcast(m) = out else {
+                unreachable!()
+            };
+            (1..=N).for_each(|i| in_flight.push((i, m.clone())));
+        }
+        while let Some((to, message)) = in_flight.pop() {
+            let outgoing = sessions[to - 1]
+                .handle(&mut rng, &mut hasher, &mut buf, message)
+                .unwrap();
+            for out in outgoing {
+                let Outgoing::Broadcast(m) = out else {
+                    unreachable!()
+                };
+                (1..=N)
+                    .filter(|i| *i != to)
+                    .for_each(|i| in_flight.push((i, m.clone())));
+            }
+        }
+        assert_eq!(sessions[0].outcome(), Some(&(g * secret)));
+
+        // the same party replays its run step for step
+        let (_, transcript) = sessions.swap_remove(0).into_parts();
+        let fresh = || {
+            PartySession::new(
+                recorded_party.clone(),
+                g,
+                dealer_public_key,
+                session_id,
+                &xpows,
+            )
+            .unwrap()
+        };
+        let mut replayed = fresh();
+        assert_eq!(replay(&mut replayed, &transcript).unwrap(), None);
+        assert_eq!(replayed.outcome(), Some(&(g * secret)));
+
+        // other randomness in a step that signs something shows up right there
+        let mut altered = transcript.clone();
+        let signing = altered
+            .steps
+            .iter()
+            .position(|step| !step.output.outgoing.is_empty())
+            .unwrap();
+        altered.steps[signing].seed[0] ^= 1;
+        let divergence = replay(&mut fresh(), &altered).unwrap().unwrap();
+        assert_eq!(divergence.step, signing);
+        assert_eq!(divergence.recorded, transcript.steps[signing].output);
+    }
+
+    #[test]
+    fn equivocating_dealer() {
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            xpows,
+        } = Fixture::new(N, T);
+
+        let session_id = [5u8; 32];
+
+        let mut parties = generate_parties(&g, &mut rng, N, T);
+        let public_keys = distribute_public_keys::<PiS>(&mut parties);
+
+        let dealer_key = MemoryKey::generate(&mut rng, &g).shared();
+        let mut sessions: Vec<PartySession> = parties
+            .into_iter()
+            .map(|p| PartySession::new(p, g, dealer_key.public_key().0, session_id, &xpows))
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        // two valid dealings of the same secret, each to half of the parties. A dealer deals
+        // a session once, so the equivocating one runs two of them under the same key.
+        let secret = common::random::random_scalar(&mut rng);
+        let mut in_flight: Vec<(usize, Message)> = vec![];
+        for half in [1..=N / 2, N / 2 + 1..=N] {
+            let mut dealer = DealerSession::new(
+                Dealer::new(N, T, &public_keys).unwrap(),
+                dealer_key.clone(),
+                g,
+                session_id,
+                &xpows,
+            );
+            let dealing = dealer
+                .deal(&mut rng, &mut hasher, &mut buf, &secret)
+                .unwrap();
+            let Some(Outgoing::Broadcast(dealing)) = dealing.into_iter().next() else {
+                unreachable!()
+            };
+            half.for_each(|i| in_flight.push((i, dealing.clone())));
+        }
+
+        while let Some((to, message)) = in_flight.pop() {
+            let outgoing = sessions[to - 1]
+                .handle(&mut rng, &mut hasher, &mut buf, message)
+                .unwrap();
+            for out in outgoing {
+                let Outgoing::Broadcast(m) = out else {
+                    unreachable!()
+                };
+                (1..=N)
+                    .filter(|i| *i != to)
+                    .for_each(|i| in_flight.push((i, m.clone())));
+            }
+        }
+
+        // neither dealing got delivered, so nobody verified or decrypted anything
+        for session in &sessions {
+            assert_eq!(session.phase(), Phase::AwaitingDelivery);
+            assert!(session.party.decrypted_share.is_none());
+        }
+    }
+
+    #[test]
+    fn slashing_evidence() {
+        let Fixture {
+            mut rng,
+            mut hasher,
+            mut buf,
+            g,
+            ..
+        } = Fixture::new(N, T);
+        let label = b"slashing";
+        let session_id = [6u8; 32];
+
+        let parties = generate_parties(&g, &mut rng, N, T);
+        let public_keys: Vec<CompressedRistretto> =
+            parties.iter().map(|party| party.public_key.0).collect();
+        let proofs: Vec<(Scalar, Scalar)> = parties
+            .iter()
+            .map(|p| {
+                p.prove_possession(&mut rng, &mut hasher, &mut buf, &g, label)
+                    .unwrap()
+            })
+            .collect();
+        let committee =
+            Committee::new(&mut hasher, &mut buf, &g, T, label, (&public_keys, &proofs)).unwrap();
+        let parties: Vec<Party> = parties
+            .into_iter()
+            .map(|mut p| {
+                p.public_keys = Some(committee.points.clone());
+                p
+            })
+            .collect();
+
+        let dealer_key = MemoryKey::generate(&mut rng, &g).shared();
+        let dealer_public_key = dealer_key.public_key().0;
+        let mut dealer = DealerSession::new(
+            Dealer::from_committee(&committee),
+            dealer_key.clone(),
+            g,
+            session_id,
+            &committee.x_pows,
+        );
+        let secret = common::random::random_scalar(&mut rng);
+        let Some(Outgoing::Broadcast(Message::Dealing(dealing))) = dealer
+            .deal(&mut rng, &mut hasher, &mut buf, &secret)
+            .unwrap()
+            .pop()
+        else {
+            unreachable!()
+        };
+        // the same dealing with a proof that doesn't hold, signed all the same
+        let (encrypted_shares, (d, z)) = dealing.payload.clone();
+        let forged_dealing = Signed::sign_with(
+            &mut rng,
+            &mut hasher,
+            &mut buf,
+            &g,
+            &*dealer_key,
+            (&session_id, DEALING_ROUND, DEALER),
+            (encrypted_shares, (d + Scalar::ONE, z)),
+        )
+        .unwrap();
+
+        for (dealing, cheater) in [(forged_dealing, None), (dealing.clone(), Some(3))] {
+            let mut sessions: Vec<PartySession> = parties
+                .iter()
+                .map(|p| {
+                    PartySession::new(
+                        p.clone(),
+                        g,
+                        dealer_public_key,
+                        session_id,
+                        &committee.x_pows,
+                    )
+                })
+                .collect::<Result<_, _>>()
+                .unwrap();
+            let mut in_flight: Vec<(usize, Message)> = (1..=N)
+                .map(|i| (i, Message::Dealing(dealing.clone())))
+                .collect();
+            while let Some((to, message)) = in_flight.pop() {
+                let outgoing = sessions[to - 1]
+                    .handle(&mut rng, &mut hasher, &mut buf, message)
+                    .unwrap();
+                for out in outgoing {
+                    let Outgoing::Broadcast(mut m) = out else {
+                        unreachable!()
+                    };
+                    // the cheater publishes some other point as its decrypted share
+                    if let Message::Reveal(share) = &m
+                        && Some(to) == cheater
+                    {
+                        let forged = random_point(&mut rng).compress();
+                        m = Message::Reveal(
+                            Signed::sign_with(
+                                &mut rng,
+                                &mut hasher,
+                                &mut buf,
+                                &g,
+                                &*sessions[to - 1].party.key,
+                                (&session_id, SHARES_ROUND, to),
+                                (forged, share.payload.1),
+                            )
+                            .unwrap(),
+                        );
+                    }
+                    (1..=N)
+                        .filter(|i| *i != to)
+                        .for_each(|i| in_flight.push((i, m.clone())));
+                }
+            }
+
+            for session in sessions.iter().filter(|s| Some(s.party.index) != cheater) {
+                let [evidence] = session.evidence.as_slice() else {
+                    panic!("one piece of evidence expected")
+                };
+                assert_eq!(evidence.accused(), cheater.unwrap_or(DEALER));
+                assert!(
+                    verify_evidence(
+                        &mut hasher,
+                        &mut buf,
+                        &committee,
+                        &dealer_public_key,
+                        evidence
+                    )
+                    .unwrap()
+                );
+            }
+            if cheater.is_none() {
+                assert_eq!(sessions[0].phase(), Phase::Aborted("dealer_proof"));
+                continue;
+            }
+            assert_eq!(sessions[0].outcome(), Some(&(g * secret)));
+
+            // an honest share can't be passed off as a bad one, nor a bad one as something
+            // it isn't
+            let Evidence::Decryption {
+                dealing,
+                share,
+                check,
+            } = sessions[0].evidence[0].clone()
+            else {
+                unreachable!()
+            };
+            assert_eq!(check.status, ShareStatus::WrongChallenge);
+            let honest = sessions[1]
+                .party
+                .sign_decrypted_share(
+                    &mut rng,
+                    &mut hasher,
+                    &mut buf,
+                    &g,
+                    (&session_id, SHARES_ROUND),
+                )
+                .unwrap();
+            let mut check_2 = check.clone();
+            check_2.index = 2;
+            let mut misreported = check.clone();
+            misreported.status = ShareStatus::BadDecompression;
+            for evidence in [
+                Evidence::Dealing(dealing.clone()),
+                Evidence::Decryption {
+                    dealing: dealing.clone(),
+                    share: Box::new(honest),
+                    check: check_2,
+                },
+                Evidence::Decryption {
+                    dealing,
+                    share,
+                    check: misreported,
+                },
+            ] {
+                assert!(
+                    !verify_evidence(
+                        &mut hasher,
+                        &mut buf,
+                        &committee,
+                        &dealer_public_key,
+                        &evidence
+                    )
+                    .unwrap()
+                );
+            }
+        }
     }
 }
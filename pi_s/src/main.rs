@@ -1,11 +1,13 @@
 use common::{
-    precompute::gen_powers,
-    random::random_scalar,
+    committee::Committee,
+    keystore::MemoryKey,
+    pop::prove_possession,
+    random::{random_scalar, random_scalars},
     secret_sharing::{reconstruct_secret_exponent, select_qualified_set},
-    utils::{compute_lagrange_bases, ingest_public_keys},
+    utils::compute_lagrange_bases,
 };
 use curve25519_dalek::{RistrettoPoint, ristretto::CompressedRistretto, scalar::Scalar};
-use pi_s::{dealer::Dealer, party::generate_parties};
+use pi_s::{dealer::Dealer, party::Party};
 
 fn main() {
     const N: usize = 2048;
@@ -17,38 +19,45 @@ fn main() {
 
     let g: RistrettoPoint = RistrettoPoint::mul_base(&random_scalar(&mut rng));
 
-    let xpows = gen_powers(N, T);
-
-    let mut parties = generate_parties(&g, &mut rng, N, T);
+    let label = b"pi_s example committee";
 
+    let private_keys = random_scalars(&mut rng, N);
     let public_keys: Vec<CompressedRistretto> =
-        parties.iter().map(|party| party.public_key.0).collect();
-
-    let mut dealer = Dealer::new(N, T, &public_keys).unwrap();
-
-    let public_keys: Vec<CompressedRistretto> =
-        parties.iter().map(|party| party.public_key.0).collect();
-
-    for party in &mut parties {
-        let public_keys: Vec<CompressedRistretto> = public_keys
-            .iter()
-            .filter(|pk| &party.public_key.0 != *pk)
-            .copied()
-            .collect();
-
-        party.public_keys =
-            Some(ingest_public_keys(N, &party.public_key.1, party.index, &public_keys).unwrap());
-    }
+        private_keys.iter().map(|sk| (g * sk).compress()).collect();
+    let proofs: Vec<(Scalar, Scalar)> = private_keys
+        .iter()
+        .enumerate()
+        .map(|(i, sk)| prove_possession(&mut rng, &mut hasher, &mut buf, &g, sk, i + 1, label))
+        .collect();
+
+    let committee =
+        Committee::new(&mut hasher, &mut buf, &g, T, label, (&public_keys, &proofs)).unwrap();
+    let xpows = &committee.x_pows;
+
+    let mut dealer = Dealer::from_committee(&committee);
+
+    let mut parties: Vec<Party> = private_keys
+        .iter()
+        .enumerate()
+        .map(|(i, sk)| {
+            Party::from_committee(
+                &committee,
+                MemoryKey::import(&g, sk).unwrap().shared(),
+                i + 1,
+            )
+            .unwrap()
+        })
+        .collect();
     let secret = random_scalar(&mut rng);
     let (encrypted_shares, (d, z)) =
-        dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret);
+        dealer.deal_secret(&mut rng, &mut hasher, &mut buf, xpows, &secret);
 
     for p in &mut parties {
         p.ingest_encrypted_shares(&encrypted_shares).unwrap();
         p.ingest_dealer_proof(d, z.clone()).unwrap();
 
         let res = p
-            .verify_encrypted_shares(&mut hasher, &mut buf, &xpows)
+            .verify_encrypted_shares(&mut hasher, &mut buf, xpows)
             .unwrap();
 
         assert!(res, "encrypted share verification failure");
@@ -1,22 +1,49 @@
+use alloc::{format, vec, vec::Vec};
 use blake3::Hasher;
-use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto};
+use curve25519_dalek::{RistrettoPoint, Scalar, ristretto::CompressedRistretto, traits::Identity};
 
 use rand::{CryptoRng, RngCore};
-use zeroize::Zeroize;
+use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "std")]
+use common::snapshot::Vault;
+
+use crate::{
+    auditor::{verify_dealing, verify_decryptions},
+    reconstruction::{ReconstructionProof, prove_reconstruction},
+};
 use common::{
+    committee::Committee,
+    dealings::transcript_digest,
     error::{
         Error,
-        ErrorKind::{CountMismatch, InvalidPararmeterSet, InvalidProof, UninitializedValue},
+        ErrorKind::{
+            CountMismatch, DuplicateMessage, ForeignPublicKey, InsufficientShares,
+            InvalidPararmeterSet, InvalidProof, UninitializedValue,
+        },
     },
+    execution::ExecutionContext,
+    keys::VerificationKeys,
+    keystore::{MemoryKey, SharedKey},
+    message::{Signed, open_all},
+    opcount, phase,
     polynomial::Polynomial,
-    secret_sharing::decrypt_share,
+    pop::prove_key_possession,
+    report::{ShareCheck, ShareStatus, ShareTracker, VerificationReport},
+    session::DEALER,
     utils::batch_decompress_ristretto_points,
+    verification_failure, verified,
 };
-use rayon::prelude::*;
 
+// (encrypted shares, (d, z)) as signed by the dealer
+pub type SignedDealing = Signed<(Vec<CompressedRistretto>, (Scalar, Polynomial))>;
+// (decrypted share, DLEQ proof) as signed by its party
+pub type SignedDecryptedShare = Signed<(CompressedRistretto, (Scalar, Scalar))>;
+
+#[derive(Clone)]
 pub struct Party {
-    pub private_key: Scalar,
+    // the private key never leaves it
+    pub key: SharedKey,
     pub public_key: (CompressedRistretto, RistrettoPoint),
     pub index: usize,
     pub n: usize,
@@ -33,6 +60,29 @@ pub struct Party {
     pub decrypted_shares: Option<Vec<RistrettoPoint>>,
     pub share_proofs: Option<Vec<(Scalar, Scalar)>>,
     pub validated_shares: Vec<usize>,
+    // the running valid set while decrypted shares are checked on arrival
+    pub tracker: Option<ShareTracker>,
+    pub qualified_set: Option<Vec<(usize, RistrettoPoint)>>,
+    pub ctx: ExecutionContext,
+}
+
+// Everything a party holds but its key and execution context.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PartySnapshot {
+    pub public_key: CompressedRistretto,
+    pub index: usize,
+    pub n: usize,
+    pub t: usize,
+    pub share_proof: Option<(Scalar, Scalar)>,
+    pub encrypted_share: Option<RistrettoPoint>,
+    pub decrypted_share: Option<RistrettoPoint>,
+    pub dealer_proof: Option<(Scalar, Polynomial)>,
+    pub public_keys: Option<Vec<RistrettoPoint>>,
+    pub encrypted_shares: Option<Vec<CompressedRistretto>>,
+    pub decrypted_shares: Option<Vec<RistrettoPoint>>,
+    pub share_proofs: Option<Vec<(Scalar, Scalar)>>,
+    pub validated_shares: Vec<usize>,
+    pub tracker: Option<ShareTracker>,
     pub qualified_set: Option<Vec<(usize, RistrettoPoint)>>,
 }
 
@@ -47,13 +97,15 @@ impl Party {
     where
         R: CryptoRng + RngCore,
     {
-        let private_key = common::random::random_scalar(rng);
-        let public_key = g * private_key;
+        Self::with_key(MemoryKey::generate(rng, g).shared(), n, t, index)
+    }
 
-        if index <= n && t < n && t as f32 == ((n - 1) as f32 / 2.0).floor() {
+    // A party around an existing key.
+    pub fn with_key(key: SharedKey, n: usize, t: usize, index: usize) -> Result<Self, Error> {
+        if index <= n && t < n && t == (n - 1) / 2 {
             Ok(Self {
-                private_key,
-                public_key: (public_key.compress(), public_key),
+                public_key: *key.public_key(),
+                key,
                 index,
                 n,
                 t,
@@ -66,43 +118,181 @@ impl Party {
                 decrypted_shares: None,
                 public_keys: None,
                 validated_shares: vec![],
+                tracker: None,
                 qualified_set: None,
+                ctx: ExecutionContext::default(),
             })
         } else {
             Err(InvalidPararmeterSet(n, t as isize, index).into())
         }
     }
 
+    pub fn from_committee(
+        committee: &Committee,
+        key: SharedKey,
+        index: usize,
+    ) -> Result<Self, Error> {
+        committee.check_key(&key.public_key().1, index)?;
+
+        Ok(Self {
+            public_key: (
+                committee.public_keys[index - 1],
+                committee.points[index - 1],
+            ),
+            key,
+            index,
+            n: committee.n,
+            t: committee.t,
+            dealer_proof: None,
+            encrypted_share: None,
+            decrypted_share: None,
+            share_proof: None,
+            share_proofs: None,
+            encrypted_shares: None,
+            decrypted_shares: None,
+            public_keys: Some(committee.points.clone()),
+            validated_shares: vec![],
+            tracker: None,
+            qualified_set: None,
+            ctx: ExecutionContext::default(),
+        })
+    }
+
+    pub fn with_context(mut self, ctx: ExecutionContext) -> Self {
+        self.ctx = ctx;
+        self
+    }
+
+    pub fn snapshot(&self) -> PartySnapshot {
+        PartySnapshot {
+            public_key: self.public_key.0,
+            index: self.index,
+            n: self.n,
+            t: self.t,
+            share_proof: self.share_proof,
+            encrypted_share: self.encrypted_share,
+            decrypted_share: self.decrypted_share,
+            dealer_proof: self.dealer_proof.clone(),
+            public_keys: self.public_keys.clone(),
+            encrypted_shares: self.encrypted_shares.as_ref().map(|(c, _)| c.clone()),
+            decrypted_shares: self.decrypted_shares.clone(),
+            share_proofs: self.share_proofs.clone(),
+            validated_shares: self.validated_shares.clone(),
+            tracker: self.tracker.clone(),
+            qualified_set: self.qualified_set.clone(),
+        }
+    }
+
+    // Picks a party back up from its snapshot, `key` must be the key it was taken with.
+    pub fn restore(snapshot: PartySnapshot, key: SharedKey) -> Result<Self, Error> {
+        if key.public_key().0 != snapshot.public_key {
+            return Err(ForeignPublicKey(snapshot.index).into());
+        }
+        let mut party = Self::with_key(key, snapshot.n, snapshot.t, snapshot.index)?;
+        party.public_keys = snapshot.public_keys;
+        if let Some(encrypted_shares) = snapshot.encrypted_shares {
+            party.ingest_encrypted_shares(&encrypted_shares)?;
+        }
+        party.dealer_proof = snapshot.dealer_proof;
+        party.encrypted_share = snapshot.encrypted_share;
+        party.decrypted_share = snapshot.decrypted_share;
+        party.share_proof = snapshot.share_proof;
+        party.decrypted_shares = snapshot.decrypted_shares;
+        party.share_proofs = snapshot.share_proofs;
+        party.validated_shares = snapshot.validated_shares;
+        party.tracker = snapshot.tracker;
+        party.qualified_set = snapshot.qualified_set;
+        Ok(party)
+    }
+
+    // Saves the party's state, call it after each phase and before sending what the phase
+    // produced.
+    #[cfg(feature = "std")]
+    pub fn checkpoint(&self, vault: &Vault) -> Result<(), Error> {
+        vault.save(&self.snapshot())
+    }
+
+    // The party as last checkpointed in `vault`, `None` if it never was.
+    #[cfg(feature = "std")]
+    pub fn resume(vault: &Vault, key: SharedKey) -> Result<Option<Self>, Error> {
+        match vault.load()? {
+            Some(snapshot) => Self::restore(snapshot, key).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // Proof of knowledge of our private key for registering `public_key` under `index`.
+    pub fn prove_possession<R>(
+        &self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        committee: &[u8],
+    ) -> Result<(Scalar, Scalar), Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        prove_key_possession(rng, hasher, buf, g, &*self.key, self.index, committee)
+    }
+
     pub fn ingest_encrypted_shares(
         &mut self,
         encrypted_shares: &[CompressedRistretto],
     ) -> Result<(), Error> {
-        if encrypted_shares.len() == self.n {
-            match batch_decompress_ristretto_points(encrypted_shares) {
-                Ok(enc_shares) => {
-                    self.encrypted_share = Some(enc_shares[self.index - 1]);
-                    self.encrypted_shares = Some((encrypted_shares.to_vec(), enc_shares));
-                    Ok(())
-                }
-                Err(x) => Err(x),
-            }
-        } else {
-            Err(CountMismatch(
+        let _phase = phase!("ingest", n = self.n, t = self.t, k = 1);
+        if encrypted_shares.len() != self.n {
+            return Err(CountMismatch(
                 self.n,
                 "parties",
                 encrypted_shares.len(),
                 "encrypted shares",
             )
-            .into())
+            .into());
+        }
+        let enc_shares = self
+            .ctx
+            .install(|| batch_decompress_ristretto_points(encrypted_shares))?;
+        let encrypted_share = enc_shares[self.index - 1];
+        // a decryption and its proof belong to the share they were made for
+        if self.encrypted_share != Some(encrypted_share) {
+            self.decrypted_share = None;
+            self.share_proof = None;
         }
+        if self
+            .encrypted_shares
+            .as_ref()
+            .is_some_and(|(shares, _)| shares.as_slice() != encrypted_shares)
+        {
+            self.forget_dealing();
+        }
+        self.encrypted_share = Some(encrypted_share);
+        self.encrypted_shares = Some((encrypted_shares.to_vec(), enc_shares));
+        Ok(())
+    }
+
+    // Drops what we verified of the other parties' shares, it belongs to the previous dealing.
+    fn forget_dealing(&mut self) {
+        self.tracker = None;
+        self.decrypted_shares = None;
+        self.share_proofs = None;
+        self.validated_shares.clear();
+        self.qualified_set = None;
     }
 
     pub fn ingest_dealer_proof(&mut self, d: Scalar, z: Polynomial) -> Result<(), Error> {
+        let _phase = phase!("ingest", n = self.n, t = self.t, k = 1);
         if d == Scalar::ZERO {
+            verification_failure!("malformed_proof");
             Err(InvalidProof(format!("d == {d:?}",)).into())
         } else if z.len() != self.t + 1 {
+            verification_failure!("malformed_proof");
             Err(InvalidProof(format!("z len: {}, t: {}", z.len(), self.t + 1)).into())
         } else {
+            // the tracker derives its batch challenge from `d`
+            if self.dealer_proof.as_ref().is_some_and(|(old, _)| *old != d) {
+                self.forget_dealing();
+            }
             self.dealer_proof = Some((d, z));
             Ok(())
         }
@@ -112,71 +302,161 @@ impl Party {
         decrypted_shares: &[CompressedRistretto],
         proofs: Vec<(Scalar, Scalar)>,
     ) -> Result<(), Error> {
-        if decrypted_shares.len() == self.n - 1 {
-            if proofs.len() == decrypted_shares.len() {
-                match batch_decompress_ristretto_points(decrypted_shares) {
-                    Ok(mut dec_shares) => match (self.decrypted_share, self.share_proof) {
-                        (Some(own_dec_share), Some(own_proof)) => {
-                            dec_shares.insert(self.index - 1, own_dec_share);
-                            self.decrypted_shares = Some(dec_shares);
-                            let mut proofs = proofs;
-                            proofs.insert(self.index - 1, own_proof);
-                            self.share_proofs = Some(proofs);
-                            Ok(())
-                        }
-                        (None, Some(_)) => Err(UninitializedValue("party.decrypted_share").into()),
-                        (Some(_), None) => Err(UninitializedValue("party.share_proof").into()),
-                        (None, None) => {
-                            Err(UninitializedValue("party.{decrypted_share, share_proof}").into())
-                        }
-                    },
-                    Err(x) => Err(x),
+        let _phase = phase!("ingest", n = self.n, t = self.t, k = 1);
+        self.ctx.install(|| {
+            if decrypted_shares.len() == self.n - 1 {
+                if proofs.len() == decrypted_shares.len() {
+                    match batch_decompress_ristretto_points(decrypted_shares) {
+                        Ok(mut dec_shares) => match (self.decrypted_share, self.share_proof) {
+                            (Some(own_dec_share), Some(own_proof)) => {
+                                dec_shares.insert(self.index - 1, own_dec_share);
+                                self.decrypted_shares = Some(dec_shares);
+                                let mut proofs = proofs;
+                                proofs.insert(self.index - 1, own_proof);
+                                self.share_proofs = Some(proofs);
+                                Ok(())
+                            }
+                            (None, Some(_)) => {
+                                Err(UninitializedValue("party.decrypted_share").into())
+                            }
+                            (Some(_), None) => Err(UninitializedValue("party.share_proof").into()),
+                            (None, None) => {
+                                Err(UninitializedValue("party.{decrypted_share, share_proof}")
+                                    .into())
+                            }
+                        },
+                        Err(x) => Err(x),
+                    }
+                } else {
+                    Err(CountMismatch(self.n, "parties", proofs.len(), "proofs").into())
                 }
             } else {
-                Err(CountMismatch(self.n, "parties", proofs.len(), "proofs").into())
+                Err(CountMismatch(
+                    self.n,
+                    "parties",
+                    decrypted_shares.len(),
+                    "decrypted shares",
+                )
+                .into())
             }
-        } else {
-            Err(CountMismatch(
-                self.n,
-                "parties",
-                decrypted_shares.len(),
-                "decrypted shares",
-            )
-            .into())
+        })
+    }
+
+    // Opens a dealing signed under `dealer_public_key` and ingests it.
+    pub fn ingest_signed_dealing(
+        &mut self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        dealer_public_key: &CompressedRistretto,
+        session: (&[u8; 32], u64),
+        dealing: SignedDealing,
+    ) -> Result<(), Error> {
+        let (encrypted_shares, (d, z)) =
+            dealing.open(hasher, buf, g, dealer_public_key, session)?;
+        self.ingest_encrypted_shares(&encrypted_shares)?;
+        self.ingest_dealer_proof(d, z)
+    }
+
+    // Signs this party's decrypted share and proof for the other parties.
+    pub fn sign_decrypted_share<R>(
+        &self,
+        rng: &mut R,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        session: (&[u8; 32], u64),
+    ) -> Result<SignedDecryptedShare, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        match (&self.decrypted_share, &self.share_proof) {
+            (Some(decrypted_share), Some(share_proof)) => Signed::sign_with(
+                rng,
+                hasher,
+                buf,
+                g,
+                &*self.key,
+                (session.0, session.1, self.index),
+                (decrypted_share.compress(), *share_proof),
+            ),
+            (None, _) => Err(UninitializedValue("party.decrypted_share").into()),
+            (_, None) => Err(UninitializedValue("party.share_proof").into()),
         }
     }
 
+    // `ingest_decrypted_shares_and_proofs` for signed messages, each placed by its sender
+    // rather than by its position in `messages`.
+    pub fn ingest_signed_decrypted_shares_and_proofs(
+        &mut self,
+        hasher: &mut Hasher,
+        buf: &mut [u8; 64],
+        g: &RistrettoPoint,
+        session: (&[u8; 32], u64),
+        messages: Vec<SignedDecryptedShare>,
+    ) -> Result<(), Error> {
+        match &self.public_keys {
+            Some(public_keys) => {
+                opcount::compressions(public_keys.len());
+                let public_keys: Vec<CompressedRistretto> =
+                    public_keys.iter().map(|pk| pk.compress()).collect();
+                let senders: Vec<usize> = (1..=self.n).filter(|i| *i != self.index).collect();
+
+                let (decrypted_shares, proofs): (Vec<_>, Vec<_>) =
+                    open_all(hasher, buf, g, &public_keys, session, &senders, messages)?
+                        .into_iter()
+                        .unzip();
+                self.ingest_decrypted_shares_and_proofs(&decrypted_shares, proofs)
+            }
+            None => Err(UninitializedValue("party.public_keys").into()),
+        }
+    }
+
+    // Transcript digest of the ingested dealing, as the dealer published it in `session_id`.
+    pub fn transcript_digest(
+        &self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+    ) -> Result<[u8; 32], Error> {
+        match (&self.encrypted_shares, &self.dealer_proof) {
+            (Some((encrypted_shares, _)), Some((d, z))) => Ok(transcript_digest(
+                hasher,
+                &(DEALER, *session_id),
+                &(encrypted_shares, (d, z)),
+            )),
+            (None, _) => Err(UninitializedValue("party.encrypted_shares").into()),
+            (_, None) => Err(UninitializedValue("party.dealer_proof").into()),
+        }
+    }
+
+    // True if the ingested dealing is the one whose digest was reliably broadcast.
+    pub fn verify_delivery(
+        &self,
+        hasher: &mut Hasher,
+        session_id: &[u8; 32],
+        delivered: &[u8; 32],
+    ) -> Result<bool, Error> {
+        let digest = self.transcript_digest(hasher, session_id)?;
+        Ok(verified!(digest == *delivered, "undelivered_dealing"))
+    }
+
     pub fn verify_encrypted_shares(
         &self,
         hasher: &mut Hasher,
         buf: &mut [u8; 64],
         xpows: &Vec<Vec<Scalar>>,
     ) -> Result<bool, Error> {
-        match &self.dealer_proof {
+        let _phase = phase!("verify", n = self.n, t = self.t, k = 1);
+        self.ctx.install(|| match &self.dealer_proof {
             Some((d, z)) => match (&self.encrypted_shares, &self.public_keys) {
-                (Some(encrypted_shares), Some(public_keys)) => {
-                    let z_evals = z.evaluate_range_precomp(xpows, 1, self.n);
-
-                    let shares: Vec<CompressedRistretto> = z_evals
-                        .par_iter()
-                        .zip(public_keys)
-                        .zip(encrypted_shares.1.par_iter())
-                        .map(|((z_eval, pub_key), enc_share)| {
-                            (z_eval * pub_key - (enc_share * d)).compress()
-                        })
-                        .collect();
-
-                    encrypted_shares.0.iter().chain(&shares).for_each(|x| {
-                        hasher.update(x.as_bytes());
-                    });
-
-                    hasher.finalize_xof().fill(buf);
-                    let reconstructed_d = Scalar::from_bytes_mod_order_wide(buf);
-
-                    hasher.reset();
-                    buf.zeroize();
-                    Ok(*d == reconstructed_d)
-                }
+                (Some(encrypted_shares), Some(public_keys)) => Ok(verify_dealing(
+                    hasher,
+                    buf,
+                    xpows,
+                    public_keys,
+                    (&encrypted_shares.0, &encrypted_shares.1),
+                    (d, z),
+                )),
                 (Some(_), None) => Err(UninitializedValue("party.public_keys").into()),
                 (None, Some(_)) => Err(UninitializedValue("party.encrypted_shares").into()),
                 (None, None) => {
@@ -184,16 +464,17 @@ impl Party {
                 }
             },
             None => Err(UninitializedValue("party.dealer_proof").into()),
-        }
+        })
     }
     pub fn decrypt_share(&mut self) -> Result<(), Error> {
-        match &self.encrypted_share {
+        let _phase = phase!("decrypt", n = self.n, t = self.t, k = 1);
+        self.ctx.install(|| match &self.encrypted_share {
             Some(encrypted_share) => {
-                self.decrypted_share = Some(decrypt_share(&self.private_key, encrypted_share));
+                self.decrypted_share = self.key.decrypt(&[*encrypted_share])?.pop();
                 Ok(())
             }
             None => Err(UninitializedValue("party.encrypted_share").into()),
-        }
+        })
     }
     pub fn dleq_share<R>(
         &mut self,
@@ -205,26 +486,26 @@ impl Party {
     where
         R: CryptoRng + RngCore,
     {
+        let _phase = phase!("prove", n = self.n, t = self.t, k = 1);
+        // a resumed party resends the proof it may already have sent, so what it sends is what
+        // its transcript holds. A second proof with a fresh nonce would leak nothing, only a
+        // nonce reused under another challenge gives the key away.
+        if self.share_proof.is_some() {
+            return Ok(());
+        }
         match (&self.decrypted_share, &self.encrypted_share) {
             (Some(decrypted_share), Some(encrypted_share)) => {
-                let r = common::random::random_scalar(rng);
-                let c1 = (g * &r).compress();
-                let c2 = (decrypted_share * r).compress();
-
-                hasher.update(self.public_key.0.as_bytes());
-                hasher.update(encrypted_share.compress().as_bytes());
-                hasher.update(c1.as_bytes());
-                hasher.update(c2.as_bytes());
-
-                hasher.finalize_xof().fill(buf);
-
-                let d = Scalar::from_bytes_mod_order_wide(buf);
-                let z = r + d * self.private_key;
-
-                self.share_proof = Some((d, z));
-
-                hasher.reset();
-                buf.zeroize();
+                opcount::point_muls(2);
+                opcount::scalar_muls(1);
+                opcount::compressions(3);
+                opcount::hashes(1, 4 * 32);
+                self.share_proof = Some(self.key.prove_dleq(
+                    rng,
+                    hasher,
+                    buf,
+                    g,
+                    (decrypted_share, &encrypted_share.compress()),
+                )?);
 
                 Ok(())
             }
@@ -237,59 +518,148 @@ impl Party {
     }
 
     pub fn verify_decrypted_shares(&mut self, g: &RistrettoPoint) -> Result<bool, Error> {
-        match (&self.public_keys, &self.encrypted_shares) {
-            (Some(public_keys), Some(enc_shares)) => {
-                match (&self.decrypted_shares, &self.share_proofs) {
-                    (Some(dec_shares), Some(proofs)) => {
-                        self.validated_shares = dec_shares
-                            .par_iter()
-                            .zip(
-                                proofs
-                                    .par_iter()
-                                    .zip(public_keys.par_iter().zip(enc_shares.1.par_iter())),
-                            )
-                            .enumerate()
-                            .map_init(
-                                ||(blake3::Hasher::new(), [0u8;64]), | (hasher, buf),
-                                (i, (dec_share, ((d, z), (public_key, enc_share)))) | {
-                                    let num1 =  g * z;
-                                    let num2 = dec_share * z;
-
-                                    let denom1 = public_key * d;
-                                    let denom2 = enc_share * d;
-
-                                    hasher.update(public_key.compress().as_bytes());
-                                    hasher.update(enc_share.compress().as_bytes());
-                                    hasher.update((num1 - denom1).compress().as_bytes());
-                                    hasher.update((num2 - denom2).compress().as_bytes());
-                                    hasher.finalize_xof().fill(buf);
-
-                                    let reconstructed_d = Scalar::from_bytes_mod_order_wide(buf);
-
-                                    hasher.reset();
-                                    buf.zeroize();
-
-                                    if *d == reconstructed_d {
-                                        Some(i)
-                                    } else {
-                                        None
-                                    }
-                                },
-                            ).filter(Option::is_some).map(|res| res.unwrap()).collect();
-                        Ok(self.validated_shares.len() > self.t)
-                    }
-                    (None, Some(_)) => Err(UninitializedValue("party.decrypted_shares").into()),
-                    (Some(_), None) => Err(UninitializedValue("party.share_proofs").into()),
-                    (None, None) => {
-                        Err(UninitializedValue("party.{decrypted_shares, share_proofs}").into())
+        let report = self.decrypted_share_report(g)?;
+        self.validated_shares = report.valid();
+        Ok(verified!(report.is_sufficient(), "insufficient_shares"))
+    }
+
+    // Checks party `index`'s (decrypted share, DLEQ proof) as it arrives, ours included, and
+    // keeps it with the others in `decrypted_shares` and `share_proofs` if it holds. Parties
+    // that haven't sent, or whose share failed, have the identity there, and a second message
+    // from a party is an error. True once, when more than t of them hold, see `ShareTracker`.
+    pub fn verify_decrypted_share_from(
+        &mut self,
+        g: &RistrettoPoint,
+        index: usize,
+        share: (&CompressedRistretto, &(Scalar, Scalar)),
+    ) -> Result<bool, Error> {
+        let _phase = phase!("verify", n = self.n, t = self.t, k = 1);
+        if index == 0 || index > self.n {
+            return Err(InvalidPararmeterSet(self.n, self.t as isize, index).into());
+        }
+        if self
+            .tracker
+            .as_ref()
+            .is_some_and(|tracker| tracker.check(index).is_some())
+        {
+            return Err(DuplicateMessage(index).into());
+        }
+        let (decrypted_share, proof) = share;
+        let (check, dec_share) = match (&self.public_keys, &self.encrypted_shares) {
+            (Some(public_keys), Some(enc_shares)) => match decrypted_share.decompress() {
+                Some(dec_share) => {
+                    let mut check = verify_decryptions(
+                        g,
+                        &public_keys[index - 1..index],
+                        &enc_shares.1[index - 1..index],
+                        &[dec_share],
+                        core::slice::from_ref(proof),
+                    )
+                    .remove(0);
+                    check.index = index;
+                    (check, Some(dec_share))
+                }
+                None => {
+                    verification_failure!("point_decompression");
+                    (
+                        ShareCheck::checked(index, false, ShareStatus::BadDecompression),
+                        None,
+                    )
+                }
+            },
+            (None, _) => return Err(UninitializedValue("party.public_keys").into()),
+            (_, None) => return Err(UninitializedValue("party.encrypted_shares").into()),
+        };
+
+        let valid = check.status == ShareStatus::Valid;
+        let (n, t, d) = (self.n, self.t, self.dealer_proof.as_ref().map(|(d, _)| *d));
+        let tracker = self
+            .tracker
+            .get_or_insert_with(|| ShareTracker::new(n, t, d));
+        let sufficient = tracker.record(check)?;
+        self.validated_shares = tracker.valid();
+        // only shares that hold are kept, the others stay placeholders
+        if let (true, Some(dec_share)) = (valid, dec_share) {
+            self.decrypted_shares
+                .get_or_insert_with(|| vec![RistrettoPoint::identity(); self.n])[index - 1] =
+                dec_share;
+            self.share_proofs
+                .get_or_insert_with(|| vec![(Scalar::ZERO, Scalar::ZERO); self.n])[index - 1] =
+                *proof;
+        }
+        Ok(sufficient)
+    }
+
+    // Every party's decrypted share with its status, see `VerificationReport`.
+    pub fn decrypted_share_report(&self, g: &RistrettoPoint) -> Result<VerificationReport, Error> {
+        let _phase = phase!("verify", n = self.n, t = self.t, k = 1);
+        self.ctx
+            .install(|| match (&self.public_keys, &self.encrypted_shares) {
+                (Some(public_keys), Some(enc_shares)) => {
+                    match (&self.decrypted_shares, &self.share_proofs) {
+                        (Some(dec_shares), Some(proofs)) => Ok(VerificationReport::new(
+                            self.n,
+                            self.t,
+                            self.dealer_proof.as_ref().map(|(d, _)| *d),
+                            verify_decryptions(g, public_keys, &enc_shares.1, dec_shares, proofs),
+                        )),
+                        (None, Some(_)) => Err(UninitializedValue("party.decrypted_shares").into()),
+                        (Some(_), None) => Err(UninitializedValue("party.share_proofs").into()),
+                        (None, None) => {
+                            Err(UninitializedValue("party.{decrypted_shares, share_proofs}").into())
+                        }
                     }
                 }
+                (None, Some(_)) => Err(UninitializedValue("party.encrypted_shares").into()),
+                (Some(_), None) => Err(UninitializedValue("party.public_keys").into()),
+                (None, None) => {
+                    Err(UninitializedValue("party.{public_keys, encrypted_shares}").into())
+                }
+            })
+    }
+
+    // The group public key and every party's verification key, interpolated from the
+    // first t + 1 validated decrypted shares, each of which is g^f(i) already.
+    pub fn verification_keys(&self) -> Result<VerificationKeys, Error> {
+        match &self.decrypted_shares {
+            Some(dec_shares) if self.validated_shares.len() > self.t => {
+                let shares: Vec<(usize, RistrettoPoint)> = self
+                    .validated_shares
+                    .iter()
+                    .take(self.t + 1)
+                    .map(|i| (i + 1, dec_shares[*i]))
+                    .collect();
+                Ok(VerificationKeys::from_shares(&shares, self.n))
             }
-            (None, Some(_)) => Err(UninitializedValue("party.encrypted_shares").into()),
-            (Some(_), None) => Err(UninitializedValue("party.public_keys").into()),
-            (None, None) => Err(UninitializedValue("party.{public_keys, encrypted_shares}").into()),
+            Some(_) => Err(InsufficientShares(self.validated_shares.len(), self.t).into()),
+            None => Err(UninitializedValue("party.decrypted_shares").into()),
         }
     }
+
+    // g^secret reconstructed from the qualified set, with a proof for light clients, see
+    // `reconstruction`.
+    pub fn prove_reconstruction(
+        &self,
+        g: &RistrettoPoint,
+    ) -> Result<(RistrettoPoint, ReconstructionProof), Error> {
+        let _phase = phase!("reconstruct", t = self.t);
+        self.ctx.install(|| {
+            match (
+                &self.public_keys,
+                &self.encrypted_shares,
+                &self.qualified_set,
+                &self.share_proofs,
+            ) {
+                (Some(public_keys), Some(enc_shares), Some(qualified_set), Some(proofs)) => Ok(
+                    prove_reconstruction(g, public_keys, &enc_shares.1, qualified_set, proofs),
+                ),
+                _ => Err(UninitializedValue(
+                    "party.{public_keys, encrypted_shares, qualified_set, share_proofs}",
+                )
+                .into()),
+            }
+        })
+    }
 }
 
 pub fn generate_parties<R>(g: &RistrettoPoint, rng: &mut R, n: usize, t: usize) -> Vec<Party>
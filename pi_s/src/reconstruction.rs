@@ -0,0 +1,194 @@
+// Proof that g^secret is what a qualified set of decrypted shares reconstructs to, for light
+// clients that only know the committee's keys and the dealing's encrypted shares. The
+// aggregator publishes t + 1 decrypted shares D_i with their DLEQ proofs in commitment form,
+// (g^z pk_i^-d, D_i^z E_i^-d) and z, so the client recomputes every challenge d from the
+// commitments and checks all of the proofs and the interpolation in one multiscalar
+// multiplication under random weights. It doesn't check the dealing itself, the committee
+// did that before decrypting.
+
+use alloc::vec::Vec;
+
+use blake3::Hasher;
+use curve25519_dalek::{
+    RistrettoPoint, Scalar,
+    ristretto::CompressedRistretto,
+    traits::{IsIdentity, VartimeMultiscalarMul},
+};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use common::{
+    error::{Error, ErrorKind::CountMismatch},
+    opcount,
+    parallel::prelude::*,
+    random::{random_scalar, random_scalars},
+    utils::compute_lagrange_bases,
+    verification_failure, verified,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareOpening {
+    pub index: usize,
+    pub decrypted_share: CompressedRistretto,
+    // (g^z pk^-d, D^z E^-d)
+    pub commitments: (CompressedRistretto, CompressedRistretto),
+    pub response: Scalar,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconstructionProof {
+    // the qualified set, by increasing index
+    pub openings: Vec<ShareOpening>,
+}
+
+// g^secret reconstructed from `qualified_set`, (index, decrypted share) that all passed
+// `verify_decryptions`, with its proof. `proofs` are every party's (d, z) in index order.
+pub fn prove_reconstruction(
+    g: &RistrettoPoint,
+    public_keys: &[RistrettoPoint],
+    encrypted_shares: &[RistrettoPoint],
+    qualified_set: &[(usize, RistrettoPoint)],
+    proofs: &[(Scalar, Scalar)],
+) -> (RistrettoPoint, ReconstructionProof) {
+    let mut qualified_set = qualified_set.to_vec();
+    qualified_set.sort_by_key(|(index, _)| *index);
+    let indices: Vec<usize> = qualified_set.iter().map(|(index, _)| *index).collect();
+    let lagrange_bases = compute_lagrange_bases(&indices);
+
+    opcount::point_muls(4 * qualified_set.len());
+    opcount::compressions(3 * qualified_set.len());
+    opcount::msms(1, qualified_set.len());
+    let secret = qualified_set
+        .par_iter()
+        .zip(lagrange_bases.par_iter())
+        .map(|((_, share), lambda)| share * lambda)
+        .sum();
+    let openings = qualified_set
+        .par_iter()
+        .map(|(index, share)| {
+            let (d, z) = proofs[index - 1];
+            ShareOpening {
+                index: *index,
+                decrypted_share: share.compress(),
+                commitments: (
+                    (g * z - public_keys[index - 1] * d).compress(),
+                    (share * z - encrypted_shares[index - 1] * d).compress(),
+                ),
+                response: z,
+            }
+        })
+        .collect();
+
+    (secret, ReconstructionProof { openings })
+}
+
+// True if `secret` is g^f(0) for the f that t + 1 correctly decrypted shares of the dealing
+// lie on. `dealing` is the committee's public keys and the encrypted shares, in index order.
+pub fn verify_reconstruction<R>(
+    rng: &mut R,
+    hasher: &mut Hasher,
+    buf: &mut [u8; 64],
+    g: &RistrettoPoint,
+    t: usize,
+    dealing: (&[CompressedRistretto], &[CompressedRistretto]),
+    (secret, proof): (&RistrettoPoint, &ReconstructionProof),
+) -> Result<bool, Error>
+where
+    R: CryptoRng + RngCore,
+{
+    let (public_keys, encrypted_shares) = dealing;
+    if public_keys.len() != encrypted_shares.len() {
+        return Err(CountMismatch(
+            public_keys.len(),
+            "parties",
+            encrypted_shares.len(),
+            "encrypted shares",
+        )
+        .into());
+    }
+    let openings = &proof.openings;
+    let n = public_keys.len();
+    if openings.len() != t + 1
+        || openings.first().is_some_and(|opening| opening.index == 0)
+        || openings.last().is_some_and(|opening| opening.index > n)
+        || openings.windows(2).any(|w| w[0].index >= w[1].index)
+    {
+        verification_failure!("malformed_proof");
+        return Ok(false);
+    }
+
+    opcount::hashes(openings.len(), 4 * 32 * openings.len());
+    let challenges: Vec<Scalar> = openings
+        .iter()
+        .map(|opening| {
+            hasher.update(public_keys[opening.index - 1].as_bytes());
+            hasher.update(encrypted_shares[opening.index - 1].as_bytes());
+            hasher.update(opening.commitments.0.as_bytes());
+            hasher.update(opening.commitments.1.as_bytes());
+            hasher.finalize_xof().fill(buf);
+            hasher.reset();
+
+            let d = Scalar::from_bytes_mod_order_wide(buf);
+            buf.zeroize();
+            d
+        })
+        .collect();
+
+    opcount::decompressions(5 * openings.len());
+    let points: Option<Vec<[RistrettoPoint; 5]>> = openings
+        .par_iter()
+        .map(|opening| {
+            Some([
+                public_keys[opening.index - 1].decompress()?,
+                encrypted_shares[opening.index - 1].decompress()?,
+                opening.decrypted_share.decompress()?,
+                opening.commitments.0.decompress()?,
+                opening.commitments.1.decompress()?,
+            ])
+        })
+        .collect();
+    let points = match points {
+        Some(points) => points,
+        None => {
+            verification_failure!("malformed_proof");
+            return Ok(false);
+        }
+    };
+
+    let indices: Vec<usize> = openings.iter().map(|opening| opening.index).collect();
+    let lagrange_bases = compute_lagrange_bases(&indices);
+    let (rho, sigma) = (
+        random_scalars(rng, openings.len()),
+        random_scalars(rng, openings.len()),
+    );
+    let tau = random_scalar(rng);
+
+    // sum of rho_i (z_i g - d_i pk_i - A1_i) + sigma_i (z_i D_i - d_i E_i - A2_i)
+    // + tau (sum of lambda_i D_i - secret)
+    let mut scalars = Vec::with_capacity(5 * openings.len() + 2);
+    let mut bases = Vec::with_capacity(5 * openings.len() + 2);
+    let mut g_scalar = Scalar::ZERO;
+    for (i, (opening, [pk, enc_share, dec_share, a1, a2])) in
+        openings.iter().zip(points).enumerate()
+    {
+        let (d, z) = (challenges[i], opening.response);
+        g_scalar += rho[i] * z;
+        scalars.extend([
+            -rho[i] * d,
+            -sigma[i] * d,
+            sigma[i] * z + tau * lagrange_bases[i],
+            -rho[i],
+            -sigma[i],
+        ]);
+        bases.extend([pk, enc_share, dec_share, a1, a2]);
+    }
+    scalars.extend([g_scalar, -tau]);
+    bases.extend([*g, *secret]);
+
+    opcount::msms(1, bases.len());
+    Ok(verified!(
+        RistrettoPoint::vartime_multiscalar_mul(scalars, bases).is_identity(),
+        "reconstruction_proof"
+    ))
+}
@@ -0,0 +1,71 @@
+// Process-wide counters, so this lives in its own test binary with a single test.
+#![cfg(all(feature = "std", feature = "op-count"))]
+
+use curve25519_dalek::{RistrettoPoint, ristretto::CompressedRistretto};
+
+use common::{
+    opcount::{OpCounts, measure},
+    precompute::gen_powers,
+};
+use pi_s::{dealer::Dealer, party::generate_parties};
+
+// Counts of one dealing and one verification against their closed forms.
+#[test]
+fn op_counts() {
+    const N: usize = 16;
+    const T: usize = 7;
+
+    let mut rng = rand::rng();
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = [0u8; 64];
+
+    let g: RistrettoPoint = RistrettoPoint::mul_base(&common::random::random_scalar(&mut rng));
+    let xpows = gen_powers(N, T);
+
+    let mut parties = generate_parties(&g, &mut rng, N, T);
+    let public_keys: Vec<CompressedRistretto> =
+        parties.iter().map(|party| party.public_key.0).collect();
+    let mut dealer = Dealer::new(N, T, &public_keys).unwrap();
+
+    let secret = common::random::random_scalar(&mut rng);
+    let ((encrypted_shares, (d, z)), dealing) =
+        measure(|| dealer.deal_secret(&mut rng, &mut hasher, &mut buf, &xpows, &secret));
+
+    assert_eq!(
+        dealing,
+        OpCounts {
+            // f and r evaluations, z = r + d * f
+            scalar_muls: (2 * N * (T + 1) + T + 1) as u64,
+            point_muls: (2 * N) as u64,
+            compressions: (2 * N) as u64,
+            hashes: 1,
+            bytes_hashed: (2 * N * 32) as u64,
+            ..Default::default()
+        }
+    );
+
+    let p = &mut parties[0];
+    p.public_keys = Some(
+        public_keys
+            .iter()
+            .map(|pk| pk.decompress().unwrap())
+            .collect(),
+    );
+    p.ingest_encrypted_shares(&encrypted_shares).unwrap();
+    p.ingest_dealer_proof(d, z).unwrap();
+
+    let (res, verification) = measure(|| p.verify_encrypted_shares(&mut hasher, &mut buf, &xpows));
+
+    assert!(res.unwrap());
+    assert_eq!(
+        verification,
+        OpCounts {
+            scalar_muls: (N * (T + 1)) as u64,
+            point_muls: (2 * N) as u64,
+            compressions: N as u64,
+            hashes: 1,
+            bytes_hashed: (2 * N * 32) as u64,
+            ..Default::default()
+        }
+    );
+}